@@ -0,0 +1,160 @@
+/* The `Accessors` derive writes a getter and a setter for every field of a struct with named fields. A field named `size` of type `T` gets:
+ *          pub fn size(&self) -> &T
+ *          pub fn set_size(&mut self, size: T)
+ *
+ * The getter returns the field itself instead of a reference when its type is one of the primitive types that are Copy, like `u32` or `bool`, and when it's a
+ * shared reference already. The macro only sees the name of the type, so a Copy type of our own still gets a reference. Two attributes change what a field gets:
+ *      - `#[accessors(skip)]` leaves the field without methods, for those that shouldn't be seen or changed from outside.
+ *      - `#[accessors(get_mut)]` also adds `pub fn size_mut(&mut self) -> &mut T`, for fields that are changed in place, like a Vec that is pushed to.
+ */
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Field, Fields, Meta, NestedMeta, Type};
+
+/** The primitive types that are Copy, which getters return by value. */
+const COPY_TYPES: &[&str] = &[
+    "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/** What the attributes of a field asked for. */
+#[derive(Default)]
+struct Options {
+    skip: bool,
+    get_mut: bool,
+}
+
+pub fn impl_accessors(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&data.fields, "Accessors can only be derived for structs with named fields")),
+        },
+        Data::Enum(data) => return Err(syn::Error::new_spanned(data.enum_token, "Accessors can only be derived for structs with named fields")),
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "Accessors can only be derived for structs with named fields")),
+    };
+
+    let mut methods = Vec::new();
+    for field in fields {
+        let options = options(field)?;
+        if !options.skip {
+            methods.push(accessors(field, &options));
+        }
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
+
+/** Reads every `#[accessors(...)]` attribute of `field`. Anything but `skip` and `get_mut` inside of them is an error. */
+fn options(field: &Field) -> syn::Result<Options> {
+    let mut options = Options::default();
+
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("accessors")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "Expected #[accessors(skip)] or #[accessors(get_mut)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => options.skip = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("get_mut") => options.get_mut = true,
+                other => return Err(syn::Error::new_spanned(other, "Unknown accessors option, expected skip or get_mut")),
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+fn accessors(field: &Field, options: &Options) -> TokenStream {
+    // Only named fields get here, so they all have a name.
+    let name = field.ident.as_ref().unwrap();
+    let ty = &field.ty;
+    let setter = format_ident!("set_{}", name);
+
+    let getter = if is_copy(ty) {
+        quote! {
+            pub fn #name(&self) -> #ty {
+                self.#name
+            }
+        }
+    } else {
+        quote! {
+            pub fn #name(&self) -> &#ty {
+                &self.#name
+            }
+        }
+    };
+    let get_mut = options.get_mut.then(|| {
+        let name_mut = format_ident!("{}_mut", name);
+        quote! {
+            pub fn #name_mut(&mut self) -> &mut #ty {
+                &mut self.#name
+            }
+        }
+    });
+
+    quote! {
+        #getter
+
+        pub fn #setter(&mut self, #name: #ty) {
+            self.#name = #name;
+        }
+
+        #get_mut
+    }
+}
+
+fn is_copy(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => reference.mutability.is_none(),
+        Type::Path(path) => path.qself.is_none() && COPY_TYPES.iter().any(|copy| path.path.is_ident(copy)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(input: &str) -> syn::Result<String> {
+        impl_accessors(&syn::parse_str(input)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn copy_types_are_returned_by_value_and_others_by_reference() {
+        let code = derive("struct Pancake { stack: u32, flavour: String, syrup: &'static str }").unwrap();
+
+        assert!(code.contains("pub fn stack (& self) -> u32 { self . stack }"));
+        assert!(code.contains("pub fn flavour (& self) -> & String { & self . flavour }"));
+        assert!(code.contains("pub fn syrup (& self) -> & 'static str { self . syrup }"));
+        assert!(code.contains("pub fn set_flavour (& mut self , flavour : String) { self . flavour = flavour ; }"));
+    }
+
+    #[test]
+    fn attributes_skip_fields_or_add_get_mut() {
+        let code = derive("struct Pancake { #[accessors(skip)] secret: u8, #[accessors(get_mut)] toppings: Vec<String> }").unwrap();
+
+        assert!(!code.contains("secret"));
+        assert!(code.contains("pub fn toppings_mut (& mut self) -> & mut Vec < String > { & mut self . toppings }"));
+    }
+
+    #[test]
+    fn misuse_is_an_error() {
+        let errors = [
+            ("struct Pancake(u32);", "Accessors can only be derived for structs with named fields"),
+            ("enum Pancake { Plain }", "Accessors can only be derived for structs with named fields"),
+            ("struct Pancake { #[accessors(set_only)] stack: u32 }", "Unknown accessors option, expected skip or get_mut"),
+            ("struct Pancake { #[accessors] stack: u32 }", "Expected #[accessors(skip)] or #[accessors(get_mut)]"),
+        ];
+
+        for (input, message) in errors {
+            assert_eq!(message, derive(input).unwrap_err().to_string(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,123 @@
+/* The `Display` derive writes `std::fmt::Display` from a format string given in an attribute, the way `write!` would take it:
+ *          #[derive(Display)]
+ *          #[display("({x}, {y})")]
+ *          struct Point { x: i32, y: i32 }
+ *
+ * Every field can be used by its name inside of the braces, along with the usual format specs, like `{x:>3}` or `{y:?}`. The fields of tuple structs and variants are
+ * named by their position after an underscore, `{_0}` and `{_1}`, as plain numbers would be the arguments of `write!`. Enums take the attribute on each variant
+ * instead, and variants without fields that don't have one are written as their name.
+ */
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr};
+
+pub fn impl_display(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+
+    let arms = match &ast.data {
+        Data::Struct(data) => {
+            let format = format(&ast.attrs)?
+                .ok_or_else(|| syn::Error::new_spanned(name, "Display needs a format, like #[display(\"{x}, {y}\")]"))?;
+            vec![arm(quote!(Self), &data.fields, &format)]
+        }
+        Data::Enum(data) => {
+            if let Some(attr) = ast.attrs.iter().find(|attr| attr.path.is_ident("display")) {
+                return Err(syn::Error::new_spanned(attr, "The formats of enums go on each of their variants"));
+            }
+
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let format = match format(&variant.attrs)? {
+                    Some(format) => format,
+                    None if variant.fields.is_empty() => LitStr::new(&ident.to_string(), ident.span()),
+                    None => return Err(syn::Error::new_spanned(ident, "Variants with fields need a format, like #[display(\"{_0}\")]")),
+                };
+                arms.push(arm(quote!(Self::#ident), &variant.fields, &format));
+            }
+            arms
+        }
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "Display can only be derived for structs and enums")),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            // The format may not use every field.
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/** The format string of the `#[display(...)]` attribute in `attrs`, if there is one. */
+fn format(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    match attrs.iter().find(|attr| attr.path.is_ident("display")) {
+        Some(attr) => attr.parse_args().map(Some),
+        None => Ok(None),
+    }
+}
+
+/** A match arm binding every field of `fields` by its name, or by its position for tuples, and writing them with `format`. */
+fn arm(path: TokenStream, fields: &Fields, format: &LitStr) -> TokenStream {
+    let pattern = match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names),* })
+        }
+        Fields::Unnamed(fields) => {
+            let names = (0..fields.unnamed.len()).map(|i| format_ident!("_{}", i));
+            quote!(#path(#(#names),*))
+        }
+        Fields::Unit => path,
+    };
+
+    quote! {
+        #pattern => ::std::write!(f, #format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(input: &str) -> syn::Result<String> {
+        impl_display(&syn::parse_str(input)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn structs_bind_their_fields_for_the_format() {
+        let code = derive(r#"#[display("({x}, {y})")] struct Point { x: i32, y: i32 }"#).unwrap();
+        assert!(code.contains(r#"Self { x , y } => :: std :: write ! (f , "({x}, {y})") ,"#));
+
+        let code = derive(r#"#[display("{_0}-{_1}")] struct Pair(u8, u8);"#).unwrap();
+        assert!(code.contains(r#"Self (_0 , _1) => :: std :: write ! (f , "{_0}-{_1}") ,"#));
+    }
+
+    #[test]
+    fn variants_have_their_own_formats() {
+        let code = derive(r#"enum Coin { Penny, #[display("a quarter from {_0:?}")] Quarter(UsState) }"#).unwrap();
+
+        assert!(code.contains(r#"Self :: Penny => :: std :: write ! (f , "Penny") ,"#));
+        assert!(code.contains(r#"Self :: Quarter (_0) => :: std :: write ! (f , "a quarter from {_0:?}") ,"#));
+    }
+
+    #[test]
+    fn misuse_is_an_error() {
+        let errors = [
+            ("struct Point { x: i32 }", "Display needs a format, like #[display(\"{x}, {y}\")]"),
+            ("#[display(\"coin\")] enum Coin { Penny }", "The formats of enums go on each of their variants"),
+            ("enum Coin { Quarter(UsState) }", "Variants with fields need a format, like #[display(\"{_0}\")]"),
+            ("#[display(x, y)] struct Point { x: i32, y: i32 }", "expected string literal"),
+            ("union Number { int: i32 }", "Display can only be derived for structs and enums"),
+        ];
+
+        for (input, message) in errors {
+            assert_eq!(message, derive(input).unwrap_err().to_string(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,73 @@
+/* The `EnumIter` derive lists every variant of an enum without fields, in the order they are declared:
+ *          pub fn variants() -> &'static [Self]
+ *          pub fn iter() -> std::slice::Iter<'static, Self>
+ *
+ * `iter()` is only `variants().iter()`, given for `for color in ShirtColor::iter()`. Both borrow a constant slice, so they don't need the enum to be Copy or Clone.
+ * Variants with fields have no single value to list, a `Quarter(UsState)` is a different coin for every state, so deriving it for an enum with one is an error
+ * on that variant.
+ */
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields};
+
+pub fn impl_enum_iter(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let variants = match &ast.data {
+        Data::Enum(data) => &data.variants,
+        Data::Struct(data) => return Err(syn::Error::new_spanned(data.struct_token, "EnumIter can only be derived for enums")),
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "EnumIter can only be derived for enums")),
+    };
+
+    let mut names = Vec::new();
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            let message = format!("EnumIter can only list variants without fields, but `{}` has some", variant.ident);
+            return Err(syn::Error::new_spanned(&variant.fields, message));
+        }
+        names.push(&variant.ident);
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn variants() -> &'static [Self] {
+                &[#(Self::#names),*]
+            }
+
+            pub fn iter() -> ::std::slice::Iter<'static, Self> {
+                Self::variants().iter()
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(input: &str) -> syn::Result<String> {
+        impl_enum_iter(&syn::parse_str(input)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn variants_are_listed_in_order() {
+        let code = derive("enum ShirtColor { Red, Blue }").unwrap();
+
+        assert!(code.contains("pub fn variants () -> & 'static [Self] { & [Self :: Red , Self :: Blue] }"));
+        assert!(code.contains("pub fn iter () -> :: std :: slice :: Iter < 'static , Self > { Self :: variants () . iter () }"));
+        assert!(derive("enum Never {}").unwrap().contains("& []"));
+    }
+
+    #[test]
+    fn misuse_is_an_error() {
+        let errors = [
+            ("enum Coin { Penny, Quarter(UsState) }", "EnumIter can only list variants without fields, but `Quarter` has some"),
+            ("enum Message { Quit, Move { x: i32, y: i32 } }", "EnumIter can only list variants without fields, but `Move` has some"),
+            ("struct ShirtColor;", "EnumIter can only be derived for enums"),
+        ];
+
+        for (input, message) in errors {
+            assert_eq!(message, derive(input).unwrap_err().to_string(), "{input}");
+        }
+    }
+}
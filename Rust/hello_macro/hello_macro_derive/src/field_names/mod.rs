@@ -0,0 +1,134 @@
+/* The `FieldNames` derive gives a struct a little reflection: the names of its fields, and their values as Strings.
+ *          pub const FIELD_NAMES: &'static [&'static str]
+ *          pub fn fields(&self) -> Vec<(&'static str, String)>
+ *
+ * The fields of tuple structs are named by their position, "0", "1" and so on. Values are written with `Debug`, which most types derive, unless the field has
+ * `#[field_names(display)]`, which writes it with `Display` instead. That's the better choice for Strings and numbers, as `Debug` puts quotes around Strings.
+ * Fields that can't be written at all, like closures, are left out of both with `#[field_names(skip)]`.
+ *
+ * The generated code calls `::std::format!` and `::std::vec!` by their whole path, because a module with a `vec!` macro of its own, like advanced_features, would
+ * otherwise get that one.
+ */
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Field, Index, Member, Meta, NestedMeta};
+
+/** What the attributes of a field asked for. */
+#[derive(Default)]
+struct Options {
+    display: bool,
+    skip: bool,
+}
+
+pub fn impl_field_names(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &ast.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(data) => return Err(syn::Error::new_spanned(data.enum_token, "FieldNames can only be derived for structs")),
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "FieldNames can only be derived for structs")),
+    };
+
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let options = options(field)?;
+        if options.skip {
+            continue;
+        }
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+        names.push(match &member {
+            Member::Named(ident) => ident.unraw().to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        });
+        values.push(if options.display {
+            quote! { ::std::format!("{}", self.#member) }
+        } else {
+            quote! { ::std::format!("{:?}", self.#member) }
+        });
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub const FIELD_NAMES: &'static [&'static str] = &[#(#names),*];
+
+            pub fn fields(&self) -> Vec<(&'static str, String)> {
+                ::std::vec![#((#names, #values)),*]
+            }
+        }
+    })
+}
+
+/** Reads every `#[field_names(...)]` attribute of `field`. Anything but `display` and `skip` inside of them is an error. */
+fn options(field: &Field) -> syn::Result<Options> {
+    let mut options = Options::default();
+
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("field_names")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("display") => options.display = true,
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => options.skip = true,
+                        other => return Err(syn::Error::new_spanned(other, "Unknown field_names option, expected display or skip")),
+                    }
+                }
+            }
+            meta => return Err(syn::Error::new_spanned(meta, "Expected #[field_names(display)] or #[field_names(skip)]")),
+        }
+    }
+
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(input: &str) -> syn::Result<String> {
+        impl_field_names(&syn::parse_str(input)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn names_and_values_of_every_field() {
+        let code = derive("struct Button { width: u32, #[field_names(display)] r#label: String }").unwrap();
+
+        assert!(code.contains(r#"pub const FIELD_NAMES : & 'static [& 'static str] = & ["width" , "label"] ;"#));
+        assert!(code.contains(r#"("width" , :: std :: format ! ("{:?}" , self . width))"#));
+        assert!(code.contains(r#"("label" , :: std :: format ! ("{}" , self . r#label))"#));
+    }
+
+    #[test]
+    fn tuple_fields_are_named_by_position() {
+        let code = derive("struct Point(i32, i32);").unwrap();
+
+        assert!(code.contains(r#"& ["0" , "1"]"#));
+        assert!(code.contains("self . 1"));
+        assert!(derive("struct Unit;").unwrap().contains("& []"));
+    }
+
+    #[test]
+    fn skipped_fields_are_left_out() {
+        let code = derive("struct Button { width: u32, #[field_names(skip)] on_click: Option<Box<dyn FnMut()>> }").unwrap();
+
+        assert!(code.contains(r#"& ["width"]"#));
+        assert!(!code.contains("on_click"));
+    }
+
+    #[test]
+    fn misuse_is_an_error() {
+        let errors = [
+            ("enum Coin { Penny }", "FieldNames can only be derived for structs"),
+            ("struct Post { #[field_names(debug)] content: String }", "Unknown field_names option, expected display or skip"),
+            ("struct Post { #[field_names = \"display\"] content: String }", "Expected #[field_names(display)] or #[field_names(skip)]"),
+        ];
+
+        for (input, message) in errors {
+            assert_eq!(message, derive(input).unwrap_err().to_string(), "{input}");
+        }
+    }
+}
@@ -1,45 +1,185 @@
 /* For this macro to work, we need to add the following to the `hello_macro/hello_macro_derive/Cargo.toml` file;
  *          [lib]
  *          proc-macro = true
- * 
+ *
  *          [dependencies]
- *          syn = "1.0"
+ *          syn = { version = "1.0", features = ["full"] }
  *          quote = "1.0"
+ *          proc-macro2 = "1.0"
  * To start defining the procedural macro, we need the code for `hello_macro_derive()`. Inside it we find the following:
  *      - The `proc_macro_derive(HelloMacro) annotation to be able to call the macro.
- *      - `syn::parse(input).unwrap()` converts the TokenStream to a data structure that we can interpret and perform operations on. The `unwrap()` call 
- *        should be changed to a more speccific message by using `panic!` or `expect`.
- *  
- * We then pass the data structure to our implementation of the macro.
+ *      - `parse_macro_input!(input as DeriveInput)` converts the TokenStream to a data structure that we can interpret and perform operations on. If the input
+ *        can't be parsed, it returns the parse error as a compiler error instead of panicking like `syn::parse(input).unwrap()` would.
+ *
+ * We then pass the data structure to our implementation of the macro. It returns a `syn::Result`, and `syn::Error::to_compile_error` turns an error into a
+ * `compile_error!` pointing at the code that caused it, so using the macro wrong shows up as a normal compiler error on that code.
  */
 use proc_macro::TokenStream;
 use quote::quote;
-use syn;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+mod accessors;
+mod display;
+mod enum_iter;
+mod field_names;
+mod new;
+mod parametrize;
+mod route;
 
 #[proc_macro_derive(HelloMacro)]
 pub fn hello_macro_derive(input: TokenStream) -> TokenStream {
     // Counstruct a representation of Rust code as syntax tree
     // that we can manipulate
-    let ast = syn::parse(input).unwrap();
+    let ast = parse_macro_input!(input as DeriveInput);
 
     // Build the trait implementation
-    impl_hello_macro(&ast)
+    impl_hello_macro(&ast).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/** Getters and setters for the fields of a struct, see src/accessors/mod.rs. Derive macros can only be defined at the root of the crate, but the code they
+ *  generate can be written anywhere, so each of the bigger ones has its own module.
+ */
+#[proc_macro_derive(Accessors, attributes(accessors))]
+pub fn accessors_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    accessors::impl_accessors(&ast).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/** The names of the fields of a struct and their values, see src/field_names/mod.rs. */
+#[proc_macro_derive(FieldNames, attributes(field_names))]
+pub fn field_names_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    field_names::impl_field_names(&ast).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/** A `new` constructor taking every field, see src/new/mod.rs. */
+#[proc_macro_derive(New, attributes(new))]
+pub fn new_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    new::impl_new(&ast).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/** `std::fmt::Display` from a format string written in `#[display("...")]`, see src/display/mod.rs. */
+#[proc_macro_derive(Display, attributes(display))]
+pub fn display_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    display::impl_display(&ast).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/** Every variant of an enum without fields, see src/enum_iter/mod.rs. */
+#[proc_macro_derive(EnumIter)]
+pub fn enum_iter_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    enum_iter::impl_enum_iter(&ast).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/** Attribute macros get two TokenStreams, the contents of the attribute and the item it's written on, and return the code that replaces the item. `route`
+ *  keeps the handler function and adds its `Route` for tcp_listener, see src/route/mod.rs.
+ */
+#[proc_macro_attribute]
+pub fn route(attr: TokenStream, item: TokenStream) -> TokenStream {
+    route::impl_route(attr.into(), item.into()).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/** Function-like macros get the TokenStream between their delimiters and return the code they expand to. `parametrize!` writes one test for every case of a
+ *  table, see src/parametrize/mod.rs.
+ */
+#[proc_macro]
+pub fn parametrize(input: TokenStream) -> TokenStream {
+    parametrize::impl_parametrize(input.into()).unwrap_or_else(|err| err.to_compile_error()).into()
 }
 
 /** In the function, we first get the name of the struct the macro is being implemented in. This is an attribute of the data structure passed into the function, so
- *  the name variable will be "STRUCT_NAME". The `quote!` macro lets us define the Rust code that we return. We can convert it into a TokenStream by calling the 
+ *  the name variable will be "STRUCT_NAME". The `quote!` macro lets us define the Rust code that we return. We can convert it into a TokenStream by calling the
  *  `into()` method, which consumes the intermediate representation and returns a value of the required TokenStream type. It also provides us some templating mechanics,
  *  like using `#name` to make quote! replace it by the value in the name varible. Then, we implement the trait for the struct defined in `name` and make it print.
- *  By using the built-in `stringify!` macro we can turn an expression into a string literal at compiile time.
+ *  By using the built-in `stringify!` macro we can turn an expression into a string literal at compiile time. The same greeting is also returned as a String
+ *  by `hello_string()`, which takes `&self` like any other method. Matching on the `Fields` of a struct, the greeting also tells what kind of struct it is: unit
+ *  structs only give their name, tuple structs their arity and structs with named fields how many of them they have. The name is also kept in the associated
+ *  constant `NAME`, a `&'static str` made by the same `stringify!`.
+ *
+ *  The function works on the types of proc_macro2 instead of those of proc_macro, which can only be used while the compiler runs a macro. That way the tests
+ *  below can call it. Unions are rejected with an error on their `union` keyword, and generic types get their generics in the impl.
  */
-fn impl_hello_macro(ast: &syn::DeriveInput) -> TokenStream {
+fn impl_hello_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if let Data::Union(data) = &ast.data {
+        return Err(syn::Error::new_spanned(data.union_token, "HelloMacro can only be derived for structs and enums"));
+    }
+
     let name = &ast.ident;
+    let shape = match &ast.data {
+        Data::Struct(data) => shape(&data.fields),
+        _ => String::new(),
+    };
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
     let gen = quote! {
-        impl HelloMacro for #name {
+        impl #impl_generics HelloMacro for #name #ty_generics #where_clause {
+            const NAME: &'static str = stringify!(#name);
+
             fn hello_macro() {
-                println!("Hello, Macro! My name is {}!", stringify!(#name));
+                println!("Hello, Macro! My name is {}{}!", stringify!(#name), #shape);
+            }
+
+            fn hello_string(&self) -> String {
+                format!("Hello, Macro! My name is {}{}!", stringify!(#name), #shape)
             }
         }
     };
-    gen.into()
-}
\ No newline at end of file
+    Ok(gen)
+}
+
+/** What the greeting of a struct says about its fields after its name. Unit structs have nothing to say, tuple structs tell how many fields they hold and named
+ *  structs how many fields they have.
+ */
+fn shape(fields: &Fields) -> String {
+    let plural = if fields.len() == 1 { "" } else { "s" };
+    match fields {
+        Fields::Named(_) => format!(", a struct with {} field{}", fields.len(), plural),
+        Fields::Unnamed(_) => format!(", a tuple struct of {} field{}", fields.len(), plural),
+        Fields::Unit => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(input: &str) -> syn::Result<String> {
+        impl_hello_macro(&syn::parse_str(input)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn derives_for_structs_and_enums() {
+        assert!(derive("struct Pancakes;").unwrap().contains("impl HelloMacro for Pancakes"));
+        assert!(derive("struct Pancakes;").unwrap().contains("const NAME : & 'static str = stringify ! (Pancakes) ;"));
+        assert!(derive("enum Pancakes { Plain, Syrup }").is_ok());
+        assert!(derive("struct Stack<T: Clone> { pancakes: Vec<T> }").unwrap().contains("impl < T : Clone > HelloMacro for Stack < T >"));
+    }
+
+    #[test]
+    fn greetings_tell_the_fields_of_structs() {
+        assert!(derive("struct Pancakes;").unwrap().contains(r#"stringify ! (Pancakes) , """#));
+        assert!(derive("struct Meters(u32);").unwrap().contains(r#"", a tuple struct of 1 field""#));
+        assert!(derive("struct Point(i32, i32);").unwrap().contains(r#"", a tuple struct of 2 fields""#));
+        assert!(derive("struct Stack { height: u32, toppings: Vec<String> }").unwrap().contains(r#"", a struct with 2 fields""#));
+        assert!(derive("struct Empty {}").unwrap().contains(r#"", a struct with 0 fields""#));
+        assert!(derive("enum Pancakes { Plain, Syrup }").unwrap().contains(r#"stringify ! (Pancakes) , """#));
+    }
+
+    #[test]
+    fn unions_are_a_compile_error() {
+        let err = derive("union Pancakes { flour: u32, eggs: f32 }").unwrap_err();
+        assert_eq!("HelloMacro can only be derived for structs and enums", err.to_string());
+        assert!(err.to_compile_error().to_string().starts_with("compile_error !"));
+    }
+
+    #[test]
+    fn input_that_is_not_a_type_is_a_parse_error() {
+        assert!(derive("fn pancakes() {}").is_err());
+    }
+}
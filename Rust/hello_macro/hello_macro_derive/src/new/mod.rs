@@ -0,0 +1,115 @@
+/* The `New` derive writes the constructor most structs get by hand, with one parameter for every field, in the order they are declared:
+ *          #[derive(New)]
+ *          struct Rectangle { width: u32, height: u32 }
+ *
+ *          Rectangle::new(30, 50)
+ *
+ * A field with `#[new(default)]` is left out of the parameters and starts as `Default::default()` instead, which suits counters and collections that always start
+ * empty. Tuple structs work the same, their parameters are just named by position, `_0`, `_1` and so on, and unit structs get a `new()` without any.
+ */
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Field, Fields, Meta, NestedMeta};
+
+pub fn impl_new(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &ast.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(data) => return Err(syn::Error::new_spanned(data.enum_token, "New can only be derived for structs")),
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "New can only be derived for structs")),
+    };
+
+    let mut params = Vec::new();
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let name = match &field.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("_{}", i),
+        };
+        let ty = &field.ty;
+
+        if is_default(field)? {
+            values.push(quote! { ::std::default::Default::default() });
+        } else {
+            params.push(quote! { #name: #ty });
+            values.push(quote! { #name });
+        }
+    }
+
+    let body = match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote! { Self { #(#names: #values),* } }
+        }
+        Fields::Unnamed(_) => quote! { Self(#(#values),*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn new(#(#params),*) -> Self {
+                #body
+            }
+        }
+    })
+}
+
+/** Whether `field` has `#[new(default)]`. Anything else inside of the attribute is an error. */
+fn is_default(field: &Field) -> syn::Result<bool> {
+    let mut default = false;
+
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("new")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => default = true,
+                        other => return Err(syn::Error::new_spanned(other, "Unknown new option, expected default")),
+                    }
+                }
+            }
+            meta => return Err(syn::Error::new_spanned(meta, "Expected #[new(default)]")),
+        }
+    }
+
+    Ok(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(input: &str) -> syn::Result<String> {
+        impl_new(&syn::parse_str(input)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn every_field_is_a_parameter_unless_it_is_default() {
+        let code = derive("struct LimitTracker<'a, T> { messenger: &'a T, #[new(default)] value: usize, max: usize }").unwrap();
+
+        assert!(code.contains("impl < 'a , T > LimitTracker < 'a , T >"));
+        assert!(code.contains(
+            "pub fn new (messenger : & 'a T , max : usize) -> Self { Self { messenger : messenger , value : :: std :: default :: Default :: default () , max : max } }"
+        ));
+    }
+
+    #[test]
+    fn tuple_and_unit_structs() {
+        assert!(derive("struct Meters(u32);").unwrap().contains("pub fn new (_0 : u32) -> Self { Self (_0) }"));
+        assert!(derive("struct Pancakes;").unwrap().contains("pub fn new () -> Self { Self }"));
+    }
+
+    #[test]
+    fn misuse_is_an_error() {
+        let errors = [
+            ("enum Coin { Penny }", "New can only be derived for structs"),
+            ("struct Guess { #[new(value = 1)] value: i32 }", "Unknown new option, expected default"),
+            ("struct Guess { #[new] value: i32 }", "Expected #[new(default)]"),
+        ];
+
+        for (input, message) in errors {
+            assert_eq!(message, derive(input).unwrap_err().to_string(), "{input}");
+        }
+    }
+}
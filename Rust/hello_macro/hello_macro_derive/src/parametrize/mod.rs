@@ -0,0 +1,120 @@
+/* The `parametrize!` function-like macro turns a table of cases into one test for each of them. It takes the function under test, then every case with a name,
+ * its input and the value it should return:
+ *          parametrize! {
+ *              add_two;
+ *              two: 2 => 4,
+ *              one_hundred: 100 => 102,
+ *          }
+ *
+ * which expands to a `#[test] fn add_two_two() { assert_eq!(4, add_two(2)); }` and a `add_two_one_hundred` one. Tests named after the function and the case can
+ * be filtered by either with `cargo test`, and a failing case shows up by its own name. Functions with more than one parameter take a tuple of the arguments,
+ * like `(2, 2) => 4`.
+ */
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Expr, Ident, Path, Token};
+
+/** Everything between the braces of `parametrize!`. */
+struct Table {
+    function: Path,
+    cases: Punctuated<Case, Token![,]>,
+}
+
+/** One line of the table, `name: input => expected`. */
+struct Case {
+    name: Ident,
+    input: Expr,
+    expected: Expr,
+}
+
+impl Parse for Table {
+    fn parse(input: ParseStream) -> syn::Result<Table> {
+        let function = input.parse()?;
+        input.parse::<Token![;]>()?;
+        let cases = Punctuated::parse_terminated(input)?;
+        Ok(Table { function, cases })
+    }
+}
+
+impl Parse for Case {
+    fn parse(input: ParseStream) -> syn::Result<Case> {
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let arguments = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let expected = input.parse()?;
+        Ok(Case { name, input: arguments, expected })
+    }
+}
+
+pub fn impl_parametrize(input: TokenStream) -> syn::Result<TokenStream> {
+    let table: Table = syn::parse2(input)?;
+    if table.cases.is_empty() {
+        return Err(syn::Error::new_spanned(&table.function, "parametrize! needs at least one case after the function"));
+    }
+
+    // Only the last segment goes in the names, `testing::add_two` still gives `add_two_two`.
+    let function = &table.function;
+    let prefix = &function.segments.last().unwrap().ident;
+
+    let tests = table.cases.iter().map(|case| {
+        let name = format_ident!("{}_{}", prefix, case.name);
+        let expected = &case.expected;
+        let arguments = match &case.input {
+            Expr::Tuple(tuple) => {
+                let elems = tuple.elems.iter();
+                quote! { #(#elems),* }
+            }
+            input => quote! { #input },
+        };
+
+        quote! {
+            #[test]
+            fn #name() {
+                ::std::assert_eq!(#expected, #function(#arguments));
+            }
+        }
+    });
+
+    Ok(quote! { #(#tests)* })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parametrize(input: &str) -> syn::Result<String> {
+        impl_parametrize(input.parse().unwrap()).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn every_case_is_a_test() {
+        let code = parametrize("add_two; two: 2 => 4, one_hundred: 100 => 102,").unwrap();
+
+        assert!(code.contains("# [test] fn add_two_two () { :: std :: assert_eq ! (4 , add_two (2)) ; }"));
+        assert!(code.contains("# [test] fn add_two_one_hundred () { :: std :: assert_eq ! (102 , add_two (100)) ; }"));
+    }
+
+    #[test]
+    fn tuples_are_the_arguments() {
+        let code = parametrize("super::internal_adder; twos: (2, 2) => 4").unwrap();
+
+        assert!(code.contains("fn internal_adder_twos () { :: std :: assert_eq ! (4 , super :: internal_adder (2 , 2)) ; }"));
+    }
+
+    #[test]
+    fn misuse_is_an_error() {
+        let errors = [
+            ("add_two;", "parametrize! needs at least one case after the function"),
+            ("add_two two: 2 => 4", "expected `;`"),
+            ("add_two; 2 => 4", "expected identifier"),
+            ("add_two; two: 2, 4", "expected `=>`"),
+        ];
+
+        for (input, message) in errors {
+            assert_eq!(message, parametrize(input).unwrap_err().to_string(), "{input}");
+        }
+    }
+}
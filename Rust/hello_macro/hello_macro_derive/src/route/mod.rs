@@ -0,0 +1,104 @@
+/* The `route` attribute macro, the one advanced_features only imagines. Written on a handler function of tcp_listener:
+ *          #[route(GET, "/")]
+ *          fn index(request: &Request, writer: &mut dyn Write) -> io::Result<Status> { // --snip-- }
+ *
+ * it leaves the function as it is and adds a static `tcp_listener::http::Route` next to it, named after it in uppercase with `_ROUTE` at the end, `INDEX_ROUTE`
+ * here, with the same visibility. The route table is then a list of those statics given to `Router::routes`:
+ *          Router::new().routes(&[INDEX_ROUTE, ABOUT_ROUTE])
+ *
+ * Rust has no way of collecting every static marked by a macro by itself, crates like inventory do it with linker tricks, so the table is written by hand. At least
+ * the method and the path of each route only live on top of its function.
+ */
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, ItemFn, LitStr, Token};
+
+/** What goes between the parentheses of `#[route(...)]`. */
+struct RouteArgs {
+    method: Ident,
+    path: LitStr,
+}
+
+impl Parse for RouteArgs {
+    fn parse(input: ParseStream) -> syn::Result<RouteArgs> {
+        let method = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        // A trailing comma is fine, as it is in function calls.
+        input.parse::<Option<Token![,]>>()?;
+        Ok(RouteArgs { method, path })
+    }
+}
+
+pub fn impl_route(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let args: RouteArgs = syn::parse2(attr)?;
+    let function: ItemFn = syn::parse2(item)?;
+
+    let variant = match args.method.to_string().as_str() {
+        "GET" => format_ident!("Get"),
+        "HEAD" => format_ident!("Head"),
+        "POST" => format_ident!("Post"),
+        "PUT" => format_ident!("Put"),
+        "DELETE" => format_ident!("Delete"),
+        "OPTIONS" => format_ident!("Options"),
+        "PATCH" => format_ident!("Patch"),
+        _ => return Err(syn::Error::new_spanned(args.method, "Unknown method, expected GET, HEAD, POST, PUT, DELETE, OPTIONS or PATCH")),
+    };
+    if !args.path.value().starts_with('/') {
+        return Err(syn::Error::new_spanned(args.path, "Route paths start with /"));
+    }
+
+    let vis = &function.vis;
+    let name = &function.sig.ident;
+    let path = &args.path;
+    let route = format_ident!("{}_ROUTE", name.to_string().to_uppercase());
+    // A function that isn't a handler gets its type error on its own name.
+    let handler = quote_spanned! {name.span()=> #name};
+
+    Ok(quote! {
+        #function
+
+        #vis static #route: ::tcp_listener::http::Route = ::tcp_listener::http::Route {
+            method: ::tcp_listener::http::Method::#variant,
+            path: #path,
+            handler: #handler,
+        };
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(attr: &str, item: &str) -> syn::Result<String> {
+        impl_route(attr.parse().unwrap(), item.parse().unwrap()).map(|tokens| tokens.to_string())
+    }
+
+    const INDEX: &str = "pub fn index(request: &Request, writer: &mut dyn Write) -> io::Result<Status> { todo!() }";
+
+    #[test]
+    fn functions_get_a_route_static() {
+        let code = route(r#"GET, "/""#, INDEX).unwrap();
+
+        assert!(code.starts_with("pub fn index"));
+        assert!(code.contains(
+            r#"pub static INDEX_ROUTE : :: tcp_listener :: http :: Route = :: tcp_listener :: http :: Route { method : :: tcp_listener :: http :: Method :: Get , path : "/" , handler : index , } ;"#
+        ));
+        assert!(route(r#"DELETE, "/posts","#, INDEX).unwrap().contains("Method :: Delete"));
+    }
+
+    #[test]
+    fn misuse_is_an_error() {
+        let errors = [
+            (r#"FETCH, "/""#, INDEX, "Unknown method, expected GET, HEAD, POST, PUT, DELETE, OPTIONS or PATCH"),
+            (r#"GET, "about""#, INDEX, "Route paths start with /"),
+            (r#"GET"#, INDEX, "expected `,`"),
+            (r#"GET, "/""#, "struct Index;", "expected `fn`"),
+        ];
+
+        for (attr, item, message) in errors {
+            assert_eq!(message, route(attr, item).unwrap_err().to_string(), "{attr}");
+        }
+    }
+}
@@ -1,5 +1,11 @@
 /** To implement this trait as a macro, we need to `cargo new CRATENAME_derive --lib` inside this directory.
  */
 pub trait HelloMacro {
+    /** The name of the type, for code that needs it without printing anything. */
+    const NAME: &'static str;
+
     fn hello_macro();
+
+    /** Returns the greeting `hello_macro()` prints instead of printing it, so it can be asserted on in tests. */
+    fn hello_string(&self) -> String;
 }
@@ -0,0 +1,36 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A line of the generated logs in every hundred says `ERROR`.
+pub const LINES: usize = 1_000_000;
+const RUNS: usize = 5;
+
+/// Runs `search` over `size` bytes a few times and prints its fastest run.
+/// Returns how long it took.
+pub fn bench(name: &str, size: u64, search: impl Fn() -> usize) -> Duration {
+    let mut fastest = Duration::MAX;
+    let mut count = 0;
+
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        count = search();
+        fastest = fastest.min(start.elapsed());
+    }
+
+    let throughput = size as f64 / 1_000_000.0 / fastest.as_secs_f64();
+    println!("{name:>16}: {fastest:>10.2?} ({throughput:.0} MB/s, {count} matches)");
+    fastest
+}
+
+/// Writes a log of `LINES` lines to `path`.
+pub fn write_log(path: &Path) {
+    let mut log = BufWriter::new(File::create(path).unwrap());
+
+    for i in 0..LINES {
+        let level = if i % 100 == 0 { "ERROR" } else { "INFO" };
+        writeln!(log, "2024-01-01T00:00:{:02} {level} request {i} served in {}ms", i % 60, i % 97)
+            .unwrap();
+    }
+}
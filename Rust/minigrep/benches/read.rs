@@ -0,0 +1,36 @@
+//! Compares reading a whole file before searching it with reading it a line
+//! at a time, on a generated log. Run with `cargo bench --bench read`.
+//!
+//! Reading line by line only ever holds one line, where reading the whole
+//! file holds all of it, so this shows what bounded memory costs in speed.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use minigrep::{search_reader, search_with, MatchMode, Matcher, SearchOutput};
+
+mod common;
+
+fn main() {
+    let path = env::temp_dir().join(format!("minigrep-read-{}.log", std::process::id()));
+    common::write_log(&path);
+    let size = fs::metadata(&path).unwrap().len();
+    let matcher = Matcher::new("ERROR", false);
+
+    println!("{} lines, {} MB", common::LINES, size / 1_000_000);
+
+    common::bench("read_to_string", size, || {
+        let contents = fs::read_to_string(&path).unwrap();
+        match search_with(&matcher, &contents, MatchMode::Count, None) {
+            SearchOutput::Count(count) => count,
+            SearchOutput::Lines(_) => unreachable!("only count mode counts"),
+        }
+    });
+    common::bench("BufReader", size, || {
+        let reader = BufReader::new(File::open(&path).unwrap());
+        search_reader(&matcher, reader, MatchMode::Count, None, |_, _| Ok(())).unwrap()
+    });
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,276 @@
+//! A small command line parser: a program declares its flags, options with
+//! values and positional arguments, and gets them back by name, with a help
+//! text written from the declarations.
+//!
+//! Options are given apart, like `-v -c` rather than `-vc`. Everything after
+//! `--` is positional, even if it starts with `-`.
+
+use std::fmt;
+
+/// The arguments a program takes.
+#[derive(Debug, Clone)]
+pub struct Parser {
+    program: &'static str,
+    about: &'static str,
+    options: Vec<Opt>,
+    positionals: Vec<(&'static str, &'static str)>,
+}
+
+#[derive(Debug, Clone)]
+struct Opt {
+    name: &'static str,
+    /// What the value is called in the help, for options taking one.
+    value: Option<&'static str>,
+    help: &'static str,
+}
+
+/// The arguments given, as `Parser::parse` found them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matches {
+    options: Vec<(&'static str, Option<String>)>,
+    positionals: Vec<String>,
+}
+
+/// Why the arguments can't be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgError {
+    /// `-h` or `--help` was given, so the program should print its help.
+    Help,
+    UnknownOption(String),
+    /// An option was last, with nothing after it for its value.
+    MissingValue {
+        option: &'static str,
+        value: &'static str,
+    },
+    /// The program itself can't use the arguments, for the given reason.
+    Usage(&'static str),
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgError::Help => write!(f, "help was asked for"),
+            ArgError::UnknownOption(option) => write!(f, "Unknown option {option}"),
+            ArgError::MissingValue { option, value } => write!(f, "{option} needs a {value}"),
+            ArgError::Usage(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+impl Parser {
+    /// Creates a parser for `program`, which `about` describes in the help.
+    pub fn new(program: &'static str, about: &'static str) -> Parser {
+        Parser {
+            program,
+            about,
+            options: Vec::new(),
+            positionals: Vec::new(),
+        }
+    }
+
+    /// Adds a flag, like `-v`, which is either given or not.
+    pub fn flag(mut self, name: &'static str, help: &'static str) -> Parser {
+        self.options.push(Opt {
+            name,
+            value: None,
+            help,
+        });
+        self
+    }
+
+    /// Adds an option taking the argument after it as its value, like
+    /// `-m 3`. The help calls the value `value`.
+    pub fn option(mut self, name: &'static str, value: &'static str, help: &'static str) -> Parser {
+        self.options.push(Opt {
+            name,
+            value: Some(value),
+            help,
+        });
+        self
+    }
+
+    /// Adds a positional argument, described as `name` in the help. They're
+    /// listed in the order they're added.
+    pub fn positional(mut self, name: &'static str, help: &'static str) -> Parser {
+        self.positionals.push((name, help));
+        self
+    }
+
+    /// Parses `args`, which don't include the name of the program.
+    ///
+    /// # Errors
+    ///
+    /// The `parse` function will return `ArgError::Help` if help is asked
+    /// for, and an error if an option isn't known or has no value.
+    /// Positional arguments aren't checked, since what's missing depends on
+    /// the program.
+    pub fn parse(&self, args: impl IntoIterator<Item = String>) -> Result<Matches, ArgError> {
+        let mut args = args.into_iter();
+        let mut matches = Matches {
+            options: Vec::new(),
+            positionals: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                matches.positionals.extend(args.by_ref());
+                break;
+            }
+            if arg == "-h" || arg == "--help" {
+                return Err(ArgError::Help);
+            }
+            if !arg.starts_with('-') || arg == "-" {
+                matches.positionals.push(arg);
+                continue;
+            }
+
+            let opt = self
+                .options
+                .iter()
+                .find(|opt| opt.name == arg)
+                .ok_or(ArgError::UnknownOption(arg))?;
+            let value = match opt.value {
+                Some(value) => Some(args.next().ok_or(ArgError::MissingValue {
+                    option: opt.name,
+                    value,
+                })?),
+                None => None,
+            };
+            matches.options.push((opt.name, value));
+        }
+
+        Ok(matches)
+    }
+
+    /// Returns the help of the program, listing its arguments.
+    pub fn help(&self) -> String {
+        let mut usage = format!("Usage: {} [OPTIONS]", self.program);
+        for (name, _) in &self.positionals {
+            usage.push(' ');
+            usage.push_str(name);
+        }
+
+        let options: Vec<(String, &str)> = self
+            .options
+            .iter()
+            .map(|opt| match opt.value {
+                Some(value) => (format!("{} <{value}>", opt.name), opt.help),
+                None => (opt.name.to_string(), opt.help),
+            })
+            .chain([(String::from("-h, --help"), "Print this help")])
+            .collect();
+        let width = options
+            .iter()
+            .map(|(name, _)| name.len())
+            .chain(self.positionals.iter().map(|(name, _)| name.len()))
+            .max()
+            .unwrap_or(0);
+
+        let mut help = format!("{}\n\n{usage}\n", self.about);
+        if !self.positionals.is_empty() {
+            help.push_str("\nArguments:\n");
+            for (name, text) in &self.positionals {
+                help.push_str(&format!("  {name:width$}  {text}\n"));
+            }
+        }
+        help.push_str("\nOptions:\n");
+        for (name, text) in &options {
+            help.push_str(&format!("  {name:width$}  {text}\n"));
+        }
+
+        help
+    }
+}
+
+impl Matches {
+    /// Returns whether the flag or option `name` was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(given, _)| *given == name)
+    }
+
+    /// Returns the value of the option `name`, the last one if it was given
+    /// more than once.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .filter(|(given, _)| *given == name)
+            .find_map(|(_, value)| value.as_deref())
+    }
+
+    /// Returns every value given to the option `name`, in order.
+    pub fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.options
+            .iter()
+            .filter(move |(given, _)| *given == name)
+            .filter_map(|(_, value)| value.as_deref())
+    }
+
+    /// Returns the positional arguments, in order.
+    pub fn positionals(&self) -> &[String] {
+        &self.positionals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> Parser {
+        Parser::new("tool", "Does things.")
+            .flag("-v", "Talk more")
+            .option("-e", "pattern", "What to look for")
+            .positional("FILE", "Where to look")
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_options_by_name() {
+        let matches = parser()
+            .parse(args(&["-e", "a", "file", "-v", "-e", "-b", "--", "-v"]))
+            .unwrap();
+
+        assert!(matches.flag("-v"));
+        assert!(!matches.flag("-x"));
+        assert_eq!(vec!["a", "-b"], matches.values("-e").collect::<Vec<_>>());
+        assert_eq!(Some("-b"), matches.value("-e"));
+        assert_eq!(["file", "-v"], matches.positionals());
+    }
+
+    #[test]
+    fn reports_bad_options() {
+        assert_eq!(
+            Err(ArgError::UnknownOption(String::from("-x"))),
+            parser().parse(args(&["-x"]))
+        );
+        assert_eq!(
+            "-e needs a pattern",
+            parser().parse(args(&["file", "-e"])).unwrap_err().to_string()
+        );
+        assert_eq!(Err(ArgError::Help), parser().parse(args(&["file", "--help"])));
+    }
+
+    #[test]
+    fn writes_help_from_the_declarations() {
+        assert_eq!(
+            "Does things.
+
+Usage: tool [OPTIONS] FILE
+
+Arguments:
+  FILE          Where to look
+
+Options:
+  -v            Talk more
+  -e <pattern>  What to look for
+  -h, --help    Print this help
+",
+            parser().help()
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Reading text that isn't UTF-8. Files starting with a UTF-16 byte order
+//! mark, as Windows tools write them, are turned into UTF-8 as they're read,
+//! so the search never sees the difference.
+
+use std::io::{self, BufRead, Read};
+
+/// How the text of a file is encoded, as its byte order mark says.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 with no byte order mark, which is what files without one are
+    /// taken to be.
+    Utf8,
+    Utf8WithBom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Returns the encoding of a file starting with `bytes`.
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => Encoding::Utf8WithBom,
+            [0xFF, 0xFE, ..] => Encoding::Utf16Le,
+            [0xFE, 0xFF, ..] => Encoding::Utf16Be,
+            _ => Encoding::Utf8,
+        }
+    }
+
+    /// Returns the byte order mark files in this encoding start with.
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => &[],
+            Encoding::Utf8WithBom => &[0xEF, 0xBB, 0xBF],
+            Encoding::Utf16Le => &[0xFF, 0xFE],
+            Encoding::Utf16Be => &[0xFE, 0xFF],
+        }
+    }
+
+    /// Returns the bytes of a file in this encoding holding `text`, byte
+    /// order mark included.
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        let mut bytes = self.bom().to_vec();
+
+        match self {
+            Encoding::Utf8 | Encoding::Utf8WithBom => bytes.extend_from_slice(text.as_bytes()),
+            Encoding::Utf16Le => bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+            Encoding::Utf16Be => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+        }
+
+        bytes
+    }
+}
+
+/// A reader giving the text of another one as UTF-8, without its byte order
+/// mark.
+///
+/// UTF-16 that isn't valid, like half of a surrogate pair or a last byte
+/// with no other one, is read as U+FFFD REPLACEMENT CHARACTER. UTF-8 is
+/// passed on as it is, so anything reading it still sees whether it's
+/// valid.
+#[derive(Debug)]
+pub struct Decoder<R> {
+    inner: R,
+    encoding: Encoding,
+    /// The first byte of a code unit whose second byte hasn't been read.
+    odd_byte: Option<u8>,
+    /// A high surrogate waiting for its low one.
+    surrogate: Option<u16>,
+    /// Decoded text not read yet, from `pos` on.
+    decoded: Vec<u8>,
+    pos: usize,
+}
+
+/// Detects the encoding of `reader` from its first bytes and skips its byte
+/// order mark.
+///
+/// # Errors
+///
+/// The `decode` function will return an error if `reader` fails.
+pub fn decode<R: BufRead>(mut reader: R) -> io::Result<Decoder<R>> {
+    let encoding = Encoding::detect(reader.fill_buf()?);
+    reader.consume(encoding.bom().len());
+
+    Ok(Decoder {
+        inner: reader,
+        encoding,
+        odd_byte: None,
+        surrogate: None,
+        decoded: Vec::new(),
+        pos: 0,
+    })
+}
+
+impl<R> Decoder<R> {
+    /// Returns the encoding the text is being decoded from.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+}
+
+impl<R: BufRead> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let little_endian = match self.encoding {
+            Encoding::Utf8 | Encoding::Utf8WithBom => return self.inner.read(buf),
+            Encoding::Utf16Le => true,
+            Encoding::Utf16Be => false,
+        };
+
+        while self.pos == self.decoded.len() {
+            self.decoded.clear();
+            self.pos = 0;
+
+            let bytes = self.inner.fill_buf()?;
+            let read = bytes.len();
+            if read == 0 {
+                if self.odd_byte.take().is_none() && self.surrogate.take().is_none() {
+                    return Ok(0);
+                }
+                push_char(&mut self.decoded, char::REPLACEMENT_CHARACTER);
+                break;
+            }
+
+            for &byte in bytes {
+                let Some(first) = self.odd_byte.take() else {
+                    self.odd_byte = Some(byte);
+                    continue;
+                };
+                let unit = if little_endian {
+                    u16::from_le_bytes([first, byte])
+                } else {
+                    u16::from_be_bytes([first, byte])
+                };
+                decode_unit(unit, &mut self.surrogate, &mut self.decoded);
+            }
+            self.inner.consume(read);
+        }
+
+        let len = buf.len().min(self.decoded.len() - self.pos);
+        buf[..len].copy_from_slice(&self.decoded[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Decodes one UTF-16 code unit into `decoded`, keeping high surrogates in
+/// `surrogate` until the unit after them.
+fn decode_unit(unit: u16, surrogate: &mut Option<u16>, decoded: &mut Vec<u8>) {
+    if let Some(high) = surrogate.take() {
+        if (0xDC00..=0xDFFF).contains(&unit) {
+            let c = 0x10000 + ((u32::from(high) - 0xD800) << 10) + (u32::from(unit) - 0xDC00);
+            push_char(decoded, char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER));
+            return;
+        }
+        push_char(decoded, char::REPLACEMENT_CHARACTER);
+    }
+
+    match unit {
+        0xD800..=0xDBFF => *surrogate = Some(unit),
+        0xDC00..=0xDFFF => push_char(decoded, char::REPLACEMENT_CHARACTER),
+        _ => push_char(decoded, char::from_u32(u32::from(unit)).unwrap()),
+    }
+}
+
+fn push_char(decoded: &mut Vec<u8>, c: char) {
+    decoded.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn decoded(bytes: &[u8]) -> String {
+        // A tiny buffer splits code units and surrogate pairs between reads.
+        let mut text = String::new();
+        decode(BufReader::with_capacity(3, bytes))
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn detects_byte_order_marks() {
+        assert_eq!(Encoding::Utf8WithBom, Encoding::detect(b"\xEF\xBB\xBFhi"));
+        assert_eq!(Encoding::Utf16Le, Encoding::detect(b"\xFF\xFEh\0"));
+        assert_eq!(Encoding::Utf16Be, Encoding::detect(b"\xFE\xFF\0h"));
+        assert_eq!(Encoding::Utf8, Encoding::detect(b"hi"));
+        assert_eq!(Encoding::Utf8, Encoding::detect(b""));
+    }
+
+    #[test]
+    fn decodes_utf16_both_ways() {
+        let text = "Rust 🦀\r\nsafe";
+
+        for encoding in [Encoding::Utf16Le, Encoding::Utf16Be, Encoding::Utf8WithBom] {
+            assert_eq!(text, decoded(&encoding.encode(text)), "{encoding:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_utf16_is_replaced() {
+        // A low surrogate alone, a high one followed by a letter, and a
+        // byte with no other one.
+        let bytes = b"\xFF\xFE\x00\xDCa\x00\x00\xD8b\x00c";
+
+        assert_eq!("\u{FFFD}a\u{FFFD}b\u{FFFD}", decoded(bytes));
+    }
+}
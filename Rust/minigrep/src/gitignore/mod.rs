@@ -0,0 +1,243 @@
+//! The rules of `.gitignore` files, so searching a directory can skip the
+//! files git would.
+//!
+//! Each line of a file is a glob: `*` and `?` match within a path component,
+//! `**` matches across them and `[a-z]` matches a set of characters. A
+//! pattern with a `/` before its end is matched against the whole path from
+//! the directory of the file, otherwise against the name alone. A trailing
+//! `/` only matches directories, and a leading `!` takes a path back out.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The rules of one `.gitignore` file.
+#[derive(Debug)]
+pub struct Gitignore {
+    dir: PathBuf,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    glob: Vec<char>,
+    /// Matched against the path from `dir` instead of the name alone.
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+impl Gitignore {
+    /// Parses the rules in `text`, for paths below `dir`.
+    pub fn parse(dir: impl Into<PathBuf>, text: &str) -> Gitignore {
+        Gitignore {
+            dir: dir.into(),
+            rules: text.lines().filter_map(Rule::parse).collect(),
+        }
+    }
+
+    /// Reads the `.gitignore` file in `dir`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// The `from_dir` function will return an error if the file exists but
+    /// can't be read.
+    pub fn from_dir(dir: &Path) -> io::Result<Option<Gitignore>> {
+        match fs::read_to_string(dir.join(".gitignore")) {
+            Ok(text) => Ok(Some(Gitignore::parse(dir, &text))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns whether the last rule matching `path` ignores it, or `None`
+    /// if no rule matches it.
+    pub fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.dir).ok()?;
+        let relative: Vec<char> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+            .chars()
+            .collect();
+        let name_start = relative.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                let target = if rule.anchored {
+                    &relative[..]
+                } else {
+                    &relative[name_start..]
+                };
+                (is_dir || !rule.dir_only) && glob_matches(&rule.glob, target)
+            })
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Returns whether `path` is ignored by the rules of `stack`, the
+/// `.gitignore` files from the root of the walk down to its directory.
+/// Deeper files take precedence over the ones above them.
+pub fn is_ignored(stack: &[Gitignore], path: &Path, is_dir: bool) -> bool {
+    stack
+        .iter()
+        .rev()
+        .find_map(|gitignore| gitignore.matched(path, is_dir))
+        .unwrap_or(false)
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Rule> {
+        let mut line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let negated = line.starts_with('!');
+        if negated {
+            line = &line[1..];
+        }
+        // A backslash lets a pattern start with `#` or `!`.
+        if line.starts_with("\\#") || line.starts_with("\\!") {
+            line = &line[1..];
+        }
+
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(Rule {
+            glob: line.chars().collect(),
+            anchored,
+            dir_only,
+            negated,
+        })
+    }
+}
+
+fn glob_matches(glob: &[char], text: &[char]) -> bool {
+    match glob {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            // `**/` also matches no directories at all.
+            glob_matches(rest, text)
+                || (0..text.len()).any(|i| text[i] == '/' && glob_matches(rest, &text[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        ['*', rest @ ..] => {
+            let component = text.iter().position(|&c| c == '/').unwrap_or(text.len());
+            (0..=component).any(|i| glob_matches(rest, &text[i..]))
+        }
+        ['?', rest @ ..] => matches!(text, [c, ..] if *c != '/') && glob_matches(rest, &text[1..]),
+        ['[', class @ ..] => match (text, class_matches(class, text.first())) {
+            ([_, text @ ..], Some((true, rest))) => glob_matches(rest, text),
+            // A `[` without its `]` is taken as it is.
+            (_, None) => text.first() == Some(&'[') && glob_matches(class, &text[1..]),
+            _ => false,
+        },
+        ['\\', c, rest @ ..] | [c, rest @ ..] => {
+            text.first() == Some(c) && glob_matches(rest, &text[1..])
+        }
+    }
+}
+
+/// Matches `c` against the set at the start of `class`, which follows its
+/// `[`. Returns whether it matched and the rest of the glob after the `]`,
+/// or `None` if the set is never closed.
+fn class_matches<'a>(class: &'a [char], c: Option<&char>) -> Option<(bool, &'a [char])> {
+    let (negated, mut class) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut first = true;
+
+    loop {
+        match class {
+            [']', rest @ ..] if !first => {
+                let matched = c.is_some_and(|&c| c != '/' && found != negated);
+                return Some((matched, rest));
+            }
+            [low, '-', high, rest @ ..] if *high != ']' => {
+                found |= c.is_some_and(|c| (low..=high).contains(&c));
+                class = rest;
+            }
+            [member, rest @ ..] => {
+                found |= c == Some(member);
+                class = rest;
+            }
+            [] => return None,
+        }
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignores(rules: &str, path: &str, is_dir: bool) -> bool {
+        let gitignore = Gitignore::parse("root", rules);
+        gitignore.matched(&Path::new("root").join(path), is_dir) == Some(true)
+    }
+
+    #[test]
+    fn names_match_at_any_depth() {
+        assert!(ignores("*.log", "debug.log", false));
+        assert!(ignores("*.log", "logs/debug.log", false));
+        assert!(ignores("target", "crates/app/target", true));
+        assert!(!ignores("*.log", "debug.log.txt", false));
+        assert!(!ignores("# *.log", "debug.log", false));
+    }
+
+    #[test]
+    fn slashes_anchor_patterns() {
+        assert!(ignores("/build", "build", true));
+        assert!(!ignores("/build", "src/build", true));
+        assert!(ignores("doc/*.txt", "doc/notes.txt", false));
+        assert!(!ignores("doc/*.txt", "doc/api/notes.txt", false));
+        assert!(ignores("doc/**/*.txt", "doc/api/notes.txt", false));
+        assert!(ignores("doc/**/*.txt", "doc/notes.txt", false));
+        assert!(ignores("**/cache", "a/b/cache", true));
+    }
+
+    #[test]
+    fn trailing_slashes_only_match_directories() {
+        assert!(ignores("out/", "out", true));
+        assert!(!ignores("out/", "out", false));
+    }
+
+    #[test]
+    fn later_rules_and_negations_win() {
+        assert!(!ignores("*.log\n!keep.log", "keep.log", false));
+        assert!(ignores("!keep.log\n*.log", "keep.log", false));
+        assert!(ignores("\\!important", "!important", false));
+    }
+
+    #[test]
+    fn classes_and_single_characters() {
+        assert!(ignores("file[0-9].txt", "file7.txt", false));
+        assert!(!ignores("file[!0-9].txt", "file7.txt", false));
+        assert!(ignores("file?.txt", "fileA.txt", false));
+        assert!(!ignores("file?.txt", "file.txt", false));
+    }
+
+    #[test]
+    fn deeper_files_take_precedence() {
+        let stack = [
+            Gitignore::parse("root", "*.txt"),
+            Gitignore::parse("root/docs", "!*.txt"),
+        ];
+
+        assert!(is_ignored(&stack, Path::new("root/notes.txt"), false));
+        assert!(!is_ignored(&stack, Path::new("root/docs/notes.txt"), false));
+        assert!(!is_ignored(&stack, Path::new("root/main.rs"), false));
+    }
+}
@@ -0,0 +1,237 @@
+//! Searching a file as the query is typed, set with `--interactive`.
+//!
+//! The matching lines are drawn again on the `Screen` after each key. The
+//! standard output only gets the lines picked with Enter, so they can be
+//! piped on.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+
+use crate::decode;
+use crate::terminal::{Key, Screen};
+use crate::{Boundary, Config, ExitStatus, Matcher};
+
+/// The lines of a file and those matching the query typed so far.
+#[derive(Debug)]
+pub struct Session {
+    lines: Vec<String>,
+    ignore_case: bool,
+    boundary: Boundary,
+    query: String,
+    /// The indices of the lines matching each prefix of the query, the
+    /// empty one first. Typing a character only searches the lines matching
+    /// the query without it, and erasing one goes back to them.
+    matches: Vec<Vec<usize>>,
+}
+
+impl Session {
+    /// Starts a search of `contents` with an empty query.
+    pub fn new(contents: &str, ignore_case: bool, boundary: Boundary) -> Session {
+        let mut session = Session {
+            lines: contents.lines().map(String::from).collect(),
+            ignore_case,
+            boundary,
+            query: String::new(),
+            matches: Vec::new(),
+        };
+        let all = session.search(0..session.lines.len());
+        session.matches.push(all);
+        session
+    }
+
+    /// Returns the query typed so far.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns the matcher for the query typed so far.
+    pub fn matcher(&self) -> Matcher {
+        Matcher::new(&self.query, self.ignore_case).boundary(self.boundary)
+    }
+
+    /// Adds `c` to the end of the query.
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+
+        // A line containing the longer query contains the shorter one too,
+        // but that isn't so for whole words or lines.
+        let found = if self.boundary == Boundary::None {
+            let last = self.matches.last().unwrap().clone();
+            self.search(last)
+        } else {
+            self.search(0..self.lines.len())
+        };
+        self.matches.push(found);
+    }
+
+    /// Removes the last character of the query, if there is one.
+    pub fn pop(&mut self) {
+        if self.query.pop().is_some() {
+            self.matches.pop();
+        }
+    }
+
+    /// Returns the lines matching the query, with their line numbers
+    /// starting at 1.
+    pub fn matches(&self) -> impl ExactSizeIterator<Item = (usize, &str)> {
+        self.matches
+            .last()
+            .unwrap()
+            .iter()
+            .map(|&i| (i + 1, self.lines[i].as_str()))
+    }
+
+    /// Returns how many lines there are.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    fn search(&self, candidates: impl IntoIterator<Item = usize>) -> Vec<usize> {
+        let matcher = self.matcher();
+        candidates
+            .into_iter()
+            .filter(|&i| matcher.is_match(&self.lines[i]))
+            .collect()
+    }
+}
+
+/// Searches `config.file_path` as the query is typed. Enter leaves and
+/// prints the matching lines as a search would, and Escape or Ctrl-C leave
+/// without printing anything.
+///
+/// # Errors
+///
+/// The `run` function will return an error if the file can't be read, or
+/// if there's no terminal to type in.
+pub fn run(config: &Config) -> io::Result<ExitStatus> {
+    let bytes = fs::read(&config.file_path)?;
+    let mut contents = String::new();
+    decode::decode(&bytes[..])?.read_to_string(&mut contents)?;
+    let mut session = Session::new(&contents, config.ignore_case, config.boundary);
+
+    let picked = read_keys(&mut session, &Screen::open()?)?;
+
+    if !picked || session.matches().len() == 0 {
+        return Ok(ExitStatus::NotFound);
+    }
+    let mut stdout = io::stdout().lock();
+    for (_, line) in session.matches() {
+        writeln!(stdout, "{line}")?;
+    }
+    Ok(ExitStatus::Found)
+}
+
+/// Updates `session` with the keys typed on `screen` until Enter, for
+/// which it returns `true`, or Escape, Ctrl-C or Ctrl-D.
+fn read_keys(session: &mut Session, screen: &Screen) -> io::Result<bool> {
+    let (rows, columns) = screen.size();
+
+    draw(session, screen, rows, columns)?;
+    for key in screen.keys() {
+        match key? {
+            Key::Enter => return Ok(true),
+            Key::Quit => return Ok(false),
+            Key::Backspace => session.pop(),
+            Key::Char(c) => session.push(c),
+        }
+        draw(session, screen, rows, columns)?;
+    }
+
+    Ok(false)
+}
+
+/// Draws the query and as many of the matching lines as fit on a screen of
+/// `rows` by `columns`.
+fn draw(session: &Session, screen: &Screen, rows: usize, columns: usize) -> io::Result<()> {
+    let matcher = session.matcher();
+    let found = session.matches().len();
+
+    let mut text = format!(
+        "> {}  ({found} of {} lines)\r\n",
+        session.query(),
+        session.len()
+    );
+    for (line_number, line) in session.matches().take(rows.saturating_sub(1)) {
+        let prefix = format!("{line_number}:");
+        let width = columns.saturating_sub(prefix.chars().count());
+        text.push_str(&prefix);
+        text.push_str(&highlighted(line, &matcher.spans(line), width));
+        text.push_str("\r\n");
+    }
+    text.push_str(&format!("\x1b[1;{}H", session.query().chars().count() + 3));
+
+    screen.draw(&text)
+}
+
+/// Returns the first `width` characters of `line`, with `spans` in bold
+/// red.
+fn highlighted(line: &str, spans: &[Range<usize>], width: usize) -> String {
+    let end = line.char_indices().nth(width).map_or(line.len(), |(i, _)| i);
+    let mut text = String::new();
+    let mut last = 0;
+
+    for span in spans.iter().take_while(|span| span.start < end) {
+        text.push_str(&line[last..span.start]);
+        text.push_str("\x1b[1;31m");
+        text.push_str(&line[span.start..span.end.min(end)]);
+        text.push_str("\x1b[0m");
+        last = span.end.min(end);
+    }
+    text.push_str(&line[last..end]);
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENTS: &str = "\
+Rust:
+safe, fast, productive.
+Trust me.
+rusty";
+
+    fn numbers(session: &Session) -> Vec<usize> {
+        session.matches().map(|(line_number, _)| line_number).collect()
+    }
+
+    #[test]
+    fn typing_narrows_the_lines_and_erasing_widens_them() {
+        let mut session = Session::new(CONTENTS, true, Boundary::None);
+        assert_eq!(vec![1, 2, 3, 4], numbers(&session));
+
+        "rust".chars().for_each(|c| session.push(c));
+        assert_eq!(vec![1, 3, 4], numbers(&session));
+        session.push('y');
+        assert_eq!(vec![4], numbers(&session));
+
+        session.pop();
+        session.pop();
+        assert_eq!("rus", session.query());
+        assert_eq!(vec![1, 3, 4], numbers(&session));
+    }
+
+    #[test]
+    fn whole_words_are_searched_again() {
+        let mut session = Session::new(CONTENTS, false, Boundary::Word);
+
+        "fas".chars().for_each(|c| session.push(c));
+        assert!(numbers(&session).is_empty());
+        session.push('t');
+        assert_eq!(vec![2], numbers(&session));
+    }
+
+    #[test]
+    fn highlights_what_fits() {
+        let spans = [0..1, 2..4];
+
+        assert_eq!("\x1b[1;31ma\x1b[0mb\x1b[1;31mc\x1b[0m", highlighted("abcd", &spans, 3));
+        assert_eq!("\x1b[1;31ma\x1b[0mb", highlighted("abcd", &spans, 2));
+    }
+}
@@ -0,0 +1,123 @@
+//! Just enough JSON to write results for other tools to read.
+
+use std::fmt;
+
+/// A JSON value, written out with `Display`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// The members of an object, written in this order.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Builds an object from its members.
+    pub fn object<'a>(members: impl IntoIterator<Item = (&'a str, Json)>) -> Json {
+        Json::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Json {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Json {
+        Json::Number(value as f64)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{value}"),
+            // JSON has no NaN or infinity.
+            Json::Number(value) if !value.is_finite() => write!(f, "null"),
+            Json::Number(value) => write!(f, "{value}"),
+            Json::String(value) => write_string(f, value),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_nested_values() {
+        let value = Json::object([
+            ("file", Json::from("poem.txt")),
+            ("line_number", Json::from(3)),
+            ("spans", Json::Array(vec![Json::Array(vec![Json::from(0), Json::from(4)])])),
+            ("binary", Json::Bool(false)),
+            ("missing", Json::Null),
+        ]);
+
+        assert_eq!(
+            r#"{"file":"poem.txt","line_number":3,"spans":[[0,4]],"binary":false,"missing":null}"#,
+            value.to_string()
+        );
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let value = Json::from("say \"hi\"\\\n\u{1}");
+
+        assert_eq!(r#""say \"hi\"\\\n\u0001""#, value.to_string());
+    }
+
+    #[test]
+    fn numbers_that_json_lacks_are_null() {
+        assert_eq!("1.5", Json::Number(1.5).to_string());
+        assert_eq!("null", Json::Number(f64::NAN).to_string());
+    }
+}
@@ -1,6 +1,26 @@
 use std::error::Error;
 use std::fs;
 use std::env;
+use std::io::{self, BufRead, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::process::{ExitCode, Termination};
+use std::time::Instant;
+
+pub mod args;
+pub mod decode;
+pub mod gitignore;
+pub mod interactive;
+pub mod json;
+pub mod matcher;
+pub mod stats;
+pub mod terminal;
+pub mod walk;
+
+use args::{ArgError, Parser};
+use json::Json;
+pub use matcher::{Boundary, Matcher};
+use stats::Stats;
 
 #[cfg(test)]
 mod tests {
@@ -32,66 +52,774 @@ Trust me.";
             search_case_insensitive(query, contents)
         );
     }
+
+    #[test]
+    fn build_takes_query_and_file_path() {
+        let args = ["minigrep", "duct", "poem.txt"].map(String::from);
+
+        let config = Config::build(args.into_iter()).unwrap();
+
+        assert_eq!(vec!["duct"], config.queries);
+        assert_eq!("poem.txt", config.file_path);
+    }
+
+    #[test]
+    fn build_fails_without_file_path() {
+        let args = ["minigrep", "duct"].map(String::from);
+
+        assert_eq!(
+            Err(ArgError::Usage("Didn't get a file path")),
+            Config::build(args.into_iter()).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn inverted() {
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            SearchOutput::Lines(vec![(1, "Rust:"), (3, "Pick three.")]),
+            search_with_mode("fast", contents, false, MatchMode::Inverted)
+        );
+    }
+
+    #[test]
+    fn count() {
+        let contents = "\
+Rust:
+safe, fast, productive.
+Trust me.";
+
+        assert_eq!(
+            SearchOutput::Count(2),
+            search_with_mode("rust", contents, true, MatchMode::Count)
+        );
+    }
+
+    #[test]
+    fn build_reads_mode_flags() {
+        let args = ["minigrep", "-v", "duct", "poem.txt"].map(String::from);
+        assert_eq!(MatchMode::Inverted, Config::build(args.into_iter()).unwrap().mode);
+
+        let args = ["minigrep", "duct", "poem.txt", "-c"].map(String::from);
+        assert_eq!(MatchMode::Count, Config::build(args.into_iter()).unwrap().mode);
+
+        let args = ["minigrep", "--", "-v", "poem.txt"].map(String::from);
+        assert_eq!(vec!["-v"], Config::build(args.into_iter()).unwrap().queries);
+    }
+
+    #[test]
+    fn json_results_have_line_numbers_and_spans() {
+        let config = Config {
+            queries: vec![String::from("fast")],
+            file_path: String::from("poem.txt"),
+            ignore_case: false,
+            mode: MatchMode::Normal,
+            boundary: Boundary::None,
+            max_count: None,
+            quiet: false,
+            json: true,
+            replace: None,
+            write: false,
+            no_ignore: false,
+            stats: false,
+            interactive: false,
+        };
+        let contents = "Rust:\nsafe, fast, productive.";
+
+        let mut results = Vec::new();
+        search_reader(&config.matcher(), contents.as_bytes(), config.mode, None, |number, line| {
+            results.push(json_line(&config.matcher(), "poem.txt", number, line).to_string());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(
+            vec![concat!(
+                r#"{"file":"poem.txt","line_number":2,"line":"safe, fast, productive.","#,
+                r#""spans":[{"start":6,"end":10}]}"#
+            )],
+            results
+        );
+    }
+
+    #[test]
+    fn replaces_every_match() {
+        let contents = "Rust:\nTrust rust.\n";
+
+        assert_eq!("Rust:\nTGo Go.\n", replace_matches("rust", contents, false, "Go"));
+        assert_eq!("C:\nTC C.\n", replace_matches("rust", contents, true, "C"));
+    }
+
+    #[test]
+    fn build_reads_replace_flags() {
+        let args = ["minigrep", "--replace", "Go", "--write", "rust", "poem.txt"].map(String::from);
+        let config = Config::build(args.into_iter()).unwrap();
+        assert_eq!(Some("Go"), config.replace.as_deref());
+        assert!(config.write);
+
+        let args = ["minigrep", "--write", "rust", "poem.txt"].map(String::from);
+        assert!(Config::build(args.into_iter()).is_err());
+
+        let args = ["minigrep", "-c", "--replace", "Go", "rust", "poem.txt"].map(String::from);
+        assert!(Config::build(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn write_replaces_the_file() {
+        let path = env::temp_dir().join(format!("minigrep-write-{}.txt", std::process::id()));
+        fs::write(&path, "old text").unwrap();
+
+        write_atomically(&path, b"new text").unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!("new text", contents);
+    }
+
+    #[test]
+    fn spans_of_each_matching_line() {
+        let contents = "\
+Rust:
+safe, fast, productive.
+Trust rust.";
+
+        assert_eq!(
+            vec![LineMatch {
+                line_number: 3,
+                line: "Trust rust.",
+                spans: vec![1..5, 6..10],
+            }],
+            search_spans("rust", contents)
+        );
+        assert_eq!(
+            vec![0..4],
+            search_spans_case_insensitive("rust", contents)[0].spans
+        );
+    }
+
+    #[test]
+    fn build_reads_boundary_flags() {
+        let args = ["minigrep", "-w", "duct", "poem.txt"].map(String::from);
+        assert_eq!(Boundary::Word, Config::build(args.into_iter()).unwrap().boundary);
+
+        let args = ["minigrep", "-x", "-w", "duct", "poem.txt"].map(String::from);
+        assert!(Config::build(args.into_iter()).is_err());
+    }
+
+    #[test]
+    fn max_count_stops_the_search() {
+        let matcher = Matcher::new("a", false);
+        let contents = "a\nb\na\na";
+
+        assert_eq!(
+            SearchOutput::Lines(vec![(1, "a"), (3, "a")]),
+            search_with(&matcher, contents, MatchMode::Normal, Some(2))
+        );
+        assert_eq!(
+            SearchOutput::Count(1),
+            search_with(&matcher, contents, MatchMode::Count, Some(1))
+        );
+        assert_eq!(
+            SearchOutput::Lines(vec![]),
+            search_with(&matcher, contents, MatchMode::Inverted, Some(0))
+        );
+    }
+
+    #[test]
+    fn max_count_reads_no_line_after_the_last_match() {
+        let matcher = Matcher::new("a", false);
+        let lines = vec![
+            Ok("a"),
+            Ok("b"),
+            Ok("a"),
+            Err(io::Error::new(io::ErrorKind::InvalidData, "not UTF-8")),
+        ];
+
+        let pulled = std::cell::Cell::new(0);
+        let lines = lines.into_iter().inspect(|_| pulled.set(pulled.get() + 1));
+
+        let mut picked = Vec::new();
+        let count = pick_lines(&matcher, lines, MatchMode::Normal, Some(2), |number, _| {
+            picked.push(number);
+            Ok(())
+        });
+
+        assert_eq!(2, count.unwrap());
+        assert_eq!(vec![1, 3], picked);
+        assert_eq!(3, pulled.get());
+    }
+
+    #[test]
+    fn build_reads_max_count_and_quiet() {
+        let args = ["minigrep", "-q", "-m", "3", "duct", "poem.txt"].map(String::from);
+        let config = Config::build(args.into_iter()).unwrap();
+        assert_eq!(Some(3), config.max_count);
+        assert!(config.quiet);
+
+        let args = ["minigrep", "-m", "many", "duct", "poem.txt"].map(String::from);
+        assert_eq!(
+            Err(ArgError::Usage("-m needs a number of lines")),
+            Config::build(args.into_iter()).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn build_takes_every_pattern() {
+        let args = ["minigrep", "-e", "duct", "-e", "-v", "poem.txt"].map(String::from);
+        let config = Config::build(args.into_iter()).unwrap();
+
+        assert_eq!(vec!["duct", "-v"], config.queries);
+        assert_eq!("poem.txt", config.file_path);
+    }
+
+    #[test]
+    fn build_takes_no_query_when_interactive() {
+        let args = ["minigrep", "--interactive", "poem.txt"].map(String::from);
+        let config = Config::build(args.into_iter()).unwrap();
+        assert!(config.interactive);
+        assert!(config.queries.is_empty());
+        assert_eq!("poem.txt", config.file_path);
+
+        let args = ["minigrep", "--interactive", "-c", "poem.txt"].map(String::from);
+        assert!(Config::build(args.into_iter()).is_err());
+    }
 }
 
 pub struct Config {
-    pub query: String,
+    /// What to look for: the first argument, or the patterns given with
+    /// `-e <pattern>` and `-f <file>` instead. Lines match if they contain
+    /// any of them.
+    pub queries: Vec<String>,
     pub file_path: String,
     pub ignore_case: bool,
+    pub mode: MatchMode,
+    pub boundary: Boundary,
+    /// Stop searching a file after this many lines are picked, set with
+    /// `-m <count>`.
+    pub max_count: Option<usize>,
+    /// Print nothing, only tell whether anything was found through the exit
+    /// status, set with `-q`.
+    pub quiet: bool,
+    /// Print one JSON object per result instead of the lines, set with
+    /// `--json`.
+    pub json: bool,
+    /// Print the file with every match replaced by this text instead, set
+    /// with `--replace <replacement>`.
+    pub replace: Option<String>,
+    /// Write the replaced text back to the file instead of printing it, set
+    /// with `--write`.
+    pub write: bool,
+    /// Search the files ignored by `.gitignore` files too when searching a
+    /// directory, set with `--no-ignore`.
+    pub no_ignore: bool,
+    /// Print how many files and lines were gone through after the results,
+    /// set with `--stats`.
+    pub stats: bool,
+    /// Search the file as the query is typed instead, set with
+    /// `--interactive`, in which case no query is given.
+    pub interactive: bool,
+}
+
+/// Which lines a search picks, and what it reports about them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchMode {
+    /// The lines containing the query.
+    #[default]
+    Normal,
+    /// The lines not containing the query, set with `-v`.
+    Inverted,
+    /// How many lines contain the query, set with `-c`.
+    Count,
+}
+
+/// How the program ends, as grep does: `0` if something was found, `1` if
+/// nothing was and `2` if the search failed. Printing the help with
+/// `--help` ends with `0` too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Found = 0,
+    NotFound = 1,
+    Error = 2,
+}
+
+impl Termination for ExitStatus {
+    fn report(self) -> ExitCode {
+        ExitCode::from(self as u8)
+    }
+}
+
+/// What a search found, depending on its `MatchMode`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SearchOutput<'a> {
+    /// The picked lines, with their line numbers starting at 1.
+    Lines(Vec<(usize, &'a str)>),
+    Count(usize),
+}
+
+/// A line where the query was found, for tools that need to know exactly
+/// where.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LineMatch<'a> {
+    /// The number of the line, starting at 1.
+    pub line_number: usize,
+    pub line: &'a str,
+    /// The byte ranges of `line` holding each occurrence of the query.
+    pub spans: Vec<Range<usize>>,
 }
 
 impl Config {
+    /// Returns the parser for the arguments of minigrep, which also writes
+    /// its `--help`.
+    pub fn parser() -> Parser {
+        Parser::new("minigrep", "Prints the lines of a file, or of the files in a directory, \
+            containing a query.")
+            .flag("-v", "Print the lines not containing the query instead")
+            .flag("-c", "Print how many lines contain the query instead")
+            .flag("-w", "Only match whole words")
+            .flag("-x", "Only match whole lines")
+            .option("-m", "count", "Stop searching a file after this many lines")
+            .flag("-q", "Print nothing, only exit with 0 if something was found")
+            .option("-e", "pattern", "Look for this pattern, instead of the query")
+            .option("-f", "file", "Look for the patterns in this file, one per line")
+            .flag("--json", "Print one JSON object per result")
+            .option("--replace", "replacement", "Print the file with the matches replaced")
+            .flag("--write", "Write the replaced text back to the file")
+            .flag("--no-ignore", "Search the files .gitignore files ignore too")
+            .flag("--stats", "Print how much was searched after the results")
+            .flag("--interactive", "Search the file as the query is typed")
+            .positional("QUERY", "What to look for, unless -e, -f or --interactive are given")
+            .positional("FILE", "The file or directory to search")
+    }
+
+    /// Builds the configuration from the arguments of the program, its name
+    /// first.
+    ///
+    /// # Errors
+    ///
+    /// The `build` function will return `ArgError::Help` if `--help` is
+    /// given, and an error if the arguments aren't valid or don't go
+    /// together.
     pub fn build(
         mut args: impl Iterator<Item = String>,
-    ) -> Result<Config, &'static str> {
+    ) -> Result<Config, ArgError> {
         args.next();
+        let matches = Config::parser().parse(args)?;
 
-        let query = match args.next() {
-            Some(arg) => arg,
-            None => return Err("Didn't get a query string"),
+        let invert = matches.flag("-v");
+        let count = matches.flag("-c");
+        let max_count = match matches.value("-m") {
+            Some(count) => Some(
+                count
+                    .parse()
+                    .map_err(|_| ArgError::Usage("-m needs a number of lines"))?,
+            ),
+            None => None,
+        };
+        let quiet = matches.flag("-q");
+        let json = matches.flag("--json");
+        let replace = matches.value("--replace").map(String::from);
+        let write = matches.flag("--write");
+        let interactive = matches.flag("--interactive");
+
+        let mut queries: Option<Vec<String>> = None;
+        for pattern in matches.values("-e") {
+            queries.get_or_insert_with(Vec::new).push(pattern.to_string());
+        }
+        for file in matches.values("-f") {
+            let patterns = fs::read_to_string(file)
+                .map_err(|_| ArgError::Usage("Couldn't read the file of patterns"))?;
+            queries
+                .get_or_insert_with(Vec::new)
+                .extend(patterns.lines().map(String::from));
+        }
+
+        let mode = match (invert, count) {
+            (false, false) => MatchMode::Normal,
+            (true, false) => MatchMode::Inverted,
+            (false, true) => MatchMode::Count,
+            (true, true) => return Err(ArgError::Usage("-v and -c can't be used together")),
         };
 
-        let file_path = match args.next() {
+        let boundary = match (matches.flag("-w"), matches.flag("-x")) {
+            (false, false) => Boundary::None,
+            (true, false) => Boundary::Word,
+            (false, true) => Boundary::Line,
+            (true, true) => return Err(ArgError::Usage("-w and -x can't be used together")),
+        };
+
+        let searching_only = mode != MatchMode::Normal || max_count.is_some() || quiet || json;
+        if replace.is_some() && searching_only {
+            return Err(ArgError::Usage(
+                "--replace can't be used with -v, -c, -m, -q or --json",
+            ));
+        }
+        if write && replace.is_none() {
+            return Err(ArgError::Usage("--write needs --replace"));
+        }
+        if interactive && (searching_only || replace.is_some() || queries.is_some()) {
+            return Err(ArgError::Usage(
+                "--interactive can't be used with -v, -c, -m, -q, -e, -f, --json or --replace",
+            ));
+        }
+
+        let mut positional = matches.positionals().iter().cloned();
+
+        let queries = match queries {
+            Some(queries) => queries,
+            None if interactive => Vec::new(),
+            None => match positional.next() {
+                Some(arg) => vec![arg],
+                None => return Err(ArgError::Usage("Didn't get a query string")),
+            },
+        };
+
+        let file_path = match positional.next() {
             Some(arg) => arg,
-            None => return Err("Didn't get a file path"),
+            None => return Err(ArgError::Usage("Didn't get a file path")),
         };
 
         let ignore_case = env::var("IGNORE_CASE").is_ok();
 
         Ok(Config {
-            query,
+            queries,
             file_path,
             ignore_case,
+            mode,
+            boundary,
+            max_count,
+            quiet,
+            json,
+            replace,
+            write,
+            no_ignore: matches.flag("--no-ignore"),
+            stats: matches.flag("--stats"),
+            interactive,
         })
     }
+
+    /// Returns the matcher for the queries, as the options say.
+    pub fn matcher(&self) -> Matcher {
+        Matcher::any(&self.queries, self.ignore_case).boundary(self.boundary)
+    }
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.file_path)?;
+/// Searches the file at `config.file_path`, or every file below it if it's
+/// a directory, in which case each result starts with the path of its file.
+///
+/// Files are read a line at a time, so they can be bigger than the memory
+/// there is, unless their matches are being replaced. Files in UTF-16 are
+/// read as UTF-8, as `decode` does.
+///
+/// Returns whether anything was found. When `config.quiet` is set, the
+/// search stops as soon as something is, and nothing is printed, not even
+/// the stats.
+///
+/// # Errors
+///
+/// The `run` function will return an error if a file can't be read or
+/// written.
+pub fn run(config: Config) -> Result<ExitStatus, Box<dyn Error>> {
+    if config.interactive {
+        return Ok(interactive::run(&config)?);
+    }
+
+    let start = Instant::now();
+    let root = Path::new(&config.file_path);
+    let mut stats = Stats::default();
+
+    if root.is_dir() {
+        for path in walk::files(root, !config.no_ignore)? {
+            match search_file(&config, &path, true) {
+                Ok(file_stats) => stats += file_stats,
+                // Files that aren't text, like images, have nothing to find.
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => continue,
+                Err(err) => return Err(err.into()),
+            }
+
+            if stats.matches > 0 && config.quiet {
+                break;
+            }
+        }
+    } else {
+        stats = search_file(&config, root, false)?;
+    }
+
+    if config.stats && !config.quiet {
+        stats.elapsed = start.elapsed();
+        println!("\n{stats}");
+    }
 
-    let results = if config.ignore_case {
-        search_case_insensitive(&config.query, &contents)
+    if stats.matches > 0 {
+        Ok(ExitStatus::Found)
     } else {
-        search(&config.query, &contents)
+        Ok(ExitStatus::NotFound)
+    }
+}
+
+/// Searches one file and prints what was found, returning the counts of the
+/// search.
+fn search_file(config: &Config, path: &Path, show_path: bool) -> io::Result<Stats> {
+    let matcher = config.matcher();
+
+    if let Some(replacement) = &config.replace {
+        let bytes = fs::read(path)?;
+        let mut decoder = decode::decode(&bytes[..])?;
+        let mut contents = String::new();
+        decoder.read_to_string(&mut contents)?;
+
+        let replaced = matcher.replace(&contents, replacement);
+        let matches = contents.lines().filter(|line| matcher.is_match(line)).count();
+
+        if config.write {
+            if replaced != contents {
+                // The file keeps its encoding.
+                write_atomically(path, &decoder.encoding().encode(&replaced))?;
+            }
+        } else {
+            print!("{replaced}");
+        }
+        return Ok(Stats::file(matches, bytes.len() as u64));
+    }
+
+    let mut file = CountingReader {
+        inner: fs::File::open(path)?,
+        bytes: 0,
     };
+    let decoder = decode::decode(io::BufReader::new(&mut file))?;
+    let file_name = path.to_string_lossy();
+    let prefix = if show_path {
+        format!("{file_name}:")
+    } else {
+        String::new()
+    };
+    let mut out = io::stdout().lock();
+
+    // Quietly, the first line is enough to know.
+    let max_count = if config.quiet { Some(1) } else { config.max_count };
+    let printed = !config.quiet && config.mode != MatchMode::Count;
+
+    let count = search_reader(
+        &matcher,
+        io::BufReader::new(decoder),
+        config.mode,
+        max_count,
+        |line_number, line| match (printed, config.json) {
+            (false, _) => Ok(()),
+            (true, true) => writeln!(out, "{}", json_line(&matcher, &file_name, line_number, line)),
+            (true, false) => writeln!(out, "{prefix}{line}"),
+        },
+    )?;
+
+    if config.mode == MatchMode::Count && !config.quiet {
+        if config.json {
+            let result = Json::object([("file", (*file_name).into()), ("count", count.into())]);
+            writeln!(out, "{result}")?;
+        } else {
+            writeln!(out, "{prefix}{count}")?;
+        }
+    }
 
-    for line in results {
-        println!("{line}");
+    Ok(Stats::file(count, file.bytes))
+}
+
+/// A reader counting the bytes read through it.
+struct CountingReader<R> {
+    inner: R,
+    bytes: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes += read as u64;
+        Ok(read)
     }
+}
+
+/// Turns a picked line into a JSON object, with the byte ranges the query
+/// was found at.
+fn json_line(matcher: &Matcher, file: &str, line_number: usize, line: &str) -> Json {
+    let spans = matcher
+        .spans(line)
+        .into_iter()
+        .map(|span| Json::object([("start", span.start.into()), ("end", span.end.into())]))
+        .collect();
 
-    Ok(())
+    Json::object([
+        ("file", file.into()),
+        ("line_number", line_number.into()),
+        ("line", line.into()),
+        ("spans", Json::Array(spans)),
+    ])
+}
+
+/// Returns `contents` with every match of `query` replaced by
+/// `replacement`.
+///
+/// The replacement is inserted as it is. Queries are plain text, so there
+/// are no capture groups for it to refer to.
+pub fn replace_matches(
+    query: &str,
+    contents: &str,
+    ignore_case: bool,
+    replacement: &str,
+) -> String {
+    Matcher::new(query, ignore_case).replace(contents, replacement)
+}
+
+/// Replaces the file at `path` with `contents`, so that readers see either
+/// the old file or the new one and never half of it.
+///
+/// The contents go to a temporary file next to it first, which is then
+/// renamed over it, keeping its permissions.
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".minigrep-{}", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::set_permissions(&temp_path, fs::metadata(path)?.permissions())?;
+        fs::rename(&temp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
 }
 
 pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
-    contents
-        .lines()
-        .filter(|line| line.contains(query))
-        .collect()
+    lines_of(search_with_mode(query, contents, false, MatchMode::Normal))
 }
 
 pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+    lines_of(search_with_mode(query, contents, true, MatchMode::Normal))
+}
+
+/// Returns the lines of `contents` containing `query`, with where in them
+/// each occurrence is.
+pub fn search_spans<'a>(query: &str, contents: &'a str) -> Vec<LineMatch<'a>> {
+    line_matches(&Matcher::new(query, false), contents)
+}
+
+pub fn search_spans_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<LineMatch<'a>> {
+    line_matches(&Matcher::new(query, true), contents)
+}
+
+fn line_matches<'a>(matcher: &Matcher, contents: &'a str) -> Vec<LineMatch<'a>> {
     contents
         .lines()
-        .filter(|line| line.to_lowercase().contains(&query.to_lowercase()))
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let spans = matcher.spans(line);
+            (!spans.is_empty()).then(|| LineMatch {
+                line_number: i + 1,
+                line,
+                spans,
+            })
+        })
         .collect()
-}
\ No newline at end of file
+}
+
+pub fn search_with_mode<'a>(
+    query: &str,
+    contents: &'a str,
+    ignore_case: bool,
+    mode: MatchMode,
+) -> SearchOutput<'a> {
+    search_with(&Matcher::new(query, ignore_case), contents, mode, None)
+}
+
+/// Goes through the lines of `contents` once, picking them as `mode` says,
+/// and stops once `max_count` lines are picked if it's set.
+pub fn search_with<'a>(
+    matcher: &Matcher,
+    contents: &'a str,
+    mode: MatchMode,
+    max_count: Option<usize>,
+) -> SearchOutput<'a> {
+    let mut lines = Vec::new();
+    let lines_read = contents.lines().map(Ok);
+
+    let count = pick_lines(matcher, lines_read, mode, max_count, |line_number, line| {
+        if mode != MatchMode::Count {
+            lines.push((line_number, line));
+        }
+        Ok(())
+    })
+    .expect("lines of a string are never errors");
+
+    match mode {
+        MatchMode::Count => SearchOutput::Count(count),
+        MatchMode::Normal | MatchMode::Inverted => SearchOutput::Lines(lines),
+    }
+}
+
+/// Goes through the lines of `reader` like `search_with`, calling `picked`
+/// with each line it picks and its number instead of keeping them. Only one
+/// line is in memory at a time.
+///
+/// Returns how many lines were picked.
+///
+/// # Errors
+///
+/// The `search_reader` function will return an error if `reader` fails or
+/// isn't UTF-8, or if `picked` does.
+pub fn search_reader<R: BufRead>(
+    matcher: &Matcher,
+    reader: R,
+    mode: MatchMode,
+    max_count: Option<usize>,
+    mut picked: impl FnMut(usize, &str) -> io::Result<()>,
+) -> io::Result<usize> {
+    pick_lines(matcher, reader.lines(), mode, max_count, |line_number, line| {
+        picked(line_number, &line)
+    })
+}
+
+fn pick_lines<L: AsRef<str>>(
+    matcher: &Matcher,
+    lines: impl Iterator<Item = io::Result<L>>,
+    mode: MatchMode,
+    max_count: Option<usize>,
+    mut picked: impl FnMut(usize, L) -> io::Result<()>,
+) -> io::Result<usize> {
+    let mut count = 0;
+    if max_count == Some(0) {
+        return Ok(count);
+    }
+
+    // Stopping right after the last wanted line, rather than when the next
+    // one comes, keeps a pipe from being waited on for a line that isn't
+    // needed.
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        if matcher.is_match(line.as_ref()) != (mode == MatchMode::Inverted) {
+            count += 1;
+            picked(i + 1, line)?;
+            if max_count == Some(count) {
+                break;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+fn lines_of<'a>(output: SearchOutput<'a>) -> Vec<&'a str> {
+    match output {
+        SearchOutput::Lines(lines) => lines.into_iter().map(|(_, line)| line).collect(),
+        SearchOutput::Count(_) => unreachable!("only count mode counts"),
+    }
+}
@@ -1,16 +1,23 @@
 use std::env;
-use std::process;
 
-use minigrep::Config;
+use minigrep::args::ArgError;
+use minigrep::{Config, ExitStatus};
 
-fn main() {    
-    let config = Config::build(env::args()).unwrap_or_else(|err| {
-        eprintln!("Problem parsing arguments: {err}");
-        process::exit(1);
-    });
+fn main() -> ExitStatus {
+    let config = match Config::build(env::args()) {
+        Ok(config) => config,
+        Err(ArgError::Help) => {
+            print!("{}", Config::parser().help());
+            return ExitStatus::Found;
+        }
+        Err(err) => {
+            eprintln!("Problem parsing arguments: {err}");
+            return ExitStatus::Error;
+        }
+    };
 
-    if let Err(e) = minigrep::run(config) {
+    minigrep::run(config).unwrap_or_else(|e| {
         eprintln!("Application error: {e}");
-        process::exit(1);
-    }
-}
\ No newline at end of file
+        ExitStatus::Error
+    })
+}
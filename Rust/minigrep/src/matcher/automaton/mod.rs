@@ -0,0 +1,135 @@
+//! An Aho-Corasick automaton, finding any of several queries in one pass
+//! over a line.
+//!
+//! The queries make a trie of characters. Each state also links to the
+//! longest suffix of its path that is a path too, so that a character with
+//! nowhere to go from one state can be tried on that suffix instead.
+
+use std::collections::VecDeque;
+
+/// The automaton for a set of queries.
+#[derive(Debug, Clone)]
+pub(super) struct Automaton {
+    states: Vec<State>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct State {
+    /// The next state for each character, sorted by character.
+    next: Vec<(char, usize)>,
+    /// The state of the longest proper suffix of this one's path.
+    fail: usize,
+    /// The lengths, in characters, of the queries ending at this state.
+    ends: Vec<usize>,
+}
+
+impl Automaton {
+    /// The state no character has been fed to.
+    pub(super) const START: usize = 0;
+
+    /// Builds the automaton for `queries`, given by their characters.
+    pub(super) fn new(queries: &[Vec<char>]) -> Automaton {
+        let mut states = vec![State::default()];
+
+        for query in queries {
+            let mut state = Automaton::START;
+            for &c in query {
+                state = match states[state].goto(c) {
+                    Some(next) => next,
+                    None => {
+                        states.push(State::default());
+                        let next = states.len() - 1;
+                        let transitions = &mut states[state].next;
+                        let at = transitions.partition_point(|&(other, _)| other < c);
+                        transitions.insert(at, (c, next));
+                        next
+                    }
+                };
+            }
+            if !states[state].ends.contains(&query.len()) {
+                states[state].ends.push(query.len());
+            }
+        }
+
+        // Suffixes are shorter than the paths they're suffixes of, so going
+        // through the states breadth-first links each one after its suffix.
+        let mut queue: VecDeque<usize> =
+            states[Automaton::START].next.iter().map(|&(_, next)| next).collect();
+
+        while let Some(state) = queue.pop_front() {
+            for (c, next) in states[state].next.clone() {
+                let mut fail = states[state].fail;
+                let fail = loop {
+                    if let Some(target) = states[fail].goto(c) {
+                        break target;
+                    }
+                    if fail == Automaton::START {
+                        break Automaton::START;
+                    }
+                    fail = states[fail].fail;
+                };
+
+                states[next].fail = fail;
+                let inherited = states[fail].ends.clone();
+                states[next].ends.extend(inherited);
+                queue.push_back(next);
+            }
+        }
+
+        Automaton { states }
+    }
+
+    /// Returns the state after feeding `c` to `state`.
+    pub(super) fn step(&self, mut state: usize, c: char) -> usize {
+        loop {
+            if let Some(next) = self.states[state].goto(c) {
+                return next;
+            }
+            if state == Automaton::START {
+                return Automaton::START;
+            }
+            state = self.states[state].fail;
+        }
+    }
+
+    /// Returns the lengths, in characters, of the queries ending with the
+    /// last character fed to reach `state`.
+    pub(super) fn ends(&self, state: usize) -> &[usize] {
+        &self.states[state].ends
+    }
+}
+
+impl State {
+    fn goto(&self, c: char) -> Option<usize> {
+        self.next
+            .binary_search_by_key(&c, |&(other, _)| other)
+            .ok()
+            .map(|i| self.next[i].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_overlapping_queries() {
+        let queries: Vec<Vec<char>> = ["he", "she", "his", "hers"]
+            .iter()
+            .map(|query| query.chars().collect())
+            .collect();
+        let automaton = Automaton::new(&queries);
+
+        let mut found = Vec::new();
+        let mut state = Automaton::START;
+        for (i, c) in "ushers".chars().enumerate() {
+            state = automaton.step(state, c);
+            for &len in automaton.ends(state) {
+                found.push((i + 1 - len, i + 1));
+            }
+        }
+        found.sort();
+
+        assert_eq!(vec![(1, 4), (2, 4), (2, 6)], found);
+    }
+}
@@ -0,0 +1,107 @@
+//! Boyer-Moore-Horspool search for one query with the case of ASCII letters
+//! ignored, compared from its last byte.
+//!
+//! When the byte of the text under the end of the query isn't in the query,
+//! the query can move past it entirely, so the longer the query the fewer
+//! bytes are looked at. The table of how far to move is built once, rather
+//! than for every line, and the text doesn't have to be lowercased first.
+//!
+//! Searching UTF-8 bytes for UTF-8 bytes only ever finds whole characters,
+//! so the matches are at character boundaries.
+
+/// A query ready to be searched for.
+#[derive(Debug, Clone)]
+pub(super) struct Horspool {
+    /// The query, lowercased.
+    query: Vec<u8>,
+    /// How far the query can move when each byte is under its end, except
+    /// for its last byte, which is `0` there.
+    shifts: Box<[usize; 256]>,
+    /// How far the query can move when its last byte is under its end but
+    /// the rest of it doesn't match.
+    last_shift: usize,
+}
+
+impl Horspool {
+    /// Prepares the search for `query`, which mustn't be empty.
+    pub(super) fn new(query: &str) -> Horspool {
+        let query = query.to_ascii_lowercase().into_bytes();
+        let last = query.len() - 1;
+        let mut shifts = Box::new([query.len(); 256]);
+
+        for (i, &byte) in query[..last].iter().enumerate() {
+            shifts[byte as usize] = last - i;
+            shifts[byte.to_ascii_uppercase() as usize] = last - i;
+        }
+        let last_shift = shifts[query[last] as usize];
+        shifts[query[last] as usize] = 0;
+        shifts[query[last].to_ascii_uppercase() as usize] = 0;
+
+        Horspool {
+            query,
+            shifts,
+            last_shift,
+        }
+    }
+
+    /// Returns the length of the query in bytes.
+    pub(super) fn len(&self) -> usize {
+        self.query.len()
+    }
+
+    /// Returns where the first occurrence of the query starting at `from`
+    /// or after it is.
+    pub(super) fn find(&self, text: &str, from: usize) -> Option<usize> {
+        let text = text.as_bytes();
+        let last = self.query.len() - 1;
+        let mut start = from;
+
+        loop {
+            // Skip ahead until the byte under the end of the query is its
+            // last byte, which is the only one with no shift.
+            let mut shift = self.shifts[*text.get(start + last)? as usize];
+            while shift != 0 {
+                start += shift;
+                shift = self.shifts[*text.get(start + last)? as usize];
+            }
+
+            if text[start..start + last].eq_ignore_ascii_case(&self.query[..last]) {
+                return Some(start);
+            }
+            start += self.last_shift;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_like_str_find() {
+        let texts = ["", "abc", "xxabcabc", "ababcab", "aaaa", "héllo wörld", "ab"];
+
+        for query in ["abc", "ab", "a", "cab", "wö", "llo w", "zz"] {
+            let horspool = Horspool::new(query);
+            for text in texts {
+                assert_eq!(text.find(query), horspool.find(text, 0), "{query:?} in {text:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn starts_from_an_offset() {
+        let horspool = Horspool::new("ab");
+
+        assert_eq!(Some(3), horspool.find("abcab", 1));
+        assert_eq!(None, horspool.find("abcab", 4));
+    }
+
+    #[test]
+    fn ignores_ascii_case() {
+        let horspool = Horspool::new("Rust");
+
+        assert_eq!(Some(6), horspool.find("Trust RUST", 2));
+        assert_eq!(None, horspool.find("Rusty", 1));
+    }
+}
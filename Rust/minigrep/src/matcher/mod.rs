@@ -0,0 +1,372 @@
+//! Finding the query in a line.
+
+use std::ops::Range;
+
+mod automaton;
+mod horspool;
+
+use automaton::Automaton;
+use horspool::Horspool;
+
+/// Where a match has to start and end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// Anywhere in the line.
+    #[default]
+    None,
+    /// Between a word character and something else, set with `-w`. Word
+    /// characters are letters and digits of any script, and `_`.
+    Word,
+    /// At the start and the end of the line, set with `-x`.
+    Line,
+}
+
+/// The queries to look for, and how. A line matches if any of them is in
+/// it.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    query: Query,
+    /// Whether one of the queries is empty, which is in every line.
+    has_empty: bool,
+    boundary: Boundary,
+}
+
+#[derive(Debug, Clone)]
+enum Query {
+    None,
+    /// One query, matched byte for byte.
+    Exact(String),
+    /// One query with case ignored, which lowercasing leaves as ASCII.
+    Ascii(Horspool),
+    /// One query with case ignored, and its characters lowercased.
+    Lowercase(String, Vec<char>),
+    /// Several queries, found in one pass.
+    Many(Automaton, bool),
+}
+
+impl Matcher {
+    /// Creates a matcher finding `query` anywhere in a line.
+    pub fn new(query: &str, ignore_case: bool) -> Matcher {
+        Matcher::any([query], ignore_case)
+    }
+
+    /// Creates a matcher finding any of `queries` anywhere in a line. With
+    /// no queries at all, it finds nothing.
+    pub fn any<S: AsRef<str>>(queries: impl IntoIterator<Item = S>, ignore_case: bool) -> Matcher {
+        let mut queries: Vec<String> =
+            queries.into_iter().map(|query| query.as_ref().to_string()).collect();
+        let has_empty = queries.iter().any(String::is_empty);
+        queries.retain(|query| !query.is_empty());
+        queries.dedup();
+
+        let lowercase = |query: &str| query.chars().flat_map(char::to_lowercase).collect();
+        let query = match queries.len() {
+            0 => Query::None,
+            1 => {
+                let query = queries.remove(0);
+                if !ignore_case {
+                    Query::Exact(query)
+                } else if folds_as_ascii(&query) {
+                    Query::Ascii(Horspool::new(&query))
+                } else {
+                    let chars = lowercase(&query);
+                    Query::Lowercase(query.to_lowercase(), chars)
+                }
+            }
+            _ => {
+                let chars: Vec<Vec<char>> = queries
+                    .iter()
+                    .map(|query| {
+                        if ignore_case {
+                            lowercase(query)
+                        } else {
+                            query.chars().collect()
+                        }
+                    })
+                    .collect();
+                Query::Many(Automaton::new(&chars), ignore_case)
+            }
+        };
+
+        Matcher {
+            query,
+            has_empty,
+            boundary: Boundary::None,
+        }
+    }
+
+    /// Sets where matches have to start and end.
+    pub fn boundary(mut self, boundary: Boundary) -> Matcher {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Returns whether `line` contains one of the queries.
+    ///
+    /// An empty query is in every line, but only matches the whole of empty
+    /// ones.
+    pub fn is_match(&self, line: &str) -> bool {
+        if self.has_empty && (self.boundary != Boundary::Line || line.is_empty()) {
+            return true;
+        }
+        match (&self.query, self.boundary) {
+            (Query::Exact(query), Boundary::None) => line.contains(query.as_str()),
+            (Query::Ascii(query), Boundary::None) => query.find(line, 0).is_some(),
+            (Query::Lowercase(query, _), Boundary::None) => line.to_lowercase().contains(query),
+            _ => !self.occurrences(line, |span| !self.has_boundaries(line, &span)),
+        }
+    }
+
+    /// Returns the byte ranges of `line` where the queries are found,
+    /// without overlaps. Of the matches starting at the same place, the
+    /// longest is taken.
+    pub fn spans(&self, line: &str) -> Vec<Range<usize>> {
+        let mut found = Vec::new();
+        self.occurrences(line, |span| {
+            if self.has_boundaries(line, &span) {
+                found.push(span);
+            }
+            true
+        });
+        found.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        for span in found {
+            if spans.last().is_none_or(|last| last.end <= span.start) {
+                spans.push(span);
+            }
+        }
+
+        spans
+    }
+
+    /// Returns `contents` with every match replaced by `replacement`, line
+    /// by line so lines keep their endings.
+    pub fn replace(&self, contents: &str, replacement: &str) -> String {
+        let mut replaced = String::with_capacity(contents.len());
+
+        for line in contents.split_inclusive('\n') {
+            let text = line.strip_suffix('\n').unwrap_or(line);
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            let mut last = 0;
+
+            for span in self.spans(text) {
+                replaced.push_str(&line[last..span.start]);
+                replaced.push_str(replacement);
+                last = span.end;
+            }
+            replaced.push_str(&line[last..]);
+        }
+
+        replaced
+    }
+
+    /// Calls `more` with every occurrence of a query in `line`, overlapping
+    /// ones too, for as long as it returns `true`. Returns whether it
+    /// always did.
+    fn occurrences(&self, line: &str, mut more: impl FnMut(Range<usize>) -> bool) -> bool {
+        match &self.query {
+            Query::None => true,
+            Query::Exact(query) => {
+                let find = |from: usize| line[from..].find(query.as_str()).map(|i| from + i);
+                each_start(line, find, query.len(), more)
+            }
+            Query::Ascii(query) => {
+                each_start(line, |from| query.find(line, from), query.len(), more)
+            }
+            Query::Lowercase(_, query) => line.char_indices().all(|(start, _)| {
+                match lowercase_prefix_len(&line[start..], query) {
+                    Some(len) => more(start..start + len),
+                    None => true,
+                }
+            }),
+            Query::Many(automaton, ignore_case) => {
+                many_occurrences(automaton, *ignore_case, line, more)
+            }
+        }
+    }
+
+    fn has_boundaries(&self, line: &str, span: &Range<usize>) -> bool {
+        match self.boundary {
+            Boundary::None => true,
+            Boundary::Word => {
+                let before = line[..span.start].chars().next_back();
+                let after = line[span.end..].chars().next();
+                !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+            }
+            Boundary::Line => span.start == 0 && span.end == line.len(),
+        }
+    }
+}
+
+/// Calls `more` with the occurrences `find` returns starting at or after
+/// each offset, which are `len` bytes long, like `Matcher::occurrences`.
+fn each_start(
+    line: &str,
+    find: impl Fn(usize) -> Option<usize>,
+    len: usize,
+    mut more: impl FnMut(Range<usize>) -> bool,
+) -> bool {
+    let mut from = 0;
+
+    while let Some(start) = find(from) {
+        if !more(start..start + len) {
+            return false;
+        }
+        from = start + line[start..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    true
+}
+
+/// Returns whether ignoring the case of `query` only means ignoring the
+/// case of ASCII letters. The Kelvin sign lowercases to `k` and the dotted
+/// capital I to an `i` with a dot, so queries with these letters also match
+/// characters that aren't ASCII.
+fn folds_as_ascii(query: &str) -> bool {
+    query.is_ascii() && !query.contains(['k', 'K', 'i', 'I'])
+}
+
+fn many_occurrences(
+    automaton: &Automaton,
+    ignore_case: bool,
+    line: &str,
+    mut more: impl FnMut(Range<usize>) -> bool,
+) -> bool {
+    // Where the character each fed one comes from starts, since lowercasing
+    // may turn one character into several.
+    let mut starts = Vec::new();
+    let mut state = Automaton::START;
+
+    for (i, c) in line.char_indices() {
+        let end = i + c.len_utf8();
+        let mut feed = |fed: char| {
+            starts.push(i);
+            state = automaton.step(state, fed);
+        };
+        if ignore_case {
+            c.to_lowercase().for_each(&mut feed);
+        } else {
+            feed(c);
+        }
+
+        for &len in automaton.ends(state) {
+            let first = starts.len() - len;
+            // A match has to start at the start of a character.
+            if first > 0 && starts[first - 1] == starts[first] {
+                continue;
+            }
+            if !more(starts[first]..end) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns how many bytes at the start of `text` match `query` once
+/// lowercased, if they do.
+fn lowercase_prefix_len(text: &str, query: &[char]) -> Option<usize> {
+    let mut matched = 0;
+
+    for (i, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if query.get(matched) != Some(&lower) {
+                return None;
+            }
+            matched += 1;
+        }
+        if matched == query.len() {
+            return Some(i + c.len_utf8());
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_cover_every_occurrence() {
+        let spans = |line, query, ignore_case| Matcher::new(query, ignore_case).spans(line);
+
+        assert_eq!(vec![0..2, 3..5], spans("abcab", "ab", false));
+        assert_eq!(vec![0..4, 9..13], spans("Rust and rUsT", "rust", true));
+        assert_eq!(vec![2..6], spans("a ÄÖ b", "äö", true));
+        assert!(spans("Rust", "rust", false).is_empty());
+    }
+
+    #[test]
+    fn words_end_at_punctuation() {
+        let words = Matcher::new("rust", false).boundary(Boundary::Word);
+
+        assert_eq!(vec![0..4, 7..11], words.spans("rust, (rust). trusty rusty"));
+        assert_eq!(vec![9..13], words.spans("rust_fmt rust-fmt"));
+        assert!(!words.is_match("trust"));
+        assert!(!words.is_match("rusté"));
+        assert!(words.is_match("\"rust\""));
+    }
+
+    #[test]
+    fn words_may_overlap_rejected_matches() {
+        let words = Matcher::new("a-a", false).boundary(Boundary::Word);
+
+        // The match at 1 follows a word character, the one at 3 doesn't.
+        assert_eq!(vec![3..6], words.spans("xa-a-a"));
+    }
+
+    #[test]
+    fn lines_match_whole() {
+        let lines = Matcher::new("rust", true).boundary(Boundary::Line);
+
+        assert!(lines.is_match("Rust"));
+        assert!(!lines.is_match("Rust:"));
+        assert!(!lines.is_match(" rust"));
+        assert!(Matcher::new("", false).boundary(Boundary::Line).is_match(""));
+        assert!(!Matcher::new("", false).boundary(Boundary::Line).is_match("rust"));
+    }
+
+    #[test]
+    fn replace_keeps_line_endings() {
+        let lines = Matcher::new("rust", false).boundary(Boundary::Line);
+
+        assert_eq!("Go\r\nrusty\nGo", lines.replace("rust\r\nrusty\nrust", "Go"));
+    }
+
+    #[test]
+    fn any_query_matches() {
+        let matcher = Matcher::any(["he", "she", "his", "hers"], false);
+
+        assert_eq!(vec![1..4], matcher.spans("ushers"));
+        assert_eq!(vec![0..2, 4..7], matcher.spans("he, his"));
+        assert!(matcher.is_match("this"));
+        assert!(!matcher.is_match("hi"));
+        assert!(!Matcher::any([""; 0], false).is_match("anything"));
+        assert!(Matcher::any(["zzz", ""], false).is_match("anything"));
+    }
+
+    #[test]
+    fn any_query_ignoring_case() {
+        let matcher = Matcher::any(["rust", "äö"], true).boundary(Boundary::Word);
+
+        assert_eq!(vec![0..4, 8..12], matcher.spans("Rust or ÄÖ, trusty"));
+        assert!(!matcher.is_match("trusty"));
+    }
+
+    #[test]
+    fn ascii_queries_ignore_case_without_lowercasing() {
+        let matcher = Matcher::new("RUST", true);
+
+        assert!(matches!(matcher.query, Query::Ascii(_)));
+        assert_eq!(vec![1..5, 6..10], matcher.spans("TrUsT rust"));
+        // The Kelvin sign lowercases to an ASCII `k`.
+        assert!(Matcher::new("k", true).is_match("\u{212A}"));
+    }
+}
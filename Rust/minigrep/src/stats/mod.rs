@@ -0,0 +1,72 @@
+//! What a run went through, printed after its results with `--stats`.
+
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// The counts of a run, added up over the files it searched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub files: usize,
+    pub files_with_matches: usize,
+    /// The lines picked: the matching ones, or with `-v` the others.
+    pub matches: usize,
+    /// The bytes read from the files, before any decoding.
+    pub bytes: u64,
+    /// How long the whole run took.
+    pub elapsed: Duration,
+}
+
+impl Stats {
+    /// Returns the counts of searching one file, which picked `matches`
+    /// lines out of `bytes` bytes.
+    pub fn file(matches: usize, bytes: u64) -> Stats {
+        Stats {
+            files: 1,
+            files_with_matches: usize::from(matches > 0),
+            matches,
+            bytes,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// Adds up the counts of searches, which may have been made at the same
+/// time. The time taken isn't added, since it's that of the whole run.
+impl AddAssign for Stats {
+    fn add_assign(&mut self, other: Stats) {
+        self.files += other.files;
+        self.files_with_matches += other.files_with_matches;
+        self.matches += other.matches;
+        self.bytes += other.bytes;
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} files searched", self.files)?;
+        writeln!(f, "{} files with matches", self.files_with_matches)?;
+        writeln!(f, "{} matching lines", self.matches)?;
+        writeln!(f, "{} bytes searched", self.bytes)?;
+        write!(f, "{:.6} seconds", self.elapsed.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_up_files() {
+        let mut stats = Stats::default();
+        stats += Stats::file(3, 100);
+        stats += Stats::file(0, 50);
+        stats.elapsed = Duration::from_millis(15);
+
+        assert_eq!(
+            "2 files searched\n1 files with matches\n3 matching lines\n150 bytes searched\n\
+             0.015000 seconds",
+            stats.to_string()
+        );
+    }
+}
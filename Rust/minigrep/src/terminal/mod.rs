@@ -0,0 +1,166 @@
+//! Full-screen programs in a terminal, like `--interactive`.
+//!
+//! The terminal is put in raw mode with `stty`, so keys come in as they're
+//! pressed instead of a line at a time, and the alternate screen is used so
+//! the terminal is left as it was once done. Everything goes through the
+//! terminal itself rather than the standard input and output, which are
+//! left free for piping.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Bytes, Read, Write};
+use std::process::{Command, Stdio};
+use std::str;
+
+/// The terminal, in raw mode and on the alternate screen until dropped.
+#[derive(Debug)]
+pub struct Screen {
+    terminal: File,
+    saved: String,
+}
+
+/// A key pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    /// Escape, Ctrl-C or Ctrl-D.
+    Quit,
+}
+
+/// The keys pressed in a terminal, as `Screen::keys` returns them. Other
+/// control keys are skipped.
+#[derive(Debug)]
+pub struct Keys<R> {
+    bytes: Bytes<BufReader<R>>,
+    /// The bytes of a character typed in part.
+    pending: Vec<u8>,
+}
+
+impl Screen {
+    /// Takes over the terminal.
+    ///
+    /// # Errors
+    ///
+    /// The `open` function will return an error if there's no terminal, or
+    /// if `stty` can't put it in raw mode.
+    pub fn open() -> io::Result<Screen> {
+        let terminal = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let saved = stty(&["-g"])?.trim().to_string();
+        stty(&["raw", "-echo"])?;
+
+        let screen = Screen { terminal, saved };
+        (&screen.terminal).write_all(b"\x1b[?1049h")?;
+        Ok(screen)
+    }
+
+    /// Returns the rows and columns of the terminal, or those of the usual
+    /// one if `stty` can't tell.
+    pub fn size(&self) -> (usize, usize) {
+        let size = stty(&["size"]).unwrap_or_default();
+        let mut numbers = size.split_whitespace().map(|number| number.parse().ok());
+
+        match (numbers.next().flatten(), numbers.next().flatten()) {
+            (Some(rows), Some(columns)) if rows > 0 && columns > 0 => (rows, columns),
+            _ => (24, 80),
+        }
+    }
+
+    /// Returns the keys pressed from now on.
+    pub fn keys(&self) -> Keys<&File> {
+        Keys::new(&self.terminal)
+    }
+
+    /// Clears the screen and draws `text` on it. Raw mode doesn't go back
+    /// to the start of the line on `\n`, so lines have to end with `\r\n`.
+    ///
+    /// # Errors
+    ///
+    /// The `draw` function will return an error if the terminal can't be
+    /// written to.
+    pub fn draw(&self, text: &str) -> io::Result<()> {
+        let mut terminal = &self.terminal;
+        terminal.write_all(b"\x1b[H\x1b[2J")?;
+        terminal.write_all(text.as_bytes())?;
+        terminal.flush()
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = (&self.terminal).write_all(b"\x1b[?1049l");
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+impl<R: Read> Keys<R> {
+    pub fn new(reader: R) -> Keys<R> {
+        Keys {
+            bytes: BufReader::new(reader).bytes(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Keys<R> {
+    type Item = io::Result<Key>;
+
+    fn next(&mut self) -> Option<io::Result<Key>> {
+        loop {
+            let byte = match self.bytes.next()? {
+                Ok(byte) => byte,
+                Err(err) => return Some(Err(err)),
+            };
+
+            match byte {
+                b'\r' | b'\n' => return Some(Ok(Key::Enter)),
+                0x1b | 0x03 | 0x04 => return Some(Ok(Key::Quit)),
+                0x7f | 0x08 => return Some(Ok(Key::Backspace)),
+                byte if byte < 0x20 => continue,
+                byte => {
+                    self.pending.push(byte);
+                    match str::from_utf8(&self.pending) {
+                        Ok(typed) => {
+                            let c = typed.chars().next().unwrap();
+                            self.pending.clear();
+                            return Some(Ok(Key::Char(c)));
+                        }
+                        Err(err) if err.error_len().is_some() => self.pending.clear(),
+                        Err(_) => continue,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs `stty` on the terminal with `args`, returning what it prints.
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(File::open("/dev/tty")?)
+        .stderr(Stdio::inherit())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other("stty couldn't set up the terminal"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_keys_and_whole_characters() {
+        let keys: Vec<Key> = Keys::new(&b"a\xC3\xA9\x01\x7f\r\x1b"[..])
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(
+            vec![Key::Char('a'), Key::Char('é'), Key::Backspace, Key::Enter, Key::Quit],
+            keys
+        );
+    }
+}
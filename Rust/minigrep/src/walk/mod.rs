@@ -0,0 +1,106 @@
+//! Finding the files to search below a directory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::gitignore::{self, Gitignore};
+
+/// Returns the files below `root`, in order of their paths.
+///
+/// `.git` directories are always skipped. With `respect_ignores`, so are
+/// the paths ignored by the `.gitignore` files found along the way.
+/// Symbolic links to directories aren't followed, so links can't make the
+/// walk go around in circles.
+///
+/// # Errors
+///
+/// The `files` function will return an error if a directory or a
+/// `.gitignore` file can't be read.
+pub fn files(root: &Path, respect_ignores: bool) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    visit(root, respect_ignores, &mut Vec::new(), &mut files)?;
+    Ok(files)
+}
+
+fn visit(
+    dir: &Path,
+    respect_ignores: bool,
+    ignores: &mut Vec<Gitignore>,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let gitignore = if respect_ignores {
+        Gitignore::from_dir(dir)?
+    } else {
+        None
+    };
+    let pushed = gitignore.is_some();
+    ignores.extend(gitignore);
+
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let result = entries.into_iter().try_for_each(|entry| {
+        let path = entry.path();
+        let is_dir = entry.file_type()?.is_dir();
+
+        if entry.file_name() == ".git" || gitignore::is_ignored(ignores, &path, is_dir) {
+            return Ok(());
+        }
+        if is_dir {
+            visit(&path, respect_ignores, ignores, files)
+        } else {
+            if path.is_file() {
+                files.push(path);
+            }
+            Ok(())
+        }
+    });
+
+    if pushed {
+        ignores.pop();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn skips_ignored_paths_unless_asked_not_to() {
+        let root = env::temp_dir().join(format!("minigrep-walk-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "!keep.log\n").unwrap();
+        for file in ["src/main.rs", "src/debug.log", "src/keep.log", "target/out", ".git/HEAD"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let relative = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|file| file.strip_prefix(&root).unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        let respected = relative(files(&root, true).unwrap());
+        let all = relative(files(&root, false).unwrap());
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(vec![".gitignore", "src/.gitignore", "src/keep.log", "src/main.rs"], respected);
+        assert_eq!(
+            vec![
+                ".gitignore",
+                "src/.gitignore",
+                "src/debug.log",
+                "src/keep.log",
+                "src/main.rs",
+                "target/out",
+            ],
+            all
+        );
+    }
+}
@@ -509,6 +509,102 @@ fn procedural_macro() {
     Pancakes::hello_macro();
 }
 
+/* The greeting also tells the fields of tuple structs and structs with named ones. */
+#[derive(HelloMacro)]
+#[allow(dead_code)]
+struct Syrup(u32);
+
+#[test]
+fn procedural_macro_string() {
+    assert_eq!("Hello, Macro! My name is Pancakes!", Pancakes.hello_string());
+    assert_eq!("Hello, Macro! My name is Syrup, a tuple struct of 1 field!", Syrup(2).hello_string());
+}
+
+#[test]
+fn procedural_macro_name() {
+    assert_eq!("Pancakes", Pancakes::NAME);
+    assert_eq!("Stack", <Stack as HelloMacro>::NAME);
+}
+
+/** The same crate has an `Accessors` derive, which writes a getter and a setter for every field instead of a trait implementation. Fields can be left out with
+ *  `#[accessors(skip)]`, or also get a `_mut` getter with `#[accessors(get_mut)]`.
+ */
+use hello_macro_derive::Accessors;
+
+#[derive(Accessors, HelloMacro)]
+#[allow(dead_code)]
+struct Stack {
+    height: u32,
+    #[accessors(get_mut)]
+    toppings: Vec<String>,
+    #[accessors(skip)]
+    secret_ingredient: String,
+}
+
+#[test]
+fn derived_accessors() {
+    let mut stack = Stack { height: 3, toppings: Vec::new(), secret_ingredient: String::from("love") };
+
+    stack.set_height(stack.height() + 1);
+    stack.toppings_mut().push(String::from("syrup"));
+    assert_eq!(4, stack.height());
+    assert_eq!(["syrup"], stack.toppings()[..]);
+    assert_eq!("Hello, Macro! My name is Stack, a struct with 3 fields!", stack.hello_string());
+}
+
+/** A `Display` derive is there too, which saves writing `impl fmt::Display` like the one of Point above. The format goes in `#[display("...")]` and names the
+ *  fields inside of the braces, as `{_0}` for tuples. Enums have one format per variant, and those without fields default to their name.
+ */
+use hello_macro_derive::Display;
+
+#[derive(Display)]
+#[allow(dead_code)]
+#[display("{height} pancakes with {topping}")]
+struct Plate {
+    height: u32,
+    topping: Topping,
+}
+
+#[derive(Display)]
+#[allow(dead_code)]
+enum Topping {
+    Butter,
+    #[display("{_0} spoons of syrup")]
+    Syrup(u32),
+    #[display("{fruit:?}")]
+    Fruit { fruit: String },
+}
+
+#[test]
+fn derived_display() {
+    assert_eq!("3 pancakes with Butter", Plate { height: 3, topping: Topping::Butter }.to_string());
+    assert_eq!("2 spoons of syrup", Topping::Syrup(2).to_string());
+    assert_eq!("\"banana\"", Topping::Fruit { fruit: String::from("banana") }.to_string());
+}
+
+/** Constructors like `Rectangle::square` or `Guess::new` are written by hand all over this crate. The `New` derive writes the plain one, taking every field in
+ *  order, except those marked `#[new(default)]`, which start as their `Default` value.
+ */
+use hello_macro_derive::New;
+
+#[derive(New)]
+#[allow(dead_code)]
+struct Order {
+    table: u32,
+    plate: Plate,
+    #[new(default)]
+    served: bool,
+}
+
+#[test]
+fn derived_new() {
+    let order = Order::new(7, Plate { height: 2, topping: Topping::Syrup(3) });
+
+    assert_eq!(7, order.table);
+    assert_eq!("2 pancakes with 3 spoons of syrup", order.plate.to_string());
+    assert!(!order.served);
+}
+
 /* Attribute-like macros work similarly to procedural macros, but they are more flexible than them, because derive only works for structs an enums. Take for example 
  * the use case:
  *          #[route(GET, "/")]
@@ -521,7 +617,33 @@ fn procedural_macro() {
  * 
  * Here we have two attributes of type TokenStream. The first is for the contents of the attribute (GET, "/"). The second is the body of the item the attribute is 
  * attached to (fn index() {}).
+ *
+ * The hello_macro_derive crate has a real `route` attribute for the handlers of tcp_listener. It keeps the function and adds a static `Route` named after it, which
+ * a `Router` takes in with `routes`. Below, `index` gets `INDEX_ROUTE`.
  */
+use hello_macro_derive::route;
+use tcp_listener::http::{self, Request, Response};
+
+// This module has a `Write` trait and a `Status` enum of its own, so those of std::io and tcp_listener are written by their path. Only the test below routes
+// requests to `index`, so it's unused otherwise.
+#[route(GET, "/")]
+#[allow(dead_code)]
+fn index(_request: &Request, writer: &mut dyn std::io::Write) -> std::io::Result<http::Status> {
+    Response::new(http::Status::Ok).body("Hello from a macro route!").write_to(writer)?;
+    Ok(http::Status::Ok)
+}
+
+#[test]
+fn attribute_macro_route() {
+    use tcp_listener::http::{Handler, Router};
+
+    let router = Router::new().routes(&[INDEX_ROUTE]);
+    let request = Request::parse(&mut &b"GET / HTTP/1.1\r\n\r\n"[..]).unwrap();
+    let mut response = Vec::new();
+
+    assert_eq!(http::Status::Ok, router.handle(&request, &mut response).unwrap());
+    assert!(String::from_utf8(response).unwrap().ends_with("Hello from a macro route!"));
+}
 
 /* Function-like macros define macros that look like function calls. Similar to `macro_rules!` macros, they are more flexible than functions, for example, they can 
  * take an unknown number of arguments. However, function-like macros take a TokenStream parameter and their definition manipulates that TokenStream as procedural 
@@ -533,5 +655,6 @@ fn procedural_macro() {
  *          #[proc_macro]
  *          pub fn sql(input:TokenStream) -> TokenStream { // --snip-- }
  * 
- * This definition is similar to the custom derive macro's signature.
+ * This definition is similar to the custom derive macro's signature. The hello_macro_derive crate has a real one, `parametrize!`, which writes a test for every case
+ * of a table. The add_two tests of testing/mod.rs use it.
  */
@@ -0,0 +1,452 @@
+use std::cmp::Ordering;
+use std::env;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::ops::RangeInclusive;
+use std::process;
+use std::thread;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use minigrep::args::{ArgError, Matches, Parser};
+use rust::guessing_game::bot::Bot;
+use rust::guessing_game::command::Command;
+use rust::guessing_game::daily::{self, Date};
+use rust::guessing_game::messages::{Lang, Message, Usage};
+use rust::guessing_game::stats::Stats;
+use rust::guessing_game::tui;
+use rust::guessing_game::multiplayer::{self, ClientMessage, ServerMessage};
+use rust::guessing_game::word::{Letter, Word};
+use rust::guessing_game::{Difficulty, Game, Outcome, Secret, Turn};
+use tcp_listener::ThreadPool;
+
+/** How many players a host serves at once. The ones connecting after them wait until someone leaves. */
+const PLAYERS: usize = 8;
+
+/** What the arguments asked for. */
+struct Options {
+    seed: Option<u64>,
+    /** The day of the game, for `--daily`. */
+    daily: Option<Date>,
+    difficulty: Difficulty,
+    /** Whether a word is guessed instead of a number, for `--mode word`. */
+    word: bool,
+    lang: Lang,
+    mode: Mode,
+}
+
+enum Mode {
+    Play,
+    Stats,
+    Tui,
+    Bot,
+    Host(String),
+    Join { address: String, name: String },
+}
+
+/** The arguments are parsed with the parser of minigrep, which also writes the `--help`. */
+fn main() {
+    let parser = Parser::new("guessing_game", "Guess the secret number in as few attempts as you can.")
+        .option("--seed", "number", "Pick the secret number from this seed, to play the same game again")
+        .option("--mode", "number|word", "Guess a number, or a word of five letters, a number by default")
+        .option("--difficulty", "easy|normal|hard", "How big the range is and how many attempts there are, normal by default")
+        .option("--host", "address", "Host a race on this address, like 127.0.0.1:7878, instead of playing")
+        .option("--join", "address", "Join the race hosted on this address")
+        .option("--name", "name", "The name the other players see when joining a race")
+        .option("--lang", "en|es", "The language of the game, that of GUESSING_GAME_LANG or LANG by default")
+        .flag("--bot", "Watch the computer play, halving the range with every guess")
+        .flag("--tui", "Play on the full screen, with a bar showing where the number can still be")
+        .flag("--daily", "Play the game of the day, the same for everyone, and get a result to share")
+        .flag("--stats", "Show how many attempts the games played took, instead of playing");
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let matches = match parser.parse(args.clone()) {
+        Ok(matches) => matches,
+        Err(ArgError::Help) => {
+            print!("{}", parser.help());
+            return;
+        }
+        Err(err) => {
+            // The arguments weren't parsed, but the language may still be found among them.
+            let lang = args.windows(2).find(|pair| pair[0] == "--lang").and_then(|pair| Lang::parse(&pair[1]));
+            eprintln!("{}", lang.unwrap_or_else(Lang::from_env).text(&Message::BadArguments(&err)));
+            process::exit(2);
+        }
+    };
+    // The language is read first, so the other problems with the arguments are told in it.
+    let options = match parse_lang(matches.value("--lang")).and_then(|lang| options(&matches, lang).map_err(|usage| (lang, usage))) {
+        Ok(options) => options,
+        Err((lang, usage)) => {
+            eprintln!("{}", lang.text(&Message::Usage(usage)));
+            process::exit(2);
+        }
+    };
+
+    if options.word {
+        let mut game = match options.seed {
+            Some(seed) => Game::with_random_word(&mut StdRng::seed_from_u64(seed)),
+            None => Game::with_random_word(&mut rand::thread_rng()),
+        };
+        play_word(&mut game, options.lang);
+        record(&game, options.lang);
+        return;
+    }
+
+    let mut game = match options.seed {
+        Some(seed) => Game::with_difficulty(options.difficulty, &mut StdRng::seed_from_u64(seed)),
+        None => Game::with_difficulty(options.difficulty, &mut rand::thread_rng()),
+    };
+
+    let result = match options.mode {
+        Mode::Play => {
+            play(&mut game, options.lang);
+            record(&game, options.lang);
+            Ok(())
+        }
+        Mode::Stats => {
+            stats(options.lang);
+            Ok(())
+        }
+        Mode::Tui => tui(&mut game, options.lang).map(|()| record(&game, options.lang)),
+        Mode::Bot => {
+            bot(&mut game, options.lang);
+            Ok(())
+        }
+        Mode::Host(address) => host(&address, &game, options.lang),
+        Mode::Join { address, name } => join(&address, name, options.lang),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", options.lang.text(&Message::ConnectionError(&e.to_string())));
+        process::exit(1);
+    }
+
+    if let Some(share) = options.daily.and_then(|date| daily::share(&date, options.difficulty, &game)) {
+        println!();
+        println!("{share}");
+    }
+}
+
+fn options(matches: &Matches, lang: Lang) -> Result<Options, Usage> {
+    let mode = match (matches.value("--host"), matches.value("--join")) {
+        (host, join) if matches.flag("--stats") && (matches.flag("--bot") || matches.flag("--tui") || host.is_some() || join.is_some()) => {
+            return Err(Usage::StatsAlone)
+        }
+        _ if matches.flag("--bot") && matches.flag("--tui") => {
+            return Err(Usage::BotWithTui)
+        }
+        (None, None) if matches.flag("--bot") => Mode::Bot,
+        (None, None) if matches.flag("--tui") => Mode::Tui,
+        (None, None) if matches.flag("--stats") => Mode::Stats,
+        _ if matches.flag("--bot") || matches.flag("--tui") => {
+            return Err(Usage::BotOrTuiInRace)
+        }
+        (None, None) => Mode::Play,
+        (Some(address), None) => Mode::Host(address.to_string()),
+        (None, Some(address)) => Mode::Join {
+            address: address.to_string(),
+            name: matches.value("--name").unwrap_or("Anonymous").to_string(),
+        },
+        (Some(_), Some(_)) => return Err(Usage::HostWithJoin),
+    };
+
+    let daily = matches.flag("--daily").then(Date::today);
+    let seed = match (daily, parse_seed(matches.value("--seed"))?) {
+        (Some(_), Some(_)) => return Err(Usage::DailyWithSeed),
+        (Some(date), None) => Some(date.seed()),
+        (None, seed) => seed,
+    };
+
+    let word = parse_word_mode(matches.value("--mode"))?;
+    if word && (daily.is_some() || !matches!(mode, Mode::Play)) {
+        return Err(Usage::WordAlone);
+    }
+
+    Ok(Options {
+        seed,
+        daily,
+        difficulty: parse_difficulty(matches.value("--difficulty"))?,
+        word,
+        lang,
+        mode,
+    })
+}
+
+fn parse_seed(seed: Option<&str>) -> Result<Option<u64>, Usage> {
+    seed.map(|seed| seed.parse().map_err(|_| Usage::Seed))
+        .transpose()
+}
+
+fn parse_word_mode(mode: Option<&str>) -> Result<bool, Usage> {
+    match mode {
+        None | Some("number") => Ok(false),
+        Some("word") => Ok(true),
+        Some(_) => Err(Usage::Mode),
+    }
+}
+
+/** A language that isn't known is told about in that of the environment. */
+fn parse_lang(lang: Option<&str>) -> Result<Lang, (Lang, Usage)> {
+    match lang {
+        None => Ok(Lang::from_env()),
+        Some(code) => Lang::parse(code).ok_or((Lang::from_env(), Usage::Lang)),
+    }
+}
+
+fn parse_difficulty(difficulty: Option<&str>) -> Result<Difficulty, Usage> {
+    match difficulty {
+        None | Some("normal") => Ok(Difficulty::Normal),
+        Some("easy") => Ok(Difficulty::Easy),
+        Some("hard") => Ok(Difficulty::Hard),
+        Some(_) => Err(Usage::Difficulty),
+    }
+}
+
+/** Only the input and output of the game are here, the rules are in `Game`. A guess outside of the range of the game is reported to the player instead
+ *  of being compared against the secret number. Besides guesses, the player can type any other `Command`.
+ */
+fn play(game: &mut Game, lang: Lang) {
+    welcome(lang);
+
+    let range: RangeInclusive<i32> = game.range().clone();
+
+    println!();
+    println!();
+
+    loop {
+
+        println!("{}", lang.text(&Message::Prompt { min: *range.start(), max: *range.end() }));
+        if let Some(left) = game.attempts_left() {
+            println!("{}", lang.text(&Message::AttemptsLeft(left)));
+        }
+
+        let mut guess = String::new();
+        if io::stdin().read_line(&mut guess).expect("Failed to read line") == 0 {
+            return;
+        }
+        if guess.trim().is_empty() {
+            continue;
+        }
+        let guess = match Command::parse(&guess) {
+            Ok(Command::Guess(n)) => n,
+            Ok(Command::Ask(n)) => {
+                match game.ask(n) {
+                    Ok(ordering) => println!("{}", lang.text(&Message::Asked { n, ordering })),
+                    Err(e) => println!("{}", lang.text(&Message::OutOfRange(&e))),
+                }
+                continue;
+            }
+            Ok(Command::Hint) => {
+                let (low, high) = game.bounds();
+                println!("{}", lang.text(&Message::Bounds { low, high }));
+                continue;
+            }
+            Ok(Command::History) => {
+                for &(n, ordering) in game.history() {
+                    println!("{}", lang.text(&Message::Guessed { n, ordering }));
+                }
+                continue;
+            }
+            Ok(Command::Quit) => return,
+            Err(e) => {
+                println!("{}", lang.text(&Message::UnknownCommand(&e)));
+                continue;
+            }
+        };
+
+        let outcome = game.guess(guess);
+        if let Outcome::OutOfRange(e) = &outcome {
+            println!("{}", lang.text(&Message::OutOfRange(e)));
+            continue;
+        }
+
+        println!("{}", lang.text(&Message::YourGuess(guess)));
+
+        match outcome {
+            Outcome::TooSmall => println!("{}", lang.text(&Message::TooSmall)),
+            Outcome::TooBig => println!("{}", lang.text(&Message::TooBig)),
+            Outcome::Correct => {
+                println!("{}", lang.text(&Message::Won(&game.score())));
+                break;
+            }
+            Outcome::Lost { secret } => {
+                println!("{}", lang.text(&Message::Lost(secret)));
+                break;
+            }
+            Outcome::OutOfRange(_) => unreachable!(),
+        }
+    }
+}
+
+/** The word game gets its own loop, as its guesses and hints aren't numbers. Each letter of a guess is printed back on green if it's in the right place,
+ *  on yellow if it's elsewhere in the word and on grey if it isn't in it.
+ */
+fn play_word(game: &mut Game<Word>, lang: Lang) {
+    welcome(lang);
+    println!();
+    println!();
+
+    loop {
+        println!("{}", lang.text(&Message::WordPrompt));
+        if let Some(left) = game.attempts_left() {
+            println!("{}", lang.text(&Message::AttemptsLeft(left)));
+        }
+
+        let mut guess = String::new();
+        if io::stdin().read_line(&mut guess).expect("Failed to read line") == 0 {
+            return;
+        }
+        let guess = guess.trim().to_lowercase();
+
+        let (hint, turn) = match game.submit(guess.clone()) {
+            Ok(result) => result,
+            Err(e) => {
+                println!("{}", lang.text(&Message::NotAWord(&e)));
+                continue;
+            }
+        };
+
+        let colored: String = guess
+            .chars()
+            .zip(hint)
+            .map(|(c, letter)| {
+                let background = match letter {
+                    Letter::Right => 42,
+                    Letter::Elsewhere => 43,
+                    Letter::Absent => 100,
+                };
+                format!("\x1b[1;30;{background}m {} \x1b[0m", c.to_ascii_uppercase())
+            })
+            .collect();
+        println!("{colored}");
+
+        match turn {
+            Turn::Again => {}
+            Turn::Solved => {
+                println!("{}", lang.text(&Message::WordWon(&game.score())));
+                break;
+            }
+            Turn::Lost => {
+                println!("{}", lang.text(&Message::WordLost(&game.revealed().unwrap().to_string())));
+                break;
+            }
+        }
+    }
+}
+
+/** Counts a game that's over in the stats file, so `--stats` shows it. Only games played by a person on their own are counted, number and word games
+ *  alike, not those of the bot or of races.
+ */
+fn record<S: Secret>(game: &Game<S>, lang: Lang) {
+    let Some(path) = Stats::path() else { return };
+    if !game.is_over() {
+        return;
+    }
+
+    let result = Stats::load(&path).and_then(|mut stats| {
+        stats.record(game);
+        stats.save(&path)
+    });
+    if let Err(e) = result {
+        eprintln!("{}", lang.text(&Message::StatsError(&e.to_string())));
+    }
+}
+
+fn stats(lang: Lang) {
+    let stats = match Stats::path().map(|path| Stats::load(&path)).transpose() {
+        Ok(stats) => stats.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("{}", lang.text(&Message::StatsError(&e.to_string())));
+            return;
+        }
+    };
+
+    println!("{}", lang.text(&Message::Distribution { games: stats.games() }));
+    print!("{}", stats.histogram(40));
+}
+
+/** The title of the game, underlined. */
+fn welcome(lang: Lang) {
+    let welcome = lang.text(&Message::Welcome);
+    println!("{welcome}");
+    println!("{}", "-".repeat(welcome.chars().count()));
+}
+
+fn tui(game: &mut Game, lang: Lang) -> io::Result<()> {
+    match tui::run(game, lang)? {
+        Some(Outcome::Correct) => println!("{}", lang.text(&Message::Won(&game.score()))),
+        Some(Outcome::Lost { secret }) => println!("{}", lang.text(&Message::Lost(secret))),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn bot(game: &mut Game, lang: Lang) {
+    println!("{}", lang.text(&Message::BotStarts { min: *game.range().start(), max: *game.range().end() }));
+
+    let outcome = Bot::play(game, |n, outcome| {
+        let ordering = match outcome {
+            Outcome::TooSmall => Ordering::Less,
+            Outcome::TooBig => Ordering::Greater,
+            _ => Ordering::Equal,
+        };
+        println!("{}", lang.text(&Message::BotGuessed { n, ordering }));
+    });
+
+    match outcome {
+        Outcome::Correct => println!("{}", lang.text(&Message::BotWon(game.attempts()))),
+        _ => println!("{}", lang.text(&Message::BotLost)),
+    }
+}
+
+fn host(address: &str, game: &Game, lang: Lang) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?.to_string();
+    println!("{}", lang.text(&Message::Hosting { min: *game.range().start(), max: *game.range().end(), address: &address }));
+
+    match multiplayer::host(listener, game, &ThreadPool::new(PLAYERS))? {
+        Some(winner) => println!("{}", lang.text(&Message::RaceWon { name: &winner.name, attempts: winner.attempts })),
+        None => println!("{}", lang.text(&Message::NobodyWon)),
+    }
+    Ok(())
+}
+
+/** Messages from the host are printed as soon as they come, by their own thread, so everyone hears about the winner right away instead of after their
+ *  next guess.
+ */
+fn join(address: &str, name: String, lang: Lang) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    writeln!(&stream, "{}", ClientMessage::Name(name))?;
+
+    let reader = BufReader::new(stream.try_clone()?);
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            match ServerMessage::parse(&line) {
+                Ok(ServerMessage::Welcome { min, max }) => println!("{}", lang.text(&Message::RaceRange { min, max })),
+                Ok(ServerMessage::TooSmall) => println!("{}", lang.text(&Message::TooSmall)),
+                Ok(ServerMessage::TooBig) => println!("{}", lang.text(&Message::TooBig)),
+                Ok(ServerMessage::OutOfRange(e)) => println!("{}", lang.text(&Message::OutOfRange(&e))),
+                Ok(ServerMessage::Lost) => println!("{}", lang.text(&Message::RaceLost)),
+                Ok(ServerMessage::Won { attempts, name }) => {
+                    println!("{}", lang.text(&Message::RaceWon { name: &name, attempts }));
+                    process::exit(0);
+                }
+                Ok(ServerMessage::NobodyWon) => {
+                    println!("{}", lang.text(&Message::NobodyWon));
+                    process::exit(0);
+                }
+                Ok(ServerMessage::Error(message)) => println!("{}", lang.text(&Message::HostSaid(&message))),
+                Err(e) => println!("{}", lang.text(&Message::Unexpected(&e))),
+            }
+        }
+        println!("{}", lang.text(&Message::HostLeft));
+        process::exit(1);
+    });
+
+    for line in io::stdin().lock().lines() {
+        if let Ok(n) = line?.trim().parse() {
+            writeln!(&stream, "{}", ClientMessage::Guess(n))?;
+        }
+    }
+    Ok(())
+}
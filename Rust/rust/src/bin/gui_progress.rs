@@ -0,0 +1,51 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rust::gui::{Constraints, Label, ProgressBar, Screen, Size, TerminalRenderer};
+
+/** How often the screen ticks. The animations move by the same step every time, whatever the time it took to draw the frame before. */
+const STEP: Duration = Duration::from_millis(1000 / 30);
+
+/** The screen fills a progress bar in three seconds, with a fixed step loop: the time that went by since the last frame is ticked away in steps of `STEP`,
+ *  and then only what changed is drawn again.
+ */
+fn main() {
+    let mut bar = ProgressBar::new(40).with_id("bar");
+    bar.animate_to(1.0, Duration::from_secs(3));
+    let mut screen = Screen::new(vec![Box::new(Label::new("Loading crabs...").with_id("status")), Box::new(bar)]);
+    let size = Size::new(40, 2);
+    let mut terminal = TerminalRenderer::new(size);
+
+    screen.layout(Constraints::new(size.width, size.height));
+    screen.render(&mut terminal);
+    // Clears the terminal, then every frame is printed over the one before from its top left corner.
+    print!("\x1b[2J\x1b[H{}", terminal);
+
+    let mut previous = Instant::now();
+    let mut lag = Duration::ZERO;
+    loop {
+        let now = Instant::now();
+        lag += now - previous;
+        previous = now;
+        while lag >= STEP {
+            screen.tick(STEP);
+            lag -= STEP;
+        }
+
+        let done = screen.get("bar").and_then(|bar| bar.downcast_ref::<ProgressBar>()).is_some_and(ProgressBar::is_done);
+        if done {
+            if let Some(status) = screen.get_mut("status").and_then(|status| status.downcast_mut::<Label>()) {
+                status.set_text("Done!");
+            }
+        }
+
+        screen.layout(Constraints::new(size.width, size.height));
+        screen.update(&mut terminal);
+        // The lines of the terminal leave out their trailing spaces, so the rest of each line is cleared of what the frame before left there.
+        print!("\x1b[H{}", terminal.to_string().replace('\n', "\x1b[K\n"));
+        if done {
+            break;
+        }
+        thread::sleep(STEP.saturating_sub(now.elapsed()));
+    }
+}
@@ -0,0 +1,74 @@
+use std::ops::RangeInclusive;
+
+use super::{Game, Outcome};
+
+/** A player who halves the range the secret number can still be in with every guess, so it never needs more guesses than the size of the range has bits.
+ *  It only knows what the hints told it, like any player.
+ */
+#[derive(Debug)]
+pub struct Bot {
+    low: i32,
+    high: i32,
+}
+
+impl Bot {
+    pub fn new(range: &RangeInclusive<i32>) -> Bot {
+        Bot { low: *range.start(), high: *range.end() }
+    }
+
+    /** The middle of the range the number can still be in. */
+    pub fn next_guess(&self) -> i32 {
+        self.low + (self.high - self.low) / 2
+    }
+
+    /** Narrows the range down with the hint for `guess`. */
+    pub fn learn(&mut self, guess: i32, outcome: &Outcome) {
+        match outcome {
+            Outcome::TooSmall => self.low = guess + 1,
+            Outcome::TooBig => self.high = guess - 1,
+            _ => {}
+        }
+    }
+
+    /** Plays `game` until it's won or lost, calling `on_guess` after every guess. Returns the last outcome, which is `Correct` unless the attempts ran out.
+     */
+    pub fn play(game: &mut Game, mut on_guess: impl FnMut(i32, &Outcome)) -> Outcome {
+        let mut bot = Bot::new(game.range());
+
+        loop {
+            let guess = bot.next_guess();
+            let outcome = game.guess(guess);
+            on_guess(guess, &outcome);
+
+            match outcome {
+                Outcome::TooSmall | Outcome::TooBig => bot.learn(guess, &outcome),
+                _ => return outcome,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guessing_game::Difficulty;
+
+    /** The bot has to win every game of every difficulty, whatever the secret number is. */
+    #[test]
+    fn bot_always_wins() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            for secret in difficulty.range() {
+                let mut game = Game::new(difficulty.range(), secret).max_attempts(difficulty.max_attempts());
+                assert_eq!(Outcome::Correct, Bot::play(&mut game, |_, _| {}), "{difficulty:?} {secret}");
+            }
+        }
+    }
+
+    #[test]
+    fn bot_halves_the_range() {
+        let mut guesses = Vec::new();
+        Bot::play(&mut Game::new(1..=100, 1), |guess, _| guesses.push(guess));
+
+        assert_eq!(vec![50, 25, 12, 6, 3, 1], guesses);
+    }
+}
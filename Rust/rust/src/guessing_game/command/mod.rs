@@ -0,0 +1,67 @@
+use std::fmt;
+
+/** A line typed at the prompt of the number game. */
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /** A number, which uses up an attempt. */
+    Guess(i32),
+    /** A number followed by `?`, asking whether the secret is above or below it. That costs points instead of an attempt. */
+    Ask(i32),
+    /** `hint` or `pista`, for the lowest and highest numbers the secret can still be. */
+    Hint,
+    /** `history` or `historial`, for the guesses made until now. */
+    History,
+    /** `quit`, `q`, `exit` or `salir`. */
+    Quit,
+}
+
+/** Returned when a line is none of the commands. */
+#[derive(Debug, PartialEq)]
+pub struct CommandError {
+    pub input: String,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Can't understand {:?}. Type a number to guess it, a number and ? to ask about it, hint, history or quit.", self.input)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl Command {
+    /** Reads `line` as a command, with the words of any language of `Lang`. Spaces around it and the case of the words don't matter. */
+    pub fn parse(line: &str) -> Result<Command, CommandError> {
+        let line = line.trim();
+        let error = || CommandError { input: line.to_string() };
+
+        match line.to_lowercase().as_str() {
+            "hint" | "pista" => Ok(Command::Hint),
+            "history" | "historial" => Ok(Command::History),
+            "quit" | "q" | "exit" | "salir" => Ok(Command::Quit),
+            _ => match line.strip_suffix('?') {
+                Some(n) => n.trim_end().parse().map(Command::Ask).map_err(|_| error()),
+                None => line.parse().map(Command::Guess).map_err(|_| error()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_are_read_as_commands() {
+        assert_eq!(Ok(Command::Guess(-50)), Command::parse(" -50\n"));
+        assert_eq!(Ok(Command::Ask(50)), Command::parse("50?"));
+        assert_eq!(Ok(Command::Ask(50)), Command::parse("50 ?"));
+        assert_eq!(Ok(Command::Hint), Command::parse("HINT"));
+        assert_eq!(Ok(Command::History), Command::parse("history"));
+        assert_eq!(Ok(Command::Quit), Command::parse("q"));
+        assert_eq!(Ok(Command::Hint), Command::parse("pista"));
+
+        assert_eq!(Err(CommandError { input: String::from("fifty?") }), Command::parse("fifty?\n"));
+        assert!(Command::parse("?").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Difficulty, Game};
+
+/** A day of the calendar, in UTC so that everyone is on the same day at the same time. */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn today() -> Date {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        Date::from_days(seconds as i64 / 86_400)
+    }
+
+    /** The date `days` days after the 1st of January 1970, counting years from March so the leap day is the last one of its year. */
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+
+        let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+        let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+        let year = (year_of_era + era * 400) as i32 + i32::from(month <= 2);
+
+        Date { year, month, day }
+    }
+
+    /** The seed of the game of the day, which is the date written as a number, so `--daily` is the same as `--seed 20261014` on the 14th of October 2026.
+     */
+    pub fn seed(&self) -> u64 {
+        (self.year as u64) * 10_000 + u64::from(self.month) * 100 + u64::from(self.day)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/** The result of the game of the day to share with others, with a square for every guess: blue when the number was higher, red when it was lower and green
+ *  once it's found. It tells how the game went without telling the number. Returns `None` while the game isn't over.
+ */
+pub fn share(date: &Date, difficulty: Difficulty, game: &Game) -> Option<String> {
+    if !game.is_over() {
+        return None;
+    }
+
+    let won = game.history().last().is_some_and(|(_, ordering)| *ordering == Ordering::Equal);
+    let attempts = if won { game.attempts().to_string() } else { String::from("X") };
+    let max = game.attempts_left().map_or(String::new(), |left| format!("/{}", game.attempts() + left));
+    let squares: String = game
+        .history()
+        .iter()
+        .map(|(_, ordering)| match ordering {
+            Ordering::Less => '🟦',
+            Ordering::Greater => '🟥',
+            Ordering::Equal => '🟩',
+        })
+        .collect();
+
+    Some(format!("Guessing game {date} ({difficulty}) {attempts}{max}\n{squares}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_dates() {
+        assert_eq!(Date { year: 1970, month: 1, day: 1 }, Date::from_days(0));
+        assert_eq!(Date { year: 2024, month: 2, day: 29 }, Date::from_days(19_782));
+        assert_eq!(Date { year: 2024, month: 3, day: 1 }, Date::from_days(19_783));
+        assert_eq!(Date { year: 1969, month: 12, day: 31 }, Date::from_days(-1));
+        assert_eq!(20_240_229, Date::from_days(19_782).seed());
+        assert_eq!("2024-02-29", Date::from_days(19_782).to_string());
+    }
+
+    #[test]
+    fn share_shows_the_hints_not_the_number() {
+        let date = Date { year: 2026, month: 10, day: 14 };
+        let mut game = Game::new(1..=100, 30).max_attempts(8);
+        game.guess(50);
+        assert_eq!(None, share(&date, Difficulty::Normal, &game));
+
+        game.guess(20);
+        game.guess(30);
+        assert_eq!(Some(String::from("Guessing game 2026-10-14 (normal) 3/8\n🟥🟦🟩")), share(&date, Difficulty::Normal, &game));
+
+        let mut game = Game::new(1..=100, 30).max_attempts(1);
+        game.guess(50);
+        assert_eq!(Some(String::from("Guessing game 2026-10-14 (normal) X/1\n🟥")), share(&date, Difficulty::Normal, &game));
+    }
+}
@@ -0,0 +1,306 @@
+use std::cmp::Ordering;
+use std::env;
+
+use minigrep::args::ArgError;
+
+use super::command::CommandError;
+use super::multiplayer::ProtocolError;
+use super::word::WordError;
+use super::{GuessError, Score, ASK_PENALTY};
+
+/** A language the game can talk in. */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+}
+
+/** Why the arguments of the game, which the parser accepted, can't be used together. */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Usage {
+    StatsAlone,
+    BotWithTui,
+    BotOrTuiInRace,
+    HostWithJoin,
+    DailyWithSeed,
+    WordAlone,
+    Seed,
+    Mode,
+    Lang,
+    Difficulty,
+}
+
+/** Everything the game tells the player. The words are only picked by `Lang::text`, so the code printing a message doesn't know the language it's in.
+ *  Where a number compares with the secret one, `ordering` is how the number compares with it, as in `Game::history()`.
+ */
+#[derive(Debug)]
+pub enum Message<'a> {
+    Welcome,
+    Prompt { min: i32, max: i32 },
+    WordPrompt,
+    AttemptsLeft(u32),
+    YourGuess(i32),
+    TooSmall,
+    TooBig,
+    Won(&'a Score),
+    WordWon(&'a Score),
+    Lost(i32),
+    WordLost(&'a str),
+    /** The answer to a question, which cost `ASK_PENALTY` points. */
+    Asked { n: i32, ordering: Ordering },
+    Bounds { low: i32, high: i32 },
+    /** A line of the history. */
+    Guessed { n: i32, ordering: Ordering },
+    OutOfRange(&'a GuessError),
+    UnknownCommand(&'a CommandError),
+    NotAWord(&'a WordError),
+    BotStarts { min: i32, max: i32 },
+    BotGuessed { n: i32, ordering: Ordering },
+    BotWon(u32),
+    BotLost,
+    Hosting { min: i32, max: i32, address: &'a str },
+    /** The range of a race, which doesn't take commands. */
+    RaceRange { min: i32, max: i32 },
+    RaceWon { name: &'a str, attempts: u32 },
+    RaceLost,
+    NobodyWon,
+    HostSaid(&'a str),
+    /** A line from the host that isn't a message of the protocol. */
+    Unexpected(&'a ProtocolError),
+    HostLeft,
+    ConnectionError(&'a str),
+    /** The first line of the full screen game. */
+    Title { min: i32, max: i32, attempts_left: Option<u32>, attempts: u32 },
+    Higher,
+    Lower,
+    TitleWon,
+    TitleLost(i32),
+    /** The line above the histogram of `--stats`. */
+    Distribution { games: u32 },
+    StatsError(&'a str),
+    /** The arguments couldn't be parsed. */
+    BadArguments(&'a ArgError),
+    Usage(Usage),
+}
+
+impl Lang {
+    /** Reads a language from a code like `es`, or from a locale like `es_ES.UTF-8` as `LANG` has them. */
+    pub fn parse(code: &str) -> Option<Lang> {
+        let language = code.split(['_', '-', '.']).next().unwrap_or_default();
+
+        match language.to_lowercase().as_str() {
+            "en" => Some(Lang::En),
+            "es" => Some(Lang::Es),
+            _ => None,
+        }
+    }
+
+    /** The language of `GUESSING_GAME_LANG` if it's set, or else that of the locale in `LANG`. Languages the game doesn't know are English. */
+    pub fn from_env() -> Lang {
+        ["GUESSING_GAME_LANG", "LANG"]
+            .iter()
+            .find_map(|var| env::var(var).ok().and_then(|code| Lang::parse(&code)))
+            .unwrap_or_default()
+    }
+
+    pub fn text(self, message: &Message) -> String {
+        match self {
+            Lang::En => english(message),
+            Lang::Es => spanish(message),
+        }
+    }
+}
+
+fn english(message: &Message) -> String {
+    match message {
+        Message::Welcome => String::from("Welcome to LovetheFrog's Rust guessing game!"),
+        Message::Prompt { min, max } => format!("Please input your guess between {min} and {max}, or hint, history or quit."),
+        Message::WordPrompt => String::from("Please input a word of five letters."),
+        Message::AttemptsLeft(left) => format!("You have {left} attempts left."),
+        Message::YourGuess(n) => format!("Your guess was {n}"),
+        Message::TooSmall => String::from("Guess is too small!"),
+        Message::TooBig => String::from("Guess is too big!"),
+        Message::Won(score) => format!("Guess is correct! You scored {score}."),
+        Message::WordWon(score) => format!("Word is correct! You scored {score}."),
+        Message::Lost(secret) => format!("Out of attempts! The number was {secret}."),
+        Message::WordLost(word) => format!("Out of attempts! The word was {word}."),
+        Message::Asked { n, ordering } => match ordering {
+            Ordering::Less => format!("The number is bigger than {n}. That cost you {ASK_PENALTY} points."),
+            Ordering::Greater => format!("The number is smaller than {n}. That cost you {ASK_PENALTY} points."),
+            Ordering::Equal => format!("The number is {n}! That cost you {ASK_PENALTY} points, now guess it."),
+        },
+        Message::Bounds { low, high } => format!("The number is between {low} and {high}."),
+        Message::Guessed { n, ordering } => match ordering {
+            Ordering::Less => format!("{n} was too small."),
+            Ordering::Greater => format!("{n} was too big."),
+            Ordering::Equal => format!("{n} was correct."),
+        },
+        Message::OutOfRange(e) => e.to_string(),
+        Message::UnknownCommand(e) => e.to_string(),
+        Message::NotAWord(e) => e.to_string(),
+        Message::BotStarts { min, max } => format!("The bot guesses a number between {min} and {max}."),
+        Message::BotGuessed { n, ordering } => match ordering {
+            Ordering::Less => format!("{n} is too small."),
+            Ordering::Greater => format!("{n} is too big."),
+            Ordering::Equal => format!("{n}!"),
+        },
+        Message::BotWon(attempts) => format!("The bot guessed it in {attempts} attempts."),
+        Message::BotLost => String::from("The bot ran out of attempts."),
+        Message::Hosting { min, max, address } => format!("Hosting a race between {min} and {max} on {address}."),
+        Message::RaceRange { min, max } => format!("Please input your guess between {min} and {max}."),
+        Message::RaceWon { name, attempts } => format!("{name} won the race in {attempts} attempts!"),
+        Message::RaceLost => String::from("Out of attempts! Waiting for the others to finish."),
+        Message::NobodyWon => String::from("Nobody guessed the number, the race is over."),
+        Message::HostSaid(message) => format!("The host said: {message}"),
+        Message::Unexpected(e) => format!("Unexpected message from the host: {:?}", e.line),
+        Message::HostLeft => String::from("The host left."),
+        Message::ConnectionError(e) => format!("Connection error: {e}"),
+        Message::Title { min, max, attempts_left, attempts } => match attempts_left {
+            Some(left) => format!("Guess the number between {min} and {max}, {left} attempts left."),
+            None => format!("Guess the number between {min} and {max}, {attempts} attempts."),
+        },
+        Message::Higher => String::from("higher"),
+        Message::Lower => String::from("lower"),
+        Message::TitleWon => String::from("correct! Press any key."),
+        Message::TitleLost(secret) => format!("out of attempts, it was {secret}. Press any key."),
+        Message::Distribution { games: 0 } => String::from("No games played yet."),
+        Message::Distribution { games } => format!("Attempts per game, over {games} games:"),
+        Message::StatsError(e) => format!("The stats couldn't be kept: {e}"),
+        Message::BadArguments(e) => format!("Problem parsing arguments: {e}"),
+        Message::Usage(usage) => format!("Problem parsing arguments: {}", english_usage(*usage)),
+    }
+}
+
+fn english_usage(usage: Usage) -> &'static str {
+    match usage {
+        Usage::StatsAlone => "--stats can't be used with --bot, --tui, --host or --join",
+        Usage::BotWithTui => "--bot and --tui can't be used together",
+        Usage::BotOrTuiInRace => "--bot and --tui can't be used with --host or --join",
+        Usage::HostWithJoin => "--host and --join can't be used together",
+        Usage::DailyWithSeed => "--daily and --seed can't be used together",
+        Usage::WordAlone => "--mode word can't be used with --bot, --tui, --host, --join, --daily or --stats",
+        Usage::Seed => "--seed needs a whole number",
+        Usage::Mode => "--mode needs to be number or word",
+        Usage::Lang => "--lang needs to be en or es",
+        Usage::Difficulty => "--difficulty needs to be easy, normal or hard",
+    }
+}
+
+fn spanish(message: &Message) -> String {
+    match message {
+        Message::Welcome => String::from("¡Bienvenido al juego de adivinar en Rust de LovetheFrog!"),
+        Message::Prompt { min, max } => format!("Escribe un número entre {min} y {max}, o pista, historial o salir."),
+        Message::WordPrompt => String::from("Escribe una palabra de cinco letras."),
+        Message::AttemptsLeft(left) => format!("Te quedan {left} intentos."),
+        Message::YourGuess(n) => format!("Tu número fue {n}"),
+        Message::TooSmall => String::from("¡Te has quedado corto!"),
+        Message::TooBig => String::from("¡Te has pasado!"),
+        Message::Won(score) => format!("¡Correcto! Has conseguido {}.", spanish_score(score)),
+        Message::WordWon(score) => format!("¡Palabra correcta! Has conseguido {}.", spanish_score(score)),
+        Message::Lost(secret) => format!("¡Sin intentos! El número era {secret}."),
+        Message::WordLost(word) => format!("¡Sin intentos! La palabra era {word}."),
+        Message::Asked { n, ordering } => match ordering {
+            Ordering::Less => format!("El número es mayor que {n}. Te ha costado {ASK_PENALTY} puntos."),
+            Ordering::Greater => format!("El número es menor que {n}. Te ha costado {ASK_PENALTY} puntos."),
+            Ordering::Equal => format!("¡El número es {n}! Te ha costado {ASK_PENALTY} puntos, ahora adivínalo."),
+        },
+        Message::Bounds { low, high } => format!("El número está entre {low} y {high}."),
+        Message::Guessed { n, ordering } => match ordering {
+            Ordering::Less => format!("{n} era pequeño."),
+            Ordering::Greater => format!("{n} era grande."),
+            Ordering::Equal => format!("{n} era correcto."),
+        },
+        Message::OutOfRange(GuessError::TooSmall { value, min }) => format!("El número tiene que ser mayor o igual que {min}, no {value}."),
+        Message::OutOfRange(GuessError::TooLarge { value, max }) => format!("El número tiene que ser menor o igual que {max}, no {value}."),
+        Message::UnknownCommand(e) => {
+            format!("No se entiende {:?}. Escribe un número para adivinarlo, un número y ? para preguntar, pista, historial o salir.", e.input)
+        }
+        Message::NotAWord(WordError::WrongLength { expected, got }) => format!("La palabra tiene {expected} letras, no {got}."),
+        Message::NotAWord(WordError::NotALetter(c)) => format!("Solo se pueden escribir letras, no {c:?}."),
+        Message::BotStarts { min, max } => format!("El bot adivina un número entre {min} y {max}."),
+        Message::BotGuessed { n, ordering } => match ordering {
+            Ordering::Less => format!("{n} es pequeño."),
+            Ordering::Greater => format!("{n} es grande."),
+            Ordering::Equal => format!("¡{n}!"),
+        },
+        Message::BotWon(attempts) => format!("El bot lo ha adivinado en {attempts} intentos."),
+        Message::BotLost => String::from("El bot se ha quedado sin intentos."),
+        Message::Hosting { min, max, address } => format!("Organizando una carrera entre {min} y {max} en {address}."),
+        Message::RaceRange { min, max } => format!("Escribe un número entre {min} y {max}."),
+        Message::RaceWon { name, attempts } => format!("¡{name} ha ganado la carrera en {attempts} intentos!"),
+        Message::RaceLost => String::from("¡Sin intentos! Esperando a que acaben los demás."),
+        Message::NobodyWon => String::from("Nadie ha adivinado el número, la carrera ha terminado."),
+        Message::HostSaid(message) => format!("El anfitrión dice: {message}"),
+        Message::Unexpected(e) => format!("Mensaje inesperado del anfitrión: {:?}", e.line),
+        Message::HostLeft => String::from("El anfitrión se ha ido."),
+        Message::ConnectionError(e) => format!("Error de conexión: {e}"),
+        Message::Title { min, max, attempts_left, attempts } => match attempts_left {
+            Some(left) => format!("Adivina el número entre {min} y {max}, te quedan {left} intentos."),
+            None => format!("Adivina el número entre {min} y {max}, {attempts} intentos."),
+        },
+        Message::Higher => String::from("mayor"),
+        Message::Lower => String::from("menor"),
+        Message::TitleWon => String::from("¡correcto! Pulsa una tecla."),
+        Message::TitleLost(secret) => format!("sin intentos, era {secret}. Pulsa una tecla."),
+        Message::Distribution { games: 0 } => String::from("Todavía no se ha jugado ninguna partida."),
+        Message::Distribution { games } => format!("Intentos por partida, en {games} partidas:"),
+        Message::StatsError(e) => format!("No se han podido guardar las estadísticas: {e}"),
+        Message::BadArguments(ArgError::Help) => String::from("Problema con los argumentos: se ha pedido la ayuda"),
+        Message::BadArguments(ArgError::UnknownOption(option)) => format!("Problema con los argumentos: no existe la opción {option}"),
+        Message::BadArguments(ArgError::MissingValue { option, value }) => {
+            format!("Problema con los argumentos: a {option} le falta su valor, {value}")
+        }
+        // The parser of minigrep only speaks English, the reasons of the game are all `Usage`.
+        Message::BadArguments(ArgError::Usage(reason)) => format!("Problema con los argumentos: {reason}"),
+        Message::Usage(usage) => format!("Problema con los argumentos: {}", spanish_usage(*usage)),
+    }
+}
+
+fn spanish_usage(usage: Usage) -> &'static str {
+    match usage {
+        Usage::StatsAlone => "--stats no se puede usar con --bot, --tui, --host ni --join",
+        Usage::BotWithTui => "--bot y --tui no se pueden usar juntos",
+        Usage::BotOrTuiInRace => "--bot y --tui no se pueden usar con --host ni --join",
+        Usage::HostWithJoin => "--host y --join no se pueden usar juntos",
+        Usage::DailyWithSeed => "--daily y --seed no se pueden usar juntos",
+        Usage::WordAlone => "--mode word no se puede usar con --bot, --tui, --host, --join, --daily ni --stats",
+        Usage::Seed => "--seed necesita un número entero",
+        Usage::Mode => "--mode tiene que ser number o word",
+        Usage::Lang => "--lang tiene que ser en o es",
+        Usage::Difficulty => "--difficulty tiene que ser easy, normal o hard",
+    }
+}
+
+fn spanish_score(score: &Score) -> String {
+    format!("{} puntos, con {} intentos en {:.1} segundos", score.points, score.attempts, score.elapsed.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_are_read_from_codes_and_locales() {
+        assert_eq!(Some(Lang::Es), Lang::parse("es"));
+        assert_eq!(Some(Lang::Es), Lang::parse("es_ES.UTF-8"));
+        assert_eq!(Some(Lang::En), Lang::parse("EN-us"));
+        assert_eq!(None, Lang::parse("C"));
+    }
+
+    #[test]
+    fn messages_are_in_the_language_asked() {
+        let message = Message::Asked { n: 50, ordering: Ordering::Less };
+
+        assert_eq!("The number is bigger than 50. That cost you 25 points.", Lang::En.text(&message));
+        assert_eq!("El número es mayor que 50. Te ha costado 25 puntos.", Lang::Es.text(&message));
+        assert_eq!(
+            "El número tiene que ser menor o igual que 100, no 101.",
+            Lang::Es.text(&Message::OutOfRange(&GuessError::TooLarge { value: 101, max: 100 }))
+        );
+        assert_eq!(
+            "Problema con los argumentos: --seed necesita un número entero",
+            Lang::Es.text(&Message::Usage(Usage::Seed))
+        );
+    }
+}
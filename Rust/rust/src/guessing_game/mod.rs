@@ -1,36 +1,425 @@
-use std::io;
-use rand::Rng;
 use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
-fn main() {
-    println!("Welcome to LovetheFrog's Rust guessing game!");
-    println!("--------------------------------------------");
+pub use crate::testing::GuessError;
+use crate::testing::Guess;
 
-    let number_to_guess = rand::thread_rng().gen_range(1..=100);
+/** Multiplayer races over TCP: every player who connects gets the same secret number, and the first one to guess it wins. Each player is served by a job
+ *  of the `ThreadPool` from tcp_listener, so the size of the pool is how many players can play at once; the others wait for a free worker.
+ *
+ *  The protocol is one message per line. A client first sends `NAME <name>` and then `GUESS <number>` lines, which the host answers as `ServerMessage`
+ *  says. Once the number is guessed, every player is sent `WON <attempts> <name>` and disconnected. If every player runs out of attempts instead, they are
+ *  sent `NOBODY`.
+ */
+pub mod multiplayer;
 
-    println!();
-    println!();
+/** The `--bot` player, which plays the same `Game` as humans do. */
+pub mod bot;
 
-    loop {
+/** The full screen game of `--tui`, drawn on the terminal of minigrep. */
+pub mod tui;
 
-        println!("Please input your guess.");
+/** The game of the day of `--daily`, the same for everyone. */
+pub mod daily;
 
-        let mut guess = String::new();
-        io::stdin().read_line(&mut guess).expect("Failed to read line");
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
+/** The word game of `--mode word`, another `Secret` for the same `Game`. */
+pub mod word;
+
+/** What can be typed at the prompt of the number game: guesses, questions and a few commands. */
+pub mod command;
+
+/** The words of the game in every language it speaks, picked with `--lang` or from the environment. */
+pub mod messages;
+
+/** How many attempts the games took, kept in a file between games, and the histogram of `--stats`. */
+pub mod stats;
+
+/** The points asking whether the secret number is above or below another costs. */
+pub const ASK_PENALTY: u32 = 25;
+
+/** A round of the guessing game: the secret to guess, the guesses made and how long it's taking. Nothing here reads or prints anything, so the rules can
+ *  be tested without a player. The input loop lives in src/bin/guessing_game.rs.
+ *
+ *  What's guessed is a `Secret`, a number unless said otherwise, so the attempts, the time and the score work the same for every kind of game.
+ */
+#[derive(Debug)]
+pub struct Game<S: Secret = Number> {
+    secret: S,
+    /** Every valid guess, and the hint it got. */
+    history: Vec<(S::Guess, S::Hint)>,
+    /** The points taken off the score for the questions asked. */
+    penalty: u32,
+    max_attempts: Option<u32>,
+    started: Instant,
+    /** How long it took to guess the number, once it was. */
+    finished: Option<Duration>,
+}
+
+/** What has to be guessed in a `Game`, and what a guess tells about it. */
+pub trait Secret {
+    type Guess: Clone + fmt::Debug;
+    type Hint: Clone + fmt::Debug;
+    /** Why a guess can't be the secret at all, like a number outside of the range. Such guesses aren't attempts. */
+    type Error;
+
+    fn check(&self, guess: &Self::Guess) -> Result<Self::Hint, Self::Error>;
+
+    fn is_solved(&self, hint: &Self::Hint) -> bool;
+}
+
+/** The secret of the number game: a number in a range. Guesses are validated with the same `Guess` type from the testing module. */
+#[derive(Debug, Clone)]
+pub struct Number {
+    range: RangeInclusive<i32>,
+    value: i32,
+}
+
+/** How a valid guess went, for any kind of `Secret`. */
+#[derive(Debug, PartialEq)]
+pub enum Turn {
+    Again,
+    Solved,
+    /** The guess was wrong and it was the last attempt. */
+    Lost,
+}
+
+/** How hard a game is: the bigger the range and the fewer the attempts, the harder. Even on `Hard`, halving the range on every guess always finds the
+ *  number in time.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/** What a guess turned out to be. A guess outside of the range is reported with the same `GuessError` the testing module uses, and isn't counted as an
+ *  attempt.
+ */
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    TooSmall,
+    TooBig,
+    Correct,
+    OutOfRange(GuessError),
+    /** The guess was wrong and it was the last attempt. */
+    Lost { secret: i32 },
+}
+
+/** The score of a won game. Winning is worth 100 points, and every attempt left over another 100. Every second taken costs a point, and so do the
+ *  questions asked, but a win is always worth at least 10 points.
+ */
+#[derive(Debug, PartialEq)]
+pub struct Score {
+    pub attempts: u32,
+    pub elapsed: Duration,
+    pub points: u32,
+}
+
+impl Difficulty {
+    pub fn range(self) -> RangeInclusive<i32> {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => 1..=100,
+            Difficulty::Hard => 1..=1000,
+        }
+    }
+
+    pub fn max_attempts(self) -> u32 {
+        match self {
+            Difficulty::Easy => 12,
+            Difficulty::Normal => 8,
+            Difficulty::Hard => 10,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Normal => write!(f, "normal"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl Score {
+    pub fn new(attempts: u32, attempts_left: u32, elapsed: Duration) -> Score {
+        let points = (100 + 100 * attempts_left).saturating_sub(elapsed.as_secs() as u32).max(10);
+
+        Score { attempts, elapsed, points }
+    }
+
+    /** Takes `penalty` points off the score, down to the 10 points every win is worth. */
+    pub fn with_penalty(mut self, penalty: u32) -> Score {
+        self.points = self.points.saturating_sub(penalty).max(10);
+        self
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} points, for {} attempts in {:.1} seconds", self.points, self.attempts, self.elapsed.as_secs_f64())
+    }
+}
+
+impl Secret for Number {
+    type Guess = i32;
+    /** How the guess compares with the secret number. */
+    type Hint = Ordering;
+    type Error = GuessError;
+
+    fn check(&self, guess: &i32) -> Result<Ordering, GuessError> {
+        Guess::in_range(*guess, self.range.clone()).map(|guess| guess.value().cmp(&self.value))
+    }
+
+    fn is_solved(&self, hint: &Ordering) -> bool {
+        *hint == Ordering::Equal
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<S: Secret> Game<S> {
+    /** Starts a game where `secret` has to be guessed, with as many attempts as needed. */
+    pub fn with_secret(secret: S) -> Game<S> {
+        Game { secret, history: Vec::new(), penalty: 0, max_attempts: None, started: Instant::now(), finished: None }
+    }
+
+    /** Limits the number of attempts, which is unlimited by default. */
+    pub fn max_attempts(mut self, max_attempts: u32) -> Game<S> {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /** Number of valid guesses made until now, the correct one included. */
+    pub fn attempts(&self) -> u32 {
+        self.history.len() as u32
+    }
+
+    /** The valid guesses made until now, in order, and the hint each got. */
+    pub fn history(&self) -> &[(S::Guess, S::Hint)] {
+        &self.history
+    }
+
+    /** Whether the secret was guessed or the attempts ran out. */
+    pub fn is_over(&self) -> bool {
+        self.finished.is_some() || self.attempts_left() == Some(0)
+    }
+
+    /** Number of attempts the player still has, if they are limited. */
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.max_attempts.map(|max| max.saturating_sub(self.attempts()))
+    }
+
+    /** The secret, once the game is over. */
+    pub fn revealed(&self) -> Option<&S> {
+        self.is_over().then_some(&self.secret)
+    }
+
+    /** The score of the game so far, which is only worth something once the secret is guessed. The time stops once it is. */
+    pub fn score(&self) -> Score {
+        let elapsed = self.finished.unwrap_or_else(|| self.started.elapsed());
+        Score::new(self.attempts(), self.attempts_left().unwrap_or(0), elapsed).with_penalty(self.penalty)
+    }
+
+    /** Checks `guess` against the secret, returning its hint and whether the game goes on. Once the game is over, guesses aren't counted any more and
+     *  get the turn the game ended with again.
+     *
+     *  # Errors
+     *
+     *  The `submit` function will return the error of the secret if the guess isn't valid, which doesn't use up an attempt.
+     */
+    pub fn submit(&mut self, guess: S::Guess) -> Result<(S::Hint, Turn), S::Error> {
+        let hint = self.secret.check(&guess)?;
+        if self.is_over() {
+            let turn = if self.finished.is_some() { Turn::Solved } else { Turn::Lost };
+            return Ok((hint, turn));
+        }
+        self.history.push((guess, hint.clone()));
+
+        let turn = if self.secret.is_solved(&hint) {
+            self.finished = Some(self.started.elapsed());
+            Turn::Solved
+        } else if self.attempts_left() == Some(0) {
+            Turn::Lost
+        } else {
+            Turn::Again
         };
+        Ok((hint, turn))
+    }
+}
+
+impl Game {
+    /** Starts a game where `secret` has to be guessed. The secret is passed in instead of being picked here, so tests can know it.
+     *
+     *  # Panics
+     *
+     *  The `new` function will panic if `secret` is not in `range`.
+     */
+    pub fn new(range: RangeInclusive<i32>, secret: i32) -> Game {
+        assert!(range.contains(&secret), "The secret number {secret} is not in {range:?}");
+
+        Game::with_secret(Number { range, value: secret })
+    }
+
+    /** Starts a game as hard as `difficulty` says, with a secret number picked by `rng`. */
+    pub fn with_difficulty(difficulty: Difficulty, rng: &mut impl Rng) -> Game {
+        Game::with_rng(difficulty.range(), rng).max_attempts(difficulty.max_attempts())
+    }
+
+    /** Starts a game with a secret number picked by `rng`. Any random number generator can be passed in, like `rand::thread_rng()` for a different game
+     *  every time or a seeded one for the same game every time.
+     */
+    pub fn with_rng(range: RangeInclusive<i32>, rng: &mut impl Rng) -> Game {
+        let secret = rng.gen_range(range.clone());
+        Game::new(range, secret)
+    }
+
+    /** Starts a game whose secret number only depends on `seed`, so sharing the seed shares the game. The numbers are only the same for the same version of
+     *  rand, as `StdRng` may change between versions.
+     */
+    pub fn with_seed(range: RangeInclusive<i32>, seed: u64) -> Game {
+        Game::with_rng(range, &mut StdRng::seed_from_u64(seed))
+    }
+
+    pub fn range(&self) -> &RangeInclusive<i32> {
+        &self.secret.range
+    }
+
+    /** The lowest and highest numbers the secret can still be, from the guesses made. */
+    pub fn bounds(&self) -> (i32, i32) {
+        let range = self.range();
+        self.history.iter().fold((*range.start(), *range.end()), |(low, high), (guess, ordering)| match ordering {
+            Ordering::Less => (low.max(guess + 1), high),
+            Ordering::Greater => (low, high.min(guess - 1)),
+            Ordering::Equal => (*guess, *guess),
+        })
+    }
+
+    /** Tells how `n` compares with the secret number without using up an attempt, for `ASK_PENALTY` points of the score.
+     *
+     *  # Errors
+     *
+     *  The `ask` function will return an error if `n` is outside of the range, which costs nothing.
+     */
+    pub fn ask(&mut self, n: i32) -> Result<Ordering, GuessError> {
+        let ordering = self.secret.check(&n)?;
+        self.penalty += ASK_PENALTY;
+        Ok(ordering)
+    }
 
-        println!("Your guess was {guess}");
+    pub fn guess(&mut self, n: i32) -> Outcome {
+        match self.submit(n) {
+            Err(e) => Outcome::OutOfRange(e),
+            Ok((_, Turn::Solved)) => Outcome::Correct,
+            Ok((_, Turn::Lost)) => Outcome::Lost { secret: self.secret.value },
+            Ok((Ordering::Less, _)) => Outcome::TooSmall,
+            Ok(_) => Outcome::TooBig,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guesses_are_compared_with_the_secret() {
+        let mut game = Game::new(1..=100, 42);
+
+        assert_eq!(Outcome::TooSmall, game.guess(10));
+        assert_eq!(Outcome::TooBig, game.guess(50));
+        assert_eq!(Outcome::Correct, game.guess(42));
+        assert_eq!(3, game.attempts());
+    }
+
+    /** Guesses outside of the range get the error of `Guess::in_range()` and don't use up an attempt. */
+    #[test]
+    fn guesses_out_of_range_are_not_attempts() {
+        let mut game = Game::new(5..=10, 7);
+
+        assert_eq!(Outcome::OutOfRange(GuessError::TooLarge { value: 11, max: 10 }), game.guess(11));
+        assert_eq!(0, game.attempts());
+    }
+
+    #[test]
+    fn last_attempt_wins_or_loses() {
+        let mut game = Game::new(1..=100, 42).max_attempts(2);
+
+        assert_eq!(Outcome::TooSmall, game.guess(10));
+        assert_eq!(Some(1), game.attempts_left());
+        assert_eq!(Outcome::Lost { secret: 42 }, game.guess(50));
+
+        let mut game = Game::new(1..=100, 42).max_attempts(2);
+        game.guess(10);
+        assert_eq!(Outcome::Correct, game.guess(42));
+    }
 
-        match guess.cmp(&number_to_guess) {
-            Ordering::Less => println!("Guess is too small!"),
-            Ordering::Greater => println!("Guess is too big!"),
-            Ordering::Equal => {
-                println!("Guess is correct!");
-                break;
-            }
+    #[test]
+    fn guesses_after_the_game_is_over_are_not_attempts() {
+        let mut game = Game::new(1..=100, 42).max_attempts(2);
+        game.guess(42);
+        assert_eq!(Outcome::Correct, game.guess(10));
+        assert_eq!((1, Some(1)), (game.attempts(), game.attempts_left()));
+
+        let mut game = Game::new(1..=100, 42).max_attempts(1);
+        game.guess(10);
+        assert_eq!(Outcome::Lost { secret: 42 }, game.guess(42));
+        assert_eq!((1, Some(0)), (game.attempts(), game.attempts_left()));
+    }
+
+    /** Halving the range on every guess has to find the number within the attempts of each difficulty. */
+    #[test]
+    fn every_difficulty_can_be_won() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let range = difficulty.range();
+            let needed = (range.end() - range.start() + 1).ilog2() + 1;
+            assert!(needed <= difficulty.max_attempts(), "{difficulty:?}");
+        }
+    }
+
+    #[test]
+    fn score_rewards_attempts_left_and_speed() {
+        assert_eq!(300, Score::new(3, 2, Duration::ZERO).points);
+        assert_eq!(285, Score::new(3, 2, Duration::from_millis(15_500)).points);
+        assert_eq!(10, Score::new(8, 0, Duration::from_secs(600)).points);
+    }
+
+    #[test]
+    fn questions_cost_points_but_not_attempts() {
+        let mut game = Game::new(1..=100, 42).max_attempts(2);
+
+        assert_eq!(Ok(Ordering::Less), game.ask(10));
+        assert!(game.ask(101).is_err());
+        assert_eq!(Some(2), game.attempts_left());
+        game.guess(42);
+        assert_eq!(200 - ASK_PENALTY, game.score().points);
+    }
+
+    #[test]
+    fn same_seed_same_secret() {
+        let secrets: Vec<i32> = (0..10).map(|seed| Game::with_seed(1..=1000, seed).secret.value).collect();
+
+        for (seed, secret) in secrets.iter().enumerate() {
+            assert_eq!(*secret, Game::with_seed(1..=1000, seed as u64).secret.value);
+            assert!((1..=1000).contains(secret));
         }
+        assert!(secrets.iter().any(|secret| *secret != secrets[0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not in")]
+    fn secret_must_be_in_range() {
+        Game::new(1..=10, 11);
     }
 }
@@ -0,0 +1,352 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tcp_listener::ThreadPool;
+
+use super::{Game, GuessError, Outcome};
+
+/** A line sent by a player. */
+#[derive(Debug, PartialEq)]
+pub enum ClientMessage {
+    Name(String),
+    Guess(i32),
+}
+
+/** A line sent by the host. */
+#[derive(Debug, PartialEq)]
+pub enum ServerMessage {
+    /** The range of the secret number, sent once the player has a name. */
+    Welcome { min: i32, max: i32 },
+    TooSmall,
+    TooBig,
+    /** The guess was outside of the range. It's sent as `OUT <value> <min|max> <bound>`, so the player can tell it in their own language. */
+    OutOfRange(GuessError),
+    /** The player used up every attempt, and can only wait for the others now. */
+    Lost,
+    /** Someone guessed the number, and the race is over. */
+    Won { attempts: u32, name: String },
+    /** Every player ran out of attempts, and the race is over without a winner. */
+    NobodyWon,
+    /** The player sent something that isn't a message, or not when it was expected. */
+    Error(String),
+}
+
+/** Returned when a line isn't a message of the protocol. */
+#[derive(Debug, PartialEq)]
+pub struct ProtocolError {
+    pub line: String,
+}
+
+/** The player who guessed the number, and how many attempts it took them. */
+#[derive(Debug, PartialEq, Clone)]
+pub struct Winner {
+    pub name: String,
+    pub attempts: u32,
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unexpected message: {:?}", self.line)
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl ClientMessage {
+    pub fn parse(line: &str) -> Result<ClientMessage, ProtocolError> {
+        let error = || ProtocolError { line: line.to_string() };
+
+        match line.split_once(' ') {
+            Some(("NAME", name)) if !name.trim().is_empty() => Ok(ClientMessage::Name(name.trim().to_string())),
+            Some(("GUESS", n)) => n.trim().parse().map(ClientMessage::Guess).map_err(|_| error()),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientMessage::Name(name) => write!(f, "NAME {name}"),
+            ClientMessage::Guess(n) => write!(f, "GUESS {n}"),
+        }
+    }
+}
+
+impl ServerMessage {
+    pub fn parse(line: &str) -> Result<ServerMessage, ProtocolError> {
+        let error = || ProtocolError { line: line.to_string() };
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+        match kind {
+            "WELCOME" => {
+                let (min, max) = rest.split_once(' ').ok_or_else(error)?;
+                Ok(ServerMessage::Welcome {
+                    min: min.parse().map_err(|_| error())?,
+                    max: max.parse().map_err(|_| error())?,
+                })
+            }
+            "SMALL" => Ok(ServerMessage::TooSmall),
+            "BIG" => Ok(ServerMessage::TooBig),
+            "OUT" => {
+                let mut words = rest.split(' ');
+                let (Some(value), Some(side), Some(bound), None) = (words.next(), words.next(), words.next(), words.next()) else {
+                    return Err(error());
+                };
+                let (value, bound) = (value.parse().map_err(|_| error())?, bound.parse().map_err(|_| error())?);
+                match side {
+                    "min" => Ok(ServerMessage::OutOfRange(GuessError::TooSmall { value, min: bound })),
+                    "max" => Ok(ServerMessage::OutOfRange(GuessError::TooLarge { value, max: bound })),
+                    _ => Err(error()),
+                }
+            }
+            "LOST" => Ok(ServerMessage::Lost),
+            "NOBODY" => Ok(ServerMessage::NobodyWon),
+            "WON" => {
+                let (attempts, name) = rest.split_once(' ').ok_or_else(error)?;
+                Ok(ServerMessage::Won { attempts: attempts.parse().map_err(|_| error())?, name: name.to_string() })
+            }
+            "ERROR" => Ok(ServerMessage::Error(rest.to_string())),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for ServerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerMessage::Welcome { min, max } => write!(f, "WELCOME {min} {max}"),
+            ServerMessage::TooSmall => write!(f, "SMALL"),
+            ServerMessage::TooBig => write!(f, "BIG"),
+            ServerMessage::OutOfRange(GuessError::TooSmall { value, min }) => write!(f, "OUT {value} min {min}"),
+            ServerMessage::OutOfRange(GuessError::TooLarge { value, max }) => write!(f, "OUT {value} max {max}"),
+            ServerMessage::Lost => write!(f, "LOST"),
+            ServerMessage::NobodyWon => write!(f, "NOBODY"),
+            ServerMessage::Won { attempts, name } => write!(f, "WON {attempts} {name}"),
+            ServerMessage::Error(message) => write!(f, "ERROR {message}"),
+        }
+    }
+}
+
+/** What the jobs serving the players share: whether the race is over and who won, if anyone did, a handle on every connection to tell them, and how many
+ *  players are still guessing or ran out of attempts.
+ */
+struct Race {
+    over: bool,
+    winner: Option<Winner>,
+    players: Vec<TcpStream>,
+    racing: usize,
+    lost: usize,
+}
+
+impl Race {
+    /** Ends the race, telling every player `message` and disconnecting them. */
+    fn end(&mut self, message: &ServerMessage) {
+        self.over = true;
+        for player in self.players.drain(..) {
+            let _ = send(&player, message);
+            // Ends the reads of the jobs of the other players too.
+            let _ = player.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn send(mut stream: &TcpStream, message: &ServerMessage) -> io::Result<()> {
+    writeln!(stream, "{message}")
+}
+
+/** Hosts a race on `listener` until someone guesses the secret number of `game`, or every player who joined runs out of attempts, in which case there's
+ *  no winner. Every player plays their own copy of `game`, with the same range, secret number and limit of attempts.
+ *
+ *  # Errors
+ *
+ *  The `host` function will return an error if the listener fails. A player whose connection fails just leaves the race.
+ */
+pub fn host(listener: TcpListener, game: &Game, pool: &ThreadPool) -> io::Result<Option<Winner>> {
+    // Not blocking on accept lets the loop notice when the race is over.
+    listener.set_nonblocking(true)?;
+    let race = Arc::new(Mutex::new(Race { over: false, winner: None, players: Vec::new(), racing: 0, lost: 0 }));
+
+    let winner = loop {
+        let state = race.lock().unwrap();
+        if state.over {
+            break state.winner.clone();
+        }
+        drop(state);
+
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                let race = Arc::clone(&race);
+                let mut player_game = Game::with_secret(game.secret.clone());
+                player_game.max_attempts = game.max_attempts;
+                pool.execute_or_panic(move || {
+                    let _ = serve_player(stream, player_game, &race);
+                });
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(e),
+        }
+    };
+
+    pool.wait_idle();
+    Ok(winner)
+}
+
+/** Lets a player into the race for as long as they play, ending it without a winner if they were the last one guessing and someone ran out of attempts. */
+fn serve_player(stream: TcpStream, game: Game, race: &Mutex<Race>) -> io::Result<()> {
+    {
+        let mut race = race.lock().unwrap();
+        match race.winner.clone() {
+            Some(Winner { name, attempts }) => return send(&stream, &ServerMessage::Won { attempts, name }),
+            None if race.over => return send(&stream, &ServerMessage::NobodyWon),
+            None => {}
+        }
+        race.players.push(stream.try_clone()?);
+        race.racing += 1;
+    }
+
+    let result = play(&stream, game, race);
+
+    let mut race = race.lock().unwrap();
+    race.racing -= 1;
+    // The players who lost are waiting to hear how the race ends, which nobody is left to decide.
+    if !race.over && race.racing == 0 && race.lost > 0 {
+        race.end(&ServerMessage::NobodyWon);
+    }
+    result
+}
+
+fn play(stream: &TcpStream, mut game: Game, race: &Mutex<Race>) -> io::Result<()> {
+    let mut lines = BufReader::new(stream.try_clone()?).lines();
+    let name = match lines.next().transpose()?.map(|line| ClientMessage::parse(&line)) {
+        Some(Ok(ClientMessage::Name(name))) => name,
+        Some(_) => return send(stream, &ServerMessage::Error(String::from("Expected NAME <name>"))),
+        None => return Ok(()),
+    };
+    send(stream, &ServerMessage::Welcome { min: *game.range().start(), max: *game.range().end() })?;
+
+    for line in lines {
+        let n = match ClientMessage::parse(&line?) {
+            Ok(ClientMessage::Guess(n)) => n,
+            Ok(_) => {
+                send(stream, &ServerMessage::Error(String::from("Expected GUESS <number>")))?;
+                continue;
+            }
+            Err(e) => {
+                send(stream, &ServerMessage::Error(e.to_string()))?;
+                continue;
+            }
+        };
+
+        let mut race = race.lock().unwrap();
+        // Someone else may have won between the lines of this player.
+        if race.over {
+            return Ok(());
+        }
+
+        let reply = match game.guess(n) {
+            Outcome::TooSmall => ServerMessage::TooSmall,
+            Outcome::TooBig => ServerMessage::TooBig,
+            Outcome::OutOfRange(e) => ServerMessage::OutOfRange(e),
+            Outcome::Lost { .. } => {
+                race.lost += 1;
+                return send(stream, &ServerMessage::Lost);
+            }
+            Outcome::Correct => {
+                let attempts = game.attempts();
+                race.winner = Some(Winner { name: name.clone(), attempts });
+                race.end(&ServerMessage::Won { attempts, name });
+                return Ok(());
+            }
+        };
+        drop(race);
+        send(stream, &reply)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_read_as_they_are_written() {
+        let client = [ClientMessage::Name(String::from("Ferris the crab")), ClientMessage::Guess(-3)];
+        for message in client {
+            assert_eq!(Ok(&message), ClientMessage::parse(&message.to_string()).as_ref());
+        }
+
+        let server = [
+            ServerMessage::Welcome { min: 1, max: 100 },
+            ServerMessage::TooSmall,
+            ServerMessage::OutOfRange(GuessError::TooLarge { value: 101, max: 100 }),
+            ServerMessage::OutOfRange(GuessError::TooSmall { value: -3, min: 1 }),
+            ServerMessage::Lost,
+            ServerMessage::NobodyWon,
+            ServerMessage::Won { attempts: 4, name: String::from("Ferris the crab") },
+        ];
+        for message in server {
+            assert_eq!(Ok(&message), ServerMessage::parse(&message.to_string()).as_ref());
+        }
+
+        assert!(ServerMessage::parse("OUT 101 max").is_err());
+        assert!(ServerMessage::parse("OUT 101 middle 100").is_err());
+        assert!(ClientMessage::parse("GUESS fifty").is_err());
+        assert!(ClientMessage::parse("NAME  ").is_err());
+    }
+
+    fn join(address: std::net::SocketAddr, name: &str) -> (TcpStream, io::Lines<BufReader<TcpStream>>) {
+        let stream = TcpStream::connect(address).unwrap();
+        let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        writeln!(&stream, "{}", ClientMessage::Name(name.to_string())).unwrap();
+        assert_eq!("WELCOME 1 10", lines.next().unwrap().unwrap());
+        (stream, lines)
+    }
+
+    /** Two players race for the same number, and both are told who won. */
+    #[test]
+    fn first_to_guess_wins_the_race() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || host(listener, &Game::new(1..=10, 7), &ThreadPool::new(2)));
+
+        let (ferris, mut ferris_lines) = join(address, "Ferris");
+        let (corro, mut corro_lines) = join(address, "Corro");
+
+        writeln!(&ferris, "GUESS 3").unwrap();
+        assert_eq!("SMALL", ferris_lines.next().unwrap().unwrap());
+        writeln!(&corro, "GUESS 7").unwrap();
+        assert_eq!("WON 1 Corro", corro_lines.next().unwrap().unwrap());
+        assert_eq!("WON 1 Corro", ferris_lines.next().unwrap().unwrap());
+        assert!(ferris_lines.next().is_none());
+
+        assert_eq!(Some(Winner { name: String::from("Corro"), attempts: 1 }), host.join().unwrap().unwrap());
+    }
+
+    /** Both players run out of attempts, and the race ends without a winner instead of waiting for more players. */
+    #[test]
+    fn race_ends_when_every_player_lost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || host(listener, &Game::new(1..=10, 7).max_attempts(1), &ThreadPool::new(2)));
+
+        let (ferris, mut ferris_lines) = join(address, "Ferris");
+        let (corro, mut corro_lines) = join(address, "Corro");
+
+        writeln!(&ferris, "GUESS 3").unwrap();
+        assert_eq!("LOST", ferris_lines.next().unwrap().unwrap());
+        writeln!(&corro, "GUESS 9").unwrap();
+        assert_eq!("LOST", corro_lines.next().unwrap().unwrap());
+        assert_eq!("NOBODY", corro_lines.next().unwrap().unwrap());
+        assert_eq!("NOBODY", ferris_lines.next().unwrap().unwrap());
+        assert!(ferris_lines.next().is_none());
+
+        assert_eq!(None, host.join().unwrap().unwrap());
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{Game, Secret};
+
+/** How many attempts the games took, over every game played. Lost games are counted apart, as they took every attempt without a win. */
+#[derive(Debug, Default, PartialEq)]
+pub struct Stats {
+    /** How many games were won in each number of attempts. */
+    won: BTreeMap<u32, u32>,
+    lost: u32,
+}
+
+impl Stats {
+    /** Counts `game`, if it's over. */
+    pub fn record<S: Secret>(&mut self, game: &Game<S>) {
+        if game.finished.is_some() {
+            *self.won.entry(game.attempts()).or_default() += 1;
+        } else if game.is_over() {
+            self.lost += 1;
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.won.values().sum::<u32>() + self.lost
+    }
+
+    /** The file the stats are kept in between games: `GUESSING_GAME_STATS` if it's set, or else .guessing_game_stats in the home directory. */
+    pub fn path() -> Option<PathBuf> {
+        env::var_os("GUESSING_GAME_STATS")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".guessing_game_stats")))
+    }
+
+    /** Reads the stats saved in `path`, which are empty if nothing was saved yet.
+     *
+     *  # Errors
+     *
+     *  The `load` function will return an error if `path` can't be read, or if it doesn't have stats in it.
+     */
+    pub fn load(path: &Path) -> io::Result<Stats> {
+        match fs::read_to_string(path) {
+            Ok(text) => Stats::parse(&text).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "The stats file is broken")),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Stats::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /** # Errors
+     *
+     *  The `save` function will return an error if `path` can't be written.
+     */
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /** Reads stats as `Display` writes them: a line with the attempts and the games won in them for every number of attempts, and a line starting with X
+     *  for the games lost.
+     */
+    pub fn parse(text: &str) -> Option<Stats> {
+        let mut stats = Stats::default();
+
+        for line in text.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#')) {
+            let (attempts, games) = line.trim().split_once(' ')?;
+            let games: u32 = games.trim().parse().ok()?;
+            match attempts {
+                "X" => stats.lost += games,
+                attempts => *stats.won.entry(attempts.parse().ok()?).or_default() += games,
+            }
+        }
+        Some(stats)
+    }
+
+    /** Draws a bar of `#` for every number of attempts from 1 to the most a game took, and one for the games lost. The longest bar is `width` long, and
+     *  every bar ends with its number of games.
+     */
+    pub fn histogram(&self, width: usize) -> String {
+        let most = self.won.keys().max().copied().unwrap_or(0);
+        let mut rows: Vec<(String, u32)> = (1..=most).map(|attempts| (attempts.to_string(), self.won.get(&attempts).copied().unwrap_or(0))).collect();
+        if self.lost > 0 {
+            rows.push((String::from("X"), self.lost));
+        }
+
+        let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let highest = rows.iter().map(|(_, games)| *games).max().unwrap_or(0).max(1) as usize;
+        rows.iter()
+            .map(|(label, games)| {
+                // Any game at all gets at least one #.
+                let bar = (*games as usize * width).div_ceil(highest);
+                format!("{label:>label_width$} | {} {games}\n", "#".repeat(bar))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# attempts games")?;
+        for (attempts, games) in &self.won {
+            writeln!(f, "{attempts} {games}")?;
+        }
+        if self.lost > 0 {
+            writeln!(f, "X {}", self.lost)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guessing_game::Outcome;
+
+    fn played() -> Stats {
+        let mut stats = Stats::default();
+        for guesses in [&[50, 42][..], &[42], &[10, 42], &[10, 20]] {
+            let mut game = Game::new(1..=100, 42).max_attempts(2);
+            guesses.iter().for_each(|n| {
+                game.guess(*n);
+            });
+            stats.record(&game);
+        }
+        stats
+    }
+
+    #[test]
+    fn games_are_counted_by_attempts() {
+        let stats = played();
+        assert_eq!(4, stats.games());
+
+        let mut unfinished = Game::new(1..=100, 42);
+        assert_eq!(Outcome::TooSmall, unfinished.guess(10));
+        let mut same = played();
+        same.record(&unfinished);
+        assert_eq!(stats, same);
+
+        assert_eq!(Some(stats), Stats::parse(&same.to_string()));
+        assert_eq!(None, Stats::parse("two 3"));
+    }
+
+    #[test]
+    fn word_games_are_counted_too() {
+        let mut stats = Stats::default();
+        let mut game = Game::word("crabs");
+        game.submit(String::from("frogs")).unwrap();
+        game.submit(String::from("crabs")).unwrap();
+        stats.record(&game);
+
+        assert_eq!("1 |  0\n2 | ## 1\n", stats.histogram(2));
+    }
+
+    #[test]
+    fn histogram_scales_to_the_most_games() {
+        assert_eq!("1 | ##### 1\n2 | ########## 2\nX | ##### 1\n", played().histogram(10));
+        assert_eq!("", Stats::default().histogram(10));
+    }
+}
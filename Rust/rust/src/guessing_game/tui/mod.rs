@@ -0,0 +1,127 @@
+use std::io;
+use std::ops::RangeInclusive;
+
+use minigrep::terminal::{Key, Screen};
+
+use super::messages::{Lang, Message};
+use super::{Game, Outcome};
+
+/** Plays `game` on the full screen, drawing it again after every key. Only digits are typed in, and Enter guesses them. Once the game is won or lost,
+ *  the last screen stays until a key is pressed. Returns the outcome the game ended with, or `None` if the player quit with Escape or Ctrl-C.
+ *
+ *  # Errors
+ *
+ *  The `run` function will return an error if there's no terminal to play in.
+ */
+pub fn run(game: &mut Game, lang: Lang) -> io::Result<Option<Outcome>> {
+    let screen = Screen::open()?;
+    let (rows, columns) = screen.size();
+    let mut history = Vec::new();
+    let mut input = String::new();
+
+    screen.draw(&render(game, &history, &input, rows, columns, lang))?;
+    let mut keys = screen.keys();
+    while let Some(key) = keys.next() {
+        match key? {
+            Key::Quit => return Ok(None),
+            Key::Char(c) if c.is_ascii_digit() || (c == '-' && input.is_empty()) => input.push(c),
+            Key::Char(_) => {}
+            Key::Backspace => {
+                input.pop();
+            }
+            Key::Enter => {
+                if let Ok(n) = input.parse() {
+                    history.push((n, game.guess(n)));
+                }
+                input.clear();
+            }
+        }
+        screen.draw(&render(game, &history, &input, rows, columns, lang))?;
+
+        if let Some((_, Outcome::Correct | Outcome::Lost { .. })) = history.last() {
+            keys.next().transpose()?;
+            return Ok(history.pop().map(|(_, outcome)| outcome));
+        }
+    }
+
+    Ok(None)
+}
+
+/** Draws the game on a screen of `rows` by `columns`, in `lang`: the range bar, what's being typed and the previous guesses, the last one first. Nothing
+ *  is printed, so the screen can be checked without a terminal.
+ */
+pub fn render(game: &Game, history: &[(i32, Outcome)], input: &str, rows: usize, columns: usize, lang: Lang) -> String {
+    let range = game.range();
+    let (low, high) = game.bounds();
+
+    let width = columns.saturating_sub(4 + 2 * range.end().to_string().len() + 6).clamp(10, 60);
+    let (before, inside, after) = bar_cells(range, low, high, width);
+
+    let title = Message::Title { min: *range.start(), max: *range.end(), attempts_left: game.attempts_left(), attempts: game.attempts() };
+    let mut screen = format!("{}\r\n\r\n", lang.text(&title));
+    screen.push_str(&format!(
+        "  \x1b[2m{}\x1b[0;32m{}\x1b[0;2m{}\x1b[0m  {low} to {high}\r\n\r\n",
+        "░".repeat(before),
+        "█".repeat(inside),
+        "░".repeat(after)
+    ));
+    screen.push_str(&format!("> {input}\r\n\r\n"));
+
+    for (guess, outcome) in history.iter().rev().take(rows.saturating_sub(6)) {
+        let feedback = match outcome {
+            Outcome::TooSmall => format!("\x1b[34m↑ {}\x1b[0m", lang.text(&Message::Higher)),
+            Outcome::TooBig => format!("\x1b[31m↓ {}\x1b[0m", lang.text(&Message::Lower)),
+            Outcome::Correct => format!("\x1b[1;32m✓ {}\x1b[0m", lang.text(&Message::TitleWon)),
+            Outcome::OutOfRange(e) => format!("\x1b[33m! {}\x1b[0m", lang.text(&Message::OutOfRange(e))),
+            Outcome::Lost { secret } => format!("\x1b[1;31m✗ {}\x1b[0m", lang.text(&Message::TitleLost(*secret))),
+        };
+        screen.push_str(&format!("{guess:>6}  {feedback}\r\n"));
+    }
+
+    // Leaves the cursor after what's being typed.
+    screen.push_str(&format!("\x1b[5;{}H", input.len() + 3));
+    screen
+}
+
+/** Splits a bar of `width` cells for `range` into the cells before `low`, those from `low` to `high` and those after. Every cell in the middle holds at
+ *  least one number the secret can still be.
+ */
+fn bar_cells(range: &RangeInclusive<i32>, low: i32, high: i32, width: usize) -> (usize, usize, usize) {
+    let start = i64::from(*range.start());
+    let size = i64::from(*range.end()) - start + 1;
+    let width = width as i64;
+    // The cell a number falls in.
+    let cell = |n: i32| ((i64::from(n) - start) * width / size) as usize;
+
+    let before = cell(low);
+    let inside = cell(high) + 1 - before;
+    (before, inside, width as usize - before - inside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bar_narrows_down_with_the_hints() {
+        assert_eq!((0, 10, 0), bar_cells(&(1..=100), 1, 100, 10));
+        assert_eq!((5, 3, 2), bar_cells(&(1..=100), 51, 75, 10));
+        // A single number still gets a cell.
+        assert_eq!((9, 1, 0), bar_cells(&(1..=1000), 1000, 1000, 10));
+    }
+
+    #[test]
+    fn screen_shows_the_last_guess_first() {
+        let mut game = Game::new(1..=100, 60).max_attempts(8);
+        let history: Vec<(i32, Outcome)> = [50, 75].into_iter().map(|n| (n, game.guess(n))).collect();
+
+        assert_eq!((51, 74), game.bounds());
+
+        let screen = render(&game, &history, "6", 24, 80, Lang::En);
+        assert!(screen.starts_with("Guess the number between 1 and 100, 6 attempts left."));
+        assert!(screen.contains("51 to 74"));
+        let lower = screen.find("75  \x1b[31m↓ lower").unwrap();
+        let higher = screen.find("50  \x1b[34m↑ higher").unwrap();
+        assert!(lower < higher);
+    }
+}
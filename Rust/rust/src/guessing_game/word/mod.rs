@@ -0,0 +1,165 @@
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::{Game, Secret};
+
+/** The words a game can pick from. They all have five letters, as the guesses have to. */
+pub const WORDS: &[&str] = &[
+    "apple", "brick", "chair", "crane", "crate", "dance", "eagle", "earth", "flame", "frogs", "ghost", "grape", "heart", "house", "juice", "knife",
+    "lemon", "light", "mouse", "music", "night", "ocean", "piano", "plant", "queen", "river", "robot", "shell", "snake", "stone", "sugar", "table",
+    "tiger", "train", "trust", "water", "whale", "world", "yield", "zebra",
+];
+
+/** How many attempts a word game has. */
+pub const MAX_ATTEMPTS: u32 = 6;
+
+/** The secret of the word game, in lowercase. */
+#[derive(Debug, Clone)]
+pub struct Word {
+    letters: Vec<char>,
+}
+
+/** What a guess says about one of its letters. */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Letter {
+    /** The letter is in the word, in this very place. */
+    Right,
+    /** The letter is in the word, somewhere else. */
+    Elsewhere,
+    /** The letter isn't in the word, or not as many times as it was guessed. */
+    Absent,
+}
+
+/** Returned when a guess can't be the word at all. Such guesses aren't attempts. */
+#[derive(Debug, PartialEq)]
+pub enum WordError {
+    WrongLength { expected: usize, got: usize },
+    NotALetter(char),
+}
+
+impl fmt::Display for WordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordError::WrongLength { expected, got } => write!(f, "The word has {expected} letters, got {got}."),
+            WordError::NotALetter(c) => write!(f, "Guesses can only have letters, got {c:?}."),
+        }
+    }
+}
+
+impl std::error::Error for WordError {}
+
+impl Word {
+    /** # Panics
+     *
+     *  The `new` function will panic if `word` has anything but letters.
+     */
+    pub fn new(word: &str) -> Word {
+        assert!(word.chars().all(char::is_alphabetic), "The secret word {word:?} can only have letters");
+
+        Word { letters: word.to_lowercase().chars().collect() }
+    }
+}
+
+impl Secret for Word {
+    type Guess = String;
+    /** What the guess says about each of its letters, in order. */
+    type Hint = Vec<Letter>;
+    type Error = WordError;
+
+    /** Follows the usual rules for letters guessed more than once: as many of them are marked as the word has, the right ones first. */
+    fn check(&self, guess: &String) -> Result<Vec<Letter>, WordError> {
+        let guess: Vec<char> = guess.trim().to_lowercase().chars().collect();
+        if let Some(&c) = guess.iter().find(|c| !c.is_alphabetic()) {
+            return Err(WordError::NotALetter(c));
+        }
+        if guess.len() != self.letters.len() {
+            return Err(WordError::WrongLength { expected: self.letters.len(), got: guess.len() });
+        }
+
+        // The letters of the word that the right guesses didn't use up.
+        let mut left: Vec<char> = guess.iter().zip(&self.letters).filter(|(g, w)| g != w).map(|(_, &w)| w).collect();
+        let hint = guess
+            .iter()
+            .zip(&self.letters)
+            .map(|(g, w)| {
+                if g == w {
+                    Letter::Right
+                } else if let Some(i) = left.iter().position(|l| l == g) {
+                    left.swap_remove(i);
+                    Letter::Elsewhere
+                } else {
+                    Letter::Absent
+                }
+            })
+            .collect();
+        Ok(hint)
+    }
+
+    fn is_solved(&self, hint: &Vec<Letter>) -> bool {
+        hint.iter().all(|letter| *letter == Letter::Right)
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.letters.iter().collect::<String>())
+    }
+}
+
+impl Game<Word> {
+    /** Starts a word game where `word` has to be guessed, in `MAX_ATTEMPTS` attempts. */
+    pub fn word(word: &str) -> Game<Word> {
+        Game::with_secret(Word::new(word)).max_attempts(MAX_ATTEMPTS)
+    }
+
+    /** Starts a word game with a word of `WORDS` picked by `rng`. */
+    pub fn with_random_word(rng: &mut impl Rng) -> Game<Word> {
+        Game::word(WORDS.choose(rng).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guessing_game::Turn;
+
+    use Letter::*;
+
+    #[test]
+    fn letters_are_marked_once_each() {
+        let word = Word::new("crate");
+
+        assert_eq!(Ok(vec![Right, Right, Right, Right, Right]), word.check(&String::from("CRATE")));
+        assert_eq!(Ok(vec![Elsewhere, Right, Right, Elsewhere, Right]), word.check(&String::from("trace")));
+        // Only one of the e's is in the word, and the right one takes it.
+        assert_eq!(Ok(vec![Absent, Absent, Absent, Absent, Right]), word.check(&String::from("eeeee")));
+        assert_eq!(Ok(vec![Elsewhere, Absent, Absent, Absent, Absent]), word.check(&String::from("ebbbb")));
+    }
+
+    #[test]
+    fn guesses_that_cant_be_the_word_are_not_attempts() {
+        let mut game = Game::word("frogs");
+
+        assert_eq!(Err(WordError::WrongLength { expected: 5, got: 4 }), game.submit(String::from("frog")));
+        assert_eq!(Err(WordError::NotALetter('5')), game.submit(String::from("frog5")));
+        assert_eq!(0, game.attempts());
+        assert!(game.revealed().is_none());
+
+        for _ in 1..MAX_ATTEMPTS {
+            assert_eq!(Turn::Again, game.submit(String::from("grape")).unwrap().1);
+        }
+        assert_eq!(Turn::Lost, game.submit(String::from("grape")).unwrap().1);
+        assert_eq!("frogs", game.revealed().unwrap().to_string());
+    }
+
+    #[test]
+    fn every_word_fits_the_game() {
+        for word in WORDS {
+            let mut game = Game::word(word);
+            assert_eq!(Turn::Solved, game.submit(word.to_string()).unwrap().1);
+            assert_eq!(1, game.score().attempts);
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use std::{thread, vec};
 use std::time::Duration;
+use hello_macro_derive::EnumIter;
 
 /* CLOSURES */
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+/** `EnumIter`, from the hello_macro_derive crate, gives ShirtColor `variants()` and `iter()`, which list its colors. See the `shirt_colors` test below. */
+#[derive(Debug, PartialEq, Copy, Clone, EnumIter)]
 enum ShirtColor {
     Red,
     Blue,
@@ -215,6 +217,21 @@ fn iterator_map() {
     assert_eq!(v2, vec![2, 3, 4]);
 }
 
+/** An iterator over the variants of an enum comes in handy to go through every case, here to count the shirts of each color in stock. */
+#[test]
+fn shirt_colors() {
+    let store = Inventory {
+        shirts: vec![ShirtColor::Blue, ShirtColor::Red, ShirtColor::Blue],
+    };
+
+    let stock: Vec<_> = ShirtColor::iter()
+        .map(|color| (*color, store.shirts.iter().filter(|shirt| *shirt == color).count()))
+        .collect();
+
+    assert_eq!([ShirtColor::Red, ShirtColor::Blue], ShirtColor::variants());
+    assert_eq!(stock, vec![(ShirtColor::Red, 1), (ShirtColor::Blue, 2)]);
+}
+
 /** The code below uses a closure that capture its enviroment. The shoes_in_size() function takes ownership of a vector of shoes and a 
  *  shoe size as parameters. It returns a vector containing shoes of the specified size. In the body of the function, we call into_iter
  *  to create an iterator that takes ownership of the vector. Then we call filter to adapt that iterator into a new iterator taht only
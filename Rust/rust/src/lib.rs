@@ -1,5 +1,5 @@
 mod hello_world;
-mod guessing_game;
+pub mod guessing_game;
 mod functions;
 mod flow_control;
 mod ownership;
@@ -19,6 +19,23 @@ mod object_oriented_rust;
 mod patterns_matching;
 mod advanced_features;
 
+/** The GUI library of src/object_oriented_rust, re-exported for the gui_progress binary, which animates it outside of the chapter.
+ */
+pub use object_oriented_rust::gui;
+
+/** The `AveragedCollection` of src/object_oriented_rust and the `WindowedAverage` and `SharedAveragedCollection` next to it, re-exported to keep statistics
+ *  of values outside of the chapter, like the response times of a server.
+ */
+pub use object_oriented_rust::averaged_collection;
+
+/** The `StateMachine` the blog of src/object_oriented_rust is built on, re-exported for any other states and events.
+ */
+pub use object_oriented_rust::state_machine;
+
+/** The `LimitTracker` of src/smart_pointers and the messengers it warns with, re-exported to track quotas outside of the chapter.
+ */
+pub use smart_pointers::{Clock, LimitTracker, Messenger, MultiMessenger, Notification, RateLimited, SendError, Severity, SystemClock};
+
 /** Function add_two present in src/testing/mod.rs moved here for usage in tests/integration tests.
  */
 pub fn add_two(a: i32) -> i32 {
@@ -0,0 +1,282 @@
+/** The average of only the last few values added, like the one of a collection that forgets its oldest values. */
+mod windowed;
+
+/** A collection that many threads can add values to at once. */
+mod shared;
+
+pub use shared::{SharedAveragedCollection, Snapshot};
+pub use windowed::WindowedAverage;
+
+use std::fmt;
+
+/** An index past the end of a collection, which has `len` values. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfRange {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "index {} is out of range for a collection of {} values", self.index, self.len)
+    }
+}
+
+impl std::error::Error for OutOfRange {}
+
+/** Fields of `AveragedCollection` are not marked asa pub so that users are forced to use the methods defined for the struct and thus, the average is always updated.
+ *
+ *  The other statistics are kept up to date the same way. The sum of the values and the sum of their squares change with every value that is added or
+ *  removed, so the average and the variance never have to go through the list again. The minimum, maximum, median and percentiles need the values in order
+ *  instead, so the collection keeps a sorted copy of them as well, where each value is inserted at its place. This makes adding and removing values take
+ *  as long as the list is, but reading any of the statistics is immediate, however often it's done.
+ */
+#[derive(Debug, Clone)]
+pub struct AveragedCollection {
+    list: Vec<i32>,
+    sorted: Vec<i32>,
+    sum: i64,
+    squares: i128,
+    average: f64,
+}
+
+impl AveragedCollection {
+    pub fn new() -> AveragedCollection {
+        let mut collection = AveragedCollection { list: Vec::new(), sorted: Vec::new(), sum: 0, squares: 0, average: 0.0 };
+        collection.update_average();
+        collection
+    }
+
+    pub fn add(&mut self, value: i32) {
+        self.list.push(value);
+        let at = self.sorted.partition_point(|known| *known < value);
+        self.sorted.insert(at, value);
+        self.sum += value as i64;
+        self.squares += (value as i128).pow(2);
+        self.update_average();
+    }
+
+    pub fn remove(&mut self) -> Option<i32> {
+        let result = self.list.pop();
+        match result {
+            Some(value) => {
+                self.forget(value);
+                Some(value)
+            }
+            None => None,
+        }
+    }
+
+    /** Removes the value at `index`, counting from the first one added, and returns it. The values after it move one place closer to the start. */
+    pub fn remove_at(&mut self, index: usize) -> Result<i32, OutOfRange> {
+        if index >= self.list.len() {
+            return Err(OutOfRange { index, len: self.list.len() });
+        }
+        let value = self.list.remove(index);
+        self.forget(value);
+        Ok(value)
+    }
+
+    /** Removes the first value added that is equal to `value`. Returns whether there was one. */
+    pub fn remove_value(&mut self, value: &i32) -> bool {
+        match self.list.iter().position(|known| known == value) {
+            Some(index) => {
+                self.list.remove(index);
+                self.forget(*value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /** Takes `value`, which was just removed from the list, out of the statistics. */
+    fn forget(&mut self, value: i32) {
+        let at = self.sorted.partition_point(|known| *known < value);
+        self.sorted.remove(at);
+        self.sum -= value as i64;
+        self.squares -= (value as i128).pow(2);
+        self.update_average();
+    }
+
+    pub fn average(&self) -> f64 {
+        self.average
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn min(&self) -> Option<i32> {
+        self.sorted.first().copied()
+    }
+
+    pub fn max(&self) -> Option<i32> {
+        self.sorted.last().copied()
+    }
+
+    /** The value in the middle of the sorted values, or the average of the two in the middle when there's an even number of them. */
+    pub fn median(&self) -> Option<f64> {
+        let middle = self.sorted.len() / 2;
+        match self.sorted.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(self.sorted[middle] as f64),
+            _ => Some((self.sorted[middle - 1] as f64 + self.sorted[middle] as f64) / 2.0),
+        }
+    }
+
+    /** The value that `p` percent of the sorted values are at or below, like the response time that 95% of the requests of a server took at most when `p`
+     *  is 95. When it falls between two values, it's as far between them as the percentage is, so the 50th percentile is the median.
+     *
+     *  # Panics
+     *
+     *  The `percentile` method will panic if `p` is not between 0 and 100.
+     */
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        assert!((0.0..=100.0).contains(&p));
+
+        let last = self.sorted.len().checked_sub(1)?;
+        let rank = p * last as f64 / 100.0;
+        let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+        let (low, high) = (self.sorted[below] as f64, self.sorted[above] as f64);
+        Some(low + (high - low) * (rank - below as f64))
+    }
+
+    /** How far the values are from their average, as the average of the squares of their distances to it. This is the variance of the values themselves,
+     *  not the one estimated for everything they could be a sample of.
+     */
+    pub fn variance(&self) -> Option<f64> {
+        let len = self.list.len() as i128;
+        if len == 0 {
+            return None;
+        }
+        // n² times the variance is n * Σx² - (Σx)², which is exact with integers.
+        let sum = self.sum as i128;
+        Some((len * self.squares - sum * sum) as f64 / (len * len) as f64)
+    }
+
+    pub fn stddev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    fn update_average(&mut self) {
+        self.average = self.sum as f64 / self.list.len() as f64;
+    }
+}
+
+/** The same empty collection as `new`, with the average of no values, not a zero. */
+impl Default for AveragedCollection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(values: &[i32]) -> AveragedCollection {
+        let mut collection = AveragedCollection::new();
+        for value in values {
+            collection.add(*value);
+        }
+        collection
+    }
+
+    #[test]
+    fn statistics_follow_the_values() {
+        let mut collection = collection(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        assert_eq!(5.0, collection.average());
+        assert_eq!((Some(2), Some(9)), (collection.min(), collection.max()));
+        assert_eq!(Some(4.0), collection.variance());
+        assert_eq!(Some(2.0), collection.stddev());
+
+        assert_eq!(Some(9), collection.remove());
+        assert_eq!(Some(7), collection.max());
+        assert_eq!(31.0 / 7.0, collection.average());
+        assert_eq!(Some(96.0 / 49.0), collection.variance());
+    }
+
+    /** The median comes from the sorted copy of the values, which is kept in order whatever order the values are added and removed in. */
+    #[test]
+    fn the_median_is_the_middle_of_the_sorted_values() {
+        let mut collection = collection(&[7, -3, 12, 7]);
+        assert_eq!(Some(7.0), collection.median());
+
+        collection.add(1);
+        assert_eq!(Some(7.0), collection.median());
+        collection.remove();
+        collection.remove();
+        assert_eq!(Some(7.0), collection.median());
+        collection.remove();
+        assert_eq!(Some(2.0), collection.median());
+        assert_eq!(Some(-3), collection.min());
+    }
+
+    #[test]
+    fn percentiles_are_between_the_sorted_values() {
+        let mut latencies = AveragedCollection::new();
+        for latency in (0..=100).rev() {
+            latencies.add(latency);
+        }
+        assert_eq!((Some(95.0), Some(99.0)), (latencies.percentile(95.0), latencies.percentile(99.0)));
+
+        let collection = collection(&[40, 10, 30, 20]);
+        assert_eq!(Some(10.0), collection.percentile(0.0));
+        assert_eq!(Some(32.5), collection.percentile(75.0));
+        assert_eq!(Some(40.0), collection.percentile(100.0));
+        assert_eq!(collection.median(), collection.percentile(50.0));
+        assert_eq!(None, AveragedCollection::new().percentile(99.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn percentiles_are_percentages() {
+        collection(&[1]).percentile(101.0);
+    }
+
+    #[test]
+    fn values_are_removed_from_anywhere() {
+        let mut collection = collection(&[3, 8, 1, 8, 5]);
+        assert_eq!(Ok(1), collection.remove_at(2));
+        assert_eq!(Some(3), collection.min());
+        assert_eq!(6.0, collection.average());
+
+        assert!(collection.remove_value(&8));
+        assert_eq!(Some(8), collection.max());
+        assert_eq!(Some(5.0), collection.median());
+        assert!(!collection.remove_value(&1));
+
+        let error = collection.remove_at(3).unwrap_err();
+        assert_eq!(OutOfRange { index: 3, len: 3 }, error);
+        assert_eq!("index 3 is out of range for a collection of 3 values", error.to_string());
+        assert_eq!(Some(5), collection.remove());
+        assert_eq!(Some(8), collection.remove());
+        assert_eq!((Some(3), Some(3)), (collection.min(), collection.max()));
+    }
+
+    #[test]
+    fn empty_collections_have_no_statistics() {
+        let mut collection = collection(&[i32::MAX, i32::MAX]);
+        assert_eq!(Some(0.0), collection.variance());
+        collection.remove();
+        collection.remove();
+
+        assert!(collection.average().is_nan());
+        assert_eq!((None, None, None), (collection.min(), collection.max(), collection.median()));
+        assert_eq!((None, None), (collection.variance(), collection.stddev()));
+        assert_eq!(None, collection.remove());
+    }
+
+    #[test]
+    fn default_collections_are_new_ones() {
+        let (default, new) = (AveragedCollection::default(), AveragedCollection::new());
+
+        assert!(default.average().is_nan() && new.average().is_nan());
+        assert_eq!((default.len(), default.min(), default.max()), (new.len(), new.min(), new.max()));
+        assert_eq!((default.median(), default.variance()), (new.median(), new.variance()));
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use super::AveragedCollection;
+
+/** How many values a collection had and their average, read together at one moment. */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapshot {
+    pub count: usize,
+    pub average: f64,
+}
+
+/** An `AveragedCollection` that many threads can add values to at once, like the workers of a thread pool recording how long their jobs took. Every clone
+ *  is another handle to the same collection, and a `Mutex` lets only one of them change it or read it at a time, so what is read is never halfway through
+ *  an update.
+ */
+#[derive(Debug, Clone)]
+pub struct SharedAveragedCollection {
+    collection: Arc<Mutex<AveragedCollection>>,
+}
+
+impl SharedAveragedCollection {
+    pub fn new() -> SharedAveragedCollection {
+        SharedAveragedCollection { collection: Arc::new(Mutex::new(AveragedCollection::new())) }
+    }
+
+    pub fn add(&self, value: i32) {
+        self.lock().add(value);
+    }
+
+    pub fn remove(&self) -> Option<i32> {
+        self.lock().remove()
+    }
+
+    /** The number of values and their average, both from the same state of the collection. */
+    pub fn snapshot(&self) -> Snapshot {
+        let collection = self.lock();
+        Snapshot { count: collection.len(), average: collection.average() }
+    }
+
+    /** Calls `read` with the collection, for the statistics a snapshot doesn't have. No thread changes it until `read` returns. */
+    pub fn read<R>(&self, read: impl FnOnce(&AveragedCollection) -> R) -> R {
+        read(&self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, AveragedCollection> {
+        // A thread can only panic with the lock in `read`, which doesn't change the collection, so it's still whole.
+        self.collection.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for SharedAveragedCollection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn threads_add_to_the_same_collection() {
+        let shared = SharedAveragedCollection::new();
+        let handles: Vec<_> = (0..8)
+            .map(|worker| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    for value in 0..100 {
+                        shared.add(worker * 100 + value);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(Snapshot { count: 800, average: 399.5 }, shared.snapshot());
+        assert_eq!((Some(0), Some(799)), shared.read(|collection| (collection.min(), collection.max())));
+    }
+
+    #[test]
+    fn default_collections_are_new_ones() {
+        let (default, new) = (SharedAveragedCollection::default().snapshot(), SharedAveragedCollection::new().snapshot());
+
+        assert_eq!(default.count, new.count);
+        assert!(default.average.is_nan() && new.average.is_nan());
+    }
+
+    #[test]
+    fn collections_are_still_read_after_a_panic() {
+        let shared = SharedAveragedCollection::new();
+        shared.add(4);
+        let reader = shared.clone();
+        assert!(thread::spawn(move || reader.read(|_| panic!("the reader failed"))).join().is_err());
+
+        shared.add(6);
+        assert_eq!(Snapshot { count: 2, average: 5.0 }, shared.snapshot());
+        assert_eq!(Some(6), shared.remove());
+    }
+}
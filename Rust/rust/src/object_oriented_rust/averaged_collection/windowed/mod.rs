@@ -0,0 +1,99 @@
+/** An average of only the last values that were added, like the last minute of a server or the last few readings of a tracker, so old values stop
+ *  weighing on it. The values are kept in a ring buffer as long as the window: once it's full, each new value takes the place of the oldest one, and the
+ *  sum is updated with the one that leaves and the one that comes in, so adding a value takes the same time however big the window is.
+ */
+#[derive(Debug, Clone)]
+pub struct WindowedAverage {
+    values: Vec<i32>,
+    window_size: usize,
+    /** Where the next value goes, which is where the oldest one is once the window is full. */
+    next: usize,
+    sum: i64,
+}
+
+impl WindowedAverage {
+    /** An empty window for the last `window_size` values.
+     *
+     *  # Panics
+     *
+     *  The `new` function will panic if `window_size` is zero, as there would be no values to average.
+     */
+    pub fn new(window_size: usize) -> WindowedAverage {
+        assert!(window_size > 0);
+
+        WindowedAverage { values: Vec::with_capacity(window_size), window_size, next: 0, sum: 0 }
+    }
+
+    /** Adds `value` to the window. Returns the value it pushed out, if the window was full. */
+    pub fn add(&mut self, value: i32) -> Option<i32> {
+        self.sum += value as i64;
+        let oldest = if self.is_full() {
+            Some(std::mem::replace(&mut self.values[self.next], value))
+        } else {
+            self.values.push(value);
+            None
+        };
+        self.next = (self.next + 1) % self.window_size;
+        if let Some(oldest) = oldest {
+            self.sum -= oldest as i64;
+        }
+        oldest
+    }
+
+    /** The average of the values in the window. Like the one of an `AveragedCollection`, it's NaN while there are none. */
+    pub fn average(&self) -> f64 {
+        self.sum as f64 / self.values.len() as f64
+    }
+
+    /** The values in the window, from the oldest to the newest. */
+    pub fn values(&self) -> impl Iterator<Item = i32> + '_ {
+        let (newest, oldest) = self.values.split_at(if self.is_full() { self.next } else { 0 });
+        oldest.iter().chain(newest).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /** Whether the window has as many values as it can, so that adding one pushes the oldest out. */
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.window_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_last_values_are_averaged() {
+        let mut window = WindowedAverage::new(3);
+        assert!(window.average().is_nan());
+
+        assert_eq!(None, window.add(10));
+        assert_eq!(None, window.add(20));
+        assert_eq!(15.0, window.average());
+        assert_eq!(None, window.add(30));
+        assert!(window.is_full());
+
+        assert_eq!(Some(10), window.add(40));
+        assert_eq!(Some(20), window.add(-90));
+        assert_eq!(vec![30, 40, -90], window.values().collect::<Vec<_>>());
+        assert_eq!(-20.0 / 3.0, window.average());
+        assert_eq!(3, window.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn windows_hold_at_least_one_value() {
+        WindowedAverage::new(0);
+    }
+}
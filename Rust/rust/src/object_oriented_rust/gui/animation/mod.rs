@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+/** Values that can change little by little from one to another, so components can animate them. */
+pub trait Animatable: Copy {
+    /** The value `t` of the way from `self` to `to`, where `t` goes from 0, which is `self`, to 1, which is `to`. */
+    fn lerp(self, to: Self, t: f64) -> Self;
+}
+
+impl Animatable for f64 {
+    fn lerp(self, to: f64, t: f64) -> f64 {
+        self + (to - self) * t
+    }
+}
+
+/** Whole numbers, like sizes in cells, are rounded to the nearest one. */
+impl Animatable for u32 {
+    fn lerp(self, to: u32, t: f64) -> u32 {
+        (self as f64).lerp(to as f64, t).round() as u32
+    }
+}
+
+/** A value going from where it was to where it was told to go, over some time. Components keep the properties they animate this way, and move them along
+ *  when their screen ticks.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Animation<T> {
+    from: T,
+    to: T,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl<T: Animatable> Animation<T> {
+    /** An animation that isn't going anywhere, that stays at `value` until it's animated. */
+    pub fn new(value: T) -> Animation<T> {
+        Animation { from: value, to: value, duration: Duration::ZERO, elapsed: Duration::ZERO }
+    }
+
+    /** Where the value is now. */
+    pub fn value(&self) -> T {
+        if self.is_running() {
+            self.from.lerp(self.to, self.elapsed.as_secs_f64() / self.duration.as_secs_f64())
+        } else {
+            self.to
+        }
+    }
+
+    /** Where the value is going, which is where it is once the animation is over. */
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.elapsed < self.duration
+    }
+
+    /** Moves the value from where it is now to `to`, in `duration`. A duration of zero puts it there right away. */
+    pub fn animate_to(&mut self, to: T, duration: Duration) {
+        self.from = self.value();
+        self.to = to;
+        self.duration = duration;
+        self.elapsed = Duration::ZERO;
+    }
+
+    /** Moves the animation `dt` forward. Returns whether it was running, which is whether the value may have changed. */
+    pub fn tick(&mut self, dt: Duration) -> bool {
+        let running = self.is_running();
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        running
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animations_move_values_over_time() {
+        let mut width = Animation::new(0);
+        width.animate_to(10, Duration::from_secs(2));
+
+        assert!(width.tick(Duration::from_millis(500)));
+        assert_eq!(3, width.value());
+
+        // Changing where it goes starts from where it is.
+        width.animate_to(0, Duration::from_secs(1));
+        width.tick(Duration::from_millis(500));
+        assert_eq!(2, width.value());
+
+        assert!(width.tick(Duration::from_secs(5)));
+        assert!(!width.is_running());
+        assert!(!width.tick(Duration::from_secs(1)));
+        assert_eq!(0, width.value());
+    }
+}
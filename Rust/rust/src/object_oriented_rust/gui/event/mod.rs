@@ -0,0 +1,57 @@
+use super::{Draw, Rect};
+
+/** Something the user did that a component may react to. The position of a click counts from the top left corner of the component that gets it. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Click { x: u32, y: u32 },
+    KeyPress(Key),
+    /** The component is now the one keys go to. */
+    Focus,
+    /** The component was the one keys went to, and isn't anymore. */
+    Blur,
+}
+
+/** The keys components understand. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Left,
+    Right,
+    Enter,
+    Escape,
+}
+
+/** Passes `event` on to one of `children`, placed at `frames`, the way every container does it. A click goes to the child under it, which then has the focus,
+ *  and keys go to the child with the focus. `focused` is where the container keeps that child. Returns whether the child did something with the event.
+ *
+ *  Where children overlap, the click goes to the one drawn on top: the one with the highest z-index, or the last one of those with the same. A child that
+ *  captures events, like an open modal, gets every click and key, and the others get none.
+ */
+pub fn route(children: &mut [Box<dyn Draw>], frames: &[Rect], focused: &mut Option<usize>, event: &Event) -> bool {
+    let top = |children: &[Box<dyn Draw>], i: &usize| (children[*i].z_index(), *i);
+    let captor = (0..children.len().min(frames.len())).filter(|i| children[*i].captures_events()).max_by_key(|i| top(children, i));
+
+    match *event {
+        Event::Click { x, y } => {
+            let under = (0..frames.len()).filter(|i| frames[*i].contains(x, y)).max_by_key(|i| top(children, i));
+            let Some(i) = captor.or(under) else {
+                return false;
+            };
+            if !frames[i].contains(x, y) {
+                return false;
+            }
+            if *focused != Some(i) {
+                if let Some(old) = focused.replace(i) {
+                    children[old].on_event(&Event::Blur);
+                }
+                children[i].on_event(&Event::Focus);
+            }
+            children[i].on_event(&Event::Click { x: x - frames[i].x, y: y - frames[i].y })
+        }
+        Event::KeyPress(_) => captor.or(*focused).is_some_and(|i| children[i].on_event(event)),
+        // The container got the focus from a click, which already gave it to one of its children.
+        Event::Focus => false,
+        Event::Blur => focused.take().is_some_and(|i| children[i].on_event(event)),
+    }
+}
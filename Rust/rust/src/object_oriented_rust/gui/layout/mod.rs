@@ -0,0 +1,571 @@
+use std::time::Duration;
+
+use super::event::Event;
+use super::style::Styled;
+use super::tree::Children;
+use super::{Draw, FromSpec, Registry, Renderer, Spec, SpecError, Style, Theme};
+
+/** How big a component is, in cells of the screen: columns wide and rows high. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Size {
+    pub fn new(width: u32, height: u32) -> Size {
+        Size { width, height }
+    }
+}
+
+/** Where a component is and how big it is. `x` and `y` count from the top left corner of the container it's in. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, size: Size) -> Rect {
+        Rect { x, y, width: size.width, height: size.height }
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /** Whether the rects have any cell in common. */
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width && other.x < self.x + self.width && self.y < other.y + other.height && other.y < self.y + self.height
+    }
+
+    /** Whether the cell at `x` and `y` is inside of the rect. */
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height
+    }
+}
+
+/** The most room a component can take, given to it by whatever contains it. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraints {
+    pub max_width: u32,
+    pub max_height: u32,
+}
+
+impl Constraints {
+    pub fn new(max_width: u32, max_height: u32) -> Constraints {
+        Constraints { max_width, max_height }
+    }
+
+    /** `size`, cut down to fit in the constraints. */
+    pub fn constrain(&self, size: Size) -> Size {
+        Size::new(size.width.min(self.max_width), size.height.min(self.max_height))
+    }
+}
+
+/** Components placed side by side, from left to right, with `spacing` empty columns between them. Each of them gets the width the ones before it left. */
+#[derive(Default)]
+pub struct Row {
+    children: Children,
+    spacing: u32,
+    style: Styled,
+    id: Option<String>,
+}
+
+/** Components placed one under the other, from top to bottom, with `spacing` empty rows between them. Each of them gets the height the ones above it left. */
+#[derive(Default)]
+pub struct Column {
+    children: Children,
+    spacing: u32,
+    style: Styled,
+    id: Option<String>,
+}
+
+/** Components placed in a table of `columns` columns, filled row by row. Every column is as wide as its widest cell and every row as high as its highest one. */
+pub struct Grid {
+    children: Children,
+    columns: usize,
+    spacing: u32,
+    style: Styled,
+    id: Option<String>,
+}
+
+/** A box of a fixed size, with each of its children where it's told to put them, counting from its top left corner inside of the padding and border. Children
+ *  may overlap, and their z-index tells which of them are on top. Panels can be nested, each counting from its own corner. Like every container, panels only
+ *  hide what's under them when they have a background.
+ */
+pub struct Panel {
+    size: Size,
+    children: Children,
+    positions: Vec<(u32, u32)>,
+    z_index: i32,
+    style: Styled,
+    id: Option<String>,
+}
+
+impl Row {
+    pub fn new() -> Row {
+        Row::default()
+    }
+
+    pub fn spacing(mut self, spacing: u32) -> Row {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn child(mut self, child: impl Draw + 'static) -> Row {
+        self.children.push(Box::new(child));
+        self
+    }
+
+    /** Gives the container an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Row {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /** The style the container is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        if self.style.set(style) {
+            self.children.invalidate();
+        }
+    }
+
+    /** Where each child was put by the last `layout`, in the order they were added. */
+    pub fn frames(&self) -> &[Rect] {
+        self.children.frames()
+    }
+}
+
+impl Column {
+    pub fn new() -> Column {
+        Column::default()
+    }
+
+    pub fn spacing(mut self, spacing: u32) -> Column {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn child(mut self, child: impl Draw + 'static) -> Column {
+        self.children.push(Box::new(child));
+        self
+    }
+
+    /** Gives the container an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Column {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /** The style the container is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        if self.style.set(style) {
+            self.children.invalidate();
+        }
+    }
+
+    /** Where each child was put by the last `layout`, in the order they were added. */
+    pub fn frames(&self) -> &[Rect] {
+        self.children.frames()
+    }
+}
+
+impl Grid {
+    /** A grid of `columns` columns. A grid needs at least one, so 0 is taken as 1. */
+    pub fn new(columns: usize) -> Grid {
+        Grid { children: Children::default(), columns: columns.max(1), spacing: 0, style: Styled::default(), id: None }
+    }
+
+    pub fn spacing(mut self, spacing: u32) -> Grid {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn child(mut self, child: impl Draw + 'static) -> Grid {
+        self.children.push(Box::new(child));
+        self
+    }
+
+    /** Gives the container an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Grid {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /** The style the container is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        if self.style.set(style) {
+            self.children.invalidate();
+        }
+    }
+
+    /** Where each child was put by the last `layout`, in the order they were added. */
+    pub fn frames(&self) -> &[Rect] {
+        self.children.frames()
+    }
+}
+
+impl Panel {
+    /** A panel `width` columns wide and `height` rows high, its padding and border included. */
+    pub fn new(width: u32, height: u32) -> Panel {
+        Panel { size: Size::new(width, height), children: Children::default(), positions: Vec::new(), z_index: 0, style: Styled::default(), id: None }
+    }
+
+    /** Puts `child` at `x` and `y` in the panel. */
+    pub fn child_at(mut self, x: u32, y: u32, child: impl Draw + 'static) -> Panel {
+        self.children.push(Box::new(child));
+        self.positions.push((x, y));
+        self
+    }
+
+    /** Puts the panel over the components of its container with a lower z-index, and under those with a higher one. */
+    pub fn with_z_index(mut self, z_index: i32) -> Panel {
+        self.z_index = z_index;
+        self
+    }
+
+    /** Gives the panel an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Panel {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /** The style the panel is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        if self.style.set(style) {
+            self.children.invalidate();
+        }
+    }
+
+    /** Where each child was put by the last `layout`, in the order they were added. */
+    pub fn frames(&self) -> &[Rect] {
+        self.children.frames()
+    }
+}
+
+/** Containers draw, redraw, take events and ticks, pass the theme on and have their IDs the same way, through their `Children`. Only their layouts differ. */
+macro_rules! container {
+    () => {
+        fn id(&self) -> Option<&str> {
+            self.id.as_deref()
+        }
+
+        fn children(&self) -> &[Box<dyn Draw>] {
+            self.children.components()
+        }
+
+        fn children_mut(&mut self) -> &mut [Box<dyn Draw>] {
+            self.children.components_mut()
+        }
+
+        fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+            self.children.draw(renderer, area, &self.style.get());
+        }
+
+        fn redraw(&mut self, renderer: &mut dyn Renderer, area: Rect) {
+            self.children.redraw(renderer, area, &self.style.get());
+        }
+
+        fn apply_theme(&mut self, theme: &Theme, inherited: &Style) {
+            if self.style.theme(&theme.groups, inherited) {
+                self.children.invalidate();
+            }
+            self.children.apply_theme(theme, &self.style.get());
+        }
+
+        fn is_dirty(&self) -> bool {
+            self.children.is_dirty()
+        }
+
+        fn mark_clean(&mut self) {
+            self.children.mark_clean();
+        }
+
+        fn on_event(&mut self, event: &Event) -> bool {
+            self.children.route(event)
+        }
+
+        fn tick(&mut self, dt: Duration) {
+            self.children.tick(dt);
+        }
+    };
+}
+
+impl Draw for Row {
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let style = self.style.get();
+        let (room, edge) = (style.content(constraints), style.edge());
+        let mut frames = Vec::new();
+        let mut x = 0;
+        let mut height = 0;
+
+        for (i, child) in self.children.iter_mut().enumerate() {
+            if i > 0 {
+                x = (x + self.spacing).min(room.max_width);
+            }
+            let size = child.layout(Constraints::new(room.max_width - x, room.max_height));
+            frames.push(Rect::new(edge + x, edge, size));
+            x += size.width;
+            height = height.max(size.height);
+        }
+
+        self.children.place(frames);
+        constraints.constrain(style.outer(Size::new(x, height)))
+    }
+
+    container!();
+
+    fn to_spec(&self) -> Option<Spec> {
+        let spec = Spec::new(Row::KIND).property("spacing", self.spacing).common(self.id(), &self.style.own());
+        Some(spec.children_of(self.children.components()))
+    }
+}
+
+impl FromSpec for Row {
+    const KIND: &'static str = "row";
+
+    fn from_spec(spec: &Spec, registry: &Registry) -> Result<Row, SpecError> {
+        let mut row = Row::new().spacing(spec.parse("spacing", 0)?);
+        registry.build_children(spec)?.into_iter().for_each(|child| row.children.push(child));
+        row.id = spec.id().map(str::to_string);
+        row.set_style(spec.style()?);
+        Ok(row)
+    }
+}
+
+impl Draw for Column {
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let style = self.style.get();
+        let (room, edge) = (style.content(constraints), style.edge());
+        let mut frames = Vec::new();
+        let mut y = 0;
+        let mut width = 0;
+
+        for (i, child) in self.children.iter_mut().enumerate() {
+            if i > 0 {
+                y = (y + self.spacing).min(room.max_height);
+            }
+            let size = child.layout(Constraints::new(room.max_width, room.max_height - y));
+            frames.push(Rect::new(edge, edge + y, size));
+            y += size.height;
+            width = width.max(size.width);
+        }
+
+        self.children.place(frames);
+        constraints.constrain(style.outer(Size::new(width, y)))
+    }
+
+    container!();
+
+    fn to_spec(&self) -> Option<Spec> {
+        let spec = Spec::new(Column::KIND).property("spacing", self.spacing).common(self.id(), &self.style.own());
+        Some(spec.children_of(self.children.components()))
+    }
+}
+
+impl FromSpec for Column {
+    const KIND: &'static str = "column";
+
+    fn from_spec(spec: &Spec, registry: &Registry) -> Result<Column, SpecError> {
+        let mut column = Column::new().spacing(spec.parse("spacing", 0)?);
+        registry.build_children(spec)?.into_iter().for_each(|child| column.children.push(child));
+        column.id = spec.id().map(str::to_string);
+        column.set_style(spec.style()?);
+        Ok(column)
+    }
+}
+
+impl Draw for Grid {
+    /** Every cell gets an even share of the width, and the rows are laid out first to know how wide each column ended up. */
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let style = self.style.get();
+        let (room, edge) = (style.content(constraints), style.edge());
+        let columns = self.columns as u32;
+        let gaps = self.spacing.saturating_mul(columns - 1);
+        let cell = Constraints::new(room.max_width.saturating_sub(gaps) / columns, room.max_height);
+        let sizes: Vec<Size> = self.children.iter_mut().map(|child| child.layout(cell)).collect();
+
+        let mut widths = vec![0; self.columns];
+        let mut heights = vec![0; sizes.len().div_ceil(self.columns)];
+        for (i, size) in sizes.iter().enumerate() {
+            widths[i % self.columns] = widths[i % self.columns].max(size.width);
+            heights[i / self.columns] = heights[i / self.columns].max(size.height);
+        }
+
+        let frames = sizes
+            .iter()
+            .enumerate()
+            .map(|(i, size)| {
+                let (column, row) = (i % self.columns, i / self.columns);
+                let x: u32 = widths[..column].iter().map(|width| width + self.spacing).sum();
+                let y: u32 = heights[..row].iter().map(|height| height + self.spacing).sum();
+                Rect::new(edge + x, edge + y, *size)
+            })
+            .collect();
+        self.children.place(frames);
+
+        // A grid with fewer children than columns only spaces out those it has.
+        let gaps = |count: usize| self.spacing * count.saturating_sub(1) as u32;
+        let width = widths.iter().sum::<u32>() + gaps(self.columns.min(sizes.len()));
+        let height = heights.iter().sum::<u32>() + gaps(heights.len());
+        constraints.constrain(style.outer(Size::new(width, height)))
+    }
+
+    container!();
+
+    fn to_spec(&self) -> Option<Spec> {
+        let spec = Spec::new(Grid::KIND).property("columns", self.columns).property("spacing", self.spacing);
+        Some(spec.common(self.id(), &self.style.own()).children_of(self.children.components()))
+    }
+}
+
+impl FromSpec for Grid {
+    const KIND: &'static str = "grid";
+
+    fn from_spec(spec: &Spec, registry: &Registry) -> Result<Grid, SpecError> {
+        let mut grid = Grid::new(spec.parse("columns", 1)?).spacing(spec.parse("spacing", 0)?);
+        registry.build_children(spec)?.into_iter().for_each(|child| grid.children.push(child));
+        grid.id = spec.id().map(str::to_string);
+        grid.set_style(spec.style()?);
+        Ok(grid)
+    }
+}
+
+impl Draw for Panel {
+    /** Each child gets the room there is from where it is to the edge of the panel. */
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        let style = self.style.get();
+        let size = constraints.constrain(self.size);
+        let (room, edge) = (style.content(Constraints::new(size.width, size.height)), style.edge());
+
+        let frames = self
+            .children
+            .iter_mut()
+            .zip(&self.positions)
+            .map(|(child, &(x, y))| {
+                let (x, y) = (x.min(room.max_width), y.min(room.max_height));
+                Rect::new(edge + x, edge + y, child.layout(Constraints::new(room.max_width - x, room.max_height - y)))
+            })
+            .collect();
+        self.children.place(frames);
+        size
+    }
+
+    container!();
+
+    fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
+    /** Each child is written down with where it is in the panel, as its `x` and `y`. */
+    fn to_spec(&self) -> Option<Spec> {
+        let mut spec = Spec::new(Panel::KIND).property("width", self.size.width).property("height", self.size.height);
+        if self.z_index != 0 {
+            spec = spec.property("z_index", self.z_index);
+        }
+        spec = spec.common(self.id(), &self.style.own());
+        for (child, (x, y)) in self.children.components().iter().zip(&self.positions) {
+            if let Some(child) = child.to_spec() {
+                spec = spec.child(child.property("x", x).property("y", y));
+            }
+        }
+        Some(spec)
+    }
+}
+
+impl FromSpec for Panel {
+    const KIND: &'static str = "panel";
+
+    fn from_spec(spec: &Spec, registry: &Registry) -> Result<Panel, SpecError> {
+        let mut panel = Panel::new(spec.parse("width", 0)?, spec.parse("height", 0)?).with_z_index(spec.parse("z_index", 0)?);
+        for child in &spec.children {
+            panel.children.push(registry.build(child)?);
+            panel.positions.push((child.parse("x", 0)?, child.parse("y", 0)?));
+        }
+        panel.id = spec.id().map(str::to_string);
+        panel.set_style(spec.style()?);
+        Ok(panel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_oriented_rust::gui::{Checkbox, Color, Label, TerminalRenderer};
+
+    #[test]
+    fn rows_place_children_side_by_side() {
+        let mut row = Row::new().spacing(1).child(Label::new("Name")).child(Checkbox::new("OK"));
+
+        assert_eq!(Size::new(11, 1), row.layout(Constraints::new(80, 24)));
+        assert_eq!([Rect::new(0, 0, Size::new(4, 1)), Rect::new(5, 0, Size::new(6, 1))], row.frames());
+    }
+
+    #[test]
+    fn columns_stack_children_and_respect_constraints() {
+        let mut column = Column::new().child(Label::new("A much longer label")).child(Label::new("Short"));
+
+        assert_eq!(Size::new(19, 2), column.layout(Constraints::new(80, 24)));
+        assert_eq!(Rect::new(0, 1, Size::new(5, 1)), column.frames()[1]);
+
+        assert_eq!(Size::new(10, 1), column.layout(Constraints::new(10, 1)));
+        assert_eq!(0, column.frames()[1].height);
+    }
+
+    #[test]
+    fn grids_align_columns_and_rows() {
+        let mut grid = Grid::new(2)
+            .spacing(1)
+            .child(Label::new("Name"))
+            .child(Label::new("Ferris"))
+            .child(Label::new("Species"))
+            .child(Label::new("Crab"));
+
+        assert_eq!(Size::new(14, 3), grid.layout(Constraints::new(80, 24)));
+        assert_eq!(Rect::new(8, 0, Size::new(6, 1)), grid.frames()[1]);
+        assert_eq!(Rect::new(0, 2, Size::new(7, 1)), grid.frames()[2]);
+        assert_eq!(Rect::new(8, 2, Size::new(4, 1)), grid.frames()[3]);
+    }
+
+    #[test]
+    fn panels_put_children_where_they_are_told() {
+        let mut over = Panel::new(6, 2).with_z_index(1).child_at(1, 0, Label::new("Over"));
+        over.set_style(Style::new().background(Color::White));
+        let mut panel = Panel::new(12, 4).child_at(1, 1, Label::new("Under")).child_at(3, 1, over).child_at(8, 3, Label::new("Cut off"));
+        panel.set_style(Style::new().border(true));
+
+        assert_eq!(Size::new(12, 4), panel.layout(Constraints::new(80, 24)));
+        assert_eq!(Rect::new(2, 2, Size::new(5, 1)), panel.frames()[0]);
+        // Children don't go past the border.
+        assert_eq!([Rect::new(4, 2, Size::new(6, 1)), Rect::new(9, 3, Size::new(2, 0))], panel.frames()[1..]);
+
+        // The nested panel is drawn over the label, although it was added after it.
+        let mut terminal = TerminalRenderer::new(Size::new(12, 4));
+        panel.draw(&mut terminal, Rect::new(0, 0, Size::new(12, 4)));
+        // The label that got no room at all isn't drawn.
+        assert_eq!("┌──────────┐\n│          │\n│ Un\x1b[47m Over \x1b[0m │\n└──────────┘\n", terminal.to_string());
+    }
+}
@@ -0,0 +1,1380 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use hello_macro_derive::FieldNames;
+
+/** Sizes, constraints and the `Row`, `Column`, `Grid` and `Panel` containers that place other components. */
+mod layout;
+
+/** Clicks and keys, and how containers pass them on to their children. */
+mod event;
+
+/** What components are drawn on: the `TerminalRenderer` and `HtmlRenderer` that draw them as text and as HTML, and the `AccessibilityRenderer` that tells what they are. */
+mod render;
+
+/** The children of containers, kept between frames so that only what changed is drawn again. */
+mod tree;
+
+/** Colors, padding and borders, and the themes that give them to every component on a screen. */
+mod style;
+
+/** Values that components can be bound to, so that they're drawn again when the values change. */
+mod observable;
+
+/** Values that change over time, which components animate as their screen ticks. */
+mod animation;
+
+/** Boxes over everything else, that take every event until they're dismissed. */
+mod modal;
+
+/** Specs, to write components down as text and make them again from it. */
+mod spec;
+
+pub use animation::{Animatable, Animation};
+pub use event::{Event, Key};
+pub use layout::{Column, Constraints, Grid, Panel, Rect, Row, Size};
+pub use modal::Modal;
+pub use observable::{Observable, Subscription};
+pub use render::{AccessibilityRenderer, HtmlRenderer, Renderer, Role, TerminalRenderer};
+pub use spec::{FromSpec, Registry, Spec, SpecError};
+pub use style::{Color, Style, Theme};
+
+use style::Styled;
+use tree::Children;
+
+/** Every component is drawn in two steps. `layout` works out how big it is, never more than `constraints` allow, and containers use it to place their children.
+ *  Then `draw` draws it on `renderer`, in the `area` of the screen it was placed at.
+ *
+ *  Components react to what the user does in `on_event`, which returns whether the event did anything. Those that don't react to anything, like labels, keep
+ *  the default one, which ignores every event.
+ *
+ *  Screens are kept between frames, and a frame only draws again the components that are dirty, those that changed since they were drawn. Components that
+ *  don't keep track of their changes keep the default `is_dirty`, and are drawn every frame.
+ *
+ *  Components with an `id` can be found on their screen by it. Containers give the screen their `children` to look for IDs in them too.
+ *
+ *  Time goes by for components in `tick`, which is where those that are animated move their animations along. The rest keep the default one.
+ *
+ *  Components that overlap are drawn in the order of their `z_index`, so those with a higher one are drawn over the others and get the clicks where they
+ *  overlap. Those that `captures_events` get every event of their container while they do, like a modal until it's dismissed.
+ *
+ *  Components that can be written down as a `Spec` return it from `to_spec`, and are made again from it with a `Registry`. Only what they are right now is
+ *  written: a bound label writes the text it shows, and closures, like what a button does when it's clicked, are left out. Components that can't be written
+ *  down keep the default one, and are left out of the spec of their container.
+ */
+pub trait Draw: AsAny {
+    fn layout(&mut self, constraints: Constraints) -> Size;
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect);
+
+    fn on_event(&mut self, _event: &Event) -> bool {
+        false
+    }
+
+    fn is_dirty(&self) -> bool {
+        true
+    }
+
+    /** Called once the component was drawn, so it's not dirty anymore. */
+    fn mark_clean(&mut self) {}
+
+    /** Fills in what the style of the component leaves out with what `theme` has for its kind, and then with the colors of `inherited`, which are those of the
+     *  container it's in. Containers pass theirs on to their children. Components without a style keep the default, which does nothing.
+     */
+    fn apply_theme(&mut self, _theme: &Theme, _inherited: &Style) {}
+
+    /** Called every time `dt` has gone by on the screen. */
+    fn tick(&mut self, _dt: Duration) {}
+
+    fn z_index(&self) -> i32 {
+        0
+    }
+
+    fn captures_events(&self) -> bool {
+        false
+    }
+
+    fn id(&self) -> Option<&str> {
+        None
+    }
+
+    fn children(&self) -> &[Box<dyn Draw>] {
+        &[]
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Draw>] {
+        &mut []
+    }
+
+    fn to_spec(&self) -> Option<Spec> {
+        None
+    }
+
+    /** Draws the component again over what was drawn before, if it's dirty. Containers only draw again the children that are. */
+    fn redraw(&mut self, renderer: &mut dyn Renderer, area: Rect) {
+        if self.is_dirty() {
+            renderer.clear(area);
+            self.draw(renderer, area);
+            self.mark_clean();
+        }
+    }
+}
+
+/** Lets a `dyn Draw` be turned back into the component it is, with `downcast_ref` and `downcast_mut`. Every type has it, so components don't implement it. */
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<'a> dyn Draw + 'a {
+    /** The component, if it's a `T`. */
+    pub fn downcast_ref<T: Draw + Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Draw + Any>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+/** The components of a screen go one under the other, like in a `Column`. Clicks go to the component under them, which then gets the keys until another one
+ *  is clicked.
+ *
+ *  The screen keeps its components, and the terminal `run` draws them on, from one frame to the next. Only the first frame is drawn whole, the ones after it
+ *  only draw the components that changed.
+ *
+ *  Components without a style of their own are drawn with the one the theme of the screen has for them, which is the default theme unless it's changed.
+ *
+ *  Components with an ID can be found with `get` and `get_mut`, however deep in containers they are. IDs should be unique, of two components with the same one
+ *  only the first is found.
+ */
+pub struct Screen {
+    // Children keeps every component as a Box<dyn Draw>, which ensures all of their types implement the Draw trait.
+    components: Children,
+    area: Rect,
+    terminal: Option<TerminalRenderer>,
+    theme: Theme,
+    // Where the component with each ID is, as the index of every container on the way to it, from the screen down.
+    index: HashMap<String, Vec<usize>>,
+}
+
+impl Screen {
+    pub fn new(components: Vec<Box<dyn Draw>>) -> Screen {
+        let mut screen =
+            Screen { components: Children::default(), area: Rect::default(), terminal: None, theme: Theme::default(), index: HashMap::new() };
+        for component in components {
+            screen.push(component);
+        }
+        screen
+    }
+
+    /** Adds `component` under the ones the screen has. */
+    pub fn push(&mut self, component: Box<dyn Draw>) {
+        index_ids(&mut self.index, component.as_ref(), &mut vec![self.components.len()]);
+        self.components.push(component);
+    }
+
+    /** Takes out the component with the ID `id`, if it's one of the screen's own and not in a container. Those after it move up to the room it leaves. */
+    pub fn remove(&mut self, id: &str) -> Option<Box<dyn Draw>> {
+        let [at] = self.index.get(id)?[..] else {
+            return None;
+        };
+        let component = self.components.remove(at);
+
+        // Every component after it is one place earlier now.
+        self.index.clear();
+        let mut path = Vec::new();
+        for (i, component) in self.components.components().iter().enumerate() {
+            path.push(i);
+            index_ids(&mut self.index, component.as_ref(), &mut path);
+            path.pop();
+        }
+        Some(component)
+    }
+
+    /** The component with the ID `id`. Use `downcast_ref` to have it as the component it is. */
+    pub fn get(&self, id: &str) -> Option<&dyn Draw> {
+        let (first, rest) = self.index.get(id)?.split_first()?;
+        let mut component = self.components.components()[*first].as_ref();
+        for i in rest {
+            component = component.children()[*i].as_ref();
+        }
+        Some(component)
+    }
+
+    /** The component with the ID `id`, to change it. Use `downcast_mut` to have it as the component it is. */
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut dyn Draw> {
+        let (first, rest) = self.index.get(id)?.split_first()?;
+        let mut component = self.components.components_mut()[*first].as_mut();
+        for i in rest {
+            component = component.children_mut()[*i].as_mut();
+        }
+        Some(component)
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /** Changes the theme of the screen, which its components get the next time it's laid out. */
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.components.invalidate();
+    }
+
+    /** Gives every component the theme, then places each of them under the one before it, with the room the ones above it left. Returns the size of all of
+     *  them together.
+     *
+     *  Components with a z-index other than 0, like modals, are over or under the others rather than among them: they aren't placed under anything, but
+     *  from the top left corner of the screen, and with the room of the whole screen.
+     */
+    pub fn layout(&mut self, constraints: Constraints) -> Size {
+        let base = self.theme.base.colors();
+        self.components.apply_theme(&self.theme, &base);
+
+        let mut frames = Vec::new();
+        let mut height = 0;
+        let mut width = 0;
+
+        for component in self.components.iter_mut() {
+            if component.z_index() != 0 {
+                frames.push(Rect::new(0, 0, component.layout(constraints)));
+                continue;
+            }
+            let size = component.layout(Constraints::new(constraints.max_width, constraints.max_height - height));
+            frames.push(Rect::new(0, height, size));
+            height += size.height;
+            width = width.max(size.width);
+        }
+
+        self.components.place(frames);
+        self.area = Rect::new(0, 0, Size::new(constraints.max_width, constraints.max_height));
+        Size::new(width, height)
+    }
+
+    /** Lets `dt` of time go by for every component, which moves their animations along. Screens tick at a fixed step, whatever the time it takes to draw
+     *  them, so animations run at the same speed everywhere.
+     */
+    pub fn tick(&mut self, dt: Duration) {
+        self.components.tick(dt);
+    }
+
+    /** Gives `event` to the component it's for, see `Draw::on_event`. The screen has to be laid out first to know where its components are. */
+    pub fn handle(&mut self, event: &Event) -> bool {
+        self.components.route(event)
+    }
+
+    /** Draws every component where the last `layout` put it. */
+    pub fn render(&mut self, renderer: &mut dyn Renderer) {
+        self.components.draw(renderer, self.area, &self.theme.base.colors());
+        self.components.mark_clean();
+    }
+
+    /** Draws only what changed since the screen was last drawn on `renderer`, over what's there. That only works with renderers that keep what was drawn
+     *  on them, like the terminal. Others, like HTML, have to `render` the whole screen every time.
+     */
+    pub fn update(&mut self, renderer: &mut dyn Renderer) {
+        self.components.redraw(renderer, self.area, &self.theme.base.colors());
+    }
+
+    /** What every component on the screen is, drawn on an `AccessibilityRenderer`: one line for each of them with its role, name and state, indented under
+     *  the container it's in. The screen itself is the group all of them are in. Like drawing, this needs the screen laid out first.
+     */
+    pub fn accessibility_tree(&self) -> String {
+        let mut tree = AccessibilityRenderer::new();
+        self.components.draw(&mut tree, self.area, &self.theme.base.colors());
+        tree.to_string()
+    }
+
+    /** The specs of every component on the screen, written one under the other. Those that can't be written down are left out. */
+    pub fn to_spec(&self) -> String {
+        self.components.components().iter().filter_map(|component| component.to_spec()).map(|spec| spec.to_string()).collect()
+    }
+
+    /** A screen with the components of the specs in `text`, each made by the factory `registry` has for its kind. */
+    pub fn from_spec(text: &str, registry: &Registry) -> Result<Screen, SpecError> {
+        let components = Spec::parse_all(text)?.iter().map(|spec| registry.build(spec)).collect::<Result<_, _>>()?;
+        Ok(Screen::new(components))
+    }
+
+    /** Lays the screen out on a terminal of 80 columns and 24 rows, the size they have unless they're told otherwise, and prints it. The terminal is kept
+     *  for the next run, which only draws on it what changed.
+     */
+    pub fn run(&mut self) {
+        let size = Size::new(80, 24);
+        self.layout(Constraints::new(size.width, size.height));
+
+        let base = self.theme.base.colors();
+        match &mut self.terminal {
+            Some(terminal) => self.components.redraw(terminal, self.area, &base),
+            None => {
+                let mut terminal = TerminalRenderer::new(size);
+                self.components.draw(&mut terminal, self.area, &base);
+                self.components.mark_clean();
+                self.terminal = Some(terminal);
+            }
+        }
+        if let Some(terminal) = &self.terminal {
+            print!("{}", terminal);
+        }
+    }
+}
+
+/** Adds the IDs of `component` and of everything in it to `index`, where `path` leads to `component`. IDs already there are kept. */
+fn index_ids(index: &mut HashMap<String, Vec<usize>>, component: &dyn Draw, path: &mut Vec<usize>) {
+    if let Some(id) = component.id() {
+        index.entry(id.to_string()).or_insert_with(|| path.clone());
+    }
+    for (i, child) in component.children().iter().enumerate() {
+        path.push(i);
+        index_ids(index, child.as_ref(), path);
+        path.pop();
+    }
+}
+
+/** The first `width` characters of `text`, which is as much of it as fits in a component that wide. */
+fn fit(text: &str, width: u32) -> String {
+    text.chars().take(width as usize).collect()
+}
+
+/** Draws the border of `style` around `area`, if it has one, and returns where the content goes, inside of the border and the padding. */
+fn decorate(renderer: &mut dyn Renderer, area: Rect, style: &Style) -> Rect {
+    if style.has_border() {
+        renderer.border(area);
+    }
+    style.inner(area)
+}
+
+/** `FieldNames`, from the hello_macro crate, lets a button tell what its fields are, which is handy to print components while debugging a screen. The closure
+ *  called when the button is clicked can't be printed, so it's skipped, and so is the style, which isn't set by hand like the rest.
+ */
+#[derive(FieldNames)]
+pub struct Button {
+    pub width: u32,
+    pub height: u32,
+    #[field_names(display)]
+    pub label: String,
+    #[field_names(skip)]
+    on_click: Option<Box<dyn FnMut()>>,
+    #[field_names(skip)]
+    style: Styled,
+    #[field_names(skip)]
+    id: Option<String>,
+}
+
+impl Button {
+    pub fn new(width: u32, height: u32, label: &str) -> Button {
+        Button { width, height, label: label.to_string(), on_click: None, style: Styled::new(Theme::default().buttons), id: None }
+    }
+
+    /** The style the button is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.style.set(style);
+    }
+
+    /** Gives the button an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Button {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /** Calls `on_click` every time the button is clicked, or Enter is pressed while it has the focus. */
+    pub fn on_click(mut self, on_click: impl FnMut() + 'static) -> Button {
+        self.on_click = Some(Box::new(on_click));
+        self
+    }
+
+    /** Does what clicking the button does. Returns false if the button has nothing to do. */
+    pub fn click(&mut self) -> bool {
+        match &mut self.on_click {
+            Some(on_click) => {
+                on_click();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Draw for Button {
+    /** The width and height of a button are its whole size, padding and border included. */
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        constraints.constrain(Size::new(self.width, self.height))
+    }
+
+    /** The label in the middle of the button, which themes draw in a box by default. Buttons too small for their border and padding only show their label. */
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        let style = self.style();
+        renderer.begin(Role::Button, area, &style);
+        let inner = decorate(renderer, area, &style);
+        let inner = if inner.width > 0 && inner.height > 0 { inner } else { area };
+        let label = fit(&self.label, inner.width);
+        let x = inner.x + (inner.width - label.chars().count() as u32) / 2;
+        renderer.text(x, inner.y + inner.height / 2, &label);
+        renderer.end();
+    }
+
+    fn on_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Click { .. } | Event::KeyPress(Key::Enter) => self.click(),
+            _ => false,
+        }
+    }
+
+    fn apply_theme(&mut self, theme: &Theme, inherited: &Style) {
+        self.style.theme(&theme.buttons, inherited);
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn to_spec(&self) -> Option<Spec> {
+        let spec = Spec::new(Button::KIND).property("width", self.width).property("height", self.height).property("label", &self.label);
+        Some(spec.common(self.id(), &self.style.own()))
+    }
+}
+
+/** A button made from a spec does nothing when it's clicked until it's given an `on_click`. */
+impl FromSpec for Button {
+    const KIND: &'static str = "button";
+
+    fn from_spec(spec: &Spec, _registry: &Registry) -> Result<Button, SpecError> {
+        let mut button = Button::new(spec.parse("width", 0)?, spec.parse("height", 1)?, spec.get("label").unwrap_or_default());
+        button.id = spec.id().map(str::to_string);
+        button.set_style(spec.style()?);
+        Ok(button)
+    }
+}
+
+/** A line of text the user can type in. The cursor counts characters, not bytes, so typing an `ñ` moves it by one like any other letter. When the field is
+ *  empty, the placeholder is drawn in its place.
+ */
+pub struct TextField {
+    pub width: u32,
+    text: String,
+    cursor: usize,
+    placeholder: String,
+    focused: bool,
+    style: Styled,
+    id: Option<String>,
+    dirty: bool,
+}
+
+impl TextField {
+    pub fn new(width: u32, placeholder: &str) -> TextField {
+        TextField {
+            width,
+            text: String::new(),
+            cursor: 0,
+            placeholder: placeholder.to_string(),
+            focused: false,
+            style: Styled::new(Theme::default().text_fields),
+            id: None,
+            dirty: true,
+        }
+    }
+
+    /** Whether keys typed go to this field, which is when it's drawn with its cursor. */
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn placeholder(&self) -> &str {
+        &self.placeholder
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /** Replaces the whole text, leaving the cursor at its end. */
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.chars().count();
+        self.dirty = true;
+    }
+
+    /** Types `c` where the cursor is, and moves the cursor after it. */
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+        self.dirty = true;
+    }
+
+    /** Deletes the character before the cursor, if there's one, and returns it. */
+    pub fn backspace(&mut self) -> Option<char> {
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        self.dirty = true;
+        let at = self.byte_index(self.cursor);
+        Some(self.text.remove(at))
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+        self.dirty = true;
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+        self.dirty = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+        self.dirty = true;
+    }
+
+    /** The style the field is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.dirty |= self.style.set(style);
+    }
+
+    /** Gives the field an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> TextField {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /** Where the character at `cursor` starts in `text`, or its length for the cursor at the end. */
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text.char_indices().nth(cursor).map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+impl Draw for TextField {
+    /** The width of a field is that of its text, its padding and border go around it. */
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        constraints.constrain(self.style().outer(Size::new(self.width, 1)))
+    }
+
+    /** The text on a line of underscores as wide as the field, or the placeholder if it's empty. */
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        let style = self.style();
+        renderer.begin(Role::TextField { text: &self.text, placeholder: &self.placeholder, focused: self.focused }, area, &style);
+        let inner = decorate(renderer, area, &style);
+        let text = if self.text.is_empty() { &self.placeholder } else { &self.text };
+        let width = inner.width as usize;
+        renderer.text(inner.x, inner.y, &format!("{:_<width$}", fit(text, inner.width)));
+        renderer.end();
+    }
+
+    /** Clicking the field only gives it the focus, and then it takes every key but Enter and Escape. */
+    fn on_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::Focus | Event::Blur => {
+                self.focused = *event == Event::Focus;
+                self.dirty = true;
+            }
+            Event::Click { .. } => {}
+            Event::KeyPress(Key::Char(c)) => self.insert(c),
+            Event::KeyPress(Key::Backspace) => return self.backspace().is_some(),
+            Event::KeyPress(Key::Left) => self.move_left(),
+            Event::KeyPress(Key::Right) => self.move_right(),
+            Event::KeyPress(Key::Enter | Key::Escape) => return false,
+        }
+        true
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme, inherited: &Style) {
+        self.dirty |= self.style.theme(&theme.text_fields, inherited);
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn to_spec(&self) -> Option<Spec> {
+        let mut spec = Spec::new(TextField::KIND).property("width", self.width).property("placeholder", &self.placeholder);
+        if !self.text.is_empty() {
+            spec = spec.property("text", &self.text);
+        }
+        Some(spec.common(self.id(), &self.style.own()))
+    }
+}
+
+impl FromSpec for TextField {
+    const KIND: &'static str = "text_field";
+
+    fn from_spec(spec: &Spec, _registry: &Registry) -> Result<TextField, SpecError> {
+        let mut field = TextField::new(spec.parse("width", 0)?, spec.get("placeholder").unwrap_or_default());
+        field.set_text(spec.get("text").unwrap_or_default());
+        field.id = spec.id().map(str::to_string);
+        field.set_style(spec.style()?);
+        Ok(field)
+    }
+}
+
+/** A box that is either checked or not, with a label next to it. A checkbox bound to an `Observable` is checked when the value is true, and checking it
+ *  sets the value, so whatever else is bound to it sees the change.
+ */
+pub struct Checkbox {
+    label: String,
+    checked: bool,
+    style: Styled,
+    id: Option<String>,
+    binding: Option<(Observable<bool>, Subscription)>,
+    dirty: bool,
+}
+
+impl Checkbox {
+    pub fn new(label: &str) -> Checkbox {
+        Checkbox { label: label.to_string(), checked: false, style: Styled::new(Theme::default().checkboxes), id: None, binding: None, dirty: true }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_string();
+        self.dirty = true;
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+        self.dirty = true;
+        if let Some((value, _)) = &self.binding {
+            value.set(checked);
+        }
+    }
+
+    /** Binds the checkbox to `checked`, which it takes the state of right away. */
+    pub fn bind(mut self, checked: &Observable<bool>) -> Checkbox {
+        self.checked = checked.get();
+        self.binding = Some((checked.clone(), checked.subscribe()));
+        self
+    }
+
+    /** Checks the box if it wasn't and unchecks it if it was, like clicking on it does. Returns whether it's checked now. */
+    pub fn toggle(&mut self) -> bool {
+        self.set_checked(!self.checked);
+        self.checked
+    }
+
+    /** The style the checkbox is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.dirty |= self.style.set(style);
+    }
+
+    /** Gives the checkbox an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Checkbox {
+        self.id = Some(id.to_string());
+        self
+    }
+}
+
+impl Draw for Checkbox {
+    /** The box takes three columns, `[x]`, and one more separates it from the label. A bound checkbox is checked or not as its value says when it's laid out. */
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        if let Some((value, subscription)) = &self.binding {
+            if subscription.take() {
+                self.checked = value.get();
+                self.dirty = true;
+            }
+        }
+        constraints.constrain(self.style().outer(Size::new(4 + self.label.chars().count() as u32, 1)))
+    }
+
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        let style = self.style();
+        renderer.begin(Role::Checkbox { checked: self.checked, label: &self.label }, area, &style);
+        let inner = decorate(renderer, area, &style);
+        let mark = if self.checked { 'x' } else { ' ' };
+        renderer.text(inner.x, inner.y, &fit(&format!("[{}] {}", mark, self.label), inner.width));
+        renderer.end();
+    }
+
+    fn on_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Click { .. } | Event::KeyPress(Key::Char(' ')) => {
+                self.toggle();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty || self.binding.as_ref().is_some_and(|(_, subscription)| subscription.has_changed())
+    }
+
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme, inherited: &Style) {
+        self.dirty |= self.style.theme(&theme.checkboxes, inherited);
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn to_spec(&self) -> Option<Spec> {
+        let spec = Spec::new(Checkbox::KIND).property("label", &self.label).property("checked", self.checked);
+        Some(spec.common(self.id(), &self.style.own()))
+    }
+}
+
+impl FromSpec for Checkbox {
+    const KIND: &'static str = "checkbox";
+
+    fn from_spec(spec: &Spec, _registry: &Registry) -> Result<Checkbox, SpecError> {
+        let mut checkbox = Checkbox::new(spec.get("label").unwrap_or_default());
+        checkbox.set_checked(spec.parse("checked", false)?);
+        checkbox.id = spec.id().map(str::to_string);
+        checkbox.set_style(spec.style()?);
+        Ok(checkbox)
+    }
+}
+
+/** Text that is only shown, the user can't change it. A label bound to an `Observable` shows a text made from the value, which is made again every time the
+ *  value changes.
+ */
+pub struct Label {
+    text: String,
+    style: Styled,
+    id: Option<String>,
+    binding: Option<Binding>,
+    dirty: bool,
+}
+
+/** What a label is bound to: the subscription that tells when the value changed, and how to make the text out of it. */
+struct Binding {
+    subscription: Subscription,
+    text: Box<dyn Fn() -> String>,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Label {
+        Label { text: text.to_string(), style: Styled::new(Theme::default().labels), id: None, binding: None, dirty: true }
+    }
+
+    /** Binds the label to `value`, and shows what `text` makes out of it, like `Label::new("").bind(&clicks, |clicks| format!("{} clicks", clicks))`. */
+    pub fn bind<T: 'static>(mut self, value: &Observable<T>, text: impl Fn(&T) -> String + 'static) -> Label {
+        let subscription = value.subscribe();
+        let value = value.clone();
+        let text: Box<dyn Fn() -> String> = Box::new(move || value.with(&text));
+        self.text = text();
+        self.binding = Some(Binding { subscription, text });
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.dirty = true;
+    }
+
+    /** The style the label is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.dirty |= self.style.set(style);
+    }
+
+    /** Gives the label an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Label {
+        self.id = Some(id.to_string());
+        self
+    }
+}
+
+impl Draw for Label {
+    /** A bound label makes its text again when it's laid out, if its value changed, as the new text may need more room. */
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        if let Some(binding) = &self.binding {
+            if binding.subscription.take() {
+                self.text = (binding.text)();
+                self.dirty = true;
+            }
+        }
+        constraints.constrain(self.style().outer(Size::new(self.text.chars().count() as u32, 1)))
+    }
+
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        let style = self.style();
+        renderer.begin(Role::Label, area, &style);
+        let inner = decorate(renderer, area, &style);
+        renderer.text(inner.x, inner.y, &fit(&self.text, inner.width));
+        renderer.end();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty || self.binding.as_ref().is_some_and(|binding| binding.subscription.has_changed())
+    }
+
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme, inherited: &Style) {
+        self.dirty |= self.style.theme(&theme.labels, inherited);
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn to_spec(&self) -> Option<Spec> {
+        Some(Spec::new(Label::KIND).property("text", &self.text).common(self.id(), &self.style.own()))
+    }
+}
+
+impl FromSpec for Label {
+    const KIND: &'static str = "label";
+
+    fn from_spec(spec: &Spec, _registry: &Registry) -> Result<Label, SpecError> {
+        let mut label = Label::new(spec.get("text").unwrap_or_default());
+        label.id = spec.id().map(str::to_string);
+        label.set_style(spec.style()?);
+        Ok(label)
+    }
+}
+
+/** A bar that fills up as something gets done, from 0 to 1. The bar moves to its new progress little by little when it's animated, as the screen ticks. */
+pub struct ProgressBar {
+    pub width: u32,
+    progress: Animation<f64>,
+    style: Styled,
+    id: Option<String>,
+    dirty: bool,
+}
+
+impl ProgressBar {
+    pub fn new(width: u32) -> ProgressBar {
+        ProgressBar { width, progress: Animation::new(0.0), style: Styled::new(Theme::default().progress_bars), id: None, dirty: true }
+    }
+
+    /** How far the bar is filled right now, which is short of where it's going while it's animated. */
+    pub fn progress(&self) -> f64 {
+        self.progress.value()
+    }
+
+    /** Fills the bar up to `progress` right away. */
+    pub fn set_progress(&mut self, progress: f64) {
+        self.animate_to(progress, Duration::ZERO);
+    }
+
+    /** Fills the bar up, or empties it, to `progress` in `duration`. */
+    pub fn animate_to(&mut self, progress: f64, duration: Duration) {
+        self.progress.animate_to(progress.clamp(0.0, 1.0), duration);
+        self.dirty = true;
+    }
+
+    /** Whether the bar got to where it was going. */
+    pub fn is_done(&self) -> bool {
+        !self.progress.is_running()
+    }
+
+    /** The style the bar is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.dirty |= self.style.set(style);
+    }
+
+    /** Gives the bar an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> ProgressBar {
+        self.id = Some(id.to_string());
+        self
+    }
+}
+
+impl Draw for ProgressBar {
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        constraints.constrain(self.style().outer(Size::new(self.width, 1)))
+    }
+
+    /** Full blocks for the part that's done, and light shade for the rest. */
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        let style = self.style();
+        let progress = self.progress();
+        renderer.begin(Role::ProgressBar { percent: (progress * 100.0).round() as u32 }, area, &style);
+        let inner = decorate(renderer, area, &style);
+        let filled = (progress * inner.width as f64).round() as usize;
+        renderer.text(inner.x, inner.y, &format!("{}{}", "█".repeat(filled), "░".repeat(inner.width as usize - filled)));
+        renderer.end();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn apply_theme(&mut self, theme: &Theme, inherited: &Style) {
+        self.dirty |= self.style.theme(&theme.progress_bars, inherited);
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn tick(&mut self, dt: Duration) {
+        self.dirty |= self.progress.tick(dt);
+    }
+
+    /** A bar that is being animated is written down as it is once the animation is over. */
+    fn to_spec(&self) -> Option<Spec> {
+        let spec = Spec::new(ProgressBar::KIND).property("width", self.width).property("progress", self.progress.target());
+        Some(spec.common(self.id(), &self.style.own()))
+    }
+}
+
+impl FromSpec for ProgressBar {
+    const KIND: &'static str = "progress_bar";
+
+    fn from_spec(spec: &Spec, _registry: &Registry) -> Result<ProgressBar, SpecError> {
+        let mut bar = ProgressBar::new(spec.parse("width", 0)?);
+        bar.set_progress(spec.parse("progress", 0.0)?);
+        bar.id = spec.id().map(str::to_string);
+        bar.set_style(spec.style()?);
+        Ok(bar)
+    }
+}
+
+/** Keeps every text drawn on it, to see what was drawn. */
+#[cfg(test)]
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+#[cfg(test)]
+impl Renderer for Recorder {
+    fn text(&mut self, _x: u32, _y: u32, text: &str) {
+        self.0.push(text.to_string());
+    }
+
+    fn border(&mut self, _area: Rect) {}
+}
+
+#[test]
+fn button_fields() {
+    let button = Button::new(50, 10, "OK").on_click(|| {});
+
+    assert_eq!(["width", "height", "label"], Button::FIELD_NAMES);
+    assert_eq!(("label", String::from("OK")), button.fields()[2]);
+    assert_eq!(("width", String::from("50")), button.fields()[0]);
+}
+
+#[test]
+fn text_field_editing() {
+    let mut field = TextField::new(20, "Name");
+
+    for c in "Niño".chars() {
+        field.insert(c);
+    }
+    field.move_left();
+    field.move_left();
+    assert_eq!(Some('i'), field.backspace());
+    field.insert('e');
+    assert_eq!("Neño", field.text());
+    assert_eq!(2, field.cursor());
+
+    field.clear();
+    assert_eq!(None, field.backspace());
+    assert_eq!("Name", field.placeholder());
+}
+
+#[test]
+fn checkbox_and_label_state() {
+    let mut checkbox = Checkbox::new("Remember me");
+    assert!(checkbox.toggle());
+    assert!(!checkbox.toggle());
+    checkbox.set_checked(true);
+    assert!(checkbox.is_checked());
+
+    let mut label = Label::new("Hello");
+    label.set_text("Bye");
+    assert_eq!("Bye", label.text());
+}
+
+#[test]
+fn screens_route_events() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let clicks = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&clicks);
+    let mut screen = Screen::new(vec![
+        Box::new(TextField::new(20, "Name")),
+        Box::new(Row::new().spacing(1).child(Checkbox::new("Crab")).child(Button::new(6, 1, "OK").on_click(move || counter.set(counter.get() + 1)))),
+    ]);
+    screen.layout(Constraints::new(80, 24));
+
+    // Keys go nowhere until something has the focus.
+    assert!(!screen.handle(&Event::KeyPress(Key::Char('F'))));
+    assert!(screen.handle(&Event::Click { x: 3, y: 0 }));
+    assert!(screen.handle(&Event::KeyPress(Key::Char('F'))));
+
+    // The button is in the row, after the 8 columns of the checkbox and the space between them.
+    assert!(screen.handle(&Event::Click { x: 10, y: 1 }));
+    assert!(screen.handle(&Event::KeyPress(Key::Enter)));
+    assert!(!screen.handle(&Event::Click { x: 30, y: 1 }));
+    assert_eq!(2, clicks.get());
+}
+
+#[test]
+fn widgets_react_to_events() {
+    let mut field = TextField::new(20, "Name");
+    field.on_event(&Event::Focus);
+    for c in "Ferris".chars() {
+        field.on_event(&Event::KeyPress(Key::Char(c)));
+    }
+    field.on_event(&Event::KeyPress(Key::Backspace));
+    assert!(field.is_focused());
+    assert_eq!("Ferri", field.text());
+    field.on_event(&Event::Blur);
+    assert!(!field.is_focused());
+
+    let mut checkbox = Checkbox::new("Crab");
+    assert!(checkbox.on_event(&Event::Click { x: 0, y: 0 }));
+    assert!(checkbox.is_checked());
+    assert!(!Label::new("Name").on_event(&Event::Click { x: 0, y: 0 }));
+}
+
+#[test]
+fn screens_are_drawn_on_a_terminal() {
+    let mut checkbox = Checkbox::new("Crab");
+    checkbox.toggle();
+    let mut screen = Screen::new(vec![
+        Box::new(Label::new("Sign up")),
+        Box::new(Row::new().spacing(1).child(Label::new("Name")).child(TextField::new(10, "Ferris"))),
+        Box::new(Row::new().spacing(1).child(checkbox).child(Button::new(6, 3, "OK"))),
+    ]);
+    let mut terminal = TerminalRenderer::new(Size::new(20, 8));
+
+    screen.layout(Constraints::new(20, 8));
+    screen.render(&mut terminal);
+
+    let expected = "\
+Sign up
+Name Ferris____
+[x] Crab ┌────┐
+         │ OK │
+         └────┘
+";
+    assert_eq!(expected, terminal.to_string());
+}
+
+#[test]
+fn screens_are_drawn_as_html() {
+    let mut screen = Screen::new(vec![
+        Box::new(Row::new().spacing(1).child(Label::new("Name")).child(TextField::new(10, "Ferris"))),
+    ]);
+    let mut html = HtmlRenderer::new(Size::new(20, 1));
+
+    screen.layout(Constraints::new(20, 1));
+    screen.render(&mut html);
+
+    let html = html.html();
+    assert!(html.contains(r#"<div style="position:absolute;left:0ch;top:0em;width:15ch;height:1em"><span style="#));
+    assert!(html.contains(r#"<span style="position:absolute;left:0ch;top:0em;width:4ch;height:1em">Name</span>"#));
+    assert!(html.contains(r#"<input style="position:absolute;left:5ch;top:0em;width:10ch;height:1em" type="text" value="" placeholder="Ferris">"#));
+}
+
+#[test]
+fn only_what_changed_is_drawn_again() {
+    let mut screen = Screen::new(vec![
+        Box::new(Label::new("Sign up")),
+        Box::new(Row::new().spacing(1).child(Checkbox::new("Crab")).child(TextField::new(6, "Name"))),
+    ]);
+    let mut recorder = Recorder::default();
+    screen.layout(Constraints::new(20, 4));
+    screen.render(&mut recorder);
+    assert_eq!(["Sign up", "[ ] Crab", "Name__"], recorder.0[..]);
+
+    recorder.0.clear();
+    screen.update(&mut recorder);
+    assert!(recorder.0.is_empty());
+
+    screen.handle(&Event::Click { x: 0, y: 1 });
+    screen.layout(Constraints::new(20, 4));
+    screen.update(&mut recorder);
+    assert_eq!(["[x] Crab"], recorder.0[..]);
+}
+
+#[test]
+fn updates_clear_what_was_there() {
+    let mut screen = Screen::new(vec![Box::new(TextField::new(6, "Name"))]);
+    let mut terminal = TerminalRenderer::new(Size::new(10, 1));
+    screen.layout(Constraints::new(10, 1));
+    screen.render(&mut terminal);
+
+    screen.handle(&Event::Click { x: 0, y: 0 });
+    screen.handle(&Event::KeyPress(Key::Char('F')));
+    screen.update(&mut terminal);
+    assert_eq!("F_____\n", terminal.to_string());
+}
+
+#[test]
+fn themes_style_what_sets_no_style_of_its_own() {
+    let mut label = Label::new("Name");
+    label.set_style(Style::new().foreground(Color::Green));
+    let mut row = Row::new().child(Checkbox::new("Crab"));
+    row.set_style(Style::new().background(Color::Blue));
+    let mut screen = Screen::new(vec![Box::new(label), Box::new(row), Box::new(Label::new("Bye"))]);
+    screen.set_theme(Theme { labels: Style::new().foreground(Color::Red).padding(1), ..Theme::default() });
+    let mut html = HtmlRenderer::new(Size::new(20, 8));
+
+    // The padding of the labels makes them two rows higher and two columns wider.
+    assert_eq!(Size::new(8, 7), screen.layout(Constraints::new(20, 8)));
+    screen.render(&mut html);
+
+    let html = html.html();
+    assert!(html.contains(r#"<span style="position:absolute;left:0ch;top:0em;width:6ch;height:3em;color:green;padding:1em 1ch;box-sizing:border-box">Name</span>"#));
+    // The checkbox has the background of the row it's in, and the last label the color of the theme.
+    assert!(html.contains(r#"<label style="position:absolute;left:0ch;top:0em;width:8ch;height:1em;background:blue">"#));
+    assert!(html.contains(r#"<span style="position:absolute;left:0ch;top:4em;width:5ch;height:3em;color:red;padding:1em 1ch;box-sizing:border-box">Bye</span>"#));
+}
+
+#[test]
+fn styles_are_drawn_on_a_terminal() {
+    let mut field = TextField::new(6, "Name");
+    field.set_style(Style::new().border(true));
+    let mut button = Button::new(4, 1, "OK");
+    button.set_style(Style::new().border(false).foreground(Color::Red));
+    let mut screen = Screen::new(vec![Box::new(field), Box::new(button)]);
+    let mut terminal = TerminalRenderer::new(Size::new(10, 4));
+
+    screen.layout(Constraints::new(10, 4));
+    screen.render(&mut terminal);
+
+    assert_eq!("┌──────┐\n│Name__│\n└──────┘\n \x1b[31mOK\x1b[0m\n", terminal.to_string());
+}
+
+#[test]
+fn components_are_found_by_their_ids() {
+    let mut screen = Screen::new(vec![
+        Box::new(Label::new("Sign up").with_id("title")),
+        Box::new(Row::new().with_id("form").child(Label::new("Name")).child(TextField::new(10, "Ferris").with_id("name"))),
+    ]);
+
+    assert_eq!(Some("Sign up"), screen.get("title").and_then(|title| title.downcast_ref::<Label>()).map(Label::text));
+    assert!(screen.get("form").is_some_and(|form| form.downcast_ref::<Row>().is_some()));
+    assert!(screen.get("nothing").is_none());
+
+    let name = screen.get_mut("name").and_then(|name| name.downcast_mut::<TextField>()).unwrap();
+    name.set_text("Corro");
+    assert_eq!(Some("Corro"), screen.get("name").and_then(|name| name.downcast_ref::<TextField>()).map(TextField::text));
+}
+
+#[test]
+fn ids_follow_components_in_and_out_of_screens() {
+    let mut screen = Screen::new(vec![Box::new(Label::new("Sign up").with_id("title"))]);
+    screen.push(Box::new(Row::new().child(Checkbox::new("Crab").with_id("crab"))));
+    assert!(screen.get_mut("crab").and_then(|crab| crab.downcast_mut::<Checkbox>()).is_some_and(|crab| crab.toggle()));
+
+    // Only the screen's own components can be taken out, and those after them are still found where they moved.
+    assert!(screen.remove("crab").is_none());
+    assert_eq!(Some("title"), screen.remove("title").as_deref().and_then(Draw::id));
+    assert!(screen.get("title").is_none());
+    assert!(screen.get("crab").and_then(|crab| crab.downcast_ref::<Checkbox>()).is_some_and(Checkbox::is_checked));
+}
+
+#[test]
+fn bound_widgets_change_with_their_values() {
+    let clicks = Observable::new(0);
+    let subscribed = Observable::new(false);
+    let counter = clicks.clone();
+    let mut screen = Screen::new(vec![
+        Box::new(Label::new("").bind(&clicks, |clicks| format!("{} clicks", clicks))),
+        Box::new(Checkbox::new("Subscribe").bind(&subscribed)),
+        Box::new(Label::new("").bind(&subscribed, |subscribed| String::from(if *subscribed { "Thanks!" } else { "Not yet" }))),
+        Box::new(Button::new(4, 1, "+1").with_id("add").on_click(move || counter.update(|clicks| *clicks += 1))),
+    ]);
+    let mut recorder = Recorder::default();
+    screen.layout(Constraints::new(20, 6));
+    screen.render(&mut recorder);
+
+    // Clicking the button only draws again the label bound to the clicks, and the button, which is always drawn.
+    recorder.0.clear();
+    screen.handle(&Event::Click { x: 0, y: 3 });
+    screen.layout(Constraints::new(20, 6));
+    screen.update(&mut recorder);
+    assert_eq!(["1 clicks", "+1"], recorder.0[..]);
+
+    // Checking the box sets the value it's bound to, which the other label is bound to too. Its text is as long as before, so nothing moves.
+    recorder.0.clear();
+    screen.handle(&Event::Click { x: 0, y: 1 });
+    screen.layout(Constraints::new(20, 6));
+    screen.update(&mut recorder);
+    assert!(subscribed.get());
+    assert_eq!(["[x] Subscribe", "Thanks!", "+1"], recorder.0[..]);
+}
+
+#[test]
+fn screens_tick_their_animations() {
+    let mut bar = ProgressBar::new(10).with_id("bar");
+    bar.animate_to(1.0, Duration::from_secs(1));
+    let mut screen = Screen::new(vec![Box::new(Row::new().child(bar)), Box::new(Label::new("Loading"))]);
+    let mut recorder = Recorder::default();
+    screen.layout(Constraints::new(20, 2));
+    screen.render(&mut recorder);
+    assert_eq!(["░░░░░░░░░░", "Loading"], recorder.0[..]);
+
+    // Only the bar is drawn again, while it fills up, and not anymore once it's full.
+    for drawn in ["███░░░░░░░", "██████░░░░", "█████████░", "██████████", ""] {
+        recorder.0.clear();
+        screen.tick(Duration::from_millis(300));
+        screen.layout(Constraints::new(20, 2));
+        screen.update(&mut recorder);
+        assert_eq!(drawn, recorder.0.concat());
+    }
+    assert!(screen.get("bar").and_then(|bar| bar.downcast_ref::<ProgressBar>()).is_some_and(ProgressBar::is_done));
+}
+
+#[test]
+fn modals_take_every_event_until_dismissed() {
+    let mut screen = Screen::new(vec![
+        Box::new(Checkbox::new("Crab").with_id("crab")),
+        Box::new(Label::new("Send crabs?")),
+        Box::new(Modal::new(Checkbox::new("Sure").with_id("sure")).with_id("modal")),
+    ]);
+    let mut terminal = TerminalRenderer::new(Size::new(16, 5));
+    screen.layout(Constraints::new(16, 5));
+    screen.render(&mut terminal);
+
+    // The box is in the middle of the screen, over the label.
+    let expected = "\
+[ ] Crab
+Sen┌────────┐
+   │[ ] Sure│
+   └────────┘
+";
+    assert_eq!(expected, terminal.to_string());
+
+    // Clicks outside of the box don't get to what's under it, and those inside of it go to its content.
+    assert!(!screen.handle(&Event::Click { x: 0, y: 0 }));
+    assert!(screen.handle(&Event::Click { x: 5, y: 2 }));
+    let checked = |screen: &Screen, id: &str| screen.get(id).and_then(|checkbox| checkbox.downcast_ref::<Checkbox>()).is_some_and(Checkbox::is_checked);
+    assert!(!checked(&screen, "crab"));
+    assert!(checked(&screen, "sure"));
+
+    assert!(screen.handle(&Event::KeyPress(Key::Escape)));
+    screen.layout(Constraints::new(16, 5));
+    screen.update(&mut terminal);
+    assert_eq!("[ ] Crab\nSend crabs?\n", terminal.to_string());
+    assert!(screen.handle(&Event::Click { x: 0, y: 0 }));
+    assert!(checked(&screen, "crab"));
+}
+
+#[test]
+fn accessibility_trees_tell_what_every_component_is() {
+    let mut bar = ProgressBar::new(10);
+    bar.set_progress(0.5);
+    let mut screen = Screen::new(vec![
+        Box::new(Label::new("Sign up")),
+        Box::new(Row::new().spacing(1).child(Label::new("Name")).child(TextField::new(10, "Ferris"))),
+        Box::new(Row::new().spacing(1).child(Checkbox::new("Crab")).child(Button::new(6, 3, "OK"))),
+        Box::new(bar),
+    ]);
+    screen.layout(Constraints::new(40, 10));
+    screen.handle(&Event::Click { x: 6, y: 1 });
+    screen.handle(&Event::KeyPress(Key::Char('F')));
+
+    let expected = "\
+group
+  label \"Sign up\"
+  group
+    label \"Name\"
+    text field \"F\" placeholder \"Ferris\" focused
+  group
+    checkbox \"Crab\" unchecked
+    button \"OK\"
+  progress bar 50%
+";
+    assert_eq!(expected, screen.accessibility_tree());
+}
+
+#[test]
+fn screens_are_made_again_from_their_specs() {
+    let text = "\
+label text=\"Sign up\" foreground=blue
+column spacing=1 id=form border=true
+  row spacing=1
+    label text=\"Name:\"
+    text_field width=10 placeholder=Ferris text=Corro id=name
+  grid columns=2 spacing=0
+    checkbox label=Crab checked=true
+    progress_bar width=4 progress=0.5
+panel width=12 height=1
+  button width=4 height=1 label=OK border=false x=8 y=0
+modal open=false
+  label text=\"Sure?\"
+";
+    let mut screen = Screen::from_spec(text, &Registry::new()).unwrap();
+    assert_eq!(text, screen.to_spec());
+
+    let name = screen.get("name").and_then(|name| name.downcast_ref::<TextField>()).unwrap();
+    assert_eq!("Corro", name.text());
+    assert_eq!(Style::new().border(true), screen.get("form").and_then(|form| form.downcast_ref::<Column>()).unwrap().style());
+
+    let mut terminal = TerminalRenderer::new(Size::new(20, 8));
+    screen.layout(Constraints::new(20, 8));
+    screen.render(&mut terminal);
+    // The modal is closed, so it takes no room and draws nothing.
+    let expected = "\
+\x1b[34mSign up\x1b[0m
+┌────────────────┐
+│Name: Corro_____│
+│                │
+│[x] Crab██░░    │
+└────────────────┘
+         OK
+";
+    assert_eq!(expected, terminal.to_string());
+}
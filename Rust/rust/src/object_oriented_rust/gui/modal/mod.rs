@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use super::event::{Event, Key};
+use super::style::Styled;
+use super::tree::Children;
+use super::{Constraints, Draw, FromSpec, Rect, Registry, Renderer, Size, Spec, SpecError, Style, Theme};
+
+/** A box in the middle of its container, over everything else in it, with a component inside. While it's open it takes every event of its container, so
+ *  nothing under it can be clicked or typed in, until it's dismissed with `dismiss` or Escape. A dismissed modal takes no room and draws nothing.
+ *
+ *  The modal takes the whole room of its container, but only the box hides what's under it. The box keeps the size it got when the modal was opened, as
+ *  what's under it isn't drawn again while it's open.
+ */
+pub struct Modal {
+    content: Children,
+    open: bool,
+    // Where the box is in the modal, once it was laid out after being opened.
+    frame: Option<Rect>,
+    z_index: i32,
+    style: Styled,
+    id: Option<String>,
+}
+
+impl Modal {
+    /** An open modal with `content` in its box. Modals have a z-index of 100 unless they're given another, to be over everything else. */
+    pub fn new(content: impl Draw + 'static) -> Modal {
+        Modal::containing(Box::new(content))
+    }
+
+    fn containing(content: Box<dyn Draw>) -> Modal {
+        let mut children = Children::default();
+        children.push(content);
+        Modal { content: children, open: true, frame: None, z_index: 100, style: Styled::new(Theme::default().modals), id: None }
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Modal {
+        self.z_index = z_index;
+        self
+    }
+
+    /** Gives the modal an ID, to find it on its screen with `Screen::get`. */
+    pub fn with_id(mut self, id: &str) -> Modal {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /** Shows the modal again, over what its container has now. */
+    pub fn open(&mut self) {
+        self.open = true;
+        self.frame = None;
+    }
+
+    /** Hides the modal, and gives the events back to the rest of its container. */
+    pub fn dismiss(&mut self) {
+        self.open = false;
+        self.frame = None;
+    }
+
+    /** The style the modal is drawn with: the one set with `set_style`, with what it leaves out taken from the theme. */
+    pub fn style(&self) -> Style {
+        self.style.get()
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        if self.style.set(style) {
+            self.content.invalidate();
+        }
+    }
+
+    /** Where the box is on the screen, when the modal is at `area`. */
+    fn boxed(&self, area: Rect) -> Option<Rect> {
+        self.frame.filter(|_| self.open).map(|frame| Rect { x: area.x + frame.x, y: area.y + frame.y, ..frame })
+    }
+}
+
+impl Draw for Modal {
+    /** The box is as big as the content needs, and the modal as big as the room it's given. */
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        if !self.open {
+            return Size::default();
+        }
+        let style = self.style.get();
+        let room = match self.frame {
+            Some(frame) => style.content(Constraints::new(frame.width, frame.height)),
+            None => style.content(constraints),
+        };
+        let Some(content) = self.content.iter_mut().next() else {
+            return Size::default();
+        };
+        let size = content.layout(room);
+
+        if self.frame.is_none() {
+            let outer = constraints.constrain(style.outer(size));
+            let (x, y) = ((constraints.max_width - outer.width) / 2, (constraints.max_height - outer.height) / 2);
+            self.frame = Some(Rect::new(x, y, outer));
+        }
+        self.content.place(vec![Rect::new(style.edge(), style.edge(), size)]);
+        Size::new(constraints.max_width, constraints.max_height)
+    }
+
+    /** The box wipes out what's under it before the content is drawn in it. */
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        if let Some(boxed) = self.boxed(area) {
+            renderer.clear(boxed);
+            self.content.draw(renderer, boxed, &self.style.get());
+        }
+    }
+
+    fn redraw(&mut self, renderer: &mut dyn Renderer, area: Rect) {
+        if let Some(boxed) = self.boxed(area) {
+            self.content.redraw(renderer, boxed, &self.style.get());
+        }
+    }
+
+    fn on_event(&mut self, event: &Event) -> bool {
+        if !self.open {
+            return false;
+        }
+        match *event {
+            Event::KeyPress(Key::Escape) => {
+                self.dismiss();
+                true
+            }
+            Event::Click { x, y } => match self.frame {
+                Some(frame) if frame.contains(x, y) => self.content.route(&Event::Click { x: x - frame.x, y: y - frame.y }),
+                _ => false,
+            },
+            _ => self.content.route(event),
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.content.is_dirty()
+    }
+
+    fn mark_clean(&mut self) {
+        self.content.mark_clean();
+    }
+
+    fn apply_theme(&mut self, theme: &Theme, inherited: &Style) {
+        if self.style.theme(&theme.modals, inherited) {
+            self.content.invalidate();
+        }
+        self.content.apply_theme(theme, &self.style.get());
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn children(&self) -> &[Box<dyn Draw>] {
+        self.content.components()
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Draw>] {
+        self.content.components_mut()
+    }
+
+    fn tick(&mut self, dt: Duration) {
+        self.content.tick(dt);
+    }
+
+    fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
+    fn captures_events(&self) -> bool {
+        self.open
+    }
+
+    fn to_spec(&self) -> Option<Spec> {
+        let mut spec = Spec::new(Modal::KIND).property("open", self.open);
+        if self.z_index != 100 {
+            spec = spec.property("z_index", self.z_index);
+        }
+        Some(spec.common(self.id(), &self.style.own()).children_of(self.content.components()))
+    }
+}
+
+/** The spec of a modal has its content as its only child. */
+impl FromSpec for Modal {
+    const KIND: &'static str = "modal";
+
+    fn from_spec(spec: &Spec, registry: &Registry) -> Result<Modal, SpecError> {
+        let [content] = &spec.children[..] else {
+            return Err(SpecError::Children { kind: spec.kind.clone(), reason: "a modal has one component in its box" });
+        };
+        let mut modal = Modal::containing(registry.build(content)?).with_z_index(spec.parse("z_index", 100)?);
+        if !spec.parse("open", true)? {
+            modal.dismiss();
+        }
+        modal.id = spec.id().map(str::to_string);
+        modal.set_style(spec.style()?);
+        Ok(modal)
+    }
+}
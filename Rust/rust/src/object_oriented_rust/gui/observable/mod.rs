@@ -0,0 +1,122 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+/** A value components can be bound to. Every clone of an observable shares the same value, and changing it through any of them tells every `Subscription`
+ *  to it that it changed, which is how the components bound to it know they're dirty.
+ *
+ *  The value only keeps `Weak` references to its subscriptions, so a component that is dropped along with its subscription isn't kept around by the value.
+ *  Components keep a clone of the observable they're bound to, an `Rc`, and as the references back from the value are weak they don't make a cycle.
+ */
+pub struct Observable<T> {
+    shared: Rc<Shared<T>>,
+}
+
+struct Shared<T> {
+    value: RefCell<T>,
+    subscribers: RefCell<Vec<Weak<Cell<bool>>>>,
+}
+
+/** Deriving `Clone` would need `T` to be `Clone`, and clones only share the `Rc` of the value. */
+impl<T> Clone for Observable<T> {
+    fn clone(&self) -> Observable<T> {
+        Observable { shared: Rc::clone(&self.shared) }
+    }
+}
+
+impl<T> Observable<T> {
+    pub fn new(value: T) -> Observable<T> {
+        Observable { shared: Rc::new(Shared { value: RefCell::new(value), subscribers: RefCell::new(Vec::new()) }) }
+    }
+
+    /** Calls `f` with the value, for values that aren't `Clone`. The value can't be changed from inside of `f`. */
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.shared.value.borrow())
+    }
+
+    pub fn set(&self, value: T) {
+        *self.shared.value.borrow_mut() = value;
+        self.notify();
+    }
+
+    /** Changes the value in place with `f`, like adding one to a counter. */
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.shared.value.borrow_mut());
+        self.notify();
+    }
+
+    /** A new subscription to the value, which is told about every change from now on. */
+    pub fn subscribe(&self) -> Subscription {
+        let changed = Rc::new(Cell::new(false));
+        self.shared.subscribers.borrow_mut().push(Rc::downgrade(&changed));
+        Subscription { changed }
+    }
+
+    /** How many of the subscriptions to the value are still around. */
+    pub fn subscribers(&self) -> usize {
+        self.shared.subscribers.borrow().iter().filter(|subscriber| subscriber.strong_count() > 0).count()
+    }
+
+    /** Tells every subscription the value changed, and forgets those that were dropped. */
+    fn notify(&self) {
+        self.shared.subscribers.borrow_mut().retain(|subscriber| match subscriber.upgrade() {
+            Some(changed) => {
+                changed.set(true);
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+impl<T: Clone> Observable<T> {
+    pub fn get(&self) -> T {
+        self.shared.value.borrow().clone()
+    }
+}
+
+/** Knows whether the value it's a subscription to changed since it was last dealt with. */
+pub struct Subscription {
+    changed: Rc<Cell<bool>>,
+}
+
+impl Subscription {
+    pub fn has_changed(&self) -> bool {
+        self.changed.get()
+    }
+
+    /** Whether the value changed, which is then taken as dealt with until it changes again. */
+    pub fn take(&self) -> bool {
+        self.changed.replace(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_value_and_tell_subscriptions() {
+        let counter = Observable::new(0);
+        let subscription = counter.subscribe();
+        assert!(!subscription.has_changed());
+
+        counter.clone().update(|count| *count += 1);
+        assert_eq!(1, counter.get());
+        assert!(subscription.take());
+        assert!(!subscription.take());
+    }
+
+    #[test]
+    fn dropped_subscriptions_are_forgotten() {
+        let name = Observable::new(String::from("Ferris"));
+        let kept = name.subscribe();
+        let dropped = name.subscribe();
+        assert_eq!(2, name.subscribers());
+
+        drop(dropped);
+        name.set(String::from("Corro"));
+        assert_eq!(1, name.subscribers());
+        assert!(kept.has_changed());
+        assert_eq!(5, name.with(|name| name.len()));
+    }
+}
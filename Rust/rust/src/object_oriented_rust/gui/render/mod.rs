@@ -0,0 +1,451 @@
+use std::fmt;
+
+use super::{Color, Rect, Size, Style};
+
+/** What kind of component is being drawn, for backends that draw each kind as something of their own. Text fields and checkboxes carry what's in them and
+ *  their state, as their HTML is made from it rather than from the text they draw.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role<'a> {
+    /** A container, or any component holding others. */
+    Group,
+    Button,
+    TextField { text: &'a str, placeholder: &'a str, focused: bool },
+    Checkbox { checked: bool, label: &'a str },
+    Label,
+    /** How far along the bar is, from 0 to 100. */
+    ProgressBar { percent: u32 },
+}
+
+/** What components draw on. Each backend draws these few things its own way, so the same components can be shown anywhere there's a renderer for.
+ *
+ *  Components draw everything they are between a call to `begin` and one to `end`, which tell the backend what the drawing is and the style it has. Padding
+ *  and borders are drawn by the components themselves, so backends only have to honor the colors of the style. Those that have none can ignore both, which
+ *  by default do nothing.
+ */
+pub trait Renderer {
+    /** Writes `text` on one line, starting at the cell at `x` and `y`. */
+    fn text(&mut self, x: u32, y: u32, text: &str);
+
+    /** Draws the border of a box around the edge of `area`. */
+    fn border(&mut self, area: Rect);
+
+    /** Wipes out what was drawn in `area`, before a component is drawn again there. */
+    fn clear(&mut self, _area: Rect) {}
+
+    fn begin(&mut self, _role: Role, _area: Rect, _style: &Style) {}
+
+    fn end(&mut self) {}
+}
+
+/** Draws on a grid of characters, the size of a terminal, which is then printed with `Display`. Borders are drawn with the box characters of Unicode, and
+ *  anything drawn outside of the grid is cut off. Each cell keeps the colors of the component that drew it last.
+ */
+pub struct TerminalRenderer {
+    size: Size,
+    cells: Vec<Vec<Cell>>,
+    // The styles of the components begun and not ended yet, the innermost last.
+    styles: Vec<Style>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    c: char,
+    foreground: Option<Color>,
+    background: Option<Color>,
+}
+
+impl Cell {
+    const EMPTY: Cell = Cell { c: ' ', foreground: None, background: None };
+}
+
+impl TerminalRenderer {
+    pub fn new(size: Size) -> TerminalRenderer {
+        TerminalRenderer { size, cells: vec![vec![Cell::EMPTY; size.width as usize]; size.height as usize], styles: Vec::new() }
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /** Writes `c` with the colors of the component being drawn. */
+    fn put(&mut self, x: u32, y: u32, c: char) {
+        let style = self.styles.last().copied().unwrap_or_default();
+        self.paint(x, y, Cell { c, foreground: style.foreground, background: style.background });
+    }
+
+    fn paint(&mut self, x: u32, y: u32, cell: Cell) {
+        if x < self.size.width && y < self.size.height {
+            self.cells[y as usize][x as usize] = cell;
+        }
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn text(&mut self, x: u32, y: u32, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            self.put(x + i as u32, y, c);
+        }
+    }
+
+    /** Boxes need two cells in each direction for their corners, smaller areas have no border. */
+    fn border(&mut self, area: Rect) {
+        if area.width < 2 || area.height < 2 {
+            return;
+        }
+        let (right, bottom) = (area.x + area.width - 1, area.y + area.height - 1);
+
+        for x in area.x + 1..right {
+            self.put(x, area.y, '─');
+            self.put(x, bottom, '─');
+        }
+        for y in area.y + 1..bottom {
+            self.put(area.x, y, '│');
+            self.put(right, y, '│');
+        }
+        self.put(area.x, area.y, '┌');
+        self.put(right, area.y, '┐');
+        self.put(area.x, bottom, '└');
+        self.put(right, bottom, '┘');
+    }
+
+    fn clear(&mut self, area: Rect) {
+        for y in area.y..area.y + area.height {
+            for x in area.x..area.x + area.width {
+                self.paint(x, y, Cell::EMPTY);
+            }
+        }
+    }
+
+    /** Components have the colors of the one they're in unless they have their own, and those with a background fill their whole area with it. */
+    fn begin(&mut self, _role: Role, area: Rect, style: &Style) {
+        let style = style.or(&self.styles.last().copied().unwrap_or_default()).colors();
+        self.styles.push(style);
+        if style.background.is_some() {
+            for y in area.y..area.y + area.height {
+                for x in area.x..area.x + area.width {
+                    self.put(x, y, ' ');
+                }
+            }
+        }
+    }
+
+    fn end(&mut self) {
+        self.styles.pop();
+    }
+}
+
+/** The grid, one line for each row. The spaces at the end of the lines and the empty lines at the end are left out, unless they have a background. Colors are
+ *  written with the escape codes of ANSI terminals, only where they change, and reset at the end of every line that has any.
+ */
+impl fmt::Display for TerminalRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lengths: Vec<usize> = self.cells.iter().map(|row| row.iter().rposition(|cell| *cell != Cell::EMPTY).map_or(0, |last| last + 1)).collect();
+        let used = lengths.iter().rposition(|length| *length > 0).map_or(0, |last| last + 1);
+
+        for (row, length) in self.cells.iter().zip(&lengths[..used]) {
+            let mut colors = (None, None);
+            for cell in &row[..*length] {
+                if (cell.foreground, cell.background) != colors {
+                    if colors != (None, None) {
+                        write!(f, "\x1b[0m")?;
+                    }
+                    if let Some(color) = cell.foreground {
+                        write!(f, "\x1b[{}m", color.ansi())?;
+                    }
+                    if let Some(color) = cell.background {
+                        write!(f, "\x1b[{}m", color.ansi() + 10)?;
+                    }
+                    colors = (cell.foreground, cell.background);
+                }
+                write!(f, "{}", cell.c)?;
+            }
+            if colors != (None, None) {
+                write!(f, "\x1b[0m")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/** Draws components as HTML, each as the element of its `Role`: buttons are `<button>`, text fields `<input>` and so on. Elements are placed with inline styles
+ *  at the cells they'd take in a terminal, one `ch` for each column and one `em` for each row, so a screen looks much the same in a browser. The tcp_listener
+ *  server can send it as the body of a response.
+ */
+pub struct HtmlRenderer {
+    size: Size,
+    // The elements begun and not ended yet, the innermost last.
+    open: Vec<Element>,
+    html: String,
+}
+
+/** An element being drawn: what it is, where, and the HTML of what's inside of it so far. */
+struct Element {
+    tag: &'static str,
+    attributes: String,
+    area: Rect,
+    style: Style,
+    border: bool,
+    texts: Vec<String>,
+    // Text fields and checkboxes are made from their role, not from what they draw, so they have their content from the start.
+    content: Option<String>,
+    children: String,
+}
+
+impl HtmlRenderer {
+    pub fn new(size: Size) -> HtmlRenderer {
+        HtmlRenderer { size, open: Vec::new(), html: String::new() }
+    }
+
+    /** The HTML of everything drawn, inside of a `<div>` the size of the screen. Elements that were begun and not ended are left out. */
+    pub fn html(&self) -> String {
+        format!(
+            "<div style=\"position:relative;width:{}ch;height:{}em;font-family:monospace;line-height:1em\">{}</div>",
+            self.size.width, self.size.height, self.html
+        )
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    /** Text goes inside of the element being drawn, one line after the other. Text drawn outside of any is placed on its own. */
+    fn text(&mut self, x: u32, y: u32, text: &str) {
+        match self.open.last_mut() {
+            Some(element) => {
+                if element.content.is_none() {
+                    element.texts.push(escape(text));
+                }
+            }
+            None => self.html.push_str(&format!("<span style=\"position:absolute;left:{}ch;top:{}em\">{}</span>", x, y, escape(text))),
+        }
+    }
+
+    /** Borders are a CSS border on the element being drawn, so those drawn outside of any are lost. */
+    fn border(&mut self, _area: Rect) {
+        if let Some(element) = self.open.last_mut() {
+            element.border = true;
+        }
+    }
+
+    fn begin(&mut self, role: Role, area: Rect, style: &Style) {
+        let (tag, attributes, content) = match role {
+            Role::Group => ("div", String::new(), None),
+            Role::Button => ("button", String::new(), None),
+            Role::TextField { text, placeholder, .. } => {
+                let attributes = format!(" type=\"text\" value=\"{}\" placeholder=\"{}\"", escape(text), escape(placeholder));
+                ("input", attributes, Some(String::new()))
+            }
+            Role::Checkbox { checked, label } => {
+                let checked = if checked { " checked" } else { "" };
+                ("label", String::new(), Some(format!("<input type=\"checkbox\"{}> {}", checked, escape(label))))
+            }
+            Role::Label => ("span", String::new(), None),
+            Role::ProgressBar { percent } => ("progress", format!(" value=\"{}\" max=\"100\"", percent), Some(String::new())),
+        };
+        self.open.push(Element { tag, attributes, area, style: *style, border: false, texts: Vec::new(), content, children: String::new() });
+    }
+
+    fn end(&mut self) {
+        let Some(element) = self.open.pop() else {
+            return;
+        };
+        // Absolute positions count from the element the element is in.
+        let (left, top) = match self.open.last() {
+            Some(parent) => (element.area.x - parent.area.x, element.area.y - parent.area.y),
+            None => (element.area.x, element.area.y),
+        };
+        let mut style = format!("position:absolute;left:{}ch;top:{}em;width:{}ch;height:{}em", left, top, element.area.width, element.area.height);
+        if let Some(color) = element.style.foreground {
+            style.push_str(&format!(";color:{}", color.css()));
+        }
+        if let Some(color) = element.style.background {
+            style.push_str(&format!(";background:{}", color.css()));
+        }
+        if let Some(padding) = element.style.padding.filter(|padding| *padding > 0) {
+            style.push_str(&format!(";padding:{}em {}ch", padding, padding));
+        }
+        if element.border {
+            style.push_str(";border:1px solid");
+        }
+        // The width and height are those of the whole component, padding and border included.
+        if element.border || element.style.padding.is_some_and(|padding| padding > 0) {
+            style.push_str(";box-sizing:border-box");
+        }
+
+        let html = match element.tag {
+            // Inputs have nothing inside, and no end tag.
+            "input" => format!("<input style=\"{}\"{}>", style, element.attributes),
+            tag => {
+                let content = element.content.unwrap_or_else(|| element.texts.join("<br>"));
+                format!("<{} style=\"{}\"{}>{}{}</{}>", tag, style, element.attributes, content, element.children, tag)
+            }
+        };
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push_str(&html),
+            None => self.html.push_str(&html),
+        }
+    }
+}
+
+/** Draws nothing, but keeps what each component is: its role, its name and its state, like whether a checkbox is checked. That's what tools like screen
+ *  readers need to know of a screen, and what tests can check without minding where things are drawn. The name of a component is what it carries in its
+ *  role, or else the text it draws. Progress bars have no name, only how far along they are.
+ */
+#[derive(Default)]
+pub struct AccessibilityRenderer {
+    // The components begun and not ended yet, the innermost last.
+    open: Vec<Node>,
+    roots: Vec<Node>,
+}
+
+struct Node {
+    role: &'static str,
+    name: Option<String>,
+    // Whether the name is made from the text drawn, for roles that don't carry one.
+    named_by_text: bool,
+    states: Vec<String>,
+    texts: Vec<String>,
+    children: Vec<Node>,
+}
+
+impl AccessibilityRenderer {
+    pub fn new() -> AccessibilityRenderer {
+        AccessibilityRenderer::default()
+    }
+}
+
+impl Renderer for AccessibilityRenderer {
+    fn text(&mut self, _x: u32, _y: u32, text: &str) {
+        if let Some(node) = self.open.last_mut().filter(|node| node.named_by_text) {
+            let text = text.trim();
+            if !text.is_empty() {
+                node.texts.push(text.to_string());
+            }
+        }
+    }
+
+    fn border(&mut self, _area: Rect) {}
+
+    fn begin(&mut self, role: Role, _area: Rect, _style: &Style) {
+        let (role, name, states) = match role {
+            Role::Group => ("group", None, Vec::new()),
+            Role::Button => ("button", None, Vec::new()),
+            Role::TextField { text, placeholder, focused } => {
+                let mut states = vec![format!("placeholder {:?}", placeholder)];
+                if focused {
+                    states.push(String::from("focused"));
+                }
+                ("text field", Some(text.to_string()), states)
+            }
+            Role::Checkbox { checked, label } => ("checkbox", Some(label.to_string()), vec![String::from(if checked { "checked" } else { "unchecked" })]),
+            Role::Label => ("label", None, Vec::new()),
+            Role::ProgressBar { percent } => ("progress bar", None, vec![format!("{}%", percent)]),
+        };
+        let named_by_text = name.is_none() && role != "progress bar";
+        self.open.push(Node { role, name, named_by_text, states, texts: Vec::new(), children: Vec::new() });
+    }
+
+    /** Groups without text of their own have no name. */
+    fn end(&mut self) {
+        let Some(mut node) = self.open.pop() else {
+            return;
+        };
+        if node.named_by_text && (node.role != "group" || !node.texts.is_empty()) {
+            node.name = Some(node.texts.join(" "));
+        }
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.roots.push(node),
+        }
+    }
+}
+
+/** One line for each component, with those inside of a container indented under it by two spaces. */
+impl fmt::Display for AccessibilityRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_node(f: &mut fmt::Formatter, node: &Node, depth: usize) -> fmt::Result {
+            write!(f, "{:indent$}{}", "", node.role, indent = depth * 2)?;
+            if let Some(name) = &node.name {
+                write!(f, " {:?}", name)?;
+            }
+            for state in &node.states {
+                write!(f, " {}", state)?;
+            }
+            writeln!(f)?;
+            node.children.iter().try_for_each(|child| write_node(f, child, depth + 1))
+        }
+
+        self.roots.iter().try_for_each(|root| write_node(f, root, 0))
+    }
+}
+
+/** `text` with the characters that mean something in HTML written as entities. */
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_and_borders_are_cut_at_the_edges() {
+        let mut terminal = TerminalRenderer::new(Size::new(8, 4));
+
+        terminal.border(Rect::new(0, 0, Size::new(6, 3)));
+        terminal.text(1, 1, "Hello, world!");
+        terminal.border(Rect::new(6, 3, Size::new(4, 4)));
+
+        assert_eq!("┌────┐\n│Hello,\n└────┘\n      ┌─\n", terminal.to_string());
+    }
+
+    #[test]
+    fn colors_are_written_where_they_change() {
+        let mut terminal = TerminalRenderer::new(Size::new(8, 3));
+
+        terminal.begin(Role::Group, Rect::new(0, 0, Size::new(4, 1)), &Style::new().background(Color::Blue));
+        terminal.begin(Role::Label, Rect::new(0, 0, Size::new(2, 1)), &Style::new().foreground(Color::Yellow));
+        terminal.text(0, 0, "Hi");
+        terminal.end();
+        terminal.end();
+        terminal.text(5, 0, "!");
+
+        assert_eq!("\x1b[33m\x1b[44mHi\x1b[0m\x1b[44m  \x1b[0m !\n", terminal.to_string());
+    }
+
+    #[test]
+    fn html_elements_nest_with_their_roles() {
+        let mut html = HtmlRenderer::new(Size::new(20, 2));
+
+        html.begin(Role::Group, Rect::new(2, 1, Size::new(18, 1)), &Style::new());
+        html.begin(Role::Button, Rect::new(2, 1, Size::new(6, 1)), &Style::new().foreground(Color::Red));
+        html.border(Rect::new(2, 1, Size::new(6, 1)));
+        html.text(3, 1, "<OK>");
+        html.end();
+        html.begin(Role::Checkbox { checked: true, label: "Crab" }, Rect::new(9, 1, Size::new(8, 1)), &Style::new().padding(1));
+        html.text(9, 1, "[x] Crab");
+        html.end();
+        html.end();
+
+        assert_eq!(
+            concat!(
+                r#"<div style="position:relative;width:20ch;height:2em;font-family:monospace;line-height:1em">"#,
+                r#"<div style="position:absolute;left:2ch;top:1em;width:18ch;height:1em">"#,
+                r#"<button style="position:absolute;left:0ch;top:0em;width:6ch;height:1em;color:red;border:1px solid;box-sizing:border-box">&lt;OK&gt;</button>"#,
+                r#"<label style="position:absolute;left:7ch;top:0em;width:8ch;height:1em;padding:1em 1ch;box-sizing:border-box"><input type="checkbox" checked> Crab</label>"#,
+                r#"</div></div>"#,
+            ),
+            html.html()
+        );
+    }
+}
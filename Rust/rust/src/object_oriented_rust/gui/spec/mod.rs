@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::iter;
+use std::str::FromStr;
+
+use super::{Color, Draw, Style};
+
+/** A component written down: its kind, its properties and the specs of its children. Specs are written as text, one component on each line, with its kind
+ *  and then its properties as `key=value`, and its children on the lines under it indented by two more spaces:
+ *
+ *  ```text
+ *  # Blank lines and lines starting with # are left out.
+ *  label text="Sign up"
+ *  row spacing=1
+ *    label text=Name
+ *    text_field width=10 placeholder="Ferris" id=name
+ *  ```
+ *
+ *  Values with spaces, quotes or nothing at all in them are quoted, with the same escapes as Rust strings.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spec {
+    pub kind: String,
+    pub properties: Vec<(String, String)>,
+    pub children: Vec<Spec>,
+}
+
+/** Everything that can go wrong reading specs, or making components out of them. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+    /** A line isn't a kind followed by `key=value` properties. */
+    Syntax { line: usize, text: String },
+    /** A line is indented by more than two spaces past the one above it, or by an odd number of them. */
+    Indentation { line: usize },
+    /** A component doesn't have the children its kind needs, for the given reason. */
+    Children { kind: String, reason: &'static str },
+    /** No factory was registered for the kind. */
+    UnknownKind(String),
+    /** A property can't be used for the component, for the given reason. */
+    InvalidValue { kind: String, key: String, value: String, reason: &'static str },
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpecError::Syntax { line, text } => write!(f, "line {}: expected a kind followed by `key=value` properties, found {:?}", line, text),
+            SpecError::Indentation { line } => write!(f, "line {}: children are indented by two spaces more than their parent", line),
+            SpecError::Children { kind, reason } => write!(f, "invalid children of {}: {}", kind, reason),
+            SpecError::UnknownKind(kind) => write!(f, "unknown kind of component {:?}", kind),
+            SpecError::InvalidValue { kind, key, value, reason } => write!(f, "invalid {} of {} {:?}: {}", key, kind, value, reason),
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+impl Spec {
+    pub fn new(kind: &str) -> Spec {
+        Spec { kind: kind.to_string(), properties: Vec::new(), children: Vec::new() }
+    }
+
+    pub fn property(mut self, key: &str, value: impl ToString) -> Spec {
+        self.properties.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn child(mut self, child: Spec) -> Spec {
+        self.children.push(child);
+        self
+    }
+
+    /** Adds the specs of the `components` that have one as children, which is what containers are written down with. */
+    pub fn children_of(mut self, components: &[Box<dyn Draw>]) -> Spec {
+        self.children.extend(components.iter().filter_map(|component| component.to_spec()));
+        self
+    }
+
+    /** Adds the ID and the style set on a component, the properties every kind has. Only what the style sets is written. */
+    pub fn common(mut self, id: Option<&str>, style: &Style) -> Spec {
+        if let Some(id) = id {
+            self = self.property("id", id);
+        }
+        if let Some(color) = style.foreground {
+            self = self.property("foreground", color.css());
+        }
+        if let Some(color) = style.background {
+            self = self.property("background", color.css());
+        }
+        if let Some(padding) = style.padding {
+            self = self.property("padding", padding);
+        }
+        if let Some(border) = style.border {
+            self = self.property("border", border);
+        }
+        self
+    }
+
+    /** The value of `key`, if the spec has it. */
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties.iter().find(|(k, _)| k == key).map(|(_, value)| value.as_str())
+    }
+
+    /** The value of `key` read as a `T`, or `default` if the spec doesn't have it. */
+    pub fn parse<T: FromStr>(&self, key: &str, default: T) -> Result<T, SpecError> {
+        match self.get(key) {
+            Some(value) => value.parse().map_err(|_| self.invalid(key, "expected a number, or true or false")),
+            None => Ok(default),
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.get("id")
+    }
+
+    /** The style written with `common`. */
+    pub fn style(&self) -> Result<Style, SpecError> {
+        let color = |key| match self.get(key) {
+            Some(name) => Color::from_css(name).map(Some).ok_or_else(|| self.invalid(key, "expected a color like red or blue")),
+            None => Ok(None),
+        };
+        Ok(Style {
+            foreground: color("foreground")?,
+            background: color("background")?,
+            padding: self.get("padding").map(|_| self.parse("padding", 0)).transpose()?,
+            border: self.get("border").map(|_| self.parse("border", false)).transpose()?,
+        })
+    }
+
+    /** The error for a value of `key` that can't be used. */
+    pub fn invalid(&self, key: &str, reason: &'static str) -> SpecError {
+        SpecError::InvalidValue { kind: self.kind.clone(), key: key.to_string(), value: self.get(key).unwrap_or_default().to_string(), reason }
+    }
+
+    /** Reads the specs written in `text`, those of the components that aren't inside of any other, each with its children. */
+    pub fn parse_all(text: &str) -> Result<Vec<Spec>, SpecError> {
+        let mut roots = Vec::new();
+        // The specs of the lines above that may still get children, one for each level of indentation.
+        let mut open: Vec<Spec> = Vec::new();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = raw.len() - raw.trim_start_matches(' ').len();
+            if indent % 2 != 0 || indent / 2 > open.len() {
+                return Err(SpecError::Indentation { line });
+            }
+            let spec = parse_line(trimmed).ok_or_else(|| SpecError::Syntax { line, text: raw.to_string() })?;
+
+            close(&mut open, &mut roots, indent / 2);
+            open.push(spec);
+        }
+        close(&mut open, &mut roots, 0);
+        Ok(roots)
+    }
+}
+
+/** Ends the specs open past `depth`, each becoming a child of the one before it, or a root if there's none. */
+fn close(open: &mut Vec<Spec>, roots: &mut Vec<Spec>, depth: usize) {
+    while open.len() > depth {
+        let spec = open.pop().unwrap();
+        match open.last_mut() {
+            Some(parent) => parent.children.push(spec),
+            None => roots.push(spec),
+        }
+    }
+}
+
+/** A line with a kind and its properties, without its children, or `None` if it's not one. */
+fn parse_line(line: &str) -> Option<Spec> {
+    let mut chars = line.chars().peekable();
+    let kind: String = chars.by_ref().take_while(|c| *c != ' ').collect();
+    let mut spec = Spec::new(&kind);
+
+    loop {
+        while chars.next_if_eq(&' ').is_some() {}
+        if chars.peek().is_none() {
+            return Some(spec);
+        }
+        let key: String = iter::from_fn(|| chars.next_if(|c| *c != '=' && *c != ' ')).collect();
+        if key.is_empty() || chars.next_if_eq(&'=').is_none() {
+            return None;
+        }
+        let value = if chars.next_if_eq(&'"').is_some() {
+            unescape(&mut chars)?
+        } else {
+            iter::from_fn(|| chars.next_if(|c| *c != ' ')).collect()
+        };
+        spec.properties.push((key, value));
+        if chars.peek().is_some_and(|c| *c != ' ') {
+            return None;
+        }
+    }
+}
+
+/** Reads a quoted value up to its closing quote, which was opened already. */
+fn unescape(chars: &mut impl Iterator<Item = char>) -> Option<String> {
+    let mut value = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'r' => value.push('\r'),
+                '0' => value.push('\0'),
+                'u' => {
+                    let code: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                    value.push(u32::from_str_radix(&code, 16).ok().and_then(char::from_u32)?);
+                }
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+}
+
+/** The spec as text, with its children indented under it. */
+impl fmt::Display for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_spec(f: &mut fmt::Formatter, spec: &Spec, depth: usize) -> fmt::Result {
+            write!(f, "{:indent$}{}", "", spec.kind, indent = depth * 2)?;
+            for (key, value) in &spec.properties {
+                let bare = !value.is_empty() && value.chars().all(|c| c.is_alphanumeric() || "._-%".contains(c));
+                if bare {
+                    write!(f, " {}={}", key, value)?;
+                } else {
+                    write!(f, " {}={:?}", key, value)?;
+                }
+            }
+            writeln!(f)?;
+            spec.children.iter().try_for_each(|child| write_spec(f, child, depth + 1))
+        }
+
+        write_spec(f, self, 0)
+    }
+}
+
+/** Components that can be made from a spec of their `KIND`. Containers make their children with `registry`. */
+pub trait FromSpec: Sized {
+    const KIND: &'static str;
+
+    fn from_spec(spec: &Spec, registry: &Registry) -> Result<Self, SpecError>;
+}
+
+type Factory = fn(&Spec, &Registry) -> Result<Box<dyn Draw>, SpecError>;
+
+/** Makes components out of specs, with the factory registered for the kind of each. `new` has every component of the library registered already, and
+ *  others can be added with `register`.
+ */
+pub struct Registry {
+    factories: HashMap<&'static str, Factory>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        use super::{Button, Checkbox, Column, Grid, Label, Modal, Panel, ProgressBar, Row, TextField};
+
+        let mut registry = Registry::empty();
+        registry.register::<Button>();
+        registry.register::<TextField>();
+        registry.register::<Checkbox>();
+        registry.register::<Label>();
+        registry.register::<ProgressBar>();
+        registry.register::<Row>();
+        registry.register::<Column>();
+        registry.register::<Grid>();
+        registry.register::<Panel>();
+        registry.register::<Modal>();
+        registry
+    }
+
+    /** A registry without any factory. */
+    pub fn empty() -> Registry {
+        Registry { factories: HashMap::new() }
+    }
+
+    /** Makes the components of the kind of `T` with `T::from_spec`, instead of any factory registered for it before. */
+    pub fn register<T: FromSpec + Draw + 'static>(&mut self) {
+        self.factories.insert(T::KIND, |spec, registry| Ok(Box::new(T::from_spec(spec, registry)?)));
+    }
+
+    pub fn build(&self, spec: &Spec) -> Result<Box<dyn Draw>, SpecError> {
+        let factory = self.factories.get(spec.kind.as_str()).ok_or_else(|| SpecError::UnknownKind(spec.kind.clone()))?;
+        factory(spec, self)
+    }
+
+    /** Makes every child of `spec`. */
+    pub fn build_children(&self, spec: &Spec) -> Result<Vec<Box<dyn Draw>>, SpecError> {
+        spec.children.iter().map(|child| self.build(child)).collect()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_are_read_back_as_they_are_written() {
+        let spec = Spec::new("row")
+            .property("spacing", 1)
+            .child(Spec::new("label").property("text", "Name: \"Ferris\"\n"))
+            .child(Spec::new("text_field").property("placeholder", "").child(Spec::new("label").property("text", "ñ")));
+
+        let text = spec.to_string();
+        assert_eq!("row spacing=1\n  label text=\"Name: \\\"Ferris\\\"\\n\"\n  text_field placeholder=\"\"\n    label text=ñ\n", text);
+        assert_eq!(Ok(vec![spec]), Spec::parse_all(&format!("# A row\n\n{}", text)));
+    }
+
+    #[test]
+    fn specs_that_cant_be_read_tell_where() {
+        assert_eq!(Err(SpecError::Indentation { line: 2 }), Spec::parse_all("row\n    label"));
+        assert_eq!(Err(SpecError::Indentation { line: 1 }), Spec::parse_all(" row"));
+        assert_eq!(Err(SpecError::Syntax { line: 1, text: String::from("label text=\"open") }), Spec::parse_all("label text=\"open"));
+        assert_eq!(Err(SpecError::Syntax { line: 1, text: String::from("label text") }), Spec::parse_all("label text"));
+
+        let spec = &Spec::parse_all("label padding=lots").unwrap()[0];
+        assert_eq!("invalid padding of label \"lots\": expected a number, or true or false", spec.style().unwrap_err().to_string());
+        assert!(matches!(Registry::new().build(&Spec::new("slider")), Err(SpecError::UnknownKind(kind)) if kind == "slider"));
+    }
+}
@@ -0,0 +1,220 @@
+use super::{Constraints, Rect, Size};
+
+/** The colors of a terminal, which HTML has too. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    /** The number of the color in the escape codes of terminals, 30 to 37 for text and those plus 10 for the background. */
+    pub fn ansi(&self) -> u8 {
+        30 + *self as u8
+    }
+
+    /** The name of the color in CSS. */
+    pub fn css(&self) -> &'static str {
+        match self {
+            Color::Black => "black",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Blue => "blue",
+            Color::Magenta => "magenta",
+            Color::Cyan => "cyan",
+            Color::White => "white",
+        }
+    }
+
+    /** The color with the name `name` has in CSS. */
+    pub fn from_css(name: &str) -> Option<Color> {
+        [Color::Black, Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan, Color::White]
+            .into_iter()
+            .find(|color| color.css() == name)
+    }
+}
+
+/** How a component looks. Everything is optional: what a style doesn't set comes from the theme of the screen, and the colors from the container around the
+ *  component if the theme doesn't set them either. Padding is the empty cells left around the content, inside of the border.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub padding: Option<u32>,
+    pub border: Option<bool>,
+}
+
+impl Style {
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    pub fn foreground(mut self, color: Color) -> Style {
+        self.foreground = Some(color);
+        self
+    }
+
+    pub fn background(mut self, color: Color) -> Style {
+        self.background = Some(color);
+        self
+    }
+
+    pub fn padding(mut self, padding: u32) -> Style {
+        self.padding = Some(padding);
+        self
+    }
+
+    pub fn border(mut self, border: bool) -> Style {
+        self.border = Some(border);
+        self
+    }
+
+    /** This style, with what it doesn't set taken from `other`. */
+    pub fn or(&self, other: &Style) -> Style {
+        Style {
+            foreground: self.foreground.or(other.foreground),
+            background: self.background.or(other.background),
+            padding: self.padding.or(other.padding),
+            border: self.border.or(other.border),
+        }
+    }
+
+    /** Only the colors of the style, which are what children inherit from their containers. Padding and borders are of each component alone. */
+    pub fn colors(&self) -> Style {
+        Style { foreground: self.foreground, background: self.background, ..Style::default() }
+    }
+
+    pub fn has_border(&self) -> bool {
+        self.border.unwrap_or(false)
+    }
+
+    /** How many cells the padding and the border take on each side of the content. */
+    pub fn edge(&self) -> u32 {
+        self.padding.unwrap_or(0) + if self.has_border() { 1 } else { 0 }
+    }
+
+    /** The size of a component with content of `size`, with its padding and border around it. */
+    pub fn outer(&self, size: Size) -> Size {
+        Size::new(size.width + 2 * self.edge(), size.height + 2 * self.edge())
+    }
+
+    /** The room left for the content of a component, once its padding and border took theirs. */
+    pub fn content(&self, constraints: Constraints) -> Constraints {
+        let edges = 2 * self.edge();
+        Constraints::new(constraints.max_width.saturating_sub(edges), constraints.max_height.saturating_sub(edges))
+    }
+
+    /** Where the content goes in a component placed at `area`, inside of its padding and border. */
+    pub fn inner(&self, area: Rect) -> Rect {
+        let edge = self.edge();
+        Rect {
+            x: area.x + edge,
+            y: area.y + edge,
+            width: area.width.saturating_sub(2 * edge),
+            height: area.height.saturating_sub(2 * edge),
+        }
+    }
+}
+
+/** The styles of each kind of component on a screen, for those that don't set their own. Only the colors of `base` are used, they're those of the screen
+ *  itself, which every component inherits unless the container it's in has colors of its own.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub base: Style,
+    pub groups: Style,
+    pub buttons: Style,
+    pub text_fields: Style,
+    pub checkboxes: Style,
+    pub labels: Style,
+    pub progress_bars: Style,
+    pub modals: Style,
+}
+
+/** The theme screens have unless they're given another: no colors, and only buttons and modals have a border. */
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            base: Style::new(),
+            groups: Style::new(),
+            buttons: Style::new().border(true),
+            text_fields: Style::new(),
+            checkboxes: Style::new(),
+            labels: Style::new(),
+            progress_bars: Style::new(),
+            modals: Style::new().border(true),
+        }
+    }
+}
+
+/** The style a component was given and the one the theme of its screen gave it, which fills in what the first leaves out. Components start with the style of
+ *  the default theme for their kind, so they look right before they're on a screen.
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Styled {
+    own: Style,
+    themed: Style,
+}
+
+impl Styled {
+    pub fn new(themed: Style) -> Styled {
+        Styled { own: Style::new(), themed }
+    }
+
+    /** The style the component is drawn with. */
+    pub fn get(&self) -> Style {
+        self.own.or(&self.themed)
+    }
+
+    /** The style the component was given, without what the theme fills in. */
+    pub fn own(&self) -> Style {
+        self.own
+    }
+
+    /** Returns whether the component looks different now. */
+    pub fn set(&mut self, style: Style) -> bool {
+        let before = self.get();
+        self.own = style;
+        before != self.get()
+    }
+
+    /** Fills in what the own style leaves out with `kind`, the style of the theme for the kind of the component, and then the colors of `inherited`. Returns
+     *  whether the component looks different now.
+     */
+    pub fn theme(&mut self, kind: &Style, inherited: &Style) -> bool {
+        let before = self.get();
+        self.themed = kind.or(&inherited.colors());
+        before != self.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_fill_what_they_dont_set() {
+        let own = Style::new().foreground(Color::Red);
+        let theme = Style::new().foreground(Color::Blue).padding(1);
+        let parent = Style::new().background(Color::White).border(true);
+
+        let style = own.or(&theme).or(&parent.colors());
+        assert_eq!(Style::new().foreground(Color::Red).background(Color::White).padding(1), style);
+    }
+
+    #[test]
+    fn padding_and_borders_surround_the_content() {
+        let style = Style::new().padding(1).border(true);
+
+        assert_eq!(Size::new(8, 5), style.outer(Size::new(4, 1)));
+        assert_eq!(Rect::new(3, 4, Size::new(4, 1)), style.inner(Rect::new(1, 2, Size::new(8, 5))));
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use super::event::{self, Event};
+use super::{Draw, Rect, Renderer, Role, Style, Theme};
+
+/** The children of a container, or of the screen, kept from one frame to the next along with where they were placed. Containers hold their children this way,
+ *  so the components of a screen are a tree that stays around between frames, and a frame only draws again what changed in it.
+ *
+ *  A component tells it changed with `Draw::is_dirty`. A container is dirty when any of its children is, so whether anything changed is asked from the root
+ *  down, and the children don't need to know their parents. Redrawing goes down the same way, to the dirty children only, unless the children were moved,
+ *  in which case the whole container is drawn again to clear where they were.
+ *
+ *  Children are drawn from the lowest z-index to the highest, and in the order they were added for those with the same one, so the last drawn are on top. A
+ *  child drawn again on top of others is drawn again above them too.
+ */
+#[derive(Default)]
+pub struct Children {
+    components: Vec<Box<dyn Draw>>,
+    frames: Vec<Rect>,
+    focused: Option<usize>,
+    moved: bool,
+}
+
+impl Children {
+    pub fn push(&mut self, component: Box<dyn Draw>) {
+        self.components.push(component);
+        self.moved = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /** Takes out the child at `index`, and the focus with it if it had it. */
+    pub fn remove(&mut self, index: usize) -> Box<dyn Draw> {
+        let component = self.components.remove(index);
+        if index < self.frames.len() {
+            self.frames.remove(index);
+        }
+        self.focused = match self.focused {
+            Some(focused) if focused == index => None,
+            Some(focused) if focused > index => Some(focused - 1),
+            focused => focused,
+        };
+        self.moved = true;
+        component
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Draw>> {
+        self.components.iter_mut()
+    }
+
+    pub fn components(&self) -> &[Box<dyn Draw>] {
+        &self.components
+    }
+
+    pub fn components_mut(&mut self) -> &mut [Box<dyn Draw>] {
+        &mut self.components
+    }
+
+    /** Where each child was put by the last layout, in the order they were added. Each frame counts from the corner of the container. */
+    pub fn frames(&self) -> &[Rect] {
+        &self.frames
+    }
+
+    /** Keeps where the layout of the container put each child. If any of them moved or changed size, the container has to be drawn again. */
+    pub fn place(&mut self, frames: Vec<Rect>) {
+        if frames != self.frames {
+            self.frames = frames;
+            self.moved = true;
+        }
+    }
+
+    /** Has the whole container drawn again on the next frame, as when it looks different and not only some of its children do. */
+    pub fn invalidate(&mut self) {
+        self.moved = true;
+    }
+
+    /** Gives the theme to every child, which inherit the colors of `style`, the style of the container. */
+    pub fn apply_theme(&mut self, theme: &Theme, style: &Style) {
+        for child in self.components.iter_mut() {
+            child.apply_theme(theme, style);
+        }
+    }
+
+    /** Moves the animations of every child `dt` forward. */
+    pub fn tick(&mut self, dt: Duration) {
+        for child in self.components.iter_mut() {
+            child.tick(dt);
+        }
+    }
+
+    /** Draws every child at its frame, inside of the `area` of the container, which is drawn with `style`. */
+    pub fn draw(&self, renderer: &mut dyn Renderer, area: Rect, style: &Style) {
+        renderer.begin(Role::Group, area, style);
+        if style.has_border() {
+            renderer.border(area);
+        }
+        for i in self.order() {
+            self.components[i].draw(renderer, inside(area, self.frames[i]));
+        }
+        renderer.end();
+    }
+
+    /** Draws again the children that changed since the last frame, or all of them if they moved. */
+    pub fn redraw(&mut self, renderer: &mut dyn Renderer, area: Rect, style: &Style) {
+        if self.moved {
+            renderer.clear(area);
+            self.draw(renderer, area, style);
+            self.mark_clean();
+            return;
+        }
+        // What was drawn again wiped out the children over it where they overlap, so those are drawn again on top, without wiping out what's under them.
+        let mut redrawn: Vec<Rect> = Vec::new();
+        for i in self.order() {
+            let (child, frame) = (&mut self.components[i], inside(area, self.frames[i]));
+            if child.is_dirty() {
+                child.redraw(renderer, frame);
+                redrawn.push(frame);
+            } else if redrawn.iter().any(|below| below.intersects(&frame)) {
+                child.draw(renderer, frame);
+                redrawn.push(frame);
+            }
+        }
+    }
+
+    /** The indices of the children that have room to be drawn, in the order they're drawn. */
+    fn order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.components.len().min(self.frames.len())).filter(|i| !self.frames[*i].is_empty()).collect();
+        order.sort_by_key(|i| self.components[*i].z_index());
+        order
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.moved || self.components.iter().any(|child| child.is_dirty())
+    }
+
+    pub fn mark_clean(&mut self) {
+        self.moved = false;
+        for child in self.components.iter_mut() {
+            child.mark_clean();
+        }
+    }
+
+    /** Gives `event` to the child it's for, see `event::route`. */
+    pub fn route(&mut self, event: &Event) -> bool {
+        event::route(&mut self.components, &self.frames, &mut self.focused, event)
+    }
+}
+
+/** Where a child at `frame` in its container is on the screen, when the container is at `area`. */
+fn inside(area: Rect, frame: Rect) -> Rect {
+    Rect { x: area.x + frame.x, y: area.y + frame.y, ..frame }
+}
@@ -0,0 +1,60 @@
+/** Where a comment is in its moderation. Comments go through it like posts through their review: they wait for a moderator, who approves them or hides
+ *  them, and only approved ones are shown.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moderation {
+    Pending,
+    Approved,
+    Hidden,
+}
+
+impl Moderation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Moderation::Pending => "pending",
+            Moderation::Approved => "approved",
+            Moderation::Hidden => "hidden",
+        }
+    }
+}
+
+/** What someone said about a published post. Like the content of a post, the text of a comment is empty until it's approved. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    // The rest of the blog sees the fields to save comments and load them back.
+    pub(super) author: String,
+    pub(super) text: String,
+    pub(super) moderation: Moderation,
+}
+
+impl Comment {
+    /** A comment of `author` waiting to be moderated. */
+    pub fn new(author: &str, text: &str) -> Comment {
+        Comment { author: author.to_string(), text: text.to_string(), moderation: Moderation::Pending }
+    }
+
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    pub fn text(&self) -> &str {
+        match self.moderation {
+            Moderation::Approved => &self.text,
+            _ => "",
+        }
+    }
+
+    pub fn moderation(&self) -> Moderation {
+        self.moderation
+    }
+
+    /** Shows the comment, whether it was waiting or hidden. */
+    pub fn approve(&mut self) {
+        self.moderation = Moderation::Approved;
+    }
+
+    /** Stops showing the comment, even if it was approved before. */
+    pub fn hide(&mut self) {
+        self.moderation = Moderation::Hidden;
+    }
+}
@@ -0,0 +1,141 @@
+/** The few parts of Markdown a post can be written with, made into HTML by `to_html`:
+ *      - Headings, a line starting with one to six `#` and a space.
+ *      - Lists, lines starting with `- ` or `* `, or with a number and `. ` when they're ordered.
+ *      - Paragraphs, the lines between those, until a blank one.
+ *      - Inside all of them, `**strong**` and `*emphasized*` or `_emphasized_` text, and `` `code` ``.
+ *  Everything else is text, and is escaped so it can't be taken for HTML. A marker without another one to close it is text too, and so is one with a space
+ *  on the inside of it.
+ */
+pub fn to_html(markdown: &str) -> String {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut open = false;
+
+    for line in markdown.lines().map(str::trim) {
+        if line.is_empty() {
+            open = false;
+        } else if let Some((level, text)) = heading(line) {
+            blocks.push(Block::Heading(level, text));
+            open = false;
+        } else if let Some((ordered, text)) = item(line) {
+            match blocks.last_mut() {
+                Some(Block::List(kind, items)) if open && *kind == ordered => items.push(text),
+                _ => blocks.push(Block::List(ordered, vec![text])),
+            }
+            open = true;
+        } else {
+            match blocks.last_mut() {
+                Some(Block::Paragraph(lines)) if open => lines.push(line),
+                _ => blocks.push(Block::Paragraph(vec![line])),
+            }
+            open = true;
+        }
+    }
+
+    blocks.iter().map(Block::to_html).collect()
+}
+
+/** A part of a post that is separated from the others. Only lists and paragraphs span more than one line. */
+enum Block<'a> {
+    Heading(usize, &'a str),
+    /** Whether it's ordered, and the text of each item. */
+    List(bool, Vec<&'a str>),
+    Paragraph(Vec<&'a str>),
+}
+
+impl Block<'_> {
+    fn to_html(&self) -> String {
+        match self {
+            Block::Heading(level, text) => format!("<h{level}>{}</h{level}>\n", inline(text)),
+            Block::List(ordered, items) => {
+                let tag = if *ordered { "ol" } else { "ul" };
+                let items: String = items.iter().map(|text| format!("<li>{}</li>\n", inline(text))).collect();
+                format!("<{tag}>\n{items}</{tag}>\n")
+            }
+            Block::Paragraph(lines) => format!("<p>{}</p>\n", inline(&lines.join("\n"))),
+        }
+    }
+}
+
+/** The level and text of `line`, if it's a heading. */
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    match (level, line[level..].strip_prefix(' ')) {
+        (1..=6, Some(text)) => Some((level, text.trim())),
+        _ => None,
+    }
+}
+
+/** Whether `line` is an item of an ordered list, and its text, if it's an item of a list. */
+fn item(line: &str) -> Option<(bool, &str)> {
+    if let Some(text) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some((false, text.trim()));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    match line[digits..].strip_prefix(". ") {
+        Some(text) if digits > 0 => Some((true, text.trim())),
+        _ => None,
+    }
+}
+
+/** The HTML of the text inside a block, with its emphasis and code. Code is left as it's written, but emphasis can have more of it inside. */
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let span = match c {
+            '`' => enclosed(rest, "`").map(|(code, after)| (format!("<code>{}</code>", escape(code)), after)),
+            '*' if rest.starts_with("**") => emphasized(rest, "**").map(|(strong, after)| (format!("<strong>{}</strong>", inline(strong)), after)),
+            '*' | '_' => emphasized(rest, &rest[..1]).map(|(em, after)| (format!("<em>{}</em>", inline(em)), after)),
+            _ => None,
+        };
+        match span {
+            Some((span, after)) => {
+                html += &span;
+                rest = after;
+            }
+            None => {
+                html += &escape(&rest[..c.len_utf8()]);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    html
+}
+
+/** What's between `marker` at the start of `text` and the next one, and what's after that, unless there's nothing between them. */
+fn enclosed<'a>(text: &'a str, marker: &str) -> Option<(&'a str, &'a str)> {
+    let inner = text.strip_prefix(marker)?;
+    match inner.find(marker)? {
+        0 => None,
+        end => Some((&inner[..end], &inner[end + marker.len()..])),
+    }
+}
+
+/** Like `enclosed`, but the text between the markers can't start or end with a space, so `2 * 3 * 4` stays as it is. */
+fn emphasized<'a>(text: &'a str, marker: &str) -> Option<(&'a str, &'a str)> {
+    enclosed(text, marker).filter(|(inner, _)| !inner.starts_with(char::is_whitespace) && !inner.ends_with(char::is_whitespace))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /** The HTML a whole post is expected to be made into is kept next to it, in the golden directory, so it can be read as a page. */
+    #[test]
+    fn posts_are_made_into_their_golden_html() {
+        assert_eq!(include_str!("golden/post.html"), to_html(include_str!("golden/post.md")));
+    }
+
+    #[test]
+    fn markers_without_an_end_are_text() {
+        assert_eq!("<p>2 * 3 = 6, but <em>not</em> 2 ** 3</p>\n", to_html("2 * 3 = 6, but _not_ 2 ** 3"));
+        assert_eq!("<p><code>&lt;closed&gt;</code> and `open</p>\n", to_html("`<closed>` and `open"));
+        assert_eq!("<p>#hashtag</p>\n<h6>####### deep</h6>\n", to_html("#hashtag\n###### ####### deep"));
+        assert_eq!("", to_html("\n  \n"));
+    }
+}
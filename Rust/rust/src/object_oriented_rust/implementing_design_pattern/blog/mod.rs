@@ -0,0 +1,451 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::object_oriented_rust::state_machine::StateMachine;
+use crate::smart_pointers::Messenger;
+
+/** Comments on published posts, and their moderation. */
+mod comment;
+
+/** Saving posts to files and loading them back, and why a file couldn't be loaded as a post. */
+pub mod storage;
+
+/** The Markdown posts are written in, made into the HTML they're shown with. */
+mod markdown;
+
+pub use comment::{Comment, Moderation};
+
+/** A point in time, as the seconds since the Unix epoch. Posts are scheduled to be published at one. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(pub u64);
+
+/** Where the time comes from. The blog is told what time it is rather than asking the system, so tests can say when it is. */
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+}
+
+/** The time of the system the blog runs on. */
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()))
+    }
+}
+
+/** What a post was like after one of its edits, and when that was. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub at: Timestamp,
+    pub content: String,
+}
+
+/** A move of a post from one state to another, and when its clock says it was made. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub from: PostState,
+    pub to: PostState,
+    pub at: Timestamp,
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "moved from {} to {} at {}", self.from.name(), self.to.name(), self.at.0)
+    }
+}
+
+type Observer = Box<dyn FnMut(&AuditEntry)>;
+
+/** What a post can't do in the state it's in. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostError {
+    /** Only drafts can be edited, and this post is in the given state. */
+    NotDraft(&'static str),
+    /** Only published posts can be commented on, and this post is in the given state. */
+    NotPublished(&'static str),
+    /** Only published posts can be read as HTML, and this post is in the given state. */
+    Unreadable(&'static str),
+}
+
+impl fmt::Display for PostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PostError::NotDraft(state) => write!(f, "only drafts can be edited, but the post is {}", state),
+            PostError::NotPublished(state) => write!(f, "only published posts can be commented on, but the post is {}", state),
+            PostError::Unreadable(state) => write!(f, "only published posts can be rendered, but the post is {}", state),
+        }
+    }
+}
+
+impl std::error::Error for PostError {}
+
+/** Where a post is on its way to being published. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PostState {
+    Draft,
+    PendingReview,
+    /** Sent back from its review. It's kept apart from drafts to remember why, until it's reviewed again. */
+    Rejected,
+    /** Approved, but not published until its time comes. It can still be rejected until then. */
+    Scheduled,
+    Published,
+}
+
+impl PostState {
+    pub const ALL: [PostState; 5] = [PostState::Draft, PostState::PendingReview, PostState::Rejected, PostState::Scheduled, PostState::Published];
+
+    /** What the state is called, in errors and in saved posts. */
+    pub fn name(&self) -> &'static str {
+        match self {
+            PostState::Draft => "draft",
+            PostState::PendingReview => "pending review",
+            PostState::Rejected => "rejected",
+            PostState::Scheduled => "scheduled",
+            PostState::Published => "published",
+        }
+    }
+}
+
+/** What moves a post from one state to another. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PostEvent {
+    RequestReview,
+    Approve,
+    Reject,
+    Schedule,
+}
+
+/** Where each event takes a post from each state. Events a state has no transition for leave posts in it, like approving a draft. */
+fn workflow(state: PostState) -> StateMachine<PostState, PostEvent> {
+    use PostEvent::*;
+    use PostState::*;
+
+    StateMachine::new(state)
+        .transition(Draft, RequestReview, PendingReview)
+        .transition(PendingReview, Approve, Published)
+        .transition(PendingReview, Reject, Rejected)
+        .transition(PendingReview, Schedule, Scheduled)
+        .transition(Rejected, RequestReview, PendingReview)
+        .transition(Scheduled, Approve, Published)
+        .transition(Scheduled, Reject, Rejected)
+        // Scheduling it again moves it to the new time.
+        .transition(Scheduled, Schedule, Scheduled)
+}
+
+/** Every edit of a post is kept in its history, with the time its clock says it was made at. How a post goes from one state to another is up to its
+ *  `workflow`, the post only keeps what its state needs: the reason it was rejected for while it's rejected, and when it's published while it's scheduled.
+ *
+ *  Every move is written down in the audit log of the post too, and told to its observers. The log is only kept in memory: a post that is loaded back
+ *  starts a new one.
+ *
+ *  Who wrote the post and what it's about, its author and tags, can be changed in any state, as they don't change what the post says.
+ *
+ *  Like the exercise at the end of the chapter of The Book asks, a post can need more than one approval to be published. The approvals are counted while it's
+ *  pending review, and it only moves on with the last one it needs. They're kept once it's scheduled or published, and forgotten when it's rejected.
+ */
+pub struct Post {
+    machine: StateMachine<PostState, PostEvent>,
+    author: Option<String>,
+    tags: Vec<String>,
+    rejection: Option<String>,
+    publish_at: Option<Timestamp>,
+    approvals: u32,
+    required_approvals: u32,
+    content: String,
+    history: Vec<Revision>,
+    comments: Vec<Comment>,
+    audit: Vec<AuditEntry>,
+    observers: Vec<Observer>,
+    clock: Box<dyn Clock>,
+}
+
+impl Post {
+    pub fn new() -> Post {
+        Post::with_clock(SystemClock)
+    }
+
+    /** A post that tells when it was edited with `clock`, rather than with the time of the system. */
+    pub fn with_clock(clock: impl Clock + 'static) -> Post {
+        Post {
+            machine: workflow(PostState::Draft),
+            author: None,
+            tags: Vec::new(),
+            rejection: None,
+            publish_at: None,
+            approvals: 0,
+            required_approvals: 1,
+            content: String::new(),
+            history: Vec::new(),
+            comments: Vec::new(),
+            audit: Vec::new(),
+            observers: Vec::new(),
+            clock: Box::new(clock),
+        }
+    }
+
+    /** Adds `text` to the end of the post, if it's a draft. Posts that were rejected aren't drafts, they can only be reviewed again as they are. */
+    pub fn add_text(&mut self, text: &str) -> Result<(), PostError> {
+        let state = self.state();
+        if state != PostState::Draft {
+            return Err(PostError::NotDraft(state.name()));
+        }
+        self.content.push_str(text);
+        self.history.push(Revision { at: self.clock.now(), content: self.content.clone() });
+        Ok(())
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn set_author(&mut self, author: &str) {
+        self.author = Some(author.to_string());
+    }
+
+    /** The tags of the post, in the order they were added. */
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|known| known == tag)
+    }
+
+    /** Tags the post with `tag`, unless it has it already. */
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    /** Takes `tag` off the post. Returns whether it had it. */
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|known| known != tag);
+        self.tags.len() != before
+    }
+
+    /** Every revision of the post, from the first edit to the last. */
+    pub fn history(&self) -> &[Revision] {
+        &self.history
+    }
+
+    pub fn state(&self) -> PostState {
+        self.machine.state()
+    }
+
+    /** Approves a post pending review, which publishes it if that was the last approval it needed. Approving a scheduled post publishes it now. */
+    pub fn approve(&mut self) {
+        if self.count_approval() {
+            self.fire(PostEvent::Approve);
+        }
+    }
+
+    /** Counts one more approval if the post is pending review. Returns whether the post has as many as it needs, or isn't in review. */
+    fn count_approval(&mut self) -> bool {
+        if self.state() != PostState::PendingReview {
+            return true;
+        }
+        self.approvals += 1;
+        self.approvals >= self.required_approvals
+    }
+
+    /** How many times the post was approved in its current review, or in the one that let it through. */
+    pub fn approvals(&self) -> u32 {
+        self.approvals
+    }
+
+    pub fn required_approvals(&self) -> u32 {
+        self.required_approvals
+    }
+
+    /** Makes the post need `required` approvals to get through its review, one at least. */
+    pub fn require_approvals(&mut self, required: u32) {
+        self.required_approvals = required.max(1);
+    }
+
+    pub fn content(&self) -> &str {
+        match self.state() {
+            PostState::Published => &self.content,
+            _ => "",
+        }
+    }
+
+    /** The content of the post as HTML, reading it as the Markdown described in `markdown::to_html`. Like the content, it's only there once the post is
+     *  published, but trying to render it before is an error rather than an empty page.
+     */
+    pub fn render_html(&self) -> Result<String, PostError> {
+        match self.state() {
+            PostState::Published => Ok(markdown::to_html(&self.content)),
+            state => Err(PostError::Unreadable(state.name())),
+        }
+    }
+
+    /** Requesting a review of a rejected post resubmits it, once the text was fixed. */
+    pub fn request_review(&mut self) {
+        self.fire(PostEvent::RequestReview);
+    }
+
+    /** Sends a post pending review back to its author, with `reason` telling what to fix. */
+    pub fn reject(&mut self, reason: &str) {
+        if self.fire(PostEvent::Reject) {
+            self.rejection = Some(reason.to_string());
+        }
+    }
+
+    /** Why the post was rejected, while it's rejected. */
+    pub fn rejection(&self) -> Option<&str> {
+        self.rejection.as_deref()
+    }
+
+    /** Approves a post pending review to be published at `publish_at`, rather than right away. It counts as one of the approvals the post needs, and it's
+     *  only scheduled if it's the last one. Approving it again publishes it now.
+     */
+    pub fn schedule(&mut self, publish_at: Timestamp) {
+        if self.count_approval() && self.fire(PostEvent::Schedule) {
+            self.publish_at = Some(publish_at);
+        }
+    }
+
+    /** When the post will be published, while it's scheduled. */
+    pub fn publish_at(&self) -> Option<Timestamp> {
+        self.publish_at
+    }
+
+    /** Moves the post where `event` takes it, if anywhere, and forgets what the state it left needed. Returns whether it moved. */
+    fn fire(&mut self, event: PostEvent) -> bool {
+        let from = self.state();
+        let Ok(to) = self.machine.fire(event) else {
+            return false;
+        };
+        self.rejection = None;
+        self.publish_at = None;
+        if !matches!(to, PostState::Scheduled | PostState::Published) {
+            self.approvals = 0;
+        }
+
+        let entry = AuditEntry { from, to, at: self.clock.now() };
+        for observer in &mut self.observers {
+            observer(&entry);
+        }
+        self.audit.push(entry);
+        true
+    }
+
+    /** Calls `observer` every time the post moves from one state to another, from now on. */
+    pub fn on_transition(&mut self, observer: impl FnMut(&AuditEntry) + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /** Sends the move with `messenger` every time the post moves from one state to another, which can show it as "moved from draft to pending review at 10"
+     *  like its `Display` does. A move that `messenger` fails to send is lost, but it's still in the audit log.
+     */
+    pub fn notify(&mut self, messenger: impl Messenger<AuditEntry> + 'static) {
+        self.on_transition(move |entry| {
+            let _ = messenger.send(entry);
+        });
+    }
+
+    /** Every move of the post since it was made or loaded, from the first to the last. */
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        &self.audit
+    }
+
+    /** The moves of the post that took it to `state`. */
+    pub fn moves_to(&self, state: PostState) -> impl Iterator<Item = &AuditEntry> {
+        self.audit.iter().filter(move |entry| entry.to == state)
+    }
+
+    /** Publishes the post if it was scheduled for `now` or before. Returns whether it was. */
+    pub fn publish_if_due(&mut self, now: Timestamp) -> bool {
+        let due = self.publish_at().is_some_and(|publish_at| publish_at <= now);
+        if due {
+            self.approve();
+        }
+        due
+    }
+
+    /** Adds a comment of `author` to the post, if it's published. The comment waits to be moderated before its text is shown. Returns its index, to moderate
+     *  it with `comment_mut`.
+     */
+    pub fn add_comment(&mut self, author: &str, text: &str) -> Result<usize, PostError> {
+        let state = self.state();
+        if state != PostState::Published {
+            return Err(PostError::NotPublished(state.name()));
+        }
+        self.comments.push(Comment::new(author, text));
+        Ok(self.comments.len() - 1)
+    }
+
+    /** The comments in `moderation`, in the order they were made. */
+    pub fn comments(&self, moderation: Moderation) -> impl Iterator<Item = &Comment> {
+        self.comments.iter().filter(move |comment| comment.moderation() == moderation)
+    }
+
+    pub fn comment_mut(&mut self, index: usize) -> Option<&mut Comment> {
+        self.comments.get_mut(index)
+    }
+}
+
+/** The posts of a blog, which publishes those that are scheduled once their time comes. The posts can be looked for by their state, tags and author,
+ *  always in the order they were added in.
+ */
+#[derive(Default)]
+pub struct Blog {
+    posts: Vec<Post>,
+}
+
+impl Blog {
+    pub fn new() -> Blog {
+        Blog::default()
+    }
+
+    /** Adds `post` to the blog. Returns its index, to get it back with `post` and `post_mut`. */
+    pub fn add(&mut self, post: Post) -> usize {
+        self.posts.push(post);
+        self.posts.len() - 1
+    }
+
+    pub fn post(&self, index: usize) -> Option<&Post> {
+        self.posts.get(index)
+    }
+
+    pub fn post_mut(&mut self, index: usize) -> Option<&mut Post> {
+        self.posts.get_mut(index)
+    }
+
+    /** Every post of the blog, in the order they were added. */
+    pub fn posts(&self) -> impl Iterator<Item = &Post> {
+        self.posts.iter()
+    }
+
+    /** The posts in `state`. */
+    pub fn in_state(&self, state: PostState) -> impl Iterator<Item = &Post> {
+        self.posts().filter(move |post| post.state() == state)
+    }
+
+    /** The posts readers can see. */
+    pub fn published(&self) -> impl Iterator<Item = &Post> {
+        self.in_state(PostState::Published)
+    }
+
+    /** The posts waiting for a reviewer. */
+    pub fn pending_review(&self) -> impl Iterator<Item = &Post> {
+        self.in_state(PostState::PendingReview)
+    }
+
+    /** The posts tagged with `tag`, whatever their state. */
+    pub fn by_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Post> {
+        self.posts().filter(move |post| post.has_tag(tag))
+    }
+
+    /** The posts `author` wrote, whatever their state. */
+    pub fn by_author<'a>(&'a self, author: &'a str) -> impl Iterator<Item = &'a Post> {
+        self.posts().filter(move |post| post.author() == Some(author))
+    }
+
+    /** Publishes every post scheduled for `now` or before, like `blog.process_due(SystemClock.now())`. Returns how many were. */
+    pub fn process_due(&mut self, now: Timestamp) -> usize {
+        self.posts.iter_mut().map(|post| post.publish_if_due(now)).filter(|published| *published).count()
+    }
+}
@@ -0,0 +1,352 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{workflow, Comment, Moderation, Post, PostState, Revision, SystemClock, Timestamp};
+
+/** Everything that can make a saved post unusable. */
+#[derive(Debug)]
+pub enum LoadError {
+    /** The file could not be read. */
+    Io(io::Error),
+    /** A line is neither a `key = value` setting, a comment nor blank. */
+    Syntax { line: usize, text: String },
+    UnknownKey { line: usize, key: String },
+    /** A key other than `tag`, `revision` and `comment` appears more than once. */
+    DuplicateKey { line: usize, key: String },
+    /** A value can't be used for its key, for the given reason. */
+    InvalidValue { line: usize, key: String, value: String, reason: &'static str },
+    /** Every line makes sense on its own, but not the post they make together, for the given reason. */
+    Invalid(&'static str),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "could not read the post: {}", err),
+            LoadError::Syntax { line, text } => write!(f, "line {}: expected `key = value`, found {:?}", line, text),
+            LoadError::UnknownKey { line, key } => write!(f, "line {}: unknown key {:?}", line, key),
+            LoadError::DuplicateKey { line, key } => write!(f, "line {}: {:?} is set more than once", line, key),
+            LoadError::InvalidValue { line, key, value, reason } => write!(f, "line {}: invalid {} {:?}: {}", line, key, value, reason),
+            LoadError::Invalid(reason) => write!(f, "invalid post: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/** Posts are saved one setting on each line, with texts quoted, and a `revision` line for each entry of their history, with the time it was made at:
+ *
+ *  ```text
+ *  state = published
+ *  author = "Ferris"
+ *  tag = "food"
+ *  content = "I ate a salad"
+ *  revision = 10 "I ate"
+ *  revision = 20 "I ate a salad"
+ *  comment = approved "Ferris" "Me too!"
+ *  ```
+ *
+ *  Posts have a `tag` line for each of their tags, and an `author` line if someone wrote them. Rejected posts have the `reason` they were rejected for, and
+ *  scheduled ones the `publish_at` time they'll be published at. Posts that were approved have the number of `approvals` they got, and those that need more
+ *  than one have the `required_approvals`. Published posts have a `comment` line for each of their comments, with its moderation, author and text. The
+ *  audit log isn't saved, it only tells what happened to the post in memory.
+ */
+impl Post {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /** Reads the post saved at `path`, which tells when it's edited from now on with the time of the system. The post is checked to be one that could have
+     *  been saved: the content is what the last revision left, every revision adds to the one before, and they're in the order they were made in. Posts got
+     *  all the approvals they need if they're scheduled or published, fewer if they're pending review, and none otherwise.
+     */
+    pub fn load(path: impl AsRef<Path>) -> Result<Post, LoadError> {
+        Post::from_text(&fs::read_to_string(path).map_err(LoadError::Io)?)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("state = {}\n", self.state().name());
+        if let Some(author) = &self.author {
+            text += &format!("author = {}\n", quote(author));
+        }
+        for tag in &self.tags {
+            text += &format!("tag = {}\n", quote(tag));
+        }
+        if let Some(reason) = &self.rejection {
+            text += &format!("reason = {}\n", quote(reason));
+        }
+        if let Some(Timestamp(publish_at)) = self.publish_at {
+            text += &format!("publish_at = {}\n", publish_at);
+        }
+        if self.approvals > 0 {
+            text += &format!("approvals = {}\n", self.approvals);
+        }
+        if self.required_approvals != 1 {
+            text += &format!("required_approvals = {}\n", self.required_approvals);
+        }
+        text += &format!("content = {}\n", quote(&self.content));
+        for revision in &self.history {
+            text += &format!("revision = {} {}\n", revision.at.0, quote(&revision.content));
+        }
+        // The text of comments that aren't approved isn't shown, but it's still saved.
+        for comment in &self.comments {
+            text += &format!("comment = {} {} {}\n", comment.moderation.name(), quote(&comment.author), quote(&comment.text));
+        }
+        text
+    }
+
+    fn from_text(text: &str) -> Result<Post, LoadError> {
+        let (mut state, mut author, mut reason, mut publish_at, mut content) = (None, None, None, None, None);
+        let (mut approvals, mut required_approvals) = (0, 1);
+        let mut tags: Vec<String> = Vec::new();
+        let mut history = Vec::new();
+        let mut comments = Vec::new();
+        let mut seen: Vec<&str> = Vec::new();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let syntax = || LoadError::Syntax { line, text: raw.to_string() };
+            let (key, value) = trimmed.split_once('=').ok_or_else(syntax)?;
+            let (key, value) = (key.trim(), value.trim());
+            if !["tag", "revision", "comment"].contains(&key) && seen.contains(&key) {
+                return Err(LoadError::DuplicateKey { line, key: key.to_string() });
+            }
+            seen.push(key);
+
+            let invalid = |reason| LoadError::InvalidValue { line, key: key.to_string(), value: value.to_string(), reason };
+            match key {
+                "state" => state = Some(value.to_string()),
+                "author" => author = Some(unquote(value).ok_or_else(syntax)?),
+                "tag" => {
+                    let tag = unquote(value).ok_or_else(syntax)?;
+                    if tags.contains(&tag) {
+                        return Err(invalid("the post has this tag already"));
+                    }
+                    tags.push(tag);
+                }
+                "reason" => reason = Some(unquote(value).ok_or_else(syntax)?),
+                "publish_at" => publish_at = Some(Timestamp(value.parse().map_err(|_| invalid("expected the seconds since the Unix epoch"))?)),
+                "approvals" => approvals = value.parse().map_err(|_| invalid("expected a number of approvals"))?,
+                "required_approvals" => {
+                    required_approvals = value.parse().ok().filter(|required| *required > 0).ok_or_else(|| invalid("expected a number of approvals, one at least"))?
+                }
+                "content" => content = Some(unquote(value).ok_or_else(syntax)?),
+                "revision" => {
+                    let (at, content) = value.split_once(' ').ok_or_else(syntax)?;
+                    let at = Timestamp(at.parse().map_err(|_| invalid("expected the seconds since the Unix epoch, then the content"))?);
+                    history.push(Revision { at, content: unquote(content.trim()).ok_or_else(syntax)? });
+                }
+                "comment" => {
+                    let (moderation, rest) = value.split_once(' ').ok_or_else(syntax)?;
+                    let moderation = match moderation {
+                        "pending" => Moderation::Pending,
+                        "approved" => Moderation::Approved,
+                        "hidden" => Moderation::Hidden,
+                        _ => return Err(invalid("expected pending, approved or hidden, then the author and the text")),
+                    };
+                    let (author, rest) = unquote_prefix(rest.trim_start()).ok_or_else(syntax)?;
+                    let text = unquote(rest.trim()).ok_or_else(syntax)?;
+                    comments.push(Comment { author, text, moderation });
+                }
+                _ => return Err(LoadError::UnknownKey { line, key: key.to_string() }),
+            }
+        }
+
+        let state = state.ok_or(LoadError::Invalid("the state of the post is missing"))?;
+        let state = PostState::ALL.into_iter().find(|known| known.name() == state).ok_or(LoadError::Invalid("the state isn't one a post can be in"))?;
+        match (state, &reason, publish_at) {
+            (PostState::Rejected, None, _) => return Err(LoadError::Invalid("rejected posts need the reason they were rejected for")),
+            (PostState::Scheduled, _, None) => return Err(LoadError::Invalid("scheduled posts need the time they're published at")),
+            (PostState::Rejected, _, None) | (PostState::Scheduled, None, _) | (_, None, None) => {}
+            _ => return Err(LoadError::Invalid("only rejected posts have a reason, and only scheduled ones a time to be published at")),
+        }
+
+        let content = content.unwrap_or_default();
+        if history.last().map_or("", |revision| revision.content.as_str()) != content {
+            return Err(LoadError::Invalid("the content isn't what the last revision left"));
+        }
+        let mut previous: Option<&Revision> = None;
+        for revision in &history {
+            if previous.is_some_and(|previous| previous.at > revision.at || !revision.content.starts_with(&previous.content)) {
+                return Err(LoadError::Invalid("every revision adds text to the one before it, and is made after it"));
+            }
+            previous = Some(revision);
+        }
+
+        if !comments.is_empty() && state != PostState::Published {
+            return Err(LoadError::Invalid("only published posts have comments"));
+        }
+
+        let approved = match state {
+            PostState::Scheduled | PostState::Published => approvals >= required_approvals,
+            PostState::PendingReview => approvals < required_approvals,
+            PostState::Draft | PostState::Rejected => approvals == 0,
+        };
+        if !approved {
+            return Err(LoadError::Invalid("only approved posts have all the approvals they need, and only posts in review have some of them"));
+        }
+
+        Ok(Post {
+            machine: workflow(state),
+            author,
+            tags,
+            rejection: reason,
+            publish_at,
+            approvals,
+            required_approvals,
+            content,
+            history,
+            comments,
+            audit: Vec::new(),
+            observers: Vec::new(),
+            clock: Box::new(SystemClock),
+        })
+    }
+}
+
+/** `text` in double quotes, with backslashes, quotes and line breaks escaped so it fits on one line. */
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\\' => quoted += "\\\\",
+            '"' => quoted += "\\\"",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c => quoted.push(c),
+        }
+    }
+    quoted + "\""
+}
+
+/** The text `quote` made `value` from, or `None` if it's not quoted. */
+fn unquote(value: &str) -> Option<String> {
+    match unquote_prefix(value)? {
+        (text, "") => Some(text),
+        _ => None,
+    }
+}
+
+/** The text `quote` made the start of `value` from, and what's after its closing quote. */
+fn unquote_prefix(value: &str) -> Option<(String, &str)> {
+    let mut chars = value.strip_prefix('"')?.char_indices();
+    let mut text = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => text.push(match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                c => c,
+            }),
+            // The quote that was stripped is one byte long, like this one.
+            '"' => return Some((text, &value[i + 2..])),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /** A file in the temporary directory of the system, removed once the test is over. */
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            TempFile(std::env::temp_dir().join(format!("{}-{}", std::process::id(), name)))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn posts_are_loaded_as_they_were_saved() {
+        let file = TempFile::new("rejected_post.txt");
+        let mut post = Post::new();
+        post.set_author("Ferris");
+        post.add_tag("food");
+        post.add_tag("lunch \"break\"");
+        post.add_text("I ate \"a salad\"").unwrap();
+        post.add_text("\nfor lunch").unwrap();
+        post.request_review();
+        post.reject("Too short");
+        post.save(&file.0).unwrap();
+
+        let mut loaded = Post::load(&file.0).unwrap();
+        assert_eq!(post.to_text(), loaded.to_text());
+        assert_eq!(Some("Too short"), loaded.rejection());
+        assert_eq!(Some("Ferris"), loaded.author());
+        assert_eq!(post.tags(), loaded.tags());
+        assert_eq!(post.history(), loaded.history());
+
+        loaded.require_approvals(2);
+        loaded.request_review();
+        loaded.approve();
+        loaded.save(&file.0).unwrap();
+        let pending = Post::load(&file.0).unwrap();
+        assert_eq!((PostState::PendingReview, 1, 2), (pending.state(), pending.approvals(), pending.required_approvals()));
+
+        loaded.schedule(Timestamp(100));
+        loaded.save(&file.0).unwrap();
+        assert_eq!(Some(Timestamp(100)), Post::load(&file.0).unwrap().publish_at());
+
+        loaded.approve();
+        loaded.add_comment("Ferris \"the crab\"", "Me too!").unwrap();
+        loaded.add_comment("Corro", "Spam").unwrap();
+        loaded.comment_mut(1).unwrap().hide();
+        loaded.save(&file.0).unwrap();
+        let comments = Post::load(&file.0).unwrap().comments;
+        assert_eq!(vec![Comment::new("Ferris \"the crab\"", "Me too!"), Comment { moderation: Moderation::Hidden, ..Comment::new("Corro", "Spam") }], comments);
+    }
+
+    #[test]
+    fn posts_that_could_not_have_been_saved_are_not_loaded() {
+        let load = |text: &str| Post::from_text(text).map(|_| ()).unwrap_err().to_string();
+
+        assert_eq!("invalid post: the state of the post is missing", load("content = \"\""));
+        assert_eq!("line 2: unknown key \"likes\"", load("state = draft\nlikes = 2"));
+        assert_eq!("line 2: \"state\" is set more than once", load("state = draft\nstate = published"));
+        assert_eq!("line 1: expected `key = value`, found \"content = \\\"open\"", load("content = \"open"));
+        assert_eq!("invalid post: the state isn't one a post can be in", load("state = lost"));
+        assert_eq!("line 3: invalid tag \"\\\"food\\\"\": the post has this tag already", load("state = draft\ntag = \"food\"\ntag = \"food\""));
+        assert_eq!("invalid post: only published posts have comments", load("state = draft\ncomment = pending \"Ferris\" \"Hi\""));
+        assert_eq!("line 2: expected `key = value`, found \"comment = pending \\\"Ferris\\\"\"", load("state = published\ncomment = pending \"Ferris\""));
+        assert_eq!("invalid post: rejected posts need the reason they were rejected for", load("state = rejected"));
+        let unapproved = "invalid post: only approved posts have all the approvals they need, and only posts in review have some of them";
+        assert_eq!(unapproved, load("state = published\nrequired_approvals = 2\napprovals = 1"));
+        assert_eq!(unapproved, load("state = pending review\napprovals = 1"));
+        assert_eq!(unapproved, load("state = draft\napprovals = 1"));
+        assert_eq!("line 2: invalid required_approvals \"0\": expected a number of approvals, one at least", load("state = draft\nrequired_approvals = 0"));
+        assert_eq!("invalid post: the content isn't what the last revision left", load("state = draft\ncontent = \"Hi\""));
+        assert_eq!(
+            "invalid post: every revision adds text to the one before it, and is made after it",
+            load("state = published\ncontent = \"Hi\"\nrevision = 20 \"H\"\nrevision = 10 \"Hi\""),
+        );
+        assert_eq!(
+            "line 3: invalid revision \"soon \\\"Hi\\\"\": expected the seconds since the Unix epoch, then the content",
+            load("state = published\ncontent = \"Hi\"\nrevision = soon \"Hi\""),
+        );
+    }
+}
@@ -1,74 +1,20 @@
-/** Fields of `AveragedCollection` are not marked asa pub so that users are forced to use the methods defined for the struct and thus, the average is always updated.
+/** A collection of numbers that always knows their average and other statistics about them.
  */
-pub struct AveragedCollection {
-    list: Vec<i32>,
-    average: f64,
-}
-
-impl AveragedCollection {
-    pub fn add(&mut self, value: i32) {
-        self.list.push(value);
-        self.update_average();
-    }
-
-    pub fn remove(&mut self) -> Option<i32> {
-        let result = self.list.pop();
-        match result {
-            Some(value) => {
-                self.update_average();
-                Some(value)
-            }
-            None => None,
-        }
-    }
+pub mod averaged_collection;
 
-    pub fn average(&self) -> f64 {
-        self.average
-    }
-
-    fn update_average(&mut self) {
-        let total: i32 = self.list.iter().sum();
-        self.average = total as f64 / self.list.len() as f64;
-    }
-}
+/** The state pattern of the blog below, for any states and events.
+ */
+pub mod state_machine;
 
 /** GUI library to show how polymorphism works in Rust making use of Enums and Traits.
  */
-mod gui {
-    pub trait Draw {
-        fn draw(&self);
-    }
-
-    pub struct Screen {
-        // Box<dyn Draw> ensures all the types inside the components vector implement the Draw trait.
-        pub components: Vec<Box<dyn Draw>>
-    }
-
-    impl Screen {
-        pub fn run(&self) {
-            for component in self.components.iter() {
-                component.draw();
-            }
-        }
-    }
-
-    pub struct Button {
-        pub width: u32,
-        pub height: u32,
-        pub label: String,
-    }
-
-    impl Draw for Button {
-        fn draw(&self) {
-            // code to actually draw a button
-        }
-    }
-}
+pub mod gui;
 
 /* Posible usage of the GUI library created above. 
  */
-use crate::object_oriented_rust::gui::{Draw, Button, Screen};
+use crate::object_oriented_rust::gui::{Draw, Button, Checkbox, Constraints, Label, Observable, Rect, Renderer, Role, Row, Screen, Size, Style, TextField};
 
+#[allow(dead_code)]
 struct SelectBox {
     width: u32,
     height: u32,
@@ -76,149 +22,314 @@ struct SelectBox {
 }
 
 impl Draw for SelectBox {
-    fn draw(&self) {
-        // code to actually draw a select box
+    fn layout(&mut self, constraints: Constraints) -> Size {
+        constraints.constrain(Size::new(self.width, self.height))
+    }
+
+    /** A box with one option on each line inside of it. */
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        renderer.begin(Role::Group, area, &Style::new());
+        renderer.border(area);
+        for (i, option) in self.options.iter().take(area.height.saturating_sub(2) as usize).enumerate() {
+            renderer.text(area.x + 1, area.y + 1 + i as u32, option);
+        }
+        renderer.end();
     }
 }
 
 fn main() {
-    let screen = Screen {
-        components: vec![
-            Box::new(SelectBox {
-                width: 75,
-                height: 10,
-                options: vec![
-                    String::from("Yes"),
-                    String::from("Maybe"),
-                    String::from("No"),
-                ],
-            }),
-            Box::new(Button {
-                width: 50,
-                height: 10,
-                label: String::from("OK"),
-            }),
-        ],
-    };
-
+    let clicks = Observable::new(0);
+    let counter = clicks.clone();
+    let mut screen = Screen::new(vec![
+        Box::new(SelectBox {
+            width: 75,
+            height: 10,
+            options: vec![
+                String::from("Yes"),
+                String::from("Maybe"),
+                String::from("No"),
+            ],
+        }),
+        Box::new(Row::new().spacing(1).child(Label::new("Name")).child(TextField::new(30, "Ferris").with_id("name"))),
+        Box::new(Checkbox::new("Send me crabs")),
+        Box::new(Button::new(50, 10, "OK").on_click(move || counter.update(|clicks| *clicks += 1))),
+        Box::new(Label::new("").bind(&clicks, |clicks| format!("OK was clicked {} times", clicks))),
+    ]);
+
+    if let Some(name) = screen.get_mut("name").and_then(|name| name.downcast_mut::<TextField>()) {
+        name.set_text("Ferris the crab");
+    }
     screen.run();
 }
 
 /** Here you can see how the state design pattern can be implemented in Rust. For more information about this module, check The Book, Chapter 17.3, as this is just 
- *  a way to implement a design pattern in Rust.
+ *  a way to implement a design pattern in Rust. The Book gives every state of the post an object that knows where each action takes it, the blog here keeps all
+ *  of that in the table of a `StateMachine` instead, which works the same way for any other states.
  */
 mod implementing_design_pattern {
-    mod blog {
-        pub struct Post {
-            state: Option<Box<dyn State>>,
-            content: String,
-        }
+    // The blog is only used by the tests below, so the rest of the crate never uses what it has.
+    #[allow(dead_code)]
+    mod blog;
 
-        impl Post {
-            pub fn new() -> Post {
-                Post {
-                    state: Some(Box::new(Draft {})),
-                    content: String::new(),
-                }
-            }
+    #[cfg(test)]
+    mod tests {
+        use std::cell::{Cell, RefCell};
+        use std::rc::Rc;
 
-            pub fn add_text(&mut self, text: &str) {
-                self.content.push_str(text);
-            }
+        use super::blog::storage::LoadError;
+        use super::blog::{AuditEntry, Blog, Clock, Moderation, Post, PostError, PostState, Revision, Timestamp};
 
-            pub fn approve(&mut self) {
-                if let Some(s) = self.state.take() {
-                    self.state = Some(s.approve())
-                }
-            }
+        #[test]
+        fn main() {
+            let mut post = Post::new();
 
-            pub fn content(&self) -> &str {
-                self.state.as_ref().unwrap().content(self)
-            }
+            post.add_text("I ate a salad for lunch today").unwrap();
+            assert_eq!("", post.content());
 
-            pub fn request_review(&mut self) {
-                if let Some(s) = self.state.take() {
-                    self.state = Some(s.request_review())
-                }
-            }
+            post.request_review();
+            assert_eq!("", post.content());
+
+            post.approve();
+            assert_eq!("I ate a salad for lunch today", post.content());
         }
 
-        trait State {
-            fn request_review(self: Box<Self>) -> Box<dyn State>;
-            fn approve(self: Box<Self>) -> Box<dyn State>;
-            fn content<'a>(&self, post: &'a Post) -> &'a str {
-                ""
-            }
-            fn reject(self: Box<Self>) -> Box<dyn State>;
+        #[test]
+        fn rejected_posts_are_resubmitted() {
+            let mut post = Post::new();
+            post.add_text("I ate a salad for lunch today").unwrap();
+
+            // Only posts pending review can be rejected.
+            post.reject("Too short");
+            assert_eq!(None, post.rejection());
+
+            post.request_review();
+            post.reject("Too short");
+            assert_eq!(Some("Too short"), post.rejection());
+            post.approve();
+            assert_eq!("", post.content());
+
+            assert_eq!(PostError::NotDraft("rejected"), post.add_text(", with tomatoes.").unwrap_err());
+            post.request_review();
+            assert_eq!(None, post.rejection());
+            post.approve();
+            assert_eq!("I ate a salad for lunch today", post.content());
         }
 
-        struct Draft {}
+        #[test]
+        fn posts_are_published_once_they_have_every_approval() {
+            let mut post = Post::new();
+            post.require_approvals(2);
+            post.add_text("I ate a salad for lunch today").unwrap();
+            post.request_review();
+
+            post.approve();
+            assert_eq!((PostState::PendingReview, 1), (post.state(), post.approvals()));
+            assert_eq!("", post.content());
+
+            // A rejection starts the count again for the next review.
+            post.reject("Too short");
+            post.request_review();
+            assert_eq!(0, post.approvals());
+            post.approve();
+            post.schedule(Timestamp(100));
+            assert_eq!((PostState::Scheduled, 2), (post.state(), post.approvals()));
+            post.approve();
+            assert_eq!("I ate a salad for lunch today", post.content());
+        }
 
-        impl State for Draft {
-            fn request_review(self: Box<Self>) -> Box<dyn State> {
-                Box::new(PendingReview {})
-            }
+        #[test]
+        fn scheduled_posts_are_published_when_due() {
+            let mut blog = Blog::new();
+            let mut lunch = Post::new();
+            lunch.add_text("I ate a salad for lunch today").unwrap();
+            lunch.request_review();
+            lunch.schedule(Timestamp(100));
+            let lunch = blog.add(lunch);
+
+            let mut dinner = Post::new();
+            dinner.add_text("And soup for dinner").unwrap();
+            dinner.schedule(Timestamp(50));
+            let dinner = blog.add(dinner);
+
+            assert_eq!(0, blog.process_due(Timestamp(99)));
+            assert_eq!(Some(Timestamp(100)), blog.post(lunch).unwrap().publish_at());
+            assert_eq!("", blog.post(lunch).unwrap().content());
+
+            // The dinner post was never reviewed, so it was never scheduled.
+            assert_eq!(1, blog.process_due(Timestamp(100)));
+            assert_eq!("I ate a salad for lunch today", blog.post(lunch).unwrap().content());
+            assert_eq!(None, blog.post(lunch).unwrap().publish_at());
+            assert_eq!("", blog.post(dinner).unwrap().content());
+            assert_eq!(0, blog.process_due(Timestamp(200)));
+        }
 
-            fn approve(self: Box<Self>) -> Box<dyn State> {
-                self
-            }
+        /** A clock the test moves forward by hand. */
+        #[derive(Clone, Default)]
+        struct TestClock(Rc<Cell<u64>>);
 
-            fn reject(self: Box<Self>) -> Box<dyn State> {
-                self
+        impl Clock for TestClock {
+            fn now(&self) -> Timestamp {
+                Timestamp(self.0.get())
             }
         }
 
-        struct PendingReview {}
+        #[test]
+        fn only_drafts_are_edited_and_every_edit_is_kept() {
+            let clock = TestClock::default();
+            let mut post = Post::with_clock(clock.clone());
+
+            clock.0.set(10);
+            post.add_text("I ate a salad").unwrap();
+            clock.0.set(20);
+            post.add_text(" for lunch today").unwrap();
+
+            post.request_review();
+            let error = post.add_text("!").unwrap_err();
+            assert_eq!(PostError::NotDraft("pending review"), error);
+            assert_eq!("only drafts can be edited, but the post is pending review", error.to_string());
+
+            post.approve();
+            assert!(post.add_text("!").is_err());
+            assert_eq!("I ate a salad for lunch today", post.content());
+
+            let expected = vec![
+                Revision { at: Timestamp(10), content: String::from("I ate a salad") },
+                Revision { at: Timestamp(20), content: String::from("I ate a salad for lunch today") },
+            ];
+            assert_eq!(expected, post.history());
+        }
 
-        impl State for PendingReview {
-            fn request_review(self: Box<Self>) -> Box<dyn State> {
-                self
-            }
+        #[test]
+        fn only_published_posts_are_commented_on() {
+            let mut post = Post::new();
+            post.add_text("I ate a salad for lunch today").unwrap();
+            assert_eq!(Err(PostError::NotPublished("draft")), post.add_comment("Ferris", "Me too!"));
+
+            post.request_review();
+            post.approve();
+            let first = post.add_comment("Ferris", "Me too!").unwrap();
+            let second = post.add_comment("Corro", "Buy crabs!").unwrap();
+            post.add_comment("Ferris", "With tomatoes?").unwrap();
+            assert_eq!(3, post.comments(Moderation::Pending).count());
+            assert_eq!(0, post.comments(Moderation::Approved).count());
+
+            post.comment_mut(first).unwrap().approve();
+            post.comment_mut(second).unwrap().hide();
+            let approved: Vec<(&str, &str)> = post.comments(Moderation::Approved).map(|comment| (comment.author(), comment.text())).collect();
+            assert_eq!(vec![("Ferris", "Me too!")], approved);
+            // Like posts that aren't published, comments that aren't approved have no text to show.
+            let hidden: Vec<(&str, &str)> = post.comments(Moderation::Hidden).map(|comment| (comment.author(), comment.text())).collect();
+            assert_eq!(vec![("Corro", "")], hidden);
+            assert_eq!(1, post.comments(Moderation::Pending).count());
+        }
 
-            fn approve(self: Box<Self>) -> Box<dyn State> {
-                Box::new(Published {})
-            }
+        #[test]
+        fn only_published_posts_are_rendered() {
+            let mut post = Post::new();
+            post.add_text("# Lunch\n\nI ate a *salad* today").unwrap();
+            assert_eq!(Err(PostError::Unreadable("draft")), post.render_html());
 
-            fn reject(self: Box<Self>) -> Box<dyn State> {
-                Box::new(Draft {})
-            }
+            post.request_review();
+            post.approve();
+            assert_eq!("<h1>Lunch</h1>\n<p>I ate a <em>salad</em> today</p>\n", post.render_html().unwrap());
         }
 
-        struct Published {}
-
-        impl State for Published {
-            fn request_review(self: Box<Self>) -> Box<dyn State> {
-                self
-            }
+        #[test]
+        fn blogs_find_posts_by_state_tag_and_author() {
+            let mut blog = Blog::new();
+            let mut post = |author: &str, tags: &[&str], text: &str| {
+                let mut post = Post::new();
+                post.set_author(author);
+                for tag in tags {
+                    post.add_tag(tag);
+                }
+                post.add_text(text).unwrap();
+                blog.add(post)
+            };
+            let lunch = post("Ferris", &["food", "lunch"], "I ate a salad for lunch today");
+            let dinner = post("Ferris", &["food"], "And soup for dinner");
+            let crabs = post("Corro", &["crabs", "food"], "Crabs are not food");
+            post("Corro", &["crabs"], "Crabs are friends");
+
+            blog.post_mut(lunch).unwrap().request_review();
+            blog.post_mut(lunch).unwrap().approve();
+            blog.post_mut(dinner).unwrap().request_review();
+            blog.post_mut(crabs).unwrap().request_review();
+
+            let contents = |posts: Vec<&Post>| posts.iter().map(|post| post.history().last().unwrap().content.clone()).collect::<Vec<_>>();
+            assert_eq!(vec!["I ate a salad for lunch today"], contents(blog.published().collect()));
+            assert_eq!(vec!["And soup for dinner", "Crabs are not food"], contents(blog.pending_review().collect()));
+            assert_eq!(vec!["And soup for dinner", "Crabs are not food"], contents(blog.by_tag("food").filter(|post| post.state() != PostState::Published).collect()));
+            assert_eq!(vec!["Crabs are not food", "Crabs are friends"], contents(blog.by_author("Corro").collect()));
+            assert_eq!(0, blog.by_tag("dinner").count());
+
+            assert!(blog.post_mut(crabs).unwrap().remove_tag("food"));
+            assert!(!blog.post_mut(crabs).unwrap().remove_tag("food"));
+            assert_eq!(2, blog.by_tag("food").count());
+            assert_eq!(["crabs"], blog.post(crabs).unwrap().tags());
+        }
 
-            fn approve(self: Box<Self>) -> Box<dyn State> {
-                self
-            }
+        #[test]
+        fn every_move_is_logged_and_told() {
+            /** The messenger of the smart pointers chapter, keeping what it was told to send. */
+            struct Inbox(Rc<RefCell<Vec<String>>>);
 
-            fn content<'a>(&self, post: &'a Post) -> &'a str {
-                &post.content
+            impl crate::smart_pointers::Messenger<AuditEntry> for Inbox {
+                fn send(&self, entry: &AuditEntry) -> Result<(), crate::smart_pointers::SendError> {
+                    self.0.borrow_mut().push(entry.to_string());
+                    Ok(())
+                }
             }
 
-            fn reject(self: Box<Self>) -> Box<dyn State> {
-                self
-            }
+            let clock = TestClock::default();
+            let mut post = Post::with_clock(clock.clone());
+            let messages = Rc::new(RefCell::new(Vec::new()));
+            post.notify(Inbox(Rc::clone(&messages)));
+            let moves = Rc::new(Cell::new(0));
+            let counter = Rc::clone(&moves);
+            post.on_transition(move |_| counter.set(counter.get() + 1));
+
+            post.add_text("I ate a salad for lunch today").unwrap();
+            clock.0.set(10);
+            post.request_review();
+            clock.0.set(20);
+            post.reject("Too short");
+            // A rejected post can't be rejected again, so it doesn't move and there is nothing to log.
+            post.reject("Still too short");
+            clock.0.set(30);
+            post.request_review();
+            post.approve();
+
+            let expected = vec![
+                AuditEntry { from: PostState::Draft, to: PostState::PendingReview, at: Timestamp(10) },
+                AuditEntry { from: PostState::PendingReview, to: PostState::Rejected, at: Timestamp(20) },
+                AuditEntry { from: PostState::Rejected, to: PostState::PendingReview, at: Timestamp(30) },
+                AuditEntry { from: PostState::PendingReview, to: PostState::Published, at: Timestamp(30) },
+            ];
+            assert_eq!(expected, post.audit_log());
+            assert_eq!(4, moves.get());
+            assert_eq!(vec![Timestamp(10), Timestamp(30)], post.moves_to(PostState::PendingReview).map(|entry| entry.at).collect::<Vec<_>>());
+            assert_eq!("moved from pending review to rejected at 20", messages.borrow()[1]);
+            assert_eq!(4, messages.borrow().len());
         }
-    }
-    
-    use blog::Post;
-    
-    #[test]
-    fn main() {
-        let mut post = Post::new();
 
-        post.add_text("I ate a salad for lunch today");
-        assert_eq!("", post.content());
-
-        post.request_review();
-        assert_eq!("", post.content());
-
-        post.approve();
-        assert_eq!("I ate a salad for lunch today", post.content());
+        #[test]
+        fn posts_survive_restarts() {
+            let path = std::env::temp_dir().join(format!("{}-scheduled_post.txt", std::process::id()));
+            let mut post = Post::new();
+            post.add_text("I ate a salad for lunch today").unwrap();
+            post.request_review();
+            post.schedule(Timestamp(100));
+            post.save(&path).unwrap();
+
+            let mut blog = Blog::new();
+            let post = blog.add(Post::load(&path).unwrap());
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(1, blog.process_due(Timestamp(100)));
+            assert_eq!("I ate a salad for lunch today", blog.post(post).unwrap().content());
+
+            assert!(matches!(Post::load(&path), Err(LoadError::Io(_))));
+        }
     }
 }
 
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/** A move from one state to another, made because of an event. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition<S, E> {
+    pub from: S,
+    pub event: E,
+    pub to: S,
+}
+
+/** An event that the machine has no transition for in the state it's in. The machine stays in that state. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalTransition<S, E> {
+    pub state: S,
+    pub event: E,
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for IllegalTransition<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "there is no transition for {:?} from {:?}", self.event, self.state)
+    }
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for IllegalTransition<S, E> {}
+
+type Hook<S, E> = Box<dyn FnMut(&Transition<S, E>)>;
+
+/** The state pattern of the blog, for any states `S` and events `E`: the machine is in one state at a time, and moves to another when an event it has a
+ *  transition for in that state is fired. Where each event leads from each state is written down once, in the table of transitions, rather than in every
+ *  state like the `State` objects of The Book do.
+ *
+ *  Hooks are called on the way: those for leaving the state the machine is in first, then those for entering the state it moves to. A transition from a
+ *  state back to itself leaves it and enters it again.
+ */
+pub struct StateMachine<S, E> {
+    state: S,
+    transitions: HashMap<(S, E), S>,
+    on_exit: HashMap<S, Vec<Hook<S, E>>>,
+    on_enter: HashMap<S, Vec<Hook<S, E>>>,
+}
+
+impl<S: Copy + Eq + Hash, E: Copy + Eq + Hash> StateMachine<S, E> {
+    /** A machine in `initial`, without any transition yet. */
+    pub fn new(initial: S) -> StateMachine<S, E> {
+        StateMachine { state: initial, transitions: HashMap::new(), on_exit: HashMap::new(), on_enter: HashMap::new() }
+    }
+
+    /** Makes `event` move the machine from `from` to `to`, instead of wherever it did before. */
+    pub fn transition(mut self, from: S, event: E, to: S) -> StateMachine<S, E> {
+        self.transitions.insert((from, event), to);
+        self
+    }
+
+    /** Calls `hook` every time the machine enters `state`. */
+    pub fn on_enter(mut self, state: S, hook: impl FnMut(&Transition<S, E>) + 'static) -> StateMachine<S, E> {
+        self.on_enter.entry(state).or_default().push(Box::new(hook));
+        self
+    }
+
+    /** Calls `hook` every time the machine leaves `state`. */
+    pub fn on_exit(mut self, state: S, hook: impl FnMut(&Transition<S, E>) + 'static) -> StateMachine<S, E> {
+        self.on_exit.entry(state).or_default().push(Box::new(hook));
+        self
+    }
+
+    pub fn state(&self) -> S {
+        self.state
+    }
+
+    /** Whether `event` has a transition from the state the machine is in. */
+    pub fn can(&self, event: E) -> bool {
+        self.transitions.contains_key(&(self.state, event))
+    }
+
+    /** Moves the machine where `event` leads from the state it's in, and returns the state it's in now. */
+    pub fn fire(&mut self, event: E) -> Result<S, IllegalTransition<S, E>> {
+        let to = *self.transitions.get(&(self.state, event)).ok_or(IllegalTransition { state: self.state, event })?;
+        let transition = Transition { from: self.state, event, to };
+
+        for hook in self.on_exit.get_mut(&transition.from).into_iter().flatten() {
+            hook(&transition);
+        }
+        self.state = to;
+        for hook in self.on_enter.get_mut(&to).into_iter().flatten() {
+            hook(&transition);
+        }
+        Ok(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Light {
+        Red,
+        Green,
+        Yellow,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Signal {
+        Go,
+        Slow,
+        Stop,
+    }
+
+    #[test]
+    fn machines_follow_their_table_and_call_their_hooks() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (exits, enters) = (Rc::clone(&log), Rc::clone(&log));
+        let mut light = StateMachine::new(Light::Red)
+            .transition(Light::Red, Signal::Go, Light::Green)
+            .transition(Light::Green, Signal::Slow, Light::Yellow)
+            .transition(Light::Yellow, Signal::Stop, Light::Red)
+            .transition(Light::Red, Signal::Stop, Light::Red)
+            .on_exit(Light::Red, move |transition| exits.borrow_mut().push(format!("leaving red for {:?}", transition.event)))
+            .on_enter(Light::Red, move |transition| enters.borrow_mut().push(format!("red from {:?}", transition.from)));
+
+        assert!(light.can(Signal::Go));
+        assert_eq!(Ok(Light::Green), light.fire(Signal::Go));
+        let error = light.fire(Signal::Stop).unwrap_err();
+        assert_eq!(IllegalTransition { state: Light::Green, event: Signal::Stop }, error);
+        assert_eq!("there is no transition for Stop from Green", error.to_string());
+        assert_eq!(Light::Green, light.state());
+
+        light.fire(Signal::Slow).unwrap();
+        light.fire(Signal::Stop).unwrap();
+        light.fire(Signal::Stop).unwrap();
+        assert_eq!(vec!["leaving red for Go", "red from Yellow", "leaving red for Stop", "red from Red"], *log.borrow());
+    }
+}
@@ -1,5 +1,9 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
 use std::ops::Deref;
 use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /* Box<T> Smart Pointer */
 
@@ -142,50 +146,265 @@ fn rc_count() {
 /* RefCell<T> Smart Pointer */
 
 /** Implemented below is a library to test with the following elements:
- *      - A trait `Messenger` with a method in it that sends a message
- *      - A struct `LimitTracker` with three fields, the value of a message, the max lenght and the messanger (wich must implement the Messager trait).
+ *      - A trait `Messenger` with a method in it that sends a message, or returns a `SendError` telling why it could not. The messages are
+ *        `Notification`s unless told otherwise, which have the `Severity` of the warning and the numbers behind it, so every messenger can show them its
+ *        own way. Their `Display` is the default text for those that only send text.
+ *      - A struct `MultiMessenger` that sends every message with each messenger in it, and collects the errors of those that fail.
+ *      - A struct `RateLimited` that wraps a messenger and drops the messages that go over a number of them in a window of time. It is a messenger itself,
+ *        so anything that sends with a messenger can be rate limited without knowing it, which is what the decorator pattern is about. The times it keeps
+ *        are inside a RefCell<T>, as sending only borrows the messenger.
+ *      - A struct `LimitTracker` with five fields, the value of a message, the max lenght, the messanger (wich must implement the Messager trait), the
+ *        thresholds that have already sent their message and the hysteresis margin.
  *      - An implementation for the struct LimitTracker with the methods:
- *          * new       -> creates a new instance of the LimitTracker struct.
- *          * set_value -> sets a value and depending on its relation to the max field makes the messager send a message.
+ *          * new             -> creates a new instance of the LimitTracker struct.
+ *          * with_hysteresis -> sets how far below a threshold the value must drop before its message can be sent again.
+ *          * set_value       -> sets a value and depending on its relation to the max field makes the messager send a notification. If the messenger fails,
+ *                               the error is returned, but the threshold still counts as having sent its message.
+ *
+ *  The message of a threshold is only sent when the value goes over it, not every time the value is set while it's still over it. Once it was sent, the
+ *  threshold is only armed again when the value drops below it minus the hysteresis margin, so a value going up and down around a threshold doesn't send the
+ *  same message over and over. Going over a threshold also counts as going over the ones below it.
  */
-pub trait Messenger {
-    fn send(&self, msg: &str);
+pub trait Messenger<M = Notification> {
+    fn send(&self, msg: &M) -> Result<(), SendError>;
+}
+
+/** How bad a warning of a `LimitTracker` is, from the least to the most. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /** Over 75% of the quota. */
+    Warning,
+    /** Over 90% of the quota. */
+    Urgent,
+    /** The whole quota or more. */
+    Error,
+}
+
+/** What a `LimitTracker` tells its messenger: how bad it is, how much of the quota is `used` out of the `max`, and the `percent` that is. */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Notification {
+    pub severity: Severity,
+    pub used: usize,
+    pub max: usize,
+    pub percent: f64,
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "Error: You are over your quota!"),
+            Severity::Urgent => write!(f, "Urgent warning: You've used up over 90% of your quota!"),
+            Severity::Warning => write!(f, "Warning: You've used up over 75% of ypur quota!"),
+        }
+    }
+}
+
+/** Why a message was not sent. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /** The messenger could not send it, for the given reason. */
+    Failed(String),
+    /** Some of the messengers of a `MultiMessenger` could not send it: the index of each of them, with its error. The others sent it. */
+    Partial(Vec<(usize, SendError)>),
+    /** A `RateLimited` messenger dropped it, as it had already sent as many messages as it can in its window. */
+    RateLimited,
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SendError::Failed(reason) => write!(f, "could not send the message: {}", reason),
+            SendError::Partial(failures) => {
+                write!(f, "{} of the messengers failed", failures.len())?;
+                for (index, error) in failures {
+                    write!(f, "; messenger {}: {}", index, error)?;
+                }
+                Ok(())
+            }
+            SendError::RateLimited => write!(f, "dropped the message to stay under the rate limit"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+/** Sends every message with each of its messengers, like a warning that goes both to a log and by email. A messenger that fails doesn't stop the next ones
+ *  from sending the message.
+ */
+pub struct MultiMessenger<'a, M = Notification> {
+    messengers: Vec<&'a dyn Messenger<M>>,
+}
+
+impl<'a, M> MultiMessenger<'a, M> {
+    pub fn new() -> MultiMessenger<'a, M> {
+        MultiMessenger { messengers: Vec::new() }
+    }
+
+    /** Adds `messenger` after the ones the multi messenger already has. */
+    pub fn with(mut self, messenger: &'a dyn Messenger<M>) -> MultiMessenger<'a, M> {
+        self.messengers.push(messenger);
+        self
+    }
+}
+
+impl<M> Default for MultiMessenger<'_, M> {
+    fn default() -> Self {
+        MultiMessenger::new()
+    }
+}
+
+impl<M> Messenger<M> for MultiMessenger<'_, M> {
+    fn send(&self, msg: &M) -> Result<(), SendError> {
+        let failures: Vec<(usize, SendError)> = self
+            .messengers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, messenger)| messenger.send(msg).err().map(|error| (index, error)))
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(SendError::Partial(failures))
+        }
+    }
+}
+
+/** Where a `RateLimited` messenger gets the time from, as the time since some moment that never changes. Tests give it a clock they move forward by hand. */
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/** The time of the system, since the Unix epoch. */
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+/** Sends at most `limit` messages with the messenger it wraps in any `window` of time, and drops the others, returning `SendError::RateLimited` for them.
+ *  The window slides: a message can be sent as soon as the oldest of the last `limit` ones is more than `window` ago. A message the wrapped messenger fails
+ *  to send still counts, as it was tried.
+ */
+pub struct RateLimited<M, C = SystemClock> {
+    messenger: M,
+    clock: C,
+    limit: usize,
+    window: Duration,
+    /** When the messages of the current window were sent, from the oldest to the newest. */
+    sent: RefCell<VecDeque<Duration>>,
+    dropped: Cell<usize>,
+}
+
+impl<M> RateLimited<M> {
+    pub fn new(messenger: M, limit: usize, window: Duration) -> RateLimited<M> {
+        RateLimited { messenger, clock: SystemClock, limit, window, sent: RefCell::new(VecDeque::new()), dropped: Cell::new(0) }
+    }
+}
+
+impl<M, C> RateLimited<M, C> {
+    /** The same messenger, telling the time with `clock` instead. */
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> RateLimited<M, C2> {
+        RateLimited { messenger: self.messenger, clock, limit: self.limit, window: self.window, sent: self.sent, dropped: self.dropped }
+    }
+
+    /** The messenger that is wrapped. */
+    pub fn inner(&self) -> &M {
+        &self.messenger
+    }
+
+    /** How many messages were dropped since the messenger was made. */
+    pub fn dropped(&self) -> usize {
+        self.dropped.get()
+    }
+}
+
+impl<T, M: Messenger<T>, C: Clock> Messenger<T> for RateLimited<M, C> {
+    fn send(&self, msg: &T) -> Result<(), SendError> {
+        let now = self.clock.now();
+        let mut sent = self.sent.borrow_mut();
+        while sent.front().is_some_and(|at| now.saturating_sub(*at) >= self.window) {
+            sent.pop_front();
+        }
+
+        if sent.len() >= self.limit {
+            self.dropped.set(self.dropped.get() + 1);
+            return Err(SendError::RateLimited);
+        }
+        sent.push_back(now);
+        // The times aren't borrowed anymore while the wrapped messenger sends, in case it sends with this one again.
+        drop(sent);
+        self.messenger.send(msg)
+    }
 }
 
 pub struct LimitTracker<'a, T: Messenger> {
     messenger: &'a T,
     value: usize,
     max: usize,
+    /** Whether each of the `THRESHOLDS` has sent its message since it was last armed. */
+    fired: [bool; 3],
+    hysteresis: f64,
 }
 
 impl <'a, T> LimitTracker<'a, T>
 where
     T: Messenger,
 {
+    /** The thresholds, as parts of the max, from the highest to the lowest, with the severity of the notification each of them sends. */
+    const THRESHOLDS: [(f64, Severity); 3] = [(1.0, Severity::Error), (0.9, Severity::Urgent), (0.75, Severity::Warning)];
+
     pub fn new(messenger: &'a T, max: usize) -> LimitTracker<'a, T> {
         LimitTracker { 
             messenger,
             value: 0,
             max,
+            fired: [false; 3],
+            hysteresis: 0.0,
         }
     }
 
-    pub fn set_value(&mut self, value: usize) {
+    /** Makes the value drop `margin` below a threshold before it's armed again, as a part of the max like the thresholds are. With 0.05, a warning sent at
+     *  75% is sent again only after the value went under 70%.
+     */
+    pub fn with_hysteresis(mut self, margin: f64) -> LimitTracker<'a, T> {
+        self.hysteresis = margin;
+        self
+    }
+
+    pub fn set_value(&mut self, value: usize) -> Result<(), SendError> {
         self.value = value;
 
         let percentage_of_max = self.value as f64 / self.max as f64;
 
-        if percentage_of_max >= 1.0 {
-            self.messenger.send("Error: You are over your quota!");
-        } else if percentage_of_max >= 0.9 {
-            self.messenger.send("Urgent warning: You've used up over 90% of your quota!");
-        } else if percentage_of_max >= 0.75 {
-            self.messenger.send("Warning: You've used up over 75% of ypur quota!");
+        for ((threshold, _), fired) in Self::THRESHOLDS.iter().zip(&mut self.fired) {
+            if percentage_of_max < threshold - self.hysteresis {
+                *fired = false;
+            }
+        }
+
+        let Some(reached) = Self::THRESHOLDS.iter().position(|(threshold, _)| percentage_of_max >= *threshold) else {
+            return Ok(());
+        };
+        let already_sent = self.fired[reached];
+        for fired in &mut self.fired[reached..] {
+            *fired = true;
+        }
+        if already_sent {
+            return Ok(());
         }
+        self.messenger.send(&Notification {
+            severity: Self::THRESHOLDS[reached].1,
+            used: self.value,
+            max: self.max,
+            percent: percentage_of_max * 100.0,
+        })
     }
 }
 
-/** Below are the tests for the library implemented on lines 151 to 186. In this test we will create a Mock Object, explained later in this comment. This
+/** Below are the tests for the library implemented on lines 168 to 405. In this test we will create a Mock Object, explained later in this comment. This
  *  tests have the following elements:
  *      - A `MockMessenger` struct, that keeps track of the messages sent inside a RefCell<T> smart pointer wich contains a vector of Strings.
  *      - An implementation for the `new` method of this struct, wich creates a new instance of RefCell<T>.
@@ -217,8 +436,18 @@ mod tests {
     }
 
     impl Messenger for MockMessenger {
-        fn send(&self, message: &str) {
-            self.sent_messages.borrow_mut().push(String::from(message));
+        fn send(&self, notification: &Notification) -> Result<(), SendError> {
+            self.sent_messages.borrow_mut().push(notification.to_string());
+            Ok(())
+        }
+    }
+
+    /** A messenger that is never able to send anything. */
+    struct OfflineMessenger;
+
+    impl Messenger for OfflineMessenger {
+        fn send(&self, _notification: &Notification) -> Result<(), SendError> {
+            Err(SendError::Failed(String::from("offline")))
         }
     }
 
@@ -227,9 +456,137 @@ mod tests {
         let mock_messenger = MockMessenger::new();
         let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
 
-        limit_tracker.set_value(80);
+        limit_tracker.set_value(80).unwrap();
         assert_eq!(mock_messenger.sent_messages.borrow().len(), 1);
     }
+
+    #[test]
+    fn it_sends_each_warning_once_until_the_value_drops() {
+        let mock_messenger = MockMessenger::new();
+        let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
+
+        for value in [80, 85, 80, 95, 80, 110, 74, 76] {
+            limit_tracker.set_value(value).unwrap();
+        }
+        assert_eq!(
+            *mock_messenger.sent_messages.borrow(),
+            vec![
+                "Warning: You've used up over 75% of ypur quota!",
+                "Urgent warning: You've used up over 90% of your quota!",
+                "Error: You are over your quota!",
+                "Warning: You've used up over 75% of ypur quota!",
+            ],
+        );
+    }
+
+    #[test]
+    fn it_sends_with_every_messenger_and_collects_their_failures() {
+        let (log, email) = (MockMessenger::new(), MockMessenger::new());
+        let everywhere: MultiMessenger = MultiMessenger::new().with(&log).with(&OfflineMessenger).with(&email).with(&OfflineMessenger);
+        let mut limit_tracker = LimitTracker::new(&everywhere, 100);
+
+        assert_eq!(Ok(()), limit_tracker.set_value(50));
+        let error = limit_tracker.set_value(100).unwrap_err();
+        let offline = || SendError::Failed(String::from("offline"));
+        assert_eq!(SendError::Partial(vec![(1, offline()), (3, offline())]), error);
+        assert_eq!(
+            "2 of the messengers failed; messenger 1: could not send the message: offline; messenger 3: could not send the message: offline",
+            error.to_string(),
+        );
+        assert_eq!(*log.sent_messages.borrow(), vec!["Error: You are over your quota!"]);
+        assert_eq!(*log.sent_messages.borrow(), *email.sent_messages.borrow());
+
+        // The message counts as sent even though some messengers failed, so it's not sent again to those that didn't.
+        assert_eq!(Ok(()), limit_tracker.set_value(100));
+        assert_eq!(Ok(()), MultiMessenger::new().send(&"Nobody listens"));
+    }
+
+    /** A messenger that writes notifications its own way instead of with their default text, like a log would. */
+    struct LogMessenger {
+        lines: RefCell<Vec<String>>,
+    }
+
+    impl Messenger for LogMessenger {
+        fn send(&self, notification: &Notification) -> Result<(), SendError> {
+            let line = format!("[{:?}] {}/{} ({:.0}%)", notification.severity, notification.used, notification.max, notification.percent);
+            self.lines.borrow_mut().push(line);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_sends_notifications_that_messengers_show_their_own_way() {
+        let log = LogMessenger { lines: RefCell::new(vec![]) };
+        let mut limit_tracker = LimitTracker::new(&log, 200);
+
+        limit_tracker.set_value(160).unwrap();
+        limit_tracker.set_value(185).unwrap();
+        limit_tracker.set_value(250).unwrap();
+        assert_eq!(*log.lines.borrow(), vec!["[Warning] 160/200 (80%)", "[Urgent] 185/200 (92%)", "[Error] 250/200 (125%)"]);
+        assert!(Severity::Warning < Severity::Urgent && Severity::Urgent < Severity::Error);
+    }
+
+    /** A clock the test moves forward by hand, shared with the messenger that tells the time with it. */
+    #[derive(Clone, Default)]
+    struct TestClock(Rc<Cell<Duration>>);
+
+    impl Clock for TestClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn it_drops_the_messages_over_the_rate_limit() {
+        let clock = TestClock::default();
+        let messenger = RateLimited::new(MockMessenger::new(), 2, Duration::from_secs(60)).with_clock(clock.clone());
+        let notification = |used| Notification { severity: Severity::Warning, used, max: 100, percent: used as f64 };
+
+        assert_eq!(Ok(()), messenger.send(&notification(80)));
+        clock.0.set(Duration::from_secs(30));
+        assert_eq!(Ok(()), messenger.send(&notification(81)));
+        assert_eq!(Err(SendError::RateLimited), messenger.send(&notification(82)));
+
+        // The first message is a minute old now, so there is room for one more.
+        clock.0.set(Duration::from_secs(60));
+        assert_eq!(Ok(()), messenger.send(&notification(83)));
+        assert_eq!(Err(SendError::RateLimited), messenger.send(&notification(84)));
+        clock.0.set(Duration::from_secs(200));
+        assert_eq!(Ok(()), messenger.send(&notification(85)));
+
+        assert_eq!(4, messenger.inner().sent_messages.borrow().len());
+        assert_eq!(2, messenger.dropped());
+        assert_eq!("dropped the message to stay under the rate limit", SendError::RateLimited.to_string());
+    }
+
+    #[test]
+    fn it_rate_limits_any_messenger() {
+        let clock = TestClock::default();
+        let limited = RateLimited::new(OfflineMessenger, 1, Duration::from_secs(10)).with_clock(clock.clone());
+        let mut limit_tracker = LimitTracker::new(&limited, 100);
+
+        // The message fails to be sent, but it was tried, so the next one is dropped.
+        assert_eq!(Err(SendError::Failed(String::from("offline"))), limit_tracker.set_value(76));
+        assert_eq!(Err(SendError::RateLimited), limit_tracker.set_value(91));
+        clock.0.set(Duration::from_secs(10));
+        assert_eq!(Err(SendError::Failed(String::from("offline"))), limit_tracker.set_value(100));
+        assert_eq!(1, limited.dropped());
+    }
+
+    #[test]
+    fn it_keeps_quiet_while_the_value_oscillates_within_the_margin() {
+        let mock_messenger = MockMessenger::new();
+        let mut limit_tracker = LimitTracker::new(&mock_messenger, 100).with_hysteresis(0.05);
+
+        for value in [76, 72, 78, 71, 89, 91, 86, 92, 70, 76] {
+            limit_tracker.set_value(value).unwrap();
+        }
+        assert_eq!(mock_messenger.sent_messages.borrow().len(), 2);
+
+        limit_tracker.set_value(69).unwrap();
+        limit_tracker.set_value(76).unwrap();
+        assert_eq!(mock_messenger.sent_messages.borrow().len(), 3);
+    }
 }
 
 /** Using a RefCell<T> Smart Pointer doesn't allow us to fully bypass the borrow rules. The following test is similar to the last one, but it changes the 
@@ -257,12 +614,13 @@ mod panic_tests {
     }
 
     impl Messenger for MockMessenger {
-        fn send(&self, message: &str) {
+        fn send(&self, notification: &Notification) -> Result<(), SendError> {
             let mut one_borrow = self.sent_messages.borrow_mut();
             let mut two_borrow = self.sent_messages.borrow_mut();
 
-            one_borrow.push(String::from(message));
-            two_borrow.push(String::from(message));
+            one_borrow.push(notification.to_string());
+            two_borrow.push(notification.to_string());
+            Ok(())
         }
     }
 
@@ -272,7 +630,7 @@ mod panic_tests {
         let mock_messenger = MockMessenger::new();
         let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
 
-        limit_tracker.set_value(80);
+        limit_tracker.set_value(80).unwrap();
     }
 }
 
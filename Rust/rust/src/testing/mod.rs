@@ -71,6 +71,25 @@ mod tests {
         Guess::new(200);
     }
 
+    /** `Guess::in_range()` returns an Err instead of panicking, so we can check the error with assert_eq! like any other value. */
+    #[test]
+    fn in_range_accepts_bounds() {
+        assert_eq!(5, Guess::in_range(5, 5..=10).unwrap().value());
+        assert_eq!(10, Guess::in_range(10, 5..=10).unwrap().value());
+    }
+
+    #[test]
+    fn in_range_rejects_out_of_range() {
+        assert_eq!(Some(GuessError::TooSmall { value: 4, min: 5 }), Guess::in_range(4, 5..=10).err());
+        assert_eq!(Some(GuessError::TooLarge { value: 11, max: 10 }), Guess::in_range(11, 5..=10).err());
+    }
+
+    #[test]
+    fn guess_error_display() {
+        let err = Guess::in_range(0, 1..=50).err().unwrap();
+        assert_eq!("Guess value must be greater than or equal to 1, got 0.", err.to_string());
+    }
+
     /** Result<T, E> can also be used in our tests so they don't panic! and return an Err instead. */
     #[test]
     fn it_works() -> Result<(), String> {
@@ -91,24 +110,20 @@ mod tests {
         assert_eq!(10, value);
     }
 
-    /** The following three test have been coded to show the commands that can be used to filter out the tests you want to run. Let's have a look at two of these commands:
+    /** The following three test have been coded to show the commands that can be used to filter out the tests you want to run. They only differ in the value given
+     *  to add_two, so instead of writing each of them by hand, the `parametrize!` macro of the hello_macro_derive crate writes a test for every line of its table,
+     *  named after the function and the case: add_two_two_and_two(), add_two_three_and_two() and add_two_one_hundred(). Let's have a look at two of these commands:
      *      cargo test              ->  Will run the three of them.
-     *      cargo test one_hundred  ->  Will run the test one_hundred()
-     *      cargo test add          ->  Will run the first two tests as they are the ones that contain the substring add.
+     *      cargo test one_hundred  ->  Will run the test add_two_one_hundred()
+     *      cargo test and_two      ->  Will run the first two tests as they are the ones that contain the substring and_two.
      */
-    #[test]
-    fn add_two_and_two() {
-        assert_eq!(4, add_two(2));
-    }
-
-    #[test]
-    fn add_three_and_two() {
-        assert_eq!(5, add_two(3));
-    }
+    use hello_macro_derive::parametrize;
 
-    #[test]
-    fn one_hundred() {
-        assert_eq!(102, add_two(100));
+    parametrize! {
+        add_two;
+        two_and_two: 2 => 4,
+        three_and_two: 3 => 5,
+        one_hundred: 100 => 102,
     }
 
     /** Sometimes you won't want to execute a test. To avoid this, you can use the #[ignore] attribute below the #[test] one. If we wanted to run JUST the ignored tests, 
@@ -127,6 +142,10 @@ mod tests {
     }
 }
 
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
 /* Struct Rectangle imported from structs/rectangles/mod.rs file */
 #[derive(Debug)]
 struct Rectangle {
@@ -153,15 +172,48 @@ pub struct Guess {
     value: i32,
 }
 
+/** Error returned by `Guess::in_range()` when the value falls outside of the allowed range. Implementing Display lets callers (like the guessing game) show
+ *  the exact same message that `Guess::new()` panics with.
+ */
+#[derive(Debug, PartialEq)]
+pub enum GuessError {
+    TooSmall { value: i32, min: i32 },
+    TooLarge { value: i32, max: i32 },
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuessError::TooSmall { value, min } => {
+                write!(f, "Guess value must be greater than or equal to {min}, got {value}.")
+            }
+            GuessError::TooLarge { value, max } => {
+                write!(f, "Guess value must be less than or equal to {max}, got {value}.")
+            }
+        }
+    }
+}
+
+impl Error for GuessError {}
+
 impl Guess {
     pub fn new(value: i32) -> Guess {
-        if value < 1 {
-            panic!("Guess value must be greater than or equal to 1, got {}.", value);
-        } else if value > 100 {
-            panic!("Guess value must be less than or equal to 100, got {}.", value);
+        Guess::in_range(value, 1..=100).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /** Non-panicking version of `new()` that validates the value against any inclusive range instead of the hard-coded 1..=100. */
+    pub fn in_range(value: i32, range: RangeInclusive<i32>) -> Result<Guess, GuessError> {
+        if value < *range.start() {
+            Err(GuessError::TooSmall { value, min: *range.start() })
+        } else if value > *range.end() {
+            Err(GuessError::TooLarge { value, max: *range.end() })
+        } else {
+            Ok(Guess { value })
         }
+    }
 
-        Guess { value }
+    pub fn value(&self) -> i32 {
+        self.value
     }
 }
 
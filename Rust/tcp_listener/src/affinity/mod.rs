@@ -0,0 +1,135 @@
+//! Pinning worker threads to CPU cores.
+//!
+//! Only Linux is supported, through `sched_setaffinity`. On every other
+//! platform pinning reports `io::ErrorKind::Unsupported` and the worker
+//! keeps running wherever the scheduler of the OS puts it.
+
+use std::{io, thread};
+
+/// Returns the core worker `id` is pinned to: workers are spread round-robin
+/// over the cores available to the process.
+pub(crate) fn core_for_worker(id: usize) -> usize {
+    let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+
+    id % cores
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{io, mem};
+
+    /// Mirrors `cpu_set_t` from glibc, a bit mask of 1024 CPUs.
+    #[repr(C)]
+    pub(super) struct CpuSet {
+        bits: [u64; 16],
+    }
+
+    impl CpuSet {
+        pub(super) fn with_core(core: usize) -> io::Result<CpuSet> {
+            let mut set = CpuSet { bits: [0; 16] };
+            let word = set
+                .bits
+                .get_mut(core / 64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "core index out of range"))?;
+
+            *word |= 1 << (core % 64);
+            Ok(set)
+        }
+
+        #[cfg(test)]
+        pub(super) fn contains(&self, core: usize) -> bool {
+            self.bits[core / 64] & (1 << (core % 64)) != 0
+        }
+    }
+
+    extern "C" {
+        fn sched_setaffinity(pid: i32, cpusetsize: usize, mask: *const CpuSet) -> i32;
+        #[cfg(test)]
+        fn sched_getaffinity(pid: i32, cpusetsize: usize, mask: *mut CpuSet) -> i32;
+    }
+
+    pub(super) fn set_current(set: &CpuSet) -> io::Result<()> {
+        // Safety: a pid of 0 means the calling thread, and the pointer and
+        // size describe a valid, initialized mask.
+        let result = unsafe { sched_setaffinity(0, mem::size_of::<CpuSet>(), set) };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn get_current() -> io::Result<CpuSet> {
+        let mut set = CpuSet { bits: [0; 16] };
+
+        // Safety: same as in `set_current`, the kernel writes into `set`.
+        let result = unsafe { sched_getaffinity(0, mem::size_of::<CpuSet>(), &mut set) };
+
+        if result == 0 {
+            Ok(set)
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+/// Pins the calling thread to `core`.
+#[cfg(target_os = "linux")]
+pub(crate) fn pin_current_thread(core: usize) -> io::Result<()> {
+    linux::set_current(&linux::CpuSet::with_core(core)?)
+}
+
+/// Pins the calling thread to `core`.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn pin_current_thread(_core: usize) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "pinning threads is only supported on Linux",
+    ))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::ThreadPool;
+    use std::sync::mpsc;
+
+    #[test]
+    fn pinned_thread_only_runs_on_its_core() {
+        thread::spawn(|| {
+            pin_current_thread(0).unwrap();
+
+            let set = linux::get_current().unwrap();
+            assert!(set.contains(0));
+            assert!((1..1024).all(|core| !set.contains(core)));
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn pinning_to_a_core_past_the_mask_fails() {
+        let err = pin_current_thread(4096).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn builder_pins_workers() {
+        let pool = ThreadPool::builder().size(2).pin_workers(true).build().unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        pool.execute(move || {
+            let set = linux::get_current().unwrap();
+            let pinned: Vec<usize> = (0..1024).filter(|&core| set.contains(core)).collect();
+            tx.send(pinned).unwrap();
+        })
+        .unwrap();
+
+        let pinned = rx.recv().unwrap();
+        assert_eq!(1, pinned.len());
+        assert!(pinned[0] < 2);
+    }
+}
@@ -0,0 +1,37 @@
+//! Cooperative cancellation of jobs sent with
+//! `ThreadPool::execute_cancellable`.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A token shared by the code that may cancel some jobs and the jobs
+/// themselves.
+///
+/// Cloning the token gives another handle to the same flag. Once cancelled,
+/// jobs tagged with it that no worker has started yet are discarded, and
+/// running jobs can notice it through `is_cancelled` and return early.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a new token that is not cancelled.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancels every job tagged with this token. Cancelling twice has no
+    /// further effect.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if `cancel` was called on this token or on any clone
+    /// of it.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
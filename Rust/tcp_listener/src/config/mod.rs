@@ -0,0 +1,368 @@
+//! The configuration of the web server, read from a file.
+//!
+//! The file holds one `key = value` setting per line. Blank lines and lines
+//! starting with `#` are ignored, and values may be wrapped in double quotes
+//! like in TOML:
+//!
+//! ```text
+//! # Where the server listens.
+//! bind = "0.0.0.0:8080"
+//! pool_size = 8
+//! document_root = "public"
+//! read_timeout = 30s
+//! write_timeout = 500ms
+//!
+//! # Other sites, told apart by the Host header of requests.
+//! host.blog.example.com = "blog"
+//! ```
+//!
+//! Settings that are left out keep their default value.
+
+use std::{
+    fmt, fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+use crate::http::Host;
+
+/// How the server is set up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    /// The address the server listens on. Defaults to `127.0.0.1:7878`.
+    pub bind: SocketAddr,
+    /// Threads in the pool for CPU-bound work. Defaults to one per core.
+    pub pool_size: usize,
+    /// The directory files are served from. Defaults to `public`.
+    pub document_root: PathBuf,
+    /// How long to wait for a client to send its request. Defaults to 30
+    /// seconds.
+    pub read_timeout: Duration,
+    /// How long to wait for a client to accept the response. Defaults to 30
+    /// seconds.
+    pub write_timeout: Duration,
+    /// Host names with the directory their site is served from, set with
+    /// `host.<name> = <directory>`. Requests for any other host get the
+    /// document root.
+    pub virtual_hosts: Vec<(String, PathBuf)>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            bind: SocketAddr::from(([127, 0, 0, 1], 7878)),
+            pool_size: thread::available_parallelism().map_or(1, |cores| cores.get()),
+            document_root: PathBuf::from("public"),
+            read_timeout: Duration::from_secs(30),
+            write_timeout: Duration::from_secs(30),
+            virtual_hosts: Vec::new(),
+        }
+    }
+}
+
+/// Everything that can make a configuration unusable.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(PathBuf, io::Error),
+    /// A line is neither a setting, a comment nor blank.
+    Syntax { line: usize, text: String },
+    UnknownKey { line: usize, key: String },
+    /// A setting appears more than once.
+    DuplicateKey { line: usize, key: String },
+    /// A value can't be used for its setting, for the given reason.
+    InvalidValue {
+        line: usize,
+        key: String,
+        value: String,
+        reason: &'static str,
+    },
+    /// The document root, or the root of a virtual host, doesn't exist or
+    /// isn't a directory.
+    MissingDocumentRoot(PathBuf),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "could not read {}: {err}", path.display()),
+            ConfigError::Syntax { line, text } => {
+                write!(f, "line {line}: expected `key = value`, found {text:?}")
+            }
+            ConfigError::UnknownKey { line, key } => write!(f, "line {line}: unknown key {key:?}"),
+            ConfigError::DuplicateKey { line, key } => {
+                write!(f, "line {line}: {key:?} is set more than once")
+            }
+            ConfigError::InvalidValue {
+                line,
+                key,
+                value,
+                reason,
+            } => write!(f, "line {line}: invalid {key} {value:?}: {reason}"),
+            ConfigError::MissingDocumentRoot(path) => {
+                write!(f, "document root {} is not a directory", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads, parses and validates the configuration file at `path`.
+    ///
+    /// Relative document roots are taken relative to the directory of the
+    /// file, so the file can be used from anywhere.
+    ///
+    /// # Errors
+    ///
+    /// The `from_file` function will return an error if the file can't be
+    /// read, if it isn't a valid configuration, or if `validate` fails.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<ServerConfig, ConfigError> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        let mut config: ServerConfig = text.parse()?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let roots = config.virtual_hosts.iter_mut().map(|(_, root)| root);
+        for root in roots.chain([&mut config.document_root]) {
+            if root.is_relative() {
+                *root = dir.join(&*root);
+            }
+        }
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks the parts of the configuration that depend on the system,
+    /// which parsing leaves alone.
+    ///
+    /// # Errors
+    ///
+    /// The `validate` function will return
+    /// `ConfigError::MissingDocumentRoot` if the document root, or the root
+    /// of a virtual host, isn't a directory.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let roots = self.virtual_hosts.iter().map(|(_, root)| root);
+
+        match roots.chain([&self.document_root]).find(|root| !root.is_dir()) {
+            Some(root) => Err(ConfigError::MissingDocumentRoot(root.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for ServerConfig {
+    type Err = ConfigError;
+
+    fn from_str(text: &str) -> Result<ServerConfig, ConfigError> {
+        let mut config = ServerConfig::default();
+        let mut seen: Vec<&str> = Vec::new();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let syntax = || ConfigError::Syntax {
+                line,
+                text: raw.to_string(),
+            };
+            let (key, value) = trimmed.split_once('=').ok_or_else(syntax)?;
+            let key = key.trim();
+            let value = unquote(value.trim()).ok_or_else(syntax)?;
+            if key.is_empty() {
+                return Err(syntax());
+            }
+
+            if seen.contains(&key) {
+                return Err(ConfigError::DuplicateKey {
+                    line,
+                    key: key.to_string(),
+                });
+            }
+            seen.push(key);
+
+            let invalid = |reason| ConfigError::InvalidValue {
+                line,
+                key: key.to_string(),
+                value: value.to_string(),
+                reason,
+            };
+            match key {
+                "bind" => {
+                    config.bind = value
+                        .parse()
+                        .map_err(|_| invalid("expected an address like 127.0.0.1:7878"))?;
+                }
+                "pool_size" => {
+                    config.pool_size = match value.parse() {
+                        Ok(0) => return Err(invalid("the pool needs at least one thread")),
+                        Ok(size) => size,
+                        Err(_) => return Err(invalid("expected a number of threads")),
+                    };
+                }
+                "document_root" => {
+                    if value.is_empty() {
+                        return Err(invalid("expected a directory"));
+                    }
+                    config.document_root = PathBuf::from(value);
+                }
+                "read_timeout" => config.read_timeout = parse_timeout(value).map_err(invalid)?,
+                "write_timeout" => config.write_timeout = parse_timeout(value).map_err(invalid)?,
+                _ if key.starts_with("host.") => {
+                    let host = match key["host.".len()..].parse::<Host>() {
+                        Ok(host) if host.port.is_none() && !value.is_empty() => host,
+                        _ => return Err(invalid("expected `host.<name> = <directory>`")),
+                    };
+                    config.virtual_hosts.push((host.name, PathBuf::from(value)));
+                }
+                _ => {
+                    return Err(ConfigError::UnknownKey {
+                        line,
+                        key: key.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Strips the double quotes around a value, if it has any. Returns `None`
+/// for a value with only one of them.
+fn unquote(value: &str) -> Option<&str> {
+    match (value.starts_with('"'), value.ends_with('"')) {
+        (true, true) if value.len() >= 2 => Some(&value[1..value.len() - 1]),
+        (false, false) => Some(value),
+        _ => None,
+    }
+}
+
+/// Parses a duration like `30s` or `500ms`. A bare number is in seconds.
+fn parse_timeout(value: &str) -> Result<Duration, &'static str> {
+    let (number, to_duration): (&str, fn(u64) -> Duration) =
+        if let Some(number) = value.strip_suffix("ms") {
+            (number, Duration::from_millis)
+        } else if let Some(number) = value.strip_suffix('s') {
+            (number, Duration::from_secs)
+        } else {
+            (value, Duration::from_secs)
+        };
+
+    match number.trim().parse() {
+        Ok(0) => Err("a timeout must be longer than zero"),
+        Ok(amount) => Ok(to_duration(amount)),
+        Err(_) => Err("expected a duration like 30s or 500ms"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn parses_every_setting() {
+        let config: ServerConfig = "
+            # A comment.
+            bind = \"0.0.0.0:8080\"
+            pool_size = 3
+
+            document_root = site
+            read_timeout = 5s
+            write_timeout = 250ms
+            host.Blog.Example.com = \"blog\"
+        "
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            ServerConfig {
+                bind: "0.0.0.0:8080".parse().unwrap(),
+                pool_size: 3,
+                document_root: PathBuf::from("site"),
+                read_timeout: Duration::from_secs(5),
+                write_timeout: Duration::from_millis(250),
+                virtual_hosts: vec![("blog.example.com".into(), PathBuf::from("blog"))],
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn missing_settings_keep_their_defaults() {
+        let config: ServerConfig = "read_timeout = 10".parse().unwrap();
+        let default = ServerConfig::default();
+
+        assert_eq!(Duration::from_secs(10), config.read_timeout);
+        assert_eq!(default.bind, config.bind);
+        assert_eq!(default.document_root, config.document_root);
+    }
+
+    #[test]
+    fn reports_the_line_of_each_error() {
+        let error = |text: &str| text.parse::<ServerConfig>().unwrap_err();
+
+        assert!(matches!(error("\n\nbind"), ConfigError::Syntax { line: 3, .. }));
+        assert!(matches!(error("bind = \"oops"), ConfigError::Syntax { line: 1, .. }));
+        assert!(matches!(
+            error("port = 80"),
+            ConfigError::UnknownKey { line: 1, key } if key == "port"
+        ));
+        assert!(matches!(
+            error("pool_size = 2\npool_size = 4"),
+            ConfigError::DuplicateKey { line: 2, .. }
+        ));
+        assert!(matches!(
+            error("pool_size = 0"),
+            ConfigError::InvalidValue { line: 1, key, .. } if key == "pool_size"
+        ));
+        assert!(matches!(
+            error("bind = localhost"),
+            ConfigError::InvalidValue { key, .. } if key == "bind"
+        ));
+        assert!(matches!(
+            error("host. = site"),
+            ConfigError::InvalidValue { key, .. } if key == "host."
+        ));
+        assert!(matches!(
+            error("write_timeout = soon"),
+            ConfigError::InvalidValue { key, .. } if key == "write_timeout"
+        ));
+    }
+
+    #[test]
+    fn from_file_resolves_the_root_next_to_the_file() {
+        let dir = env::temp_dir().join(format!("server-config-{}", std::process::id()));
+        fs::create_dir_all(dir.join("site")).unwrap();
+        fs::create_dir_all(dir.join("blog")).unwrap();
+        fs::write(dir.join("server.conf"), "document_root = site\nhost.blog = blog\n").unwrap();
+        fs::write(dir.join("broken.conf"), "host.blog = nowhere\n").unwrap();
+
+        let config = ServerConfig::from_file(dir.join("server.conf")).unwrap();
+        let missing = ServerConfig::from_file(dir.join("broken.conf")).unwrap_err();
+        let unreadable = ServerConfig::from_file(dir.join("absent.conf")).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(dir.join("site"), config.document_root);
+        assert_eq!(dir.join("blog"), config.virtual_hosts[0].1);
+        assert!(matches!(missing, ConfigError::MissingDocumentRoot(_)));
+        assert!(matches!(unreadable, ConfigError::Io(..)));
+    }
+}
@@ -0,0 +1,202 @@
+//! A minimal executor that drives futures on the workers of a ThreadPool.
+//!
+//! A future does nothing until it is polled. Polling either finishes it,
+//! returning `Poll::Ready`, or returns `Poll::Pending` after handing the
+//! `Waker` from its `Context` to whatever it is waiting on. Calling `wake`
+//! on that waker tells the executor the future can make progress again.
+//!
+//! Here every spawned future lives in a `Task`, and the waker of a task is
+//! the task itself: waking it sends a job to the pool that polls the future
+//! once more. While the future is pending, no worker is busy with it.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Wake, Waker},
+};
+
+use crate::{ExecuteError, Message, Shared, ThreadPool};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// A spawned future together with the pool it runs on.
+struct Task {
+    /// `None` once the future has completed, so that late wakeups are
+    /// ignored.
+    future: Mutex<Option<BoxFuture>>,
+    pool: Arc<Shared>,
+}
+
+impl Task {
+    /// Sends a job to the pool that polls the future once.
+    fn schedule(self: Arc<Task>) -> Result<(), ExecuteError> {
+        let pool = Arc::clone(&self.pool);
+
+        pool.submit(Message::NewJob(Box::new(move || self.poll())), true)
+    }
+
+    fn poll(self: Arc<Task>) {
+        // If a poll panicked, the future was already taken out of the slot,
+        // so the poisoned lock holds nothing that could be inconsistent.
+        let mut slot = self.future.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // The lock is held while polling. A wakeup that arrives meanwhile
+        // schedules another poll, which waits here until this one is over.
+        if let Some(mut future) = slot.take() {
+            let waker = Waker::from(Arc::clone(&self));
+            let mut cx = Context::from_waker(&waker);
+
+            if future.as_mut().poll(&mut cx).is_pending() {
+                *slot = Some(future);
+            }
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Task>) {
+        // Once the pool is shut down there is nobody left to poll the
+        // future, so it is dropped along with the task.
+        let _ = self.schedule();
+    }
+}
+
+impl ThreadPool {
+    /// Runs a future on the thread pool.
+    ///
+    /// The future is polled by the workers, once right away and then every
+    /// time it is woken. Jobs only exist while the future is being polled,
+    /// so `wait_idle` doesn't wait for futures that are pending.
+    ///
+    /// # Errors
+    ///
+    /// The `spawn_future` function will return `ExecuteError::PoolShutDown`
+    /// if the pool no longer accepts jobs. The returned job polls the
+    /// future when called.
+    pub fn spawn_future<F>(&self, future: F) -> Result<(), ExecuteError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(future))),
+            pool: Arc::clone(&self.shared),
+        });
+
+        task.schedule()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::mpsc, task::Poll, thread, time::Duration};
+
+    /// A future that is pending a few times, waking itself every time, like
+    /// a task yielding to let the others run.
+    struct YieldTimes(usize);
+
+    impl Future for YieldTimes {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                return Poll::Ready(());
+            }
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    /// A value filled in by another thread, which wakes the future waiting
+    /// for it.
+    #[derive(Default)]
+    struct Slot {
+        state: Mutex<(Option<i32>, Option<Waker>)>,
+    }
+
+    impl Slot {
+        fn fill(&self, value: i32) {
+            let mut state = self.state.lock().unwrap();
+            state.0 = Some(value);
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    struct SlotFuture(Arc<Slot>);
+
+    impl Future for SlotFuture {
+        type Output = i32;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<i32> {
+            let mut state = self.0.state.lock().unwrap();
+            match state.0 {
+                Some(value) => Poll::Ready(value),
+                None => {
+                    state.1 = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn future_that_yields_runs_to_completion() {
+        let pool = ThreadPool::new(2);
+        let (tx, rx) = mpsc::channel();
+
+        pool.spawn_future(async move {
+            YieldTimes(5).await;
+            tx.send("done").unwrap();
+        })
+        .unwrap();
+
+        assert_eq!("done", rx.recv_timeout(Duration::from_secs(1)).unwrap());
+    }
+
+    #[test]
+    fn future_is_polled_again_when_woken_from_another_thread() {
+        let pool = ThreadPool::new(1);
+        let slot = Arc::new(Slot::default());
+        let (tx, rx) = mpsc::channel();
+
+        let future_slot = Arc::clone(&slot);
+        pool.spawn_future(async move {
+            let value = SlotFuture(future_slot).await;
+            tx.send(value * 2).unwrap();
+        })
+        .unwrap();
+
+        // The pending future doesn't keep the only worker busy.
+        let (job_tx, job_rx) = mpsc::channel();
+        pool.execute(move || job_tx.send(()).unwrap()).unwrap();
+        job_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+
+        thread::spawn(move || slot.fill(21)).join().unwrap();
+
+        assert_eq!(42, rx.recv_timeout(Duration::from_secs(1)).unwrap());
+    }
+
+    #[test]
+    fn many_futures_share_a_small_pool() {
+        let pool = ThreadPool::new(2);
+        let (tx, rx) = mpsc::channel();
+
+        for i in 0..20 {
+            let tx = tx.clone();
+            pool.spawn_future(async move {
+                YieldTimes(i % 4).await;
+                tx.send(i).unwrap();
+            })
+            .unwrap();
+        }
+        drop(tx);
+
+        let mut finished: Vec<usize> = rx.iter().collect();
+        finished.sort();
+        assert_eq!((0..20).collect::<Vec<_>>(), finished);
+    }
+}
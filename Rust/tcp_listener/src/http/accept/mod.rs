@@ -0,0 +1,297 @@
+//! Deciding which connections the server takes on.
+//!
+//! An `AcceptPolicy` sits in front of the accept loop. It caps how many
+//! connections are open at once, and gives every peer address a token
+//! bucket: each connection takes a token, and tokens come back at a fixed
+//! rate up to a burst size. A peer opening connections faster than that is
+//! turned away until its bucket refills.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::{Response, Status};
+
+/// How many peers are tracked before the buckets of quiet ones are dropped.
+const MAX_TRACKED_PEERS: usize = 1024;
+
+/// A connection cap and a per-peer rate limit, both off by default.
+///
+/// Clones share their open connections and buckets, so one policy can be
+/// handed to several accept loops.
+#[derive(Debug, Clone, Default)]
+pub struct AcceptPolicy {
+    max_connections: Option<usize>,
+    rate: Option<Rate>,
+    state: Arc<State>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rate {
+    per_second: f64,
+    burst: f64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    open: Mutex<usize>,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, rate: Rate, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * rate.per_second).min(rate.burst);
+        self.updated = now;
+    }
+}
+
+/// Why a connection was turned away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The server already has as many open connections as it allows.
+    TooManyConnections,
+    /// The peer opened connections faster than its rate allows. It gets a
+    /// new token after `retry_after`.
+    RateLimited { retry_after: Duration },
+}
+
+impl Rejection {
+    /// Returns `503 Service Unavailable` for too many connections and
+    /// `429 Too Many Requests` for a rate limited peer.
+    pub fn status(&self) -> Status {
+        match self {
+            Rejection::TooManyConnections => Status::ServiceUnavailable,
+            Rejection::RateLimited { .. } => Status::TooManyRequests,
+        }
+    }
+
+    /// Returns the response to send the rejected peer, with a
+    /// `Retry-After` header when it is rate limited.
+    pub fn response(&self) -> Response {
+        let status = self.status();
+        let response = Response::new(status)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(status.to_string());
+
+        match self {
+            Rejection::TooManyConnections => response,
+            Rejection::RateLimited { retry_after } => {
+                // Retry-After is in whole seconds, so round up.
+                let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                response.header("Retry-After", seconds.to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::TooManyConnections => write!(f, "too many open connections"),
+            Rejection::RateLimited { retry_after } => {
+                write!(f, "rate limited, retry in {retry_after:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+/// An admitted connection. Dropping it, once the connection is closed,
+/// frees its place under the connection cap.
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    state: Arc<State>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        *self.state.open.lock().unwrap() -= 1;
+    }
+}
+
+impl AcceptPolicy {
+    /// Creates a policy that accepts every connection.
+    pub fn new() -> AcceptPolicy {
+        AcceptPolicy::default()
+    }
+
+    /// Caps how many admitted connections may be open at once.
+    pub fn max_connections(mut self, max: usize) -> AcceptPolicy {
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// Limits every peer to `per_second` new connections per second, with
+    /// bursts of up to `burst` connections at once.
+    ///
+    /// # Panics
+    ///
+    /// The `rate_limit` function will panic if either value is zero.
+    pub fn rate_limit(mut self, per_second: u32, burst: u32) -> AcceptPolicy {
+        assert!(per_second > 0 && burst > 0);
+
+        self.rate = Some(Rate {
+            per_second: f64::from(per_second),
+            burst: f64::from(burst),
+        });
+        self
+    }
+
+    /// Returns how many admitted connections are still open.
+    pub fn open_connections(&self) -> usize {
+        *self.state.open.lock().unwrap()
+    }
+
+    /// Decides whether to take on a connection from `peer`.
+    ///
+    /// The connection counts as open until the returned guard is dropped,
+    /// so the guard should live as long as the connection does.
+    ///
+    /// # Errors
+    ///
+    /// The `admit` function will return a `Rejection` if the connection
+    /// cap is reached or `peer` is out of tokens. Its `response` is what
+    /// the peer should be sent before closing the connection.
+    pub fn admit(&self, peer: IpAddr) -> Result<ConnectionGuard, Rejection> {
+        self.admit_at(peer, Instant::now())
+    }
+
+    fn admit_at(&self, peer: IpAddr, now: Instant) -> Result<ConnectionGuard, Rejection> {
+        let mut open = self.state.open.lock().unwrap();
+
+        if self.max_connections.is_some_and(|max| *open >= max) {
+            return Err(Rejection::TooManyConnections);
+        }
+        if let Some(rate) = self.rate {
+            self.take_token(rate, peer, now)?;
+        }
+
+        *open += 1;
+        Ok(ConnectionGuard {
+            state: Arc::clone(&self.state),
+        })
+    }
+
+    fn take_token(&self, rate: Rate, peer: IpAddr, now: Instant) -> Result<(), Rejection> {
+        let mut buckets = self.state.buckets.lock().unwrap();
+
+        if buckets.len() >= MAX_TRACKED_PEERS && !buckets.contains_key(&peer) {
+            // A full bucket is the same as a new one, so forgetting it
+            // changes nothing for its peer.
+            buckets.retain(|_, bucket| {
+                bucket.refill(rate, now);
+                bucket.tokens < rate.burst
+            });
+        }
+
+        let bucket = buckets.entry(peer).or_insert(Bucket {
+            tokens: rate.burst,
+            updated: now,
+        });
+        bucket.refill(rate, now);
+
+        if bucket.tokens < 1.0 {
+            let missing = 1.0 - bucket.tokens;
+            return Err(Rejection::RateLimited {
+                retry_after: Duration::from_secs_f64(missing / rate.per_second),
+            });
+        }
+
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const PEER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const OTHER_PEER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    #[test]
+    fn default_policy_accepts_everything() {
+        let policy = AcceptPolicy::new();
+
+        let guards: Vec<_> = (0..100).map(|_| policy.admit(PEER).unwrap()).collect();
+
+        assert_eq!(100, policy.open_connections());
+        drop(guards);
+        assert_eq!(0, policy.open_connections());
+    }
+
+    #[test]
+    fn connection_cap_frees_places_when_guards_drop() {
+        let policy = AcceptPolicy::new().max_connections(2);
+
+        let first = policy.admit(PEER).unwrap();
+        let _second = policy.admit(OTHER_PEER).unwrap();
+        assert_eq!(Err(Rejection::TooManyConnections), policy.admit(PEER).map(|_| ()));
+
+        drop(first);
+        assert!(policy.admit(PEER).is_ok());
+    }
+
+    #[test]
+    fn peers_get_a_burst_then_wait_for_tokens() {
+        let policy = AcceptPolicy::new().rate_limit(2, 3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            policy.admit_at(PEER, start).unwrap();
+        }
+        let rejection = policy.admit_at(PEER, start).unwrap_err();
+        assert_eq!(
+            Rejection::RateLimited {
+                retry_after: Duration::from_millis(500)
+            },
+            rejection
+        );
+
+        // Other peers have buckets of their own.
+        assert!(policy.admit_at(OTHER_PEER, start).is_ok());
+
+        let later = start + Duration::from_millis(500);
+        assert!(policy.admit_at(PEER, later).is_ok());
+        assert!(policy.admit_at(PEER, later).is_err());
+    }
+
+    #[test]
+    fn rate_limited_connections_do_not_count_as_open() {
+        let policy = AcceptPolicy::new().max_connections(10).rate_limit(1, 1);
+        let now = Instant::now();
+
+        let _guard = policy.admit_at(PEER, now).unwrap();
+        assert!(policy.admit_at(PEER, now).is_err());
+
+        assert_eq!(1, policy.open_connections());
+    }
+
+    #[test]
+    fn rejections_map_to_responses() {
+        let busy = Rejection::TooManyConnections.response();
+        assert_eq!(Status::ServiceUnavailable, busy.status);
+        assert_eq!(None, busy.headers.get("Retry-After"));
+
+        let limited = Rejection::RateLimited {
+            retry_after: Duration::from_millis(1500),
+        }
+        .response();
+        assert_eq!(Status::TooManyRequests, limited.status);
+        assert_eq!(Some("2"), limited.headers.get("Retry-After"));
+    }
+}
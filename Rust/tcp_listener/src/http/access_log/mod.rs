@@ -0,0 +1,228 @@
+//! An access log in the Common Log Format.
+//!
+//! Every exchange becomes one line, like
+//!
+//! `127.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET /index.html HTTP/1.1" 200 2326 1042`
+//!
+//! which is the client, two fields the server doesn't know (`-`), the time
+//! the request came in, the request line, the status and the bytes sent. The
+//! last field, the time taken in microseconds, is an extension that most
+//! log tools accept after the standard ones.
+
+use std::{
+    fmt::Write as _,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{Exchange, Observer};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// An observer that writes a line per exchange to a writer.
+///
+/// Writing is best effort: a log that can't be written to doesn't make the
+/// request fail.
+pub struct AccessLog<W: Write + Send> {
+    out: Mutex<W>,
+}
+
+impl AccessLog<io::Stderr> {
+    /// Creates a log that writes to the standard error.
+    pub fn stderr() -> AccessLog<io::Stderr> {
+        AccessLog::new(io::stderr())
+    }
+}
+
+impl AccessLog<File> {
+    /// Creates a log that appends to the file at `path`, creating it if it
+    /// doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// The `to_file` function will return an error if the file can't be
+    /// opened for writing.
+    pub fn to_file(path: impl AsRef<Path>) -> io::Result<AccessLog<File>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(AccessLog::new(file))
+    }
+}
+
+impl<W: Write + Send> AccessLog<W> {
+    pub fn new(out: W) -> AccessLog<W> {
+        AccessLog {
+            out: Mutex::new(out),
+        }
+    }
+
+    /// Returns the writer, to read back what was logged.
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().unwrap()
+    }
+}
+
+impl<W: Write + Send> Observer for AccessLog<W> {
+    fn observe(&self, exchange: &Exchange) {
+        let line = format_line(exchange);
+
+        // The whole line is written under the lock, so lines from several
+        // workers never interleave.
+        let _ = self.out.lock().unwrap().write_all(line.as_bytes());
+    }
+}
+
+/// Formats an exchange as a line of the log, with its line ending.
+fn format_line(exchange: &Exchange) -> String {
+    let request = exchange.request;
+    let client = match request.peer {
+        Some(peer) => peer.ip().to_string(),
+        None => String::from("-"),
+    };
+    let status = match exchange.status {
+        Some(status) => status.code().to_string(),
+        None => String::from("-"),
+    };
+    let bytes = match exchange.bytes_sent {
+        0 => String::from("-"),
+        bytes => bytes.to_string(),
+    };
+
+    let mut target = request.path.clone();
+    for (i, (key, value)) in request.query.iter().enumerate() {
+        let separator = if i == 0 { '?' } else { '&' };
+        let _ = write!(target, "{separator}{key}={value}");
+    }
+
+    format!(
+        "{client} - - [{}] \"{} {} {}\" {status} {bytes} {}\n",
+        format_time(exchange.received_at),
+        request.method,
+        escape(&target),
+        request.version,
+        exchange.elapsed.as_micros(),
+    )
+}
+
+/// Formats a time as `10/Oct/2000:13:55:36 +0000`, always in UTC.
+fn format_time(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let seconds_of_day = seconds % 86_400;
+
+    format!(
+        "{day:02}/{}/{year}:{:02}:{:02}:{:02} +0000",
+        MONTHS[month as usize - 1],
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+    )
+}
+
+/// Turns days since 1970-01-01 into a year, month and day of the Gregorian
+/// calendar, with Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Escapes quotes, backslashes and control characters, so a decoded target
+/// can't break the quoted request field or forge a second line.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\x{:02x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Request, Status};
+    use std::time::Duration;
+
+    fn exchange_line(raw: &str, status: Option<Status>, bytes_sent: u64) -> String {
+        let mut request = Request::parse(&mut raw.as_bytes()).unwrap();
+        request.peer = Some("192.168.1.20:51000".parse().unwrap());
+
+        let log = AccessLog::new(Vec::new());
+        log.observe(&Exchange {
+            request: &request,
+            status,
+            bytes_sent,
+            // 2000-10-10 13:55:36 UTC.
+            received_at: UNIX_EPOCH + Duration::from_secs(971_186_136),
+            elapsed: Duration::from_micros(1042),
+        });
+
+        String::from_utf8(log.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn writes_common_log_format_with_latency() {
+        let line = exchange_line(
+            "GET /index.html?lang=en&q=a%20b HTTP/1.1\r\n\r\n",
+            Some(Status::Ok),
+            2326,
+        );
+
+        assert_eq!(
+            "192.168.1.20 - - [10/Oct/2000:13:55:36 +0000] \
+             \"GET /index.html?lang=en&q=a b HTTP/1.1\" 200 2326 1042\n",
+            line
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_dashes() {
+        let line = exchange_line("HEAD / HTTP/1.0\r\n\r\n", None, 0);
+
+        assert!(line.ends_with("\"HEAD / HTTP/1.0\" - - 1042\n"));
+    }
+
+    #[test]
+    fn targets_cannot_break_the_line() {
+        let line = exchange_line("GET /a%22b%0Ac HTTP/1.1\r\n\r\n", Some(Status::NotFound), 9);
+
+        assert!(line.contains("\"GET /a\\\"b\\x0ac HTTP/1.1\" 404 9"));
+        assert_eq!(1, line.lines().count());
+    }
+
+    #[test]
+    fn dates_are_converted_to_the_calendar() {
+        assert_eq!((1970, 1, 1), civil_from_days(0));
+        assert_eq!((2000, 2, 29), civil_from_days(11_016));
+        assert_eq!((2024, 12, 31), civil_from_days(20_088));
+    }
+}
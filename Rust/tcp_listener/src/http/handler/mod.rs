@@ -0,0 +1,192 @@
+//! Handlers, which answer requests, and the observers that watch them.
+//!
+//! A handler writes a whole response for a request. Wrapping one in
+//! `Observed` measures every exchange it handles and reports it to a list
+//! of observers, so things like access logs live in one place instead of
+//! in every handler.
+
+use std::{
+    io::{self, Write},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::{Request, Status, StaticFiles};
+
+/// Something that answers requests.
+pub trait Handler: Send + Sync {
+    /// Writes the response to `request` to `writer` and returns its status.
+    ///
+    /// # Errors
+    ///
+    /// The `handle` function will return an error if writing the response
+    /// fails.
+    fn handle(&self, request: &Request, writer: &mut dyn Write) -> io::Result<Status>;
+}
+
+impl<F> Handler for F
+where
+    F: Fn(&Request, &mut dyn Write) -> io::Result<Status> + Send + Sync,
+{
+    fn handle(&self, request: &Request, writer: &mut dyn Write) -> io::Result<Status> {
+        self(request, writer)
+    }
+}
+
+impl Handler for StaticFiles {
+    fn handle(&self, request: &Request, writer: &mut dyn Write) -> io::Result<Status> {
+        StaticFiles::handle(self, request, writer)
+    }
+}
+
+/// A request and how it was answered, as seen by an observer.
+#[derive(Debug)]
+pub struct Exchange<'a> {
+    pub request: &'a Request,
+    /// `None` if writing the response failed partway.
+    pub status: Option<Status>,
+    /// Bytes written, including the status line and the headers.
+    pub bytes_sent: u64,
+    pub received_at: SystemTime,
+    /// How long the handler took, from the start of `handle` until the
+    /// response was written.
+    pub elapsed: Duration,
+}
+
+/// Something told about every exchange a handler goes through.
+pub trait Observer: Send + Sync {
+    fn observe(&self, exchange: &Exchange);
+}
+
+impl<O: Observer + ?Sized> Observer for Arc<O> {
+    fn observe(&self, exchange: &Exchange) {
+        (**self).observe(exchange)
+    }
+}
+
+/// A handler whose exchanges are reported to observers.
+pub struct Observed<H> {
+    inner: H,
+    observers: Vec<Arc<dyn Observer>>,
+}
+
+impl<H: Handler> Observed<H> {
+    /// Wraps `inner`, with no observers yet.
+    pub fn new(inner: H) -> Observed<H> {
+        Observed {
+            inner,
+            observers: Vec::new(),
+        }
+    }
+
+    /// Adds an observer. Observers are told about an exchange in the order
+    /// they were added, once the response has been written.
+    pub fn observer(mut self, observer: impl Observer + 'static) -> Observed<H> {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+}
+
+impl<H: Handler> Handler for Observed<H> {
+    fn handle(&self, request: &Request, writer: &mut dyn Write) -> io::Result<Status> {
+        let received_at = SystemTime::now();
+        let start = Instant::now();
+        let mut counter = CountingWriter {
+            inner: writer,
+            written: 0,
+        };
+
+        let result = self.inner.handle(request, &mut counter);
+
+        let exchange = Exchange {
+            request,
+            status: result.as_ref().ok().copied(),
+            bytes_sent: counter.written,
+            received_at,
+            elapsed: start.elapsed(),
+        };
+        for observer in &self.observers {
+            observer.observe(&exchange);
+        }
+
+        result
+    }
+}
+
+/// A writer that counts the bytes going through it.
+struct CountingWriter<'a> {
+    inner: &'a mut dyn Write,
+    written: u64,
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Response;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(Option<Status>, u64)>>);
+
+    impl Observer for Recorder {
+        fn observe(&self, exchange: &Exchange) {
+            self.0.lock().unwrap().push((exchange.status, exchange.bytes_sent));
+        }
+    }
+
+    fn hello(_: &Request, writer: &mut dyn Write) -> io::Result<Status> {
+        Response::new(Status::Ok).body("hello").write_to(writer)?;
+        Ok(Status::Ok)
+    }
+
+    #[test]
+    fn observers_see_status_and_size_of_every_exchange() {
+        let recorder = Arc::new(Recorder::default());
+        let handler = Observed::new(hello).observer(Arc::clone(&recorder));
+        let request = Request::parse(&mut &b"GET / HTTP/1.1\r\n\r\n"[..]).unwrap();
+
+        let mut sent = Vec::new();
+        handler.handle(&request, &mut sent).unwrap();
+        handler.handle(&request, &mut sent).unwrap();
+
+        let each = sent.len() as u64 / 2;
+        assert_eq!(
+            vec![(Some(Status::Ok), each), (Some(Status::Ok), each)],
+            *recorder.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_exchanges_are_observed_without_status() {
+        struct Broken;
+
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let handler = Observed::new(hello).observer(Arc::clone(&recorder));
+        let request = Request::parse(&mut &b"GET / HTTP/1.1\r\n\r\n"[..]).unwrap();
+
+        assert!(handler.handle(&request, &mut Broken).is_err());
+        assert_eq!(vec![(None, 0)], *recorder.0.lock().unwrap());
+    }
+}
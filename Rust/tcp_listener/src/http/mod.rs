@@ -0,0 +1,23 @@
+//! A small HTTP/1.1 implementation for the web server.
+//!
+//! It only covers what the server needs, but it covers it properly: every
+//! way a request can be malformed is reported as a structured error instead
+//! of a panic, so one bad client can't take a worker down.
+
+mod accept;
+mod access_log;
+mod handler;
+mod headers;
+mod request;
+mod response;
+mod router;
+mod static_files;
+
+pub use accept::{AcceptPolicy, ConnectionGuard, Rejection};
+pub use access_log::AccessLog;
+pub use handler::{Exchange, Handler, Observed, Observer};
+pub use headers::Headers;
+pub use request::{Host, Method, ParseError, Request, Version};
+pub use response::{Response, Status};
+pub use router::{Route, Router, VirtualHosts};
+pub use static_files::{mime_type, StaticFiles};
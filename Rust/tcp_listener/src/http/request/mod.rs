@@ -0,0 +1,793 @@
+//! Parsing and writing HTTP/1.1 requests.
+
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Ipv6Addr, SocketAddr, TcpStream},
+    str::FromStr,
+};
+
+use super::Headers;
+
+/// Longest request or header line accepted, in bytes.
+const MAX_LINE_LEN: usize = 8 * 1024;
+/// Most headers accepted in a single request.
+const MAX_HEADERS: usize = 100;
+/// Largest body accepted, in bytes.
+pub(super) const MAX_BODY_LEN: usize = 10 * 1024 * 1024;
+
+/// The method of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Options,
+    Patch,
+}
+
+impl FromStr for Method {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Method, ParseError> {
+        match s {
+            "GET" => Ok(Method::Get),
+            "HEAD" => Ok(Method::Head),
+            "POST" => Ok(Method::Post),
+            "PUT" => Ok(Method::Put),
+            "DELETE" => Ok(Method::Delete),
+            "OPTIONS" => Ok(Method::Options),
+            "PATCH" => Ok(Method::Patch),
+            _ => Err(ParseError::UnknownMethod(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Options => "OPTIONS",
+            Method::Patch => "PATCH",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// The protocol version of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    Http10,
+    Http11,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Version::Http10 => write!(f, "HTTP/1.0"),
+            Version::Http11 => write!(f, "HTTP/1.1"),
+        }
+    }
+}
+
+/// The `Host` header of a request, which names the site it is meant for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+    /// The host name in lowercase, without a trailing dot. IPv6 addresses
+    /// are kept without their brackets.
+    pub name: String,
+    pub port: Option<u16>,
+}
+
+impl FromStr for Host {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Host, ParseError> {
+        let invalid = || ParseError::InvalidHost(value.to_string());
+
+        let (name, port) = match value.strip_prefix('[') {
+            Some(rest) => {
+                let (address, port) = rest.split_once(']').ok_or_else(invalid)?;
+                address.parse::<Ipv6Addr>().map_err(|_| invalid())?;
+                match port {
+                    "" => (address, None),
+                    port => (address, Some(port.strip_prefix(':').ok_or_else(invalid)?)),
+                }
+            }
+            None => match value.split_once(':') {
+                Some((name, port)) => (name, Some(port)),
+                None => (value, None),
+            },
+        };
+
+        let name = name.strip_suffix('.').unwrap_or(name);
+        let valid_name = !name.is_empty()
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || b"-._:".contains(&byte));
+        if !valid_name {
+            return Err(invalid());
+        }
+
+        let port = match port {
+            Some(port) => Some(port.parse().map_err(|_| invalid())?),
+            None => None,
+        };
+
+        Ok(Host {
+            name: name.to_ascii_lowercase(),
+            port,
+        })
+    }
+}
+
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.name.contains(':') {
+            write!(f, "[{}]", self.name)?;
+        } else {
+            write!(f, "{}", self.name)?;
+        }
+        match self.port {
+            Some(port) => write!(f, ":{port}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A parsed HTTP/1.1 request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    /// The percent-decoded path of the request target, without the query.
+    pub path: String,
+    /// The percent-decoded query parameters, in the order they were sent.
+    pub query: Vec<(String, String)>,
+    pub version: Version,
+    /// The site the request is meant for, from its `Host` header.
+    pub host: Option<Host>,
+    pub headers: Headers,
+    /// The body, as long as the `Content-Length` header says. Empty if the
+    /// request has no such header.
+    pub body: Vec<u8>,
+    /// The address of the client, if the request was read from a
+    /// connection.
+    pub peer: Option<SocketAddr>,
+}
+
+/// Everything that can make a request unreadable.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Reading from the connection failed.
+    Io(io::Error),
+    /// The connection was closed before a request line was sent.
+    Empty,
+    /// A line was longer than the limit, or not valid UTF-8.
+    LineTooLong,
+    /// The request line is not `METHOD TARGET VERSION`.
+    MalformedRequestLine(String),
+    UnknownMethod(String),
+    UnsupportedVersion(String),
+    /// The target isn't an absolute path, or has a broken percent escape.
+    InvalidTarget(String),
+    /// A header line has no colon or an invalid name.
+    MalformedHeader(String),
+    /// The `Host` header isn't a host name with an optional port, or is
+    /// sent more than once.
+    InvalidHost(String),
+    TooManyHeaders,
+    /// `Content-Length` isn't a number, or is sent twice with different
+    /// values.
+    InvalidContentLength(String),
+    /// `Transfer-Encoding` is not supported, bodies need a `Content-Length`.
+    UnsupportedTransferEncoding(String),
+    BodyTooLarge(usize),
+    /// The connection was closed before the whole body was sent.
+    IncompleteBody { expected: usize, received: usize },
+    /// The status line of a response is not `VERSION CODE REASON`.
+    MalformedStatusLine(String),
+    UnknownStatus(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(err) => write!(f, "could not read the request: {err}"),
+            ParseError::Empty => write!(f, "the connection closed before sending a request"),
+            ParseError::LineTooLong => {
+                write!(f, "a line is longer than {MAX_LINE_LEN} bytes or not valid UTF-8")
+            }
+            ParseError::MalformedRequestLine(line) => write!(f, "malformed request line {line:?}"),
+            ParseError::UnknownMethod(method) => write!(f, "unknown method {method:?}"),
+            ParseError::UnsupportedVersion(version) => {
+                write!(f, "unsupported HTTP version {version:?}")
+            }
+            ParseError::InvalidTarget(target) => write!(f, "invalid request target {target:?}"),
+            ParseError::MalformedHeader(line) => write!(f, "malformed header {line:?}"),
+            ParseError::InvalidHost(value) => write!(f, "invalid Host {value:?}"),
+            ParseError::TooManyHeaders => write!(f, "more than {MAX_HEADERS} headers"),
+            ParseError::InvalidContentLength(value) => {
+                write!(f, "invalid Content-Length {value:?}")
+            }
+            ParseError::UnsupportedTransferEncoding(value) => {
+                write!(f, "unsupported Transfer-Encoding {value:?}")
+            }
+            ParseError::BodyTooLarge(len) => {
+                write!(f, "a body of {len} bytes is larger than {MAX_BODY_LEN} bytes")
+            }
+            ParseError::IncompleteBody { expected, received } => {
+                write!(f, "expected a body of {expected} bytes, got {received}")
+            }
+            ParseError::MalformedStatusLine(line) => write!(f, "malformed status line {line:?}"),
+            ParseError::UnknownStatus(code) => write!(f, "unknown status code {code:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> ParseError {
+        ParseError::Io(err)
+    }
+}
+
+impl Request {
+    /// Reads and parses a request from a connection.
+    ///
+    /// The connection is read through a buffer that is dropped afterwards,
+    /// so anything the client sent past the request is lost. To read several
+    /// requests from one connection, keep a `BufReader` around and call
+    /// `parse` instead.
+    ///
+    /// # Errors
+    ///
+    /// The `from_stream` function will return an error if reading fails or
+    /// if the request is malformed.
+    pub fn from_stream(stream: &TcpStream) -> Result<Request, ParseError> {
+        let mut request = Request::parse(&mut BufReader::new(stream))?;
+        request.peer = stream.peer_addr().ok();
+
+        Ok(request)
+    }
+
+    /// Parses a request from any buffered reader.
+    ///
+    /// Only the request is read, so several requests sent one after another
+    /// on the same connection can be parsed in turn.
+    ///
+    /// # Errors
+    ///
+    /// The `parse` function will return an error if reading fails or if the
+    /// request is malformed.
+    pub fn parse<R: BufRead>(reader: &mut R) -> Result<Request, ParseError> {
+        let line = read_line(reader)?.ok_or(ParseError::Empty)?;
+        let (method, target, version) = parse_request_line(&line)?;
+        let (path, query) = parse_target(target)?;
+        let headers = parse_headers(reader)?;
+        let host = parse_host(&headers)?;
+        let body = read_body(reader, &headers)?;
+
+        Ok(Request {
+            method,
+            path,
+            query,
+            version,
+            host,
+            headers,
+            body,
+            peer: None,
+        })
+    }
+
+    /// Creates an HTTP/1.1 request for `path`, without a query, headers or
+    /// body, to send with `write_to`.
+    pub fn new(method: Method, path: impl Into<String>) -> Request {
+        Request {
+            method,
+            path: path.into(),
+            query: Vec::new(),
+            version: Version::Http11,
+            host: None,
+            headers: Headers::default(),
+            body: Vec::new(),
+            peer: None,
+        }
+    }
+
+    /// Adds a header to the request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Request {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the body of the request, replacing any previous one.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Request {
+        self.body = body.into();
+        self
+    }
+
+    /// Writes the request line, the headers and the body to `writer`.
+    ///
+    /// The path and the query are percent-encoded again, `host` is sent as
+    /// the `Host` header unless there already is one, and `Content-Length`
+    /// is computed from the body.
+    ///
+    /// # Errors
+    ///
+    /// The `write_to` function will return an error if writing to `writer`
+    /// fails.
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        let mut head = format!("{} {}", self.method, percent_encode(&self.path, true));
+        for (i, (key, value)) in self.query.iter().enumerate() {
+            let separator = if i == 0 { '?' } else { '&' };
+            head.push_str(&format!(
+                "{separator}{}={}",
+                percent_encode(key, false),
+                percent_encode(value, false)
+            ));
+        }
+        head.push_str(&format!(" {}\r\n", self.version));
+
+        if let (Some(host), None) = (&self.host, self.headers.get("Host")) {
+            head.push_str(&format!("Host: {host}\r\n"));
+        }
+        let headers = self
+            .headers
+            .iter()
+            .filter(|(name, _)| !name.eq_ignore_ascii_case("Content-Length"));
+        for (name, value) in headers {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        if !self.body.is_empty() {
+            head.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+        head.push_str("\r\n");
+
+        writer.write_all(head.as_bytes())?;
+        writer.write_all(&self.body)?;
+        writer.flush()
+    }
+
+    /// Returns the value of the first query parameter called `key`.
+    pub fn query_param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Reads a line ending in `\n` or `\r\n`, without the line ending. Returns
+/// `None` at the end of the input.
+pub(super) fn read_line<R: BufRead>(reader: &mut R) -> Result<Option<String>, ParseError> {
+    let mut line = Vec::new();
+    // One more byte than the limit, to tell a line of exactly the limit from
+    // a longer one.
+    let read = reader
+        .take(MAX_LINE_LEN as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    } else if line.len() > MAX_LINE_LEN {
+        return Err(ParseError::LineTooLong);
+    }
+
+    String::from_utf8(line).map(Some).map_err(|_| ParseError::LineTooLong)
+}
+
+fn parse_request_line(line: &str) -> Result<(Method, &str, Version), ParseError> {
+    let parts: Vec<&str> = line.split(' ').collect();
+
+    let [method, target, version] = parts[..] else {
+        return Err(ParseError::MalformedRequestLine(line.to_string()));
+    };
+    if method.is_empty() || target.is_empty() {
+        return Err(ParseError::MalformedRequestLine(line.to_string()));
+    }
+
+    let version = parse_version(version)?;
+
+    Ok((method.parse()?, target, version))
+}
+
+pub(super) fn parse_version(version: &str) -> Result<Version, ParseError> {
+    match version {
+        "HTTP/1.1" => Ok(Version::Http11),
+        "HTTP/1.0" => Ok(Version::Http10),
+        _ => Err(ParseError::UnsupportedVersion(version.to_string())),
+    }
+}
+
+fn parse_target(target: &str) -> Result<(String, Vec<(String, String)>), ParseError> {
+    let invalid = || ParseError::InvalidTarget(target.to_string());
+
+    if !target.starts_with('/') {
+        return Err(invalid());
+    }
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (target, ""),
+    };
+
+    let path = percent_decode(path, false).ok_or_else(invalid)?;
+
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((percent_decode(key, true)?, percent_decode(value, true)?))
+        })
+        .collect::<Option<_>>()
+        .ok_or_else(invalid)?;
+
+    Ok((path, query))
+}
+
+/// Decodes `%XX` escapes, and `+` as a space in query strings. Returns
+/// `None` for a broken escape or if the result isn't valid UTF-8.
+fn percent_decode(input: &str, plus_is_space: bool) -> Option<String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut input = input.bytes();
+
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let high = (input.next()? as char).to_digit(16)?;
+                let low = (input.next()? as char).to_digit(16)?;
+                bytes.push((high * 16 + low) as u8);
+            }
+            b'+' if plus_is_space => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+/// Escapes every byte but letters, digits and `-._~`, and `/` in paths.
+fn percent_encode(input: &str, is_path: bool) -> String {
+    let mut encoded = String::with_capacity(input.len());
+
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) || (is_path && byte == b'/') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    encoded
+}
+
+pub(super) fn parse_headers<R: BufRead>(reader: &mut R) -> Result<Headers, ParseError> {
+    let mut headers = Headers::default();
+
+    loop {
+        // A connection closing in the middle of the headers is treated like
+        // the end of them: whatever was sent is all there is.
+        let line = match read_line(reader)? {
+            Some(line) if !line.is_empty() => line,
+            _ => return Ok(headers),
+        };
+
+        if headers.len() == MAX_HEADERS {
+            return Err(ParseError::TooManyHeaders);
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| ParseError::MalformedHeader(line.clone()))?;
+
+        // This also rejects obsolete line folding, where a header continues
+        // on a line starting with whitespace.
+        let valid_name = !name.is_empty()
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&byte));
+        if !valid_name {
+            return Err(ParseError::MalformedHeader(line.clone()));
+        }
+
+        headers.append(name, value.trim());
+    }
+}
+
+fn parse_host(headers: &Headers) -> Result<Option<Host>, ParseError> {
+    let mut values = headers.get_all("Host");
+
+    match (values.next(), values.next()) {
+        (None, _) => Ok(None),
+        (Some(value), None) => value.parse().map(Some),
+        (Some(value), Some(_)) => Err(ParseError::InvalidHost(value.to_string())),
+    }
+}
+
+fn content_length(headers: &Headers) -> Result<usize, ParseError> {
+    let mut length = None;
+
+    for value in headers.get_all("Content-Length") {
+        let parsed: usize = value
+            .parse()
+            .map_err(|_| ParseError::InvalidContentLength(value.to_string()))?;
+
+        if length.is_some_and(|length| length != parsed) {
+            return Err(ParseError::InvalidContentLength(value.to_string()));
+        }
+        length = Some(parsed);
+    }
+
+    Ok(length.unwrap_or(0))
+}
+
+pub(super) fn read_body<R: BufRead>(reader: &mut R, headers: &Headers) -> Result<Vec<u8>, ParseError> {
+    if let Some(encoding) = headers.get("Transfer-Encoding") {
+        return Err(ParseError::UnsupportedTransferEncoding(encoding.to_string()));
+    }
+
+    let expected = content_length(headers)?;
+    if expected > MAX_BODY_LEN {
+        return Err(ParseError::BodyTooLarge(expected));
+    }
+
+    let mut body = Vec::with_capacity(expected);
+    let received = reader.take(expected as u64).read_to_end(&mut body)?;
+
+    if received < expected {
+        return Err(ParseError::IncompleteBody { expected, received });
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Request, ParseError> {
+        Request::parse(&mut input.as_bytes())
+    }
+
+    #[test]
+    fn parses_simple_get() {
+        let request = parse("GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+
+        assert_eq!(Method::Get, request.method);
+        assert_eq!("/", request.path);
+        assert!(request.query.is_empty());
+        assert_eq!(Version::Http11, request.version);
+        assert_eq!(Some("localhost"), request.headers.get("Host"));
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn accepts_bare_newlines() {
+        let request = parse("GET /sleep HTTP/1.0\nHost: localhost\n\n").unwrap();
+
+        assert_eq!("/sleep", request.path);
+        assert_eq!(Version::Http10, request.version);
+        assert_eq!(1, request.headers.len());
+    }
+
+    #[test]
+    fn decodes_path_and_query() {
+        let request = parse("GET /a%20file.html?name=J%C3%BCrgen+M&flag&empty= HTTP/1.1\r\n\r\n").unwrap();
+
+        assert_eq!("/a file.html", request.path);
+        assert_eq!(Some("Jürgen M"), request.query_param("name"));
+        assert_eq!(Some(""), request.query_param("flag"));
+        assert_eq!(Some(""), request.query_param("empty"));
+        assert_eq!(None, request.query_param("missing"));
+    }
+
+    #[test]
+    fn header_lookup_ignores_case_and_trims_values() {
+        let request = parse("GET / HTTP/1.1\r\ncontent-TYPE:   text/plain  \r\nAccept: a\r\nAccept: b\r\n\r\n").unwrap();
+
+        assert_eq!(Some("text/plain"), request.headers.get("Content-Type"));
+        assert_eq!(vec!["a", "b"], request.headers.get_all("accept").collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reads_body_of_content_length() {
+        let mut input = "POST /submit HTTP/1.1\r\nContent-Length: 5\r\n\r\nhelloGET".as_bytes();
+
+        let request = Request::parse(&mut input).unwrap();
+
+        assert_eq!(Method::Post, request.method);
+        assert_eq!(b"hello", &request.body[..]);
+        assert_eq!(b"GET", input);
+    }
+
+    #[test]
+    fn parses_pipelined_requests_in_turn() {
+        let mut input = "GET /one HTTP/1.1\r\n\r\nGET /two HTTP/1.1\r\n\r\n".as_bytes();
+
+        assert_eq!("/one", Request::parse(&mut input).unwrap().path);
+        assert_eq!("/two", Request::parse(&mut input).unwrap().path);
+        assert!(matches!(Request::parse(&mut input), Err(ParseError::Empty)));
+    }
+
+    #[test]
+    fn rejects_empty_input() {
+        assert!(matches!(parse(""), Err(ParseError::Empty)));
+    }
+
+    #[test]
+    fn rejects_malformed_request_lines() {
+        for line in ["GET /", "GET  / HTTP/1.1", "GET / HTTP/1.1 extra", " / HTTP/1.1"] {
+            let input = format!("{line}\r\n\r\n");
+            assert!(
+                matches!(parse(&input), Err(ParseError::MalformedRequestLine(_))),
+                "{line:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_method_and_version() {
+        assert!(matches!(
+            parse("BREW / HTTP/1.1\r\n\r\n"),
+            Err(ParseError::UnknownMethod(method)) if method == "BREW"
+        ));
+        assert!(matches!(
+            parse("GET / HTTP/2.0\r\n\r\n"),
+            Err(ParseError::UnsupportedVersion(version)) if version == "HTTP/2.0"
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_targets() {
+        for target in ["index.html", "/%zz", "/%4", "/?q=%", "/%FF"] {
+            let input = format!("GET {target} HTTP/1.1\r\n\r\n");
+            assert!(
+                matches!(parse(&input), Err(ParseError::InvalidTarget(_))),
+                "{target:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        for header in ["no colon", ": no name", "bad name: x", " folded: x", "a/b: x"] {
+            let input = format!("GET / HTTP/1.1\r\n{header}\r\n\r\n");
+            assert!(
+                matches!(parse(&input), Err(ParseError::MalformedHeader(_))),
+                "{header:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_too_many_headers() {
+        let headers: String = (0..=MAX_HEADERS).map(|i| format!("X-{i}: v\r\n")).collect();
+
+        let input = format!("GET / HTTP/1.1\r\n{headers}\r\n");
+
+        assert!(matches!(parse(&input), Err(ParseError::TooManyHeaders)));
+    }
+
+    #[test]
+    fn rejects_overlong_lines() {
+        let input = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_LEN));
+
+        assert!(matches!(parse(&input), Err(ParseError::LineTooLong)));
+    }
+
+    #[test]
+    fn rejects_invalid_content_length() {
+        for headers in [
+            "Content-Length: abc",
+            "Content-Length: -1",
+            "Content-Length: 1\r\nContent-Length: 2",
+        ] {
+            let input = format!("POST / HTTP/1.1\r\n{headers}\r\n\r\nab");
+            assert!(
+                matches!(parse(&input), Err(ParseError::InvalidContentLength(_))),
+                "{headers:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_repeated_identical_content_length() {
+        let request = parse("POST / HTTP/1.1\r\nContent-Length: 2\r\nContent-Length: 2\r\n\r\nab").unwrap();
+
+        assert_eq!(b"ab", &request.body[..]);
+    }
+
+    #[test]
+    fn rejects_chunked_bodies() {
+        let input = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n";
+
+        assert!(matches!(parse(input), Err(ParseError::UnsupportedTransferEncoding(_))));
+    }
+
+    #[test]
+    fn rejects_bodies_over_the_limit() {
+        let input = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_LEN + 1);
+
+        assert!(matches!(parse(&input), Err(ParseError::BodyTooLarge(_))));
+    }
+
+    #[test]
+    fn rejects_incomplete_body() {
+        let input = "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort";
+
+        assert!(matches!(
+            parse(input),
+            Err(ParseError::IncompleteBody { expected: 10, received: 5 })
+        ));
+    }
+
+    #[test]
+    fn parses_host_with_and_without_port() {
+        let host = |value: &str| {
+            parse(&format!("GET / HTTP/1.1\r\nHost: {value}\r\n\r\n"))
+                .unwrap()
+                .host
+                .unwrap()
+        };
+
+        assert_eq!(Host { name: "example.com".into(), port: None }, host("Example.COM."));
+        assert_eq!(Host { name: "localhost".into(), port: Some(7878) }, host("localhost:7878"));
+        assert_eq!(Host { name: "::1".into(), port: Some(80) }, host("[::1]:80"));
+        assert_eq!("[::1]:80", host("[::1]:80").to_string());
+        assert_eq!(None, parse("GET / HTTP/1.0\r\n\r\n").unwrap().host);
+    }
+
+    #[test]
+    fn rejects_invalid_or_repeated_host() {
+        for input in [
+            "GET / HTTP/1.1\r\nHost: exa mple.com\r\n\r\n",
+            "GET / HTTP/1.1\r\nHost: example.com:http\r\n\r\n",
+            "GET / HTTP/1.1\r\nHost: [nope]:80\r\n\r\n",
+            "GET / HTTP/1.1\r\nHost: a.com\r\nHost: b.com\r\n\r\n",
+        ] {
+            assert!(matches!(parse(input), Err(ParseError::InvalidHost(_))), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn written_requests_parse_back_the_same() {
+        let mut request = Request::new(Method::Post, "/a b/ü")
+            .header("Content-Type", "text/plain")
+            .body("hello");
+        request.query = vec![("q".into(), "x&y=z".into()), ("empty".into(), String::new())];
+        request.host = Some("example.com:8080".parse().unwrap());
+
+        let mut sent = Vec::new();
+        request.write_to(&mut sent).unwrap();
+        let parsed = Request::parse(&mut &sent[..]).unwrap();
+
+        assert!(sent.starts_with(b"POST /a%20b/%C3%BC?q=x%26y%3Dz&empty= HTTP/1.1\r\n"));
+        assert_eq!(request.path, parsed.path);
+        assert_eq!(request.query, parsed.query);
+        assert_eq!(request.host, parsed.host);
+        assert_eq!(Some("5"), parsed.headers.get("Content-Length"));
+        assert_eq!(request.body, parsed.body);
+    }
+}
@@ -0,0 +1,309 @@
+//! Building, sending and parsing HTTP/1.1 responses.
+
+use std::{
+    fmt,
+    io::{self, BufRead, Read, Write},
+};
+
+use super::{
+    request::{parse_headers, parse_version, read_body, read_line, MAX_BODY_LEN},
+    Headers, ParseError,
+};
+
+/// Every status, to look one up by its code.
+const STATUSES: [Status; 14] = [
+    Status::Ok,
+    Status::Created,
+    Status::NoContent,
+    Status::MovedPermanently,
+    Status::NotModified,
+    Status::BadRequest,
+    Status::Forbidden,
+    Status::NotFound,
+    Status::MethodNotAllowed,
+    Status::PayloadTooLarge,
+    Status::TooManyRequests,
+    Status::InternalServerError,
+    Status::NotImplemented,
+    Status::ServiceUnavailable,
+];
+
+/// The status of a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Created,
+    NoContent,
+    MovedPermanently,
+    NotModified,
+    BadRequest,
+    Forbidden,
+    NotFound,
+    MethodNotAllowed,
+    PayloadTooLarge,
+    TooManyRequests,
+    InternalServerError,
+    NotImplemented,
+    ServiceUnavailable,
+}
+
+impl Status {
+    /// Returns the status with the numeric code `code`, if it is one of the
+    /// statuses known here.
+    pub fn from_code(code: u16) -> Option<Status> {
+        STATUSES.into_iter().find(|status| status.code() == code)
+    }
+
+    /// Returns the numeric status code, like `404`.
+    pub fn code(self) -> u16 {
+        match self {
+            Status::Ok => 200,
+            Status::Created => 201,
+            Status::NoContent => 204,
+            Status::MovedPermanently => 301,
+            Status::NotModified => 304,
+            Status::BadRequest => 400,
+            Status::Forbidden => 403,
+            Status::NotFound => 404,
+            Status::MethodNotAllowed => 405,
+            Status::PayloadTooLarge => 413,
+            Status::TooManyRequests => 429,
+            Status::InternalServerError => 500,
+            Status::NotImplemented => 501,
+            Status::ServiceUnavailable => 503,
+        }
+    }
+
+    /// Returns the reason phrase sent after the code, like `Not Found`.
+    pub fn reason(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Created => "Created",
+            Status::NoContent => "No Content",
+            Status::MovedPermanently => "Moved Permanently",
+            Status::NotModified => "Not Modified",
+            Status::BadRequest => "Bad Request",
+            Status::Forbidden => "Forbidden",
+            Status::NotFound => "Not Found",
+            Status::MethodNotAllowed => "Method Not Allowed",
+            Status::PayloadTooLarge => "Payload Too Large",
+            Status::TooManyRequests => "Too Many Requests",
+            Status::InternalServerError => "Internal Server Error",
+            Status::NotImplemented => "Not Implemented",
+            Status::ServiceUnavailable => "Service Unavailable",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.code(), self.reason())
+    }
+}
+
+/// An HTTP/1.1 response, built with chained calls like
+/// `Response::new(Status::Ok).header("Content-Type", "text/html").body(page)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: Status,
+    pub headers: Headers,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Creates a response with the given status, no headers and no body.
+    pub fn new(status: Status) -> Response {
+        Response {
+            status,
+            headers: Headers::default(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a header to the response.
+    ///
+    /// Headers are sent in the order they were added, and adding one twice
+    /// sends it twice. `Content-Length` is always computed from the body, so
+    /// setting it here has no effect.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Response {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the body of the response, replacing any previous one.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Response {
+        self.body = body.into();
+        self
+    }
+
+    /// Parses a response from any buffered reader, such as the answer of a
+    /// server.
+    ///
+    /// The body is as long as the `Content-Length` header says. Without one,
+    /// it is everything up to the end of the input.
+    ///
+    /// # Errors
+    ///
+    /// The `parse` function will return an error if reading fails, if the
+    /// response is malformed or if its status isn't known.
+    pub fn parse<R: BufRead>(reader: &mut R) -> Result<Response, ParseError> {
+        let line = read_line(reader)?.ok_or(ParseError::Empty)?;
+        let malformed = || ParseError::MalformedStatusLine(line.clone());
+
+        let (version, rest) = line.split_once(' ').ok_or_else(malformed)?;
+        parse_version(version)?;
+        let code = rest.split_once(' ').map_or(rest, |(code, _)| code);
+        let status = code
+            .parse()
+            .ok()
+            .and_then(Status::from_code)
+            .ok_or_else(|| ParseError::UnknownStatus(code.to_string()))?;
+
+        let headers = parse_headers(reader)?;
+        let body = if headers.get("Content-Length").is_some() {
+            read_body(reader, &headers)?
+        } else {
+            let mut body = Vec::new();
+            reader.take(MAX_BODY_LEN as u64 + 1).read_to_end(&mut body)?;
+            if body.len() > MAX_BODY_LEN {
+                return Err(ParseError::BodyTooLarge(body.len()));
+            }
+            body
+        };
+
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Writes the status line, the headers and the body to `writer`.
+    ///
+    /// Since `&TcpStream` implements `Write`, this sends the response to a
+    /// client when given a connection.
+    ///
+    /// # Errors
+    ///
+    /// The `write_to` function will return an error if writing to `writer`
+    /// fails, in which case part of the response may have been sent.
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_head(writer, self.body.len() as u64)?;
+        writer.write_all(&self.body)?;
+        writer.flush()
+    }
+
+    /// Writes the status line and the headers, announcing a body of
+    /// `content_length` bytes instead of the one in the response. The
+    /// caller sends that body afterwards.
+    pub(crate) fn write_head<W: Write + ?Sized>(&self, writer: &mut W, content_length: u64) -> io::Result<()> {
+        // The whole head is written at once so it doesn't go out in many
+        // tiny packets.
+        let mut head = format!("HTTP/1.1 {}\r\n", self.status);
+
+        let headers = self
+            .headers
+            .iter()
+            .filter(|(name, _)| !name.eq_ignore_ascii_case("Content-Length"));
+        for (name, value) in headers {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        head.push_str(&format!("Content-Length: {content_length}\r\n\r\n"));
+
+        writer.write_all(head.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(response: &Response) -> String {
+        let mut sent = Vec::new();
+        response.write_to(&mut sent).unwrap();
+        String::from_utf8(sent).unwrap()
+    }
+
+    #[test]
+    fn writes_status_headers_and_body() {
+        let response = Response::new(Status::Ok)
+            .header("Content-Type", "text/html")
+            .body("<p>hi</p>");
+
+        assert_eq!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 9\r\n\r\n<p>hi</p>",
+            written(&response)
+        );
+    }
+
+    #[test]
+    fn empty_response_has_zero_content_length() {
+        let response = Response::new(Status::NotFound);
+
+        assert_eq!(
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+            written(&response)
+        );
+    }
+
+    #[test]
+    fn content_length_always_matches_the_body() {
+        let response = Response::new(Status::Ok)
+            .header("content-length", "100")
+            .body(vec![0u8; 3]);
+
+        let sent = written(&response);
+
+        assert!(sent.contains("Content-Length: 3\r\n"));
+        assert!(!sent.contains("100"));
+    }
+
+    #[test]
+    fn repeated_headers_are_all_sent_in_order() {
+        let response = Response::new(Status::NoContent)
+            .header("Set-Cookie", "a=1")
+            .header("Set-Cookie", "b=2");
+
+        assert_eq!(
+            vec!["a=1", "b=2"],
+            response.headers.get_all("set-cookie").collect::<Vec<_>>()
+        );
+        assert!(written(&response).contains("Set-Cookie: a=1\r\nSet-Cookie: b=2\r\n"));
+    }
+
+    #[test]
+    fn status_displays_code_and_reason() {
+        assert_eq!("429 Too Many Requests", Status::TooManyRequests.to_string());
+        assert_eq!(503, Status::ServiceUnavailable.code());
+    }
+
+    #[test]
+    fn written_responses_parse_back_the_same() {
+        let response = Response::new(Status::TooManyRequests)
+            .header("Retry-After", "2")
+            .body("slow down");
+
+        let parsed = Response::parse(&mut written(&response).as_bytes()).unwrap();
+
+        assert_eq!(Status::TooManyRequests, parsed.status);
+        assert_eq!(Some("2"), parsed.headers.get("retry-after"));
+        assert_eq!(b"slow down", &parsed.body[..]);
+    }
+
+    #[test]
+    fn parse_reads_to_the_end_without_content_length() {
+        let parsed = Response::parse(&mut &b"HTTP/1.0 200 OK\r\n\r\nall of it"[..]).unwrap();
+
+        assert_eq!(b"all of it", &parsed.body[..]);
+    }
+
+    #[test]
+    fn parse_rejects_broken_status_lines() {
+        let parse = |input: &str| Response::parse(&mut input.as_bytes()).unwrap_err();
+
+        assert!(matches!(parse("HTTP/1.1\r\n\r\n"), ParseError::MalformedStatusLine(_)));
+        assert!(matches!(parse("HTTP/1.1 299 Odd\r\n\r\n"), ParseError::UnknownStatus(_)));
+        assert!(matches!(parse("HTTP/2 200 OK\r\n\r\n"), ParseError::UnsupportedVersion(_)));
+        assert_eq!(Some(Status::NotFound), Status::from_code(404));
+    }
+}
@@ -0,0 +1,240 @@
+//! Routing requests to handlers by method and path, and by site.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::{Handler, Method, Request, Response, Status};
+
+/// A handler for one method and path, written down as a value so routes can
+/// be declared next to their function, as `#[route(GET, "/")]` from
+/// hello_macro_derive does, and handed to a `Router` all at once.
+#[derive(Debug, Clone, Copy)]
+pub struct Route {
+    pub method: Method,
+    pub path: &'static str,
+    pub handler: fn(&Request, &mut dyn Write) -> io::Result<Status>,
+}
+
+/// A route table: handlers for exact paths and methods, with a fallback for
+/// every other request.
+#[derive(Default)]
+pub struct Router {
+    routes: Vec<(Method, String, Box<dyn Handler>)>,
+    fallback: Option<Box<dyn Handler>>,
+}
+
+impl Router {
+    /// Creates a router without routes, which answers everything with
+    /// `404 Not Found`.
+    pub fn new() -> Router {
+        Router::default()
+    }
+
+    /// Sends `method` requests for exactly `path` to `handler`.
+    ///
+    /// A request for a routed path with another method gets
+    /// `405 Method Not Allowed`, listing the methods it has routes for.
+    pub fn route(mut self, method: Method, path: &str, handler: impl Handler + 'static) -> Router {
+        self.routes.push((method, path.to_string(), Box::new(handler)));
+        self
+    }
+
+    /// Adds every route of `routes`, as `route` would, in order.
+    pub fn routes(self, routes: &[Route]) -> Router {
+        routes.iter().fold(self, |router, route| {
+            router.route(route.method, route.path, route.handler)
+        })
+    }
+
+    /// Sends the requests no route matches to `handler`, such as the
+    /// `StaticFiles` of the site.
+    pub fn fallback(mut self, handler: impl Handler + 'static) -> Router {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+}
+
+impl Handler for Router {
+    fn handle(&self, request: &Request, writer: &mut dyn Write) -> io::Result<Status> {
+        let mut allowed = Vec::new();
+
+        for (method, path, handler) in &self.routes {
+            if *path == request.path {
+                if *method == request.method {
+                    return handler.handle(request, writer);
+                }
+                allowed.push(method.to_string());
+            }
+        }
+
+        if !allowed.is_empty() {
+            return send_status(
+                Response::new(Status::MethodNotAllowed).header("Allow", allowed.join(", ")),
+                writer,
+            );
+        }
+
+        match &self.fallback {
+            Some(fallback) => fallback.handle(request, writer),
+            None => send_status(Response::new(Status::NotFound), writer),
+        }
+    }
+}
+
+/// Several sites behind one listener, told apart by the `Host` header of
+/// each request.
+///
+/// Host names are matched without their port and regardless of case.
+/// Requests without a `Host` header, or for a host that isn't known, go to
+/// the default site, or get `404 Not Found` if there is none.
+#[derive(Default)]
+pub struct VirtualHosts {
+    hosts: HashMap<String, Router>,
+    default: Option<Router>,
+}
+
+impl VirtualHosts {
+    pub fn new() -> VirtualHosts {
+        VirtualHosts::default()
+    }
+
+    /// Serves the site `name` with `router`, replacing any router it had.
+    pub fn host(mut self, name: &str, router: Router) -> VirtualHosts {
+        let name = name.strip_suffix('.').unwrap_or(name);
+
+        self.hosts.insert(name.to_ascii_lowercase(), router);
+        self
+    }
+
+    /// Serves requests for unknown hosts with `router`.
+    pub fn default_host(mut self, router: Router) -> VirtualHosts {
+        self.default = Some(router);
+        self
+    }
+}
+
+impl Handler for VirtualHosts {
+    fn handle(&self, request: &Request, writer: &mut dyn Write) -> io::Result<Status> {
+        let site = request
+            .host
+            .as_ref()
+            .and_then(|host| self.hosts.get(&host.name))
+            .or(self.default.as_ref());
+
+        match site {
+            Some(router) => router.handle(request, writer),
+            None => send_status(Response::new(Status::NotFound), writer),
+        }
+    }
+}
+
+fn send_status(response: Response, writer: &mut dyn Write) -> io::Result<Status> {
+    let status = response.status;
+
+    response
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(status.to_string())
+        .write_to(writer)?;
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A handler answering with a fixed body, to tell handlers apart.
+    fn answer(body: &'static str) -> impl Handler {
+        move |_: &Request, writer: &mut dyn Write| -> io::Result<Status> {
+            Response::new(Status::Ok).body(body).write_to(writer)?;
+            Ok(Status::Ok)
+        }
+    }
+
+    fn send(handler: &dyn Handler, request: &str) -> (Status, String) {
+        let request = Request::parse(&mut request.as_bytes()).unwrap();
+        let mut sent = Vec::new();
+
+        let status = handler.handle(&request, &mut sent).unwrap();
+        let sent = String::from_utf8(sent).unwrap();
+        let (_, body) = sent.split_once("\r\n\r\n").unwrap();
+        (status, body.to_string())
+    }
+
+    #[test]
+    fn routes_match_method_and_path() {
+        let router = Router::new()
+            .route(Method::Get, "/a", answer("get a"))
+            .route(Method::Post, "/a", answer("post a"))
+            .fallback(answer("fallback"));
+
+        assert_eq!("get a", send(&router, "GET /a HTTP/1.1\r\n\r\n").1);
+        assert_eq!("post a", send(&router, "POST /a HTTP/1.1\r\n\r\n").1);
+        assert_eq!("fallback", send(&router, "GET /a/b HTTP/1.1\r\n\r\n").1);
+    }
+
+    #[test]
+    fn route_tables_are_added_in_order() {
+        fn index(_: &Request, writer: &mut dyn Write) -> io::Result<Status> {
+            Response::new(Status::Ok).body("index").write_to(writer)?;
+            Ok(Status::Ok)
+        }
+        const ROUTES: &[Route] = &[
+            Route { method: Method::Get, path: "/", handler: index },
+            Route { method: Method::Head, path: "/", handler: index },
+        ];
+
+        let router = Router::new().routes(ROUTES).route(Method::Get, "/", answer("shadowed"));
+
+        assert_eq!("index", send(&router, "GET / HTTP/1.1\r\n\r\n").1);
+        assert_eq!(Status::Ok, send(&router, "HEAD / HTTP/1.1\r\n\r\n").0);
+    }
+
+    #[test]
+    fn other_methods_on_a_route_are_not_allowed() {
+        let router = Router::new()
+            .route(Method::Get, "/a", answer("get a"))
+            .route(Method::Put, "/a", answer("put a"));
+        let request = Request::parse(&mut &b"DELETE /a HTTP/1.1\r\n\r\n"[..]).unwrap();
+
+        let mut sent = Vec::new();
+        let status = router.handle(&request, &mut sent).unwrap();
+
+        assert_eq!(Status::MethodNotAllowed, status);
+        assert!(String::from_utf8(sent).unwrap().contains("Allow: GET, PUT\r\n"));
+    }
+
+    #[test]
+    fn router_without_fallback_answers_not_found() {
+        let (status, _) = send(&Router::new(), "GET / HTTP/1.1\r\n\r\n");
+
+        assert_eq!(Status::NotFound, status);
+    }
+
+    #[test]
+    fn virtual_hosts_dispatch_on_the_host_header() {
+        let hosts = VirtualHosts::new()
+            .host("blog.example.com", Router::new().fallback(answer("blog")))
+            .host("Shop.Example.com.", Router::new().fallback(answer("shop")))
+            .default_host(Router::new().fallback(answer("default")));
+
+        let body = |host: &str| send(&hosts, &format!("GET / HTTP/1.1\r\nHost: {host}\r\n\r\n")).1;
+
+        assert_eq!("blog", body("blog.example.com"));
+        assert_eq!("blog", body("BLOG.example.com:8080"));
+        assert_eq!("shop", body("shop.example.com"));
+        assert_eq!("default", body("other.example.com"));
+        assert_eq!("default", send(&hosts, "GET / HTTP/1.0\r\n\r\n").1);
+    }
+
+    #[test]
+    fn unknown_host_without_default_is_not_found() {
+        let hosts = VirtualHosts::new().host("a.com", Router::new().fallback(answer("a")));
+
+        let (status, _) = send(&hosts, "GET / HTTP/1.1\r\nHost: b.com\r\n\r\n");
+
+        assert_eq!(Status::NotFound, status);
+    }
+}
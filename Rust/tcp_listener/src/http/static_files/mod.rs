@@ -0,0 +1,352 @@
+//! Serving the files of a directory.
+
+use std::{
+    fs::{self, File},
+    io::{self, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{Method, Request, Response, Status};
+
+/// How much of a file is read into memory at once while sending it.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A handler that answers requests with the files under a root directory.
+///
+/// The path of a request is looked up relative to the root, and a directory
+/// is answered with its `index.html`. Paths that try to leave the root, with
+/// `..` or through a symbolic link, get `403 Forbidden`. Missing files get
+/// `404 Not Found`, with the `404.html` of the root as the body if there is
+/// one.
+#[derive(Debug, Clone)]
+pub struct StaticFiles {
+    root: PathBuf,
+}
+
+impl StaticFiles {
+    /// Creates a handler serving the files under `root`.
+    ///
+    /// The directory isn't checked here: if it doesn't exist, every request
+    /// is answered with `404 Not Found`.
+    pub fn serve(root: impl Into<PathBuf>) -> StaticFiles {
+        StaticFiles { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Answers `request` with the file it asks for, writing the response to
+    /// `writer`, and returns the status that was sent.
+    ///
+    /// Only `GET` and `HEAD` requests are served, anything else gets
+    /// `405 Method Not Allowed`. The file is sent in chunks, so even large
+    /// files never sit in memory whole.
+    ///
+    /// # Errors
+    ///
+    /// The `handle` function will return an error if writing the response
+    /// fails, or if reading the file fails after the response was started.
+    /// Errors opening the file are answered with a status instead.
+    pub fn handle<W>(&self, request: &Request, writer: &mut W) -> io::Result<Status>
+    where
+        W: Write + ?Sized,
+    {
+        if !matches!(request.method, Method::Get | Method::Head) {
+            let response = Response::new(Status::MethodNotAllowed).header("Allow", "GET, HEAD");
+            return send_error(response, writer);
+        }
+
+        let (path, file, len) = match self.open(&request.path) {
+            Ok(found) => found,
+            Err(Status::NotFound) => return self.not_found(writer),
+            Err(status) => return send_error(Response::new(status), writer),
+        };
+
+        let response = Response::new(Status::Ok).header("Content-Type", mime_type(&path));
+        response.write_head(writer, len)?;
+        if request.method == Method::Get {
+            send_chunks(file.take(len), writer)?;
+        }
+        writer.flush()?;
+
+        Ok(Status::Ok)
+    }
+
+    /// Finds the file for the path of a request and opens it. Returns the
+    /// status to answer with if that isn't possible.
+    fn open(&self, path: &str) -> Result<(PathBuf, File, u64), Status> {
+        let mut resolved = self.root.clone();
+
+        for part in path.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => return Err(Status::Forbidden),
+                // A backslash separates paths on Windows, so it could be used
+                // to sneak a `..` past the check above.
+                _ if part.contains(['\\', '\0']) => return Err(Status::Forbidden),
+                _ => resolved.push(part),
+            }
+        }
+
+        if resolved.is_dir() {
+            resolved.push("index.html");
+        }
+
+        // Symbolic links are followed, but must not lead outside the root.
+        let root = self.root.canonicalize().map_err(status_for)?;
+        let resolved = resolved.canonicalize().map_err(status_for)?;
+        if !resolved.starts_with(&root) {
+            return Err(Status::Forbidden);
+        }
+
+        let file = File::open(&resolved).map_err(status_for)?;
+        let metadata = file.metadata().map_err(status_for)?;
+        if !metadata.is_file() {
+            return Err(Status::NotFound);
+        }
+
+        Ok((resolved, file, metadata.len()))
+    }
+
+    fn not_found<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<Status> {
+        let response = Response::new(Status::NotFound);
+
+        match fs::read(self.root.join("404.html")) {
+            Ok(page) => {
+                response
+                    .header("Content-Type", "text/html; charset=utf-8")
+                    .body(page)
+                    .write_to(writer)?;
+                Ok(Status::NotFound)
+            }
+            Err(_) => send_error(response, writer),
+        }
+    }
+}
+
+/// Returns the `Content-Type` of a file, based on its extension.
+///
+/// Unknown extensions get `application/octet-stream`, which browsers
+/// download instead of trying to display.
+pub fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "woff2" => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+fn status_for(err: io::Error) -> Status {
+    match err.kind() {
+        ErrorKind::NotFound => Status::NotFound,
+        ErrorKind::PermissionDenied => Status::Forbidden,
+        _ => Status::InternalServerError,
+    }
+}
+
+/// Sends a response whose body is just its status, as plain text.
+fn send_error<W: Write + ?Sized>(response: Response, writer: &mut W) -> io::Result<Status> {
+    let status = response.status;
+
+    response
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(status.to_string())
+        .write_to(writer)?;
+
+    Ok(status)
+}
+
+/// Copies everything from `reader` to `writer`, one chunk at a time.
+fn send_chunks<R: Read, W: Write + ?Sized>(mut reader: R, writer: &mut W) -> io::Result<()> {
+    let mut chunk = vec![0; CHUNK_SIZE];
+
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(read) => writer.write_all(&chunk[..read])?,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env, process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// A directory that is removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> TempDir {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+            let name = format!(
+                "static-files-{}-{}",
+                process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = env::temp_dir().join(name).join("root");
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn file(&self, name: &str, contents: &[u8]) {
+            let path = self.0.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(self.0.parent().unwrap());
+        }
+    }
+
+    fn get(files: &StaticFiles, request: &str) -> (Status, String) {
+        let request = Request::parse(&mut request.as_bytes()).unwrap();
+        let mut sent = Vec::new();
+
+        let status = files.handle(&request, &mut sent).unwrap();
+        (status, String::from_utf8(sent).unwrap())
+    }
+
+    #[test]
+    fn serves_file_with_content_type() {
+        let dir = TempDir::new();
+        dir.file("css/site.css", b"body {}");
+        let files = StaticFiles::serve(&dir.0);
+
+        let (status, sent) = get(&files, "GET /css/site.css HTTP/1.1\r\n\r\n");
+
+        assert_eq!(Status::Ok, status);
+        assert!(sent.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(sent.contains("Content-Type: text/css; charset=utf-8\r\n"));
+        assert!(sent.ends_with("Content-Length: 7\r\n\r\nbody {}"));
+    }
+
+    #[test]
+    fn directory_is_served_with_its_index() {
+        let dir = TempDir::new();
+        dir.file("index.html", b"<h1>Hi</h1>");
+        let files = StaticFiles::serve(&dir.0);
+
+        let (status, sent) = get(&files, "GET / HTTP/1.1\r\n\r\n");
+
+        assert_eq!(Status::Ok, status);
+        assert!(sent.ends_with("<h1>Hi</h1>"));
+    }
+
+    #[test]
+    fn parent_directories_are_forbidden() {
+        let dir = TempDir::new();
+        fs::write(dir.0.parent().unwrap().join("secret"), b"hidden").unwrap();
+        let files = StaticFiles::serve(&dir.0);
+
+        for target in ["/../secret", "/a/../../secret", "/%2e%2e/secret", "/..%5csecret"] {
+            let (status, sent) = get(&files, &format!("GET {target} HTTP/1.1\r\n\r\n"));
+
+            assert_eq!(Status::Forbidden, status, "{target}");
+            assert!(!sent.contains("hidden"));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_out_of_the_root_are_forbidden() {
+        let dir = TempDir::new();
+        fs::write(dir.0.parent().unwrap().join("secret"), b"hidden").unwrap();
+        std::os::unix::fs::symlink("../secret", dir.0.join("link")).unwrap();
+        let files = StaticFiles::serve(&dir.0);
+
+        let (status, _) = get(&files, "GET /link HTTP/1.1\r\n\r\n");
+
+        assert_eq!(Status::Forbidden, status);
+    }
+
+    #[test]
+    fn missing_file_uses_the_404_page_of_the_root() {
+        let dir = TempDir::new();
+        let files = StaticFiles::serve(&dir.0);
+
+        let (status, sent) = get(&files, "GET /nope.html HTTP/1.1\r\n\r\n");
+        assert_eq!(Status::NotFound, status);
+        assert!(sent.ends_with("\r\n\r\n404 Not Found"));
+
+        dir.file("404.html", b"<p>Oops!</p>");
+        let (status, sent) = get(&files, "GET /nope.html HTTP/1.1\r\n\r\n");
+        assert_eq!(Status::NotFound, status);
+        assert!(sent.ends_with("<p>Oops!</p>"));
+    }
+
+    #[test]
+    fn head_sends_the_length_without_the_body() {
+        let dir = TempDir::new();
+        dir.file("notes.txt", b"some notes");
+        let files = StaticFiles::serve(&dir.0);
+
+        let (status, sent) = get(&files, "HEAD /notes.txt HTTP/1.1\r\n\r\n");
+
+        assert_eq!(Status::Ok, status);
+        assert!(sent.ends_with("Content-Length: 10\r\n\r\n"));
+    }
+
+    #[test]
+    fn other_methods_are_not_allowed() {
+        let dir = TempDir::new();
+        dir.file("notes.txt", b"some notes");
+        let files = StaticFiles::serve(&dir.0);
+
+        let (status, sent) = get(&files, "DELETE /notes.txt HTTP/1.1\r\n\r\n");
+
+        assert_eq!(Status::MethodNotAllowed, status);
+        assert!(sent.contains("Allow: GET, HEAD\r\n"));
+    }
+
+    #[test]
+    fn files_larger_than_a_chunk_are_sent_whole() {
+        let dir = TempDir::new();
+        let contents: Vec<u8> = (0..CHUNK_SIZE * 3 + 17).map(|i| b'a' + (i % 26) as u8).collect();
+        dir.file("big.bin", &contents);
+        let files = StaticFiles::serve(&dir.0);
+
+        let (_, sent) = get(&files, "GET /big.bin HTTP/1.1\r\n\r\n");
+        let (head, body) = sent.split_once("\r\n\r\n").unwrap();
+
+        assert!(head.contains(&format!("Content-Length: {}", contents.len())));
+        assert!(head.contains("Content-Type: application/octet-stream"));
+        assert_eq!(contents, body.as_bytes());
+    }
+
+    #[test]
+    fn mime_type_comes_from_the_extension() {
+        assert_eq!("text/html; charset=utf-8", mime_type(Path::new("a/index.HTML")));
+        assert_eq!("image/png", mime_type(Path::new("logo.png")));
+        assert_eq!("application/octet-stream", mime_type(Path::new("Makefile")));
+    }
+}
@@ -0,0 +1,171 @@
+//! A small HTTP/1.1 client, mostly to test the server with real requests.
+//!
+//! Every request goes over a new connection, which the client asks the
+//! server to close once it has answered.
+
+use std::{
+    fmt,
+    io::{self, BufReader},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::http::{Method, ParseError, Request, Response};
+
+/// A client for the server at one address.
+#[derive(Debug, Clone)]
+pub struct Client {
+    addr: SocketAddr,
+    timeout: Duration,
+}
+
+/// The error returned when a request gets no usable response.
+#[derive(Debug)]
+pub enum ClientError {
+    /// Connecting or sending the request failed.
+    Io(io::Error),
+    /// The server's answer is not a valid response.
+    Parse(ParseError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::Io(err) => write!(f, "could not send the request: {err}"),
+            ClientError::Parse(err) => write!(f, "invalid response: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Io(err) => Some(err),
+            ClientError::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> ClientError {
+        ClientError::Io(err)
+    }
+}
+
+impl From<ParseError> for ClientError {
+    fn from(err: ParseError) -> ClientError {
+        ClientError::Parse(err)
+    }
+}
+
+impl Client {
+    /// How long to wait for the server by default.
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Creates a client for the server at `addr`.
+    ///
+    /// # Errors
+    ///
+    /// The `new` function will return an error if `addr` doesn't resolve to
+    /// any address.
+    pub fn new(addr: impl ToSocketAddrs) -> io::Result<Client> {
+        let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")
+        })?;
+
+        Ok(Client {
+            addr,
+            timeout: Client::DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Returns the address of the server.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Sets how long to wait for the server to accept the request and to
+    /// answer it.
+    pub fn timeout(mut self, timeout: Duration) -> Client {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends `request` over a new connection and reads the response.
+    ///
+    /// A request without a host is sent with the address of the server as
+    /// its `Host` header.
+    ///
+    /// # Errors
+    ///
+    /// The `send` function will return `ClientError::Io` if the server
+    /// can't be reached, and `ClientError::Parse` if its answer isn't a
+    /// valid response.
+    pub fn send(&self, request: &Request) -> Result<Response, ClientError> {
+        let stream = TcpStream::connect_timeout(&self.addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut request = request.clone().header("Connection", "close");
+        if request.host.is_none() {
+            request.host = Some(self.addr.to_string().parse()?);
+        }
+        request.write_to(&mut &stream)?;
+
+        Ok(Response::parse(&mut BufReader::new(&stream))?)
+    }
+
+    /// Sends a `GET` request for `path`.
+    ///
+    /// # Errors
+    ///
+    /// The `get` function will return an error in the same cases as `send`.
+    pub fn get(&self, path: &str) -> Result<Response, ClientError> {
+        self.send(&Request::new(Method::Get, path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Status;
+    use std::{io::Write, net::TcpListener, thread};
+
+    #[test]
+    fn sends_request_and_reads_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Client::new(listener.local_addr().unwrap()).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = Request::from_stream(&stream).unwrap();
+            Response::new(Status::Ok)
+                .body(format!("{} {}", request.method, request.path))
+                .write_to(&mut stream)
+                .unwrap();
+            request
+        });
+
+        let response = client.get("/hello").unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(Status::Ok, response.status);
+        assert_eq!(b"GET /hello", &response.body[..]);
+        assert_eq!(Some("close"), request.headers.get("Connection"));
+        assert_eq!(Some("127.0.0.1"), request.host.map(|host| host.name).as_deref());
+    }
+
+    #[test]
+    fn invalid_answers_are_parse_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Client::new(listener.local_addr().unwrap()).unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = Request::from_stream(&stream);
+            stream.write_all(b"not http\r\n\r\n").unwrap();
+        });
+
+        assert!(matches!(client.get("/"), Err(ClientError::Parse(_))));
+    }
+}
@@ -0,0 +1,200 @@
+//! Fork-join: running two closures potentially in parallel.
+//!
+//! The calling thread sends the second closure to the pool and runs the
+//! first one itself. When it is done, it takes the second closure back if no
+//! worker has started it yet, so it never sits waiting for a job that is
+//! stuck behind others in the queue.
+
+use std::{
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+use crate::{Job, ThreadPool};
+
+enum Slot<F, R> {
+    /// Nobody has started the closure yet.
+    Waiting(F),
+    /// A worker is running the closure.
+    Running,
+    /// The worker is done, with the result or the panic of the closure.
+    Done(thread::Result<R>),
+    /// The caller took the closure back or collected its result.
+    Taken,
+}
+
+struct JoinState<F, R> {
+    slot: Mutex<Slot<F, R>>,
+    done: Condvar,
+}
+
+impl<F, R> JoinState<F, R>
+where
+    F: FnOnce() -> R,
+{
+    /// Runs the closure on a worker, unless the caller already took it.
+    fn run_on_worker(&self) {
+        let mut slot = self.slot.lock().unwrap();
+        let f = match mem::replace(&mut *slot, Slot::Running) {
+            Slot::Waiting(f) => f,
+            other => {
+                *slot = other;
+                return;
+            }
+        };
+        drop(slot);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+
+        *self.slot.lock().unwrap() = Slot::Done(result);
+        self.done.notify_one();
+    }
+
+    /// Runs the closure on the calling thread if no worker started it, or
+    /// waits for the worker that did.
+    fn finish_on_caller(&self) -> thread::Result<R> {
+        let mut slot = self.slot.lock().unwrap();
+
+        loop {
+            match mem::replace(&mut *slot, Slot::Taken) {
+                Slot::Waiting(f) => {
+                    drop(slot);
+                    return panic::catch_unwind(AssertUnwindSafe(f));
+                }
+                Slot::Done(result) => return result,
+                Slot::Running => {
+                    *slot = Slot::Running;
+                    slot = self.done.wait(slot).unwrap();
+                }
+                Slot::Taken => unreachable!("the result of a join is only taken once"),
+            }
+        }
+    }
+}
+
+impl ThreadPool {
+    /// Runs `a` and `b`, potentially in parallel, and returns both results.
+    ///
+    /// `a` runs on the calling thread while `b` is sent to the pool. If no
+    /// worker has picked `b` up by the time `a` returns, or if the queue of
+    /// the pool is full, the calling thread runs `b` itself. Like with
+    /// `scope`, both closures may borrow from the caller.
+    ///
+    /// Calling `join` from inside a job is fine even when every worker is
+    /// busy, since the caller never waits for a job that hasn't started.
+    ///
+    /// # Panics
+    ///
+    /// The `join` function will panic if `a` or `b` panicked, once both of
+    /// them have finished.
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB + Send,
+        RB: Send,
+    {
+        let state = Arc::new(JoinState {
+            slot: Mutex::new(Slot::Waiting(b)),
+            done: Condvar::new(),
+        });
+
+        let job_state = Arc::clone(&state);
+        let job: Box<dyn FnOnce() + Send + '_> = Box::new(move || job_state.run_on_worker());
+
+        // Safety: `join` doesn't return before `b` has either finished on a
+        // worker or been taken back by the caller. A job that runs later
+        // only finds `Slot::Taken` and returns without touching anything
+        // `b` borrowed.
+        let job: Job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + '_>, Job>(job) };
+
+        // A full or closed queue just means running `b` here.
+        let _ = self.try_execute(job);
+
+        let a_result = panic::catch_unwind(AssertUnwindSafe(a));
+        let b_result = state.finish_on_caller();
+
+        match (a_result, b_result) {
+            (Ok(a), Ok(b)) => (a, b),
+            (Err(payload), _) | (_, Err(payload)) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ThreadPool;
+    use std::{
+        sync::{mpsc, Arc},
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn join_returns_both_results() {
+        let pool = ThreadPool::new(2);
+        let numbers = [1, 2, 3, 4];
+
+        let (left, right) = pool.join(
+            || numbers[..2].iter().sum::<i32>(),
+            || numbers[2..].iter().sum::<i32>(),
+        );
+
+        assert_eq!((3, 7), (left, right));
+    }
+
+    #[test]
+    fn caller_runs_second_closure_when_workers_are_busy() {
+        let pool = ThreadPool::new(1);
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let (started_tx, started_rx) = mpsc::channel();
+
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        })
+        .unwrap();
+        started_rx.recv().unwrap();
+
+        let caller = thread::current().id();
+        let (_, b_thread) = pool.join(|| {}, || thread::current().id());
+
+        assert_eq!(caller, b_thread);
+        release_tx.send(()).unwrap();
+    }
+
+    #[test]
+    fn nested_joins_from_a_single_worker_do_not_deadlock() {
+        fn sum(pool: &ThreadPool, numbers: &[u64]) -> u64 {
+            if numbers.len() <= 2 {
+                return numbers.iter().sum();
+            }
+            let (left, right) = numbers.split_at(numbers.len() / 2);
+            let (a, b) = pool.join(|| sum(pool, left), || sum(pool, right));
+            a + b
+        }
+
+        let pool = Arc::new(ThreadPool::new(1));
+        let (tx, rx) = mpsc::channel();
+
+        let job_pool = Arc::clone(&pool);
+        pool.execute(move || {
+            let numbers: Vec<u64> = (1..=100).collect();
+            tx.send(sum(&job_pool, &numbers)).unwrap();
+        })
+        .unwrap();
+
+        assert_eq!(5050, rx.recv_timeout(Duration::from_secs(5)).unwrap());
+        // Let the job drop its handle first, a worker can't join itself.
+        pool.wait_idle();
+    }
+
+    #[test]
+    #[should_panic(expected = "second closure failed")]
+    fn join_propagates_panics() {
+        let pool = ThreadPool::new(2);
+
+        pool.join(|| 1, || panic!("second closure failed"));
+    }
+}
@@ -1,15 +1,152 @@
 use std::{
-    sync::{mpsc, Arc, Mutex},
+    any::Any,
+    fmt, io,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
     thread,
+    time::{Duration, Instant},
 };
 
+mod affinity;
+mod cancel;
+mod config;
+mod executor;
+pub mod http;
+pub mod http_client;
+mod join;
+mod manager;
+mod metrics;
+mod mpmc;
+mod parallel;
+mod periodic;
+mod queue;
+mod scheduler;
+mod scope;
+mod server;
+mod shutdown;
+
+pub use cancel::CancellationToken;
+pub use config::{ConfigError, ServerConfig};
+pub use manager::{PoolManager, RouteError};
+use metrics::{Counters, WorkerStats};
+pub use metrics::{PoolMetrics, WorkerMetrics};
+pub use periodic::PeriodicHandle;
+use periodic::Timer;
+pub use queue::{JobQueue, QueuedJob, TryPushError};
+use scheduler::Scheduler;
+pub use scheduler::SchedulingMode;
+pub use scope::Scope;
+pub use server::{Server, ServerError};
+pub use shutdown::ShutdownReport;
+
 /// A structure to manage pools of various threads performing actions.
 pub struct ThreadPool {
     workers: Vec<Worker>,
-    sender: Option<mpsc::Sender<Job>>,
+    shared: Arc<Shared>,
+    retired: Mutex<mpsc::Receiver<usize>>,
+    next_id: usize,
+    timer: Mutex<Option<Timer>>,
+}
+
+/// A job that can be run by the pool.
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Messages sent from the pool to its workers.
+enum Message {
+    NewJob(Job),
+    /// A job that is discarded instead of run if its token was cancelled.
+    CancellableJob(Job, CancellationToken),
+    /// Poison pill: the idle worker that receives it retires.
+    Terminate,
+}
+
+impl Message {
+    /// Takes the job back out of a message that could not be sent.
+    fn into_job(self) -> Job {
+        match self {
+            Message::NewJob(job) | Message::CancellableJob(job, _) => job,
+            Message::Terminate => unreachable!("terminate messages carry no job"),
+        }
+    }
+}
+
+/// The error returned when a job could not be handed to the pool.
+///
+/// The job is handed back in both cases so that it can be retried later or
+/// run somewhere else.
+pub enum ExecuteError {
+    /// The pool no longer accepts jobs because it was shut down.
+    PoolShutDown(Job),
+    /// The bounded job queue is full.
+    QueueFull(Job),
+}
+
+impl ExecuteError {
+    /// Returns the job that could not be executed.
+    pub fn into_job(self) -> Job {
+        match self {
+            ExecuteError::PoolShutDown(job) | ExecuteError::QueueFull(job) => job,
+        }
+    }
+}
+
+impl fmt::Debug for ExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecuteError::PoolShutDown(_) => write!(f, "PoolShutDown(..)"),
+            ExecuteError::QueueFull(_) => write!(f, "QueueFull(..)"),
+        }
+    }
+}
+
+impl fmt::Display for ExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecuteError::PoolShutDown(_) => write!(f, "the pool was shut down"),
+            ExecuteError::QueueFull(_) => write!(f, "the job queue is full"),
+        }
+    }
 }
 
-type Job = Box<dyn FnOnce() + Send + 'static>;
+impl std::error::Error for ExecuteError {}
+
+/// State shared by the pool and all of its workers.
+struct Shared {
+    queue: Arc<dyn JobQueue>,
+    retired: Mutex<mpsc::Sender<usize>>,
+    config: WorkerConfig,
+    counters: Counters,
+    /// Set by `ThreadPool::shutdown`, workers stop starting new jobs.
+    shutting_down: AtomicBool,
+}
+
+impl Shared {
+    /// Hands a job to the workers, keeping the counters in sync whether it
+    /// was accepted or not.
+    fn submit(&self, message: Message, block: bool) -> Result<(), ExecuteError> {
+        let job = QueuedJob(message);
+
+        self.counters.job_queued();
+
+        let result = if block {
+            self.queue.push(job).map_err(TryPushError::Closed)
+        } else {
+            self.queue.try_push(job)
+        };
+
+        result.map_err(|err| {
+            self.counters.job_rejected();
+
+            match err {
+                TryPushError::Full(job) => ExecuteError::QueueFull(job.0.into_job()),
+                TryPushError::Closed(job) => ExecuteError::PoolShutDown(job.0.into_job()),
+            }
+        })
+    }
+}
 
 impl ThreadPool {
     /// Creates a new ThreadPool.
@@ -18,91 +155,708 @@ impl ThreadPool {
     /// 
     /// # Panics
     /// 
-    /// The `new` function will panic if the size is zero.
+    /// The `new` function will panic if the size is zero or if a worker
+    /// thread could not be spawned.
     pub fn new(size: usize) -> ThreadPool {
         assert!(size > 0);
 
-        let (sender, receiver) = mpsc::channel();
-
-        let receiver = Arc::new(Mutex::new(receiver));
+        ThreadPool::builder()
+            .size(size)
+            .build()
+            .unwrap_or_else(|err| panic!("Could not build the thread pool: {err}"))
+    }
 
-        let mut workers = Vec::with_capacity(size);
+    /// Creates a new ThreadPool whose jobs wait in `queue` instead of the
+    /// default work-stealing queues.
+    ///
+    /// # Panics
+    ///
+    /// The `with_queue` function will panic if the size is zero or if a
+    /// worker thread could not be spawned.
+    pub fn with_queue<Q>(size: usize, queue: Q) -> ThreadPool
+    where
+        Q: JobQueue + 'static,
+    {
+        assert!(size > 0);
 
-        for id in 0..size {
-            workers.push(Worker::new(id, Arc::clone(&receiver)));
-        }
+        ThreadPool::builder()
+            .size(size)
+            .queue(queue)
+            .build()
+            .unwrap_or_else(|err| panic!("Could not build the thread pool: {err}"))
+    }
 
-        ThreadPool { 
-            workers,
-            sender: Some(sender),
-        }
+    /// Returns a `ThreadPoolBuilder` to configure the pool before creating it.
+    pub fn builder() -> ThreadPoolBuilder {
+        ThreadPoolBuilder::new()
     }
 
     /// Exectutes a function using the thread pool.
     /// 
     /// f is the function to be executed, which must be a closure
-    /// that implements the FnOnce() and Send traits.
+    /// that implements the FnOnce() and Send traits. If the pool has a
+    /// bounded queue and it is full, this blocks until there is room.
+    /// 
+    /// # Errors
+    /// 
+    /// The `execute` function will return `ExecuteError::PoolShutDown` with
+    /// the boxed job if the pool no longer accepts jobs.
+    pub fn execute<F>(&self, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.submit(Message::NewJob(Box::new(f)), true)
+    }
+
+    /// Exectutes a function using the thread pool, like `execute`.
     /// 
     /// # Panics
     /// 
-    /// The `execute` function will panic if sender could not be unwrapped
-    /// or if the message could not be send.
-    pub fn execute<F>(&self, f: F)
+    /// The `execute_or_panic` function will panic if the job could not be
+    /// handed to the pool.
+    pub fn execute_or_panic<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.execute(f)
+            .unwrap_or_else(|err| panic!("Could not execute job: {err}"));
+    }
+
+    /// Executes a function using the thread pool, handing it the sending
+    /// half of a new channel and returning the receiving half.
+    ///
+    /// The job can send as many values as it likes. The receiver sees the
+    /// channel disconnect once the job returns, so `Receiver::iter` stops
+    /// there. If the pool refused the job, the channel is disconnected right
+    /// away without any value.
+    pub fn spawn_with_channel<T, F>(&self, f: F) -> mpsc::Receiver<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(mpsc::Sender<T>) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+
+        // A refused job is dropped along with the sender it owns, which is
+        // how the receiver learns about it.
+        let _ = self.execute(move || f(tx));
+
+        rx
+    }
+
+    /// Executes a batch of jobs using the thread pool, queueing all of them
+    /// in one go instead of one by one.
+    ///
+    /// This never blocks. If the pool has a bounded queue, only the jobs
+    /// that fit in it are accepted, in order, and the rest are dropped
+    /// without running. Returns the number of jobs that were accepted.
+    pub fn execute_batch<I>(&self, jobs: I) -> usize
+    where
+        I: IntoIterator<Item = Job>,
+    {
+        let batch: Vec<QueuedJob> = jobs
+            .into_iter()
+            .map(|job| QueuedJob(Message::NewJob(job)))
+            .collect();
+        let total = batch.len();
+
+        self.shared.counters.jobs_queued(total);
+        let rejected = self.shared.queue.push_batch(batch).len();
+        self.shared.counters.jobs_rejected(rejected);
+
+        total - rejected
+    }
+
+    /// Executes a function using the thread pool unless `token` is cancelled
+    /// before a worker picks it up.
+    ///
+    /// The function receives the token, so a long running job can check
+    /// `is_cancelled` as it goes and stop early.
+    ///
+    /// # Errors
+    ///
+    /// The `execute_cancellable` function will return
+    /// `ExecuteError::PoolShutDown` if the pool no longer accepts jobs.
+    pub fn execute_cancellable<F>(&self, token: &CancellationToken, f: F) -> Result<(), ExecuteError>
+    where
+        F: FnOnce(&CancellationToken) + Send + 'static,
+    {
+        let job_token = token.clone();
+        let job = Box::new(move || f(&job_token));
+
+        self.submit(Message::CancellableJob(job, token.clone()), true)
+    }
+
+    /// Tries to execute a function using the thread pool without blocking.
+    ///
+    /// # Errors
+    ///
+    /// The `try_execute` function will return `ExecuteError::QueueFull` with
+    /// the boxed job if the pool has a bounded queue and it is full, and
+    /// `ExecuteError::PoolShutDown` if the pool no longer accepts jobs.
+    pub fn try_execute<F>(&self, f: F) -> Result<(), ExecuteError>
     where
         F: FnOnce() + Send + 'static,
     {
-        let job = Box::new(f);
+        self.submit(Message::NewJob(Box::new(f)), false)
+    }
+
+    /// Executes a function using the thread pool every `interval`, starting
+    /// one interval from now.
+    ///
+    /// The recurrence stops when the returned `PeriodicHandle` is dropped.
+    /// Every run is a regular job, so a slow run delays the other jobs only
+    /// as much as any job would.
+    pub fn execute_periodic<F>(&self, interval: Duration, f: F) -> PeriodicHandle
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let mut timer = self.timer.lock().unwrap();
+
+        let timer = timer.get_or_insert_with(|| Timer::new(Arc::clone(&self.shared)));
+
+        timer.schedule(interval, Arc::new(f))
+    }
+
+    /// Returns the number of threads in the pool.
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Blocks until every job sent to the pool so far has completed.
+    ///
+    /// Unlike dropping the pool, the workers stay alive and the pool can keep
+    /// being used afterwards. Jobs sent from other threads while waiting are
+    /// waited for too.
+    pub fn wait_idle(&self) {
+        self.shared.counters.wait_idle();
+    }
+
+    /// Returns a snapshot of the job counters of the pool and its workers.
+    pub fn metrics(&self) -> PoolMetrics {
+        let workers = self
+            .workers
+            .iter()
+            .map(|worker| worker.stats.snapshot(worker.id))
+            .collect();
+
+        self.shared.counters.snapshot(workers)
+    }
+
+    /// Changes the number of threads in the pool.
+    ///
+    /// Growing the pool spawns new workers right away. Shrinking it sends a
+    /// terminate message per extra worker, so workers only retire once they
+    /// are idle, and this function blocks until all of them have.
+    ///
+    /// # Panics
+    ///
+    /// The `resize` function will panic if the new size is zero or if a
+    /// worker thread could not be spawned.
+    pub fn resize(&mut self, new_size: usize) {
+        assert!(new_size > 0);
+
+        let current = self.workers.len();
+
+        if new_size > current {
+            for _ in current..new_size {
+                let worker = Worker::new(self.next_id, Arc::clone(&self.shared))
+                    .unwrap_or_else(|err| panic!("Could not spawn worker: {err}"));
+                self.next_id += 1;
+                self.workers.push(worker);
+            }
+        } else {
+            let retiring = current - new_size;
+
+            for _ in 0..retiring {
+                self.send(Message::Terminate);
+            }
+
+            for _ in 0..retiring {
+                let id = self.retired.lock().unwrap().recv().unwrap();
+                let index = self.workers.iter().position(|w| w.id == id).unwrap();
+                self.workers.remove(index).join();
+            }
+        }
+    }
+
+    /// Shuts the pool down, waiting at most `timeout` for its workers.
+    ///
+    /// Jobs that are already running are allowed to finish, while jobs still
+    /// waiting in the queue are discarded. Workers that are still busy when
+    /// the deadline passes are abandoned: their threads are detached instead
+    /// of joined.
+    pub fn shutdown(mut self, timeout: Duration) -> ShutdownReport {
+        let deadline = Instant::now() + timeout;
+
+        drop(self.timer.lock().unwrap().take());
+        self.shared.shutting_down.store(true, Ordering::SeqCst);
+        self.shared.queue.close();
+
+        let workers = std::mem::take(&mut self.workers);
+
+        while Instant::now() < deadline && !workers.iter().all(Worker::is_finished) {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut report = ShutdownReport {
+            finished: Vec::new(),
+            abandoned: Vec::new(),
+            dropped_jobs: 0,
+        };
+
+        for worker in workers {
+            if worker.is_finished() {
+                worker.join();
+                report.finished.push(worker.id);
+            } else {
+                println!("Abandoning worker {}", worker.id);
+                report.abandoned.push(worker.id);
+            }
+        }
+
+        report.dropped_jobs = self.shared.counters.dropped();
+        report
+    }
+
+    fn submit(&self, message: Message, block: bool) -> Result<(), ExecuteError> {
+        self.shared.submit(message, block)
+    }
 
-        self.sender.as_ref().unwrap_or_else(|| {
-            panic!("Could not unwrap sender channel, got an error.");
-        }).send(job).unwrap_or_else(|job| {
-            panic!("Could not send message {job}");
+    fn send(&self, message: Message) {
+        self.shared.queue.push(QueuedJob(message)).unwrap_or_else(|_| {
+            panic!("Could not send message, the queue was closed.");
         });
     }
 }
 
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let metrics = self.shared.counters.snapshot(Vec::new());
+
+        f.debug_struct("ThreadPool")
+            .field("size", &self.workers.len())
+            .field("queued", &metrics.queued)
+            .field("running", &metrics.running)
+            .field("queue_capacity", &self.shared.queue.capacity())
+            .field("config", &self.shared.config)
+            .field("workers", &self.workers)
+            .finish()
+    }
+}
+
 impl Drop for ThreadPool {
     fn drop(&mut self) {
-        drop(self.sender.take());
+        // The timer may be sending a job right now, so it has to stop before
+        // the queue is closed for that job not to be lost.
+        drop(self.timer.lock().unwrap().take());
+        self.shared.queue.close();
 
         for worker in &mut self.workers {
             println!("Shutting down worker {}", worker.id);
 
-            if let Some(thread) = worker.thread.take() {
-                thread.join().unwrap();
+            worker.join();
+        }
+    }
+}
+
+type NameFn = dyn Fn(usize) -> String + Send + Sync;
+type SpawnFn = dyn Fn(usize) + Send + Sync;
+type PanicFn = dyn Fn(usize, Box<dyn Any + Send>) + Send + Sync;
+
+/// Configuration shared by every worker of a pool.
+#[derive(Clone, Default)]
+struct WorkerConfig {
+    thread_name: Option<Arc<NameFn>>,
+    stack_size: Option<usize>,
+    on_spawn: Option<Arc<SpawnFn>>,
+    panic_handler: Option<Arc<PanicFn>>,
+    pin_workers: bool,
+    quiet: bool,
+}
+
+impl fmt::Debug for WorkerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The callbacks can't be printed, only whether they are set.
+        f.debug_struct("WorkerConfig")
+            .field("thread_name", &self.thread_name.is_some())
+            .field("stack_size", &self.stack_size)
+            .field("on_spawn", &self.on_spawn.is_some())
+            .field("panic_handler", &self.panic_handler.is_some())
+            .field("pin_workers", &self.pin_workers)
+            .field("quiet", &self.quiet)
+            .finish()
+    }
+}
+
+/// A builder to configure a `ThreadPool` before creating it.
+///
+/// The pool defaults to a single worker whose threads are unnamed and use
+/// the default stack size of the platform.
+pub struct ThreadPoolBuilder {
+    size: usize,
+    queue_capacity: Option<usize>,
+    scheduling_mode: SchedulingMode,
+    queue: Option<Arc<dyn JobQueue>>,
+    config: WorkerConfig,
+}
+
+impl ThreadPoolBuilder {
+    /// Creates a new builder with the default configuration.
+    pub fn new() -> ThreadPoolBuilder {
+        ThreadPoolBuilder {
+            size: 1,
+            queue_capacity: None,
+            scheduling_mode: SchedulingMode::default(),
+            queue: None,
+            config: WorkerConfig::default(),
+        }
+    }
+
+    /// Sets the number of threads in the pool.
+    pub fn size(mut self, size: usize) -> ThreadPoolBuilder {
+        self.size = size;
+        self
+    }
+
+    /// Bounds the job queue to `capacity` jobs waiting to be picked up.
+    ///
+    /// Once the queue is full, `execute` blocks and `try_execute` returns an
+    /// error until a worker takes a job out. A capacity of zero means every
+    /// job is handed directly to an idle worker. By default the queue is
+    /// unbounded.
+    pub fn queue_capacity(mut self, capacity: usize) -> ThreadPoolBuilder {
+        self.queue_capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the order in which workers take jobs. The default is
+    /// `SchedulingMode::Fifo`.
+    pub fn scheduling_mode(mut self, mode: SchedulingMode) -> ThreadPoolBuilder {
+        self.scheduling_mode = mode;
+        self
+    }
+
+    /// Makes the jobs of the pool wait in `queue` instead of the default
+    /// work-stealing queues. The capacity set with `queue_capacity` and the
+    /// scheduling mode are ignored, a custom queue decides on both itself.
+    pub fn queue<Q>(mut self, queue: Q) -> ThreadPoolBuilder
+    where
+        Q: JobQueue + 'static,
+    {
+        self.queue = Some(Arc::new(queue));
+        self
+    }
+
+    /// Sets the function used to name each worker thread.
+    ///
+    /// The function receives the id of the worker and returns its name.
+    pub fn thread_name<F>(mut self, f: F) -> ThreadPoolBuilder
+    where
+        F: Fn(usize) -> String + Send + Sync + 'static,
+    {
+        self.config.thread_name = Some(Arc::new(f));
+        self
+    }
+
+    /// Sets the stack size, in bytes, of each worker thread.
+    pub fn stack_size(mut self, size: usize) -> ThreadPoolBuilder {
+        self.config.stack_size = Some(size);
+        self
+    }
+
+    /// Pins each worker thread to a CPU core, spreading the workers
+    /// round-robin over the available cores.
+    ///
+    /// Pinning is only supported on Linux. Elsewhere, or if the OS refuses,
+    /// the worker prints a warning and runs unpinned.
+    pub fn pin_workers(mut self, pin: bool) -> ThreadPoolBuilder {
+        self.config.pin_workers = pin;
+        self
+    }
+
+    /// Stops the workers from printing a line for every job they pick up.
+    ///
+    /// Workers still report when they retire or shut down.
+    pub fn quiet(mut self, quiet: bool) -> ThreadPoolBuilder {
+        self.config.quiet = quiet;
+        self
+    }
+
+    /// Sets a callback that each worker thread runs once when it starts,
+    /// before picking up any job.
+    ///
+    /// The callback receives the id of the worker.
+    pub fn on_spawn<F>(mut self, f: F) -> ThreadPoolBuilder
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.config.on_spawn = Some(Arc::new(f));
+        self
+    }
+
+    /// Sets a callback that is called when a job panics.
+    ///
+    /// The callback receives the id of the worker that ran the job and the
+    /// panic payload. Panics are always caught, so the worker keeps running
+    /// whether a handler is set or not.
+    pub fn panic_handler<F>(mut self, f: F) -> ThreadPoolBuilder
+    where
+        F: Fn(usize, Box<dyn Any + Send>) + Send + Sync + 'static,
+    {
+        self.config.panic_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Creates the ThreadPool.
+    ///
+    /// # Errors
+    ///
+    /// The `build` function will return an error if the size is zero or if
+    /// a worker thread could not be spawned.
+    pub fn build(self) -> Result<ThreadPool, PoolCreationError> {
+        if self.size == 0 {
+            return Err(PoolCreationError::ZeroSize);
+        }
+
+        let (queue_capacity, mode) = (self.queue_capacity, self.scheduling_mode);
+        let queue = self
+            .queue
+            .unwrap_or_else(|| Arc::new(Scheduler::new(queue_capacity, mode)));
+        let (retired_sender, retired) = mpsc::channel();
+
+        let shared = Arc::new(Shared {
+            queue,
+            retired: Mutex::new(retired_sender),
+            config: self.config,
+            counters: Counters::default(),
+            shutting_down: AtomicBool::new(false),
+        });
+
+        let mut workers = Vec::with_capacity(self.size);
+
+        for id in 0..self.size {
+            match Worker::new(id, Arc::clone(&shared)) {
+                Ok(worker) => workers.push(worker),
+                Err(err) => {
+                    // Let the workers spawned so far stop.
+                    shared.queue.close();
+                    return Err(err.into());
+                }
             }
         }
+
+        Ok(ThreadPool {
+            workers,
+            shared,
+            retired: Mutex::new(retired),
+            next_id: self.size,
+            timer: Mutex::new(None),
+        })
+    }
+}
+
+impl Default for ThreadPoolBuilder {
+    fn default() -> ThreadPoolBuilder {
+        ThreadPoolBuilder::new()
+    }
+}
+
+/// The error returned when a `ThreadPool` could not be built.
+#[derive(Debug)]
+pub enum PoolCreationError {
+    /// The pool was configured with zero threads.
+    ZeroSize,
+    /// The operating system failed to spawn a worker thread.
+    Spawn(io::Error),
+}
+
+impl fmt::Display for PoolCreationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PoolCreationError::ZeroSize => write!(f, "pool size must be greater than zero"),
+            PoolCreationError::Spawn(err) => write!(f, "could not spawn worker thread: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PoolCreationError {}
+
+impl From<io::Error> for PoolCreationError {
+    fn from(err: io::Error) -> PoolCreationError {
+        PoolCreationError::Spawn(err)
     }
 }
 
+/// The slot holding the thread of a worker. It is shared with the thread
+/// itself so that a replacement thread can be stored in it on respawn.
+type ThreadSlot = Arc<Mutex<Option<thread::JoinHandle<()>>>>;
+
 struct Worker {
     id: usize,
-    thread: Option<thread::JoinHandle<()>>,
+    thread: ThreadSlot,
+    stats: Arc<WorkerStats>,
+}
+
+impl fmt::Debug for Worker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Worker")
+            .field("id", &self.id)
+            .field("state", &self.stats.state())
+            .finish()
+    }
 }
 
 impl Worker {
-    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Worker {
-        let thread = thread::spawn(move || loop {
-            let message = receiver.lock().unwrap().recv();
+    fn new(id: usize, shared: Arc<Shared>) -> io::Result<Worker> {
+        let thread: ThreadSlot = Arc::new(Mutex::new(None));
+        let stats = Arc::new(WorkerStats::default());
+
+        // Keep the slot locked while spawning so a thread that dies right
+        // away can't store its replacement before the first handle is set.
+        let mut slot = thread.lock().unwrap();
+        *slot = Some(Worker::spawn(id, shared, Arc::clone(&thread), Arc::clone(&stats))?);
+        drop(slot);
+
+        Ok(Worker { id, thread, stats })
+    }
+
+    /// Returns true if the thread of the worker has finished, without
+    /// blocking.
+    fn is_finished(&self) -> bool {
+        match &*self.thread.lock().unwrap() {
+            Some(handle) => handle.is_finished(),
+            None => true,
+        }
+    }
+
+    /// Waits for the thread of the worker to finish, including any thread
+    /// that was respawned in its place in the meantime.
+    fn join(&self) {
+        loop {
+            let handle = self.thread.lock().unwrap().take();
+
+            match handle {
+                Some(handle) => {
+                    let _ = handle.join();
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn spawn(
+        id: usize,
+        shared: Arc<Shared>,
+        slot: ThreadSlot,
+        stats: Arc<WorkerStats>,
+    ) -> io::Result<thread::JoinHandle<()>> {
+        let mut builder = thread::Builder::new();
+
+        if let Some(thread_name) = &shared.config.thread_name {
+            builder = builder.name(thread_name(id));
+        }
+        if let Some(stack_size) = shared.config.stack_size {
+            builder = builder.stack_size(stack_size);
+        }
+
+        builder.spawn(move || {
+            let sentinel = Sentinel { id, shared, slot, stats };
+
+            if sentinel.shared.config.pin_workers {
+                let core = affinity::core_for_worker(id);
+                if let Err(err) = affinity::pin_current_thread(core) {
+                    eprintln!("Worker {id} could not be pinned to core {core}: {err}");
+                }
+            }
+
+            if let Some(on_spawn) = &sentinel.shared.config.on_spawn {
+                on_spawn(id);
+            }
+
+            sentinel.shared.queue.register_worker(id);
+
+            Worker::run(id, &sentinel.shared, &sentinel.stats);
+        })
+    }
+
+    fn run(id: usize, shared: &Shared, stats: &WorkerStats) {
+        loop {
+            let message = shared.queue.pop().map(|job| job.0);
 
             match message {
-                Ok(job) => {
-                    println!("Worker {id} got a job; executing.");
+                Some(Message::NewJob(_) | Message::CancellableJob(..))
+                    if shared.shutting_down.load(Ordering::SeqCst) =>
+                {
+                    shared.counters.job_dropped();
+                }
+                Some(Message::CancellableJob(_, token)) if token.is_cancelled() => {
+                    if !shared.config.quiet {
+                        println!("Worker {id} skipped a cancelled job.");
+                    }
 
-                    job();
+                    shared.counters.job_cancelled();
                 }
-                Err(_) => {
+                Some(Message::NewJob(job) | Message::CancellableJob(job, _)) => {
+                    if !shared.config.quiet {
+                        println!("Worker {id} got a job; executing.");
+                    }
+
+                    Worker::execute_job(id, shared, stats, job);
+                }
+                Some(Message::Terminate) => {
+                    println!("Worker {id} was told to terminate; retiring.");
+                    shared.queue.unregister_worker(id);
+                    let _ = shared.retired.lock().unwrap().send(id);
+                    break;
+                }
+                None => {
                     println!("Worker {id} disconnected; shutting down.");
                     break;
                 }
             }
+        }
+    }
 
-            
-        });
+    fn execute_job(id: usize, shared: &Shared, stats: &WorkerStats, job: Job) {
+        shared.counters.job_started(stats);
+        let result = panic::catch_unwind(AssertUnwindSafe(job));
+        shared.counters.job_finished(stats, result.is_err());
 
-        Worker { 
-            id: id,
-            thread: Some(thread),
+        if let Err(payload) = result {
+            match &shared.config.panic_handler {
+                Some(handler) => handler(id, payload),
+                None => println!("Worker {id} caught a panicking job."),
+            }
+        }
+    }
+}
+
+/// Lives on the stack of every worker thread. If the thread unwinds (for
+/// example because the panic handler itself panicked), its drop spawns a
+/// replacement thread with the same id so the pool keeps its size.
+struct Sentinel {
+    id: usize,
+    shared: Arc<Shared>,
+    slot: ThreadSlot,
+    stats: Arc<WorkerStats>,
+}
+
+impl Drop for Sentinel {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            return;
+        }
+
+        println!("Worker {} died; respawning.", self.id);
+
+        let shared = Arc::clone(&self.shared);
+        let slot = Arc::clone(&self.slot);
+
+        match Worker::spawn(self.id, shared, slot, Arc::clone(&self.stats)) {
+            // The slot may be locked by `Worker::join`, which only holds it
+            // while taking the handle out, so this never waits for long.
+            Ok(handle) => *self.slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(handle),
+            Err(err) => println!("Could not respawn worker {}: {err}", self.id),
         }
     }
 }
@@ -110,6 +864,7 @@ impl Worker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::AtomicUsize;
 
     fn some_operation(x: i32) -> i32 {
         2 + x
@@ -119,6 +874,17 @@ mod tests {
         x == y
     }
 
+    /// Polls `condition` until it holds, giving up after a second.
+    fn wait_until(condition: impl Fn() -> bool) {
+        for _ in 0..1000 {
+            if condition() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("condition was never met");
+    }
+
     #[test]
     #[should_panic]
     fn threadpool_size_cannot_be_zero() {
@@ -131,10 +897,654 @@ mod tests {
         pool.execute(|| {
             let result = some_operation(5);
             assert_eq!(7, result);
-        });
+        }).unwrap();
         pool.execute(|| {
             assert!(other_operation(5, 5));
             assert!(!other_operation(1, 2));
+        }).unwrap();
+    }
+
+    #[test]
+    fn builder_size_cannot_be_zero() {
+        let result = ThreadPool::builder().size(0).build();
+        assert!(matches!(result, Err(PoolCreationError::ZeroSize)));
+    }
+
+    #[test]
+    fn builder_names_threads_and_runs_on_spawn() {
+        let (tx, rx) = mpsc::channel();
+        let spawned = Arc::new(Mutex::new(Vec::new()));
+        let spawned_clone = Arc::clone(&spawned);
+
+        let pool = ThreadPool::builder()
+            .size(2)
+            .thread_name(|id| format!("worker-{id}"))
+            .stack_size(64 * 1024)
+            .on_spawn(move |id| spawned_clone.lock().unwrap().push(id))
+            .build()
+            .unwrap();
+
+        pool.execute(move || {
+            let name = thread::current().name().map(String::from);
+            tx.send(name).unwrap();
+        }).unwrap();
+
+        let name = rx.recv().unwrap().unwrap();
+        assert!(name == "worker-0" || name == "worker-1");
+
+        drop(pool);
+        let mut spawned = spawned.lock().unwrap().clone();
+        spawned.sort();
+        assert_eq!(vec![0, 1], spawned);
+    }
+
+    #[test]
+    fn resize_grows_and_shrinks_pool() {
+        let mut pool = ThreadPool::new(2);
+
+        pool.resize(4);
+        assert_eq!(4, pool.size());
+
+        pool.resize(1);
+        assert_eq!(1, pool.size());
+
+        let (tx, rx) = mpsc::channel();
+        for i in 0..4 {
+            let tx = tx.clone();
+            pool.execute(move || tx.send(i).unwrap()).unwrap();
+        }
+        drop(tx);
+
+        let mut results: Vec<i32> = rx.iter().collect();
+        results.sort();
+        assert_eq!(vec![0, 1, 2, 3], results);
+    }
+
+    #[test]
+    fn resize_gives_new_workers_fresh_ids() {
+        let mut pool = ThreadPool::new(2);
+
+        pool.resize(1);
+        pool.resize(3);
+
+        let mut ids: Vec<usize> = pool.workers.iter().map(|w| w.id).collect();
+        ids.sort();
+        assert_eq!(3, ids.len());
+        assert!(ids.contains(&2) && ids.contains(&3));
+    }
+
+    #[test]
+    fn panicking_job_does_not_kill_worker() {
+        let (panic_tx, panic_rx) = mpsc::channel();
+        let panic_tx = Mutex::new(panic_tx);
+
+        let pool = ThreadPool::builder()
+            .size(1)
+            .panic_handler(move |id, payload| {
+                let message = payload.downcast_ref::<&str>().map(|s| s.to_string());
+                panic_tx.lock().unwrap().send((id, message)).unwrap();
+            })
+            .build()
+            .unwrap();
+
+        pool.execute(|| panic!("job failed")).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        pool.execute(move || tx.send(5).unwrap()).unwrap();
+
+        assert_eq!((0, Some(String::from("job failed"))), panic_rx.recv().unwrap());
+        assert_eq!(5, rx.recv().unwrap());
+    }
+
+    #[test]
+    fn dead_worker_is_respawned() {
+        let first_spawn = Arc::new(Mutex::new(true));
+        let spawns = Arc::new(Mutex::new(0));
+        let spawns_clone = Arc::clone(&spawns);
+
+        let pool = ThreadPool::builder()
+            .size(1)
+            .on_spawn(move |_| {
+                *spawns_clone.lock().unwrap() += 1;
+                let mut first = first_spawn.lock().unwrap();
+                if *first {
+                    *first = false;
+                    drop(first);
+                    panic!("worker failed to start");
+                }
+            })
+            .build()
+            .unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        pool.execute(move || tx.send(1).unwrap()).unwrap();
+
+        assert_eq!(1, rx.recv().unwrap());
+        assert_eq!(1, pool.size());
+
+        drop(pool);
+        assert_eq!(2, *spawns.lock().unwrap());
+    }
+
+    #[test]
+    fn try_execute_reports_full_queue() {
+        let pool = ThreadPool::builder().size(1).queue_capacity(1).build().unwrap();
+
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        }).unwrap();
+        started_rx.recv().unwrap();
+
+        // The only worker is busy, so the queue can hold exactly one job.
+        assert!(pool.try_execute(|| {}).is_ok());
+
+        let (tx, rx) = mpsc::channel();
+        let job = match pool.try_execute(move || tx.send(3).unwrap()) {
+            Err(ExecuteError::QueueFull(job)) => job,
+            other => panic!("queue should be full, got {other:?}"),
+        };
+
+        release_tx.send(()).unwrap();
+        pool.execute(job).unwrap();
+        assert_eq!(3, rx.recv().unwrap());
+    }
+
+    #[test]
+    fn spawn_with_channel_returns_receiver_for_job() {
+        let pool = ThreadPool::new(2);
+
+        let rx = pool.spawn_with_channel(|tx| {
+            for i in 0..3 {
+                tx.send(i * 10).unwrap();
+            }
         });
+
+        assert_eq!(vec![0, 10, 20], rx.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn spawn_with_channel_disconnects_when_job_returns() {
+        let pool = ThreadPool::new(1);
+
+        let rx = pool.spawn_with_channel(|_tx: mpsc::Sender<i32>| {});
+
+        assert!(rx.recv().is_err());
+    }
+
+    #[test]
+    fn execute_or_panic_runs_job() {
+        let pool = ThreadPool::new(1);
+        let (tx, rx) = mpsc::channel();
+
+        pool.execute_or_panic(move || tx.send(4).unwrap());
+
+        assert_eq!(4, rx.recv().unwrap());
+    }
+
+    #[test]
+    fn execute_batch_runs_every_job_of_unbounded_pool() {
+        let pool = ThreadPool::new(2);
+        let (tx, rx) = mpsc::channel();
+
+        let jobs = (0..10).map(|i| {
+            let tx = tx.clone();
+            Box::new(move || tx.send(i).unwrap()) as Job
+        });
+
+        assert_eq!(10, pool.execute_batch(jobs));
+
+        let mut received: Vec<i32> = rx.iter().take(10).collect();
+        received.sort();
+        assert_eq!((0..10).collect::<Vec<_>>(), received);
+    }
+
+    #[test]
+    fn execute_batch_accepts_only_what_fits_in_bounded_queue() {
+        let pool = ThreadPool::builder().size(1).queue_capacity(3).build().unwrap();
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        }).unwrap();
+        started_rx.recv().unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let jobs: Vec<Job> = (0..5)
+            .map(|i| {
+                let tx = tx.clone();
+                Box::new(move || tx.send(i).unwrap()) as Job
+            })
+            .collect();
+        drop(tx);
+
+        assert_eq!(3, pool.execute_batch(jobs));
+
+        release_tx.send(()).unwrap();
+        assert_eq!(vec![0, 1, 2], rx.iter().collect::<Vec<_>>());
+        pool.wait_idle();
+        assert_eq!(0, pool.metrics().queued);
+    }
+
+    fn send_three(pool: &ThreadPool, tx: &mpsc::Sender<i32>) {
+        for i in 0..3 {
+            let tx = tx.clone();
+            pool.execute(move || tx.send(i).unwrap()).unwrap();
+        }
+    }
+
+    /// Sends three jobs to a pool whose only worker is busy, from outside of
+    /// the pool or from inside a job, and returns the order they ran in.
+    fn order_of_three_jobs(mode: SchedulingMode, from_inside: bool) -> Vec<i32> {
+        let pool = Arc::new(ThreadPool::builder().size(1).scheduling_mode(mode).build().unwrap());
+        let (tx, rx) = mpsc::channel();
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        let job_pool = Arc::clone(&pool);
+        let job_tx = tx.clone();
+        pool.execute(move || {
+            if from_inside {
+                send_three(&job_pool, &job_tx);
+            }
+            drop(job_tx);
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        }).unwrap();
+        started_rx.recv().unwrap();
+
+        if !from_inside {
+            send_three(&pool, &tx);
+        }
+        drop(tx);
+        release_tx.send(()).unwrap();
+
+        let order = rx.iter().collect();
+        pool.wait_idle();
+        order
+    }
+
+    #[test]
+    fn fifo_mode_runs_jobs_in_the_order_they_were_sent() {
+        assert_eq!(vec![0, 1, 2], order_of_three_jobs(SchedulingMode::Fifo, false));
+        assert_eq!(vec![0, 1, 2], order_of_three_jobs(SchedulingMode::Fifo, true));
+    }
+
+    #[test]
+    fn lifo_mode_runs_the_most_recent_job_first() {
+        assert_eq!(vec![2, 1, 0], order_of_three_jobs(SchedulingMode::Lifo, false));
+        assert_eq!(vec![2, 1, 0], order_of_three_jobs(SchedulingMode::Lifo, true));
+    }
+
+    #[test]
+    fn execute_blocks_until_bounded_queue_has_room() {
+        let pool = ThreadPool::builder().size(1).queue_capacity(0).build().unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        for i in 0..5 {
+            let tx = tx.clone();
+            pool.execute(move || tx.send(i).unwrap()).unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(vec![0, 1, 2, 3, 4], rx.iter().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn metrics_count_queued_running_and_completed_jobs() {
+        let pool = ThreadPool::new(1);
+
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        }).unwrap();
+        started_rx.recv().unwrap();
+        pool.execute(|| panic!("counted as panicked")).unwrap();
+
+        let metrics = pool.metrics();
+        assert_eq!(1, metrics.queued);
+        assert_eq!(1, metrics.running);
+        assert_eq!(0, metrics.completed);
+        assert!(metrics.workers[0].busy);
+
+        release_tx.send(()).unwrap();
+        pool.wait_idle();
+
+        let metrics = pool.metrics();
+        assert_eq!(0, metrics.queued);
+        assert_eq!(0, metrics.running);
+        assert_eq!(1, metrics.completed);
+        assert_eq!(1, metrics.panicked);
+        assert_eq!(
+            vec![WorkerMetrics { id: 0, busy: false, completed: 1, panicked: 1 }],
+            metrics.workers
+        );
+    }
+
+    #[test]
+    fn shutdown_waits_for_running_jobs() {
+        let pool = ThreadPool::new(2);
+        let (tx, rx) = mpsc::channel();
+
+        for i in 0..2 {
+            let tx = tx.clone();
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(20));
+                tx.send(i).unwrap();
+            }).unwrap();
+        }
+        wait_until(|| pool.metrics().running == 2);
+
+        let report = pool.shutdown(Duration::from_secs(5));
+
+        assert!(report.is_clean());
+        assert_eq!(2, report.finished.len());
+        assert_eq!(0, report.dropped_jobs);
+        assert_eq!(2, rx.try_iter().count());
+    }
+
+    #[test]
+    fn shutdown_abandons_workers_past_deadline_and_drops_queue() {
+        let pool = ThreadPool::new(1);
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            let _ = release_rx.recv();
+        }).unwrap();
+        wait_until(|| pool.metrics().running == 1);
+        pool.execute(|| panic!("queued job should never run")).unwrap();
+        pool.execute(|| panic!("queued job should never run")).unwrap();
+
+        let report = pool.shutdown(Duration::from_millis(20));
+        assert!(!report.is_clean());
+        assert!(report.finished.is_empty());
+        assert_eq!(vec![0], report.abandoned);
+
+        release_tx.send(()).unwrap();
+    }
+
+    #[test]
+    fn debug_shows_worker_states_and_queue() {
+        let pool = ThreadPool::builder().size(1).queue_capacity(4).build().unwrap();
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        }).unwrap();
+        started_rx.recv().unwrap();
+        pool.execute(|| {}).unwrap();
+
+        let busy = format!("{pool:?}");
+        assert!(busy.contains("queued: 1"), "{busy}");
+        assert!(busy.contains("queue_capacity: Some(4)"), "{busy}");
+        assert!(busy.contains("Worker { id: 0, state: Busy { elapsed: "), "{busy}");
+
+        release_tx.send(()).unwrap();
+        pool.wait_idle();
+
+        let idle = format!("{pool:?}");
+        assert!(idle.contains("queued: 0"), "{idle}");
+        assert!(idle.contains("Worker { id: 0, state: Idle }"), "{idle}");
+    }
+
+    #[test]
+    fn wait_idle_blocks_until_jobs_complete() {
+        let pool = ThreadPool::new(3);
+        let done = Arc::new(Mutex::new(0));
+
+        for _ in 0..10 {
+            let done = Arc::clone(&done);
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(5));
+                *done.lock().unwrap() += 1;
+            }).unwrap();
+        }
+
+        pool.wait_idle();
+        assert_eq!(10, *done.lock().unwrap());
+
+        // The pool is still usable after waiting.
+        let done_clone = Arc::clone(&done);
+        pool.execute(move || *done_clone.lock().unwrap() += 1).unwrap();
+        pool.wait_idle();
+        assert_eq!(11, *done.lock().unwrap());
+    }
+
+    #[test]
+    fn wait_idle_returns_immediately_on_idle_pool() {
+        let pool = ThreadPool::new(1);
+        pool.wait_idle();
+    }
+
+    #[test]
+    fn cancelled_jobs_are_not_started() {
+        let pool = ThreadPool::new(1);
+        let token = CancellationToken::new();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            let _ = release_rx.recv();
+        }).unwrap();
+
+        let ran = Arc::new(Mutex::new(0));
+        for _ in 0..3 {
+            let ran = Arc::clone(&ran);
+            pool.execute_cancellable(&token, move |_| *ran.lock().unwrap() += 1).unwrap();
+        }
+
+        token.cancel();
+        release_tx.send(()).unwrap();
+        pool.wait_idle();
+
+        assert_eq!(0, *ran.lock().unwrap());
+        assert_eq!(3, pool.metrics().cancelled);
+    }
+
+    #[test]
+    fn running_job_can_poll_token() {
+        let pool = ThreadPool::new(1);
+        let token = CancellationToken::new();
+        let (tx, rx) = mpsc::channel();
+
+        pool.execute_cancellable(&token, move |token| {
+            let mut polls = 0;
+            while !token.is_cancelled() {
+                polls += 1;
+                thread::sleep(Duration::from_millis(1));
+            }
+            tx.send(polls).unwrap();
+        }).unwrap();
+
+        thread::sleep(Duration::from_millis(10));
+        token.cancel();
+
+        assert!(rx.recv().unwrap() > 0);
+        assert_eq!(0, pool.metrics().cancelled);
+    }
+
+    #[test]
+    fn jobs_sent_from_a_job_are_stolen_by_idle_workers() {
+        let pool = Arc::new(ThreadPool::new(4));
+        let threads = Arc::new(Mutex::new(Vec::new()));
+
+        let inner_pool = Arc::clone(&pool);
+        let inner_threads = Arc::clone(&threads);
+        pool.execute(move || {
+            for _ in 0..100 {
+                let threads = Arc::clone(&inner_threads);
+                inner_pool.execute(move || {
+                    thread::sleep(Duration::from_millis(1));
+                    threads.lock().unwrap().push(thread::current().id());
+                }).unwrap();
+            }
+        }).unwrap();
+
+        pool.wait_idle();
+
+        let mut threads = threads.lock().unwrap().clone();
+        assert_eq!(100, threads.len());
+        threads.sort_by_key(|id| format!("{id:?}"));
+        threads.dedup();
+        assert!(threads.len() > 1);
+    }
+
+    #[test]
+    fn jobs_spilling_out_of_the_injector_ring_still_run_in_order() {
+        let pool = ThreadPool::new(1);
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        pool.execute(move || {
+            let _ = release_rx.recv();
+        }).unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        for i in 0..3000 {
+            let order = Arc::clone(&order);
+            pool.execute(move || order.lock().unwrap().push(i)).unwrap();
+        }
+
+        release_tx.send(()).unwrap();
+        pool.wait_idle();
+
+        assert_eq!((0..3000).collect::<Vec<_>>(), *order.lock().unwrap());
+    }
+
+    /// The pool this crate started with: one channel whose receiver is
+    /// shared behind a mutex. Kept here as a baseline for the benchmark.
+    struct ChannelPool {
+        sender: Option<mpsc::Sender<Job>>,
+        threads: Vec<thread::JoinHandle<()>>,
+    }
+
+    impl ChannelPool {
+        fn new(size: usize) -> ChannelPool {
+            let (sender, receiver) = mpsc::channel::<Job>();
+            let receiver = Arc::new(Mutex::new(receiver));
+
+            let threads = (0..size)
+                .map(|_| {
+                    let receiver = Arc::clone(&receiver);
+                    thread::spawn(move || loop {
+                        let job = receiver.lock().unwrap().recv();
+                        match job {
+                            Ok(job) => job(),
+                            Err(_) => break,
+                        }
+                    })
+                })
+                .collect();
+
+            ChannelPool { sender: Some(sender), threads }
+        }
+
+        fn execute(&self, job: Job) {
+            self.sender.as_ref().unwrap().send(job).unwrap();
+        }
+    }
+
+    impl Drop for ChannelPool {
+        fn drop(&mut self) {
+            drop(self.sender.take());
+            for thread in self.threads.drain(..) {
+                thread.join().unwrap();
+            }
+        }
+    }
+
+    /// Each of `FAN_OUT` outer jobs sends `JOBS` tiny jobs from inside the
+    /// pool, which is where per-worker deques avoid the shared lock. Both
+    /// pools run the same job, bumping a shared counter, and neither prints
+    /// per job, so the gap is the cost of dispatch alone. It grows with the
+    /// number of cores, on a single core there is no contention to avoid and
+    /// the extra bookkeeping of the deques makes the shared channel faster.
+    ///
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn benchmark_work_stealing_against_shared_channel() {
+        const FAN_OUT: usize = 8;
+        const JOBS: usize = 50_000;
+        const TOTAL: usize = FAN_OUT * JOBS;
+
+        fn wait_for(counter: &AtomicUsize) {
+            while counter.load(Ordering::Acquire) < TOTAL {
+                thread::yield_now();
+            }
+        }
+
+        // The outer jobs may still hold a clone of the pool after the last
+        // inner job ran. Dropping the pool from one of its own workers would
+        // join that worker on itself.
+        fn wait_for_outer_jobs<T>(pool: &Arc<T>) {
+            while Arc::strong_count(pool) > 1 {
+                thread::yield_now();
+            }
+        }
+
+        let channel_pool = Arc::new(ChannelPool::new(FAN_OUT));
+        let counter = Arc::new(AtomicUsize::new(0));
+        let start = Instant::now();
+        for _ in 0..FAN_OUT {
+            let pool = Arc::clone(&channel_pool);
+            let counter = Arc::clone(&counter);
+            channel_pool.execute(Box::new(move || {
+                for _ in 0..JOBS {
+                    let counter = Arc::clone(&counter);
+                    pool.execute(Box::new(move || {
+                        counter.fetch_add(1, Ordering::Release);
+                    }));
+                }
+            }));
+        }
+        wait_for(&counter);
+        let channel_time = start.elapsed();
+        wait_for_outer_jobs(&channel_pool);
+
+        let pool = Arc::new(ThreadPool::builder().size(FAN_OUT).quiet(true).build().unwrap());
+        let counter = Arc::new(AtomicUsize::new(0));
+        let start = Instant::now();
+        for _ in 0..FAN_OUT {
+            let inner_pool = Arc::clone(&pool);
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                for _ in 0..JOBS {
+                    let counter = Arc::clone(&counter);
+                    inner_pool.execute(move || {
+                        counter.fetch_add(1, Ordering::Release);
+                    }).unwrap();
+                }
+            }).unwrap();
+        }
+        wait_for(&counter);
+        let stealing_time = start.elapsed();
+        wait_for_outer_jobs(&pool);
+
+        let per_second = |time: Duration| TOTAL as f64 / time.as_secs_f64();
+        println!(
+            "shared channel: {:.0} jobs/s, work stealing: {:.0} jobs/s",
+            per_second(channel_time),
+            per_second(stealing_time),
+        );
+        // On a single core there is no lock contention for the deques to
+        // save, so only hold the pool to being faster where there is.
+        if thread::available_parallelism().map_or(1, |n| n.get()) > 1 {
+            assert!(stealing_time < channel_time);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn resize_cannot_be_zero() {
+        let mut pool = ThreadPool::new(1);
+        pool.resize(0);
     }
 }
\ No newline at end of file
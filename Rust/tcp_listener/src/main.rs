@@ -1,46 +1,68 @@
-use tcp_listener::ThreadPool;
-use std::{
-    fs,
-    io::{prelude::*, BufReader},
-    net::{TcpListener, TcpStream},
-    thread,
-    time::Duration,
+use tcp_listener::{
+    http::{AcceptPolicy, AccessLog, Method, Observed, Request, Router, StaticFiles, VirtualHosts},
+    Server, ServerConfig,
 };
+use std::{env, io::Write, process, thread, time::Duration};
 
 fn main() {
-    let listener = TcpListener::bind("127.0.0.1:7878").unwrap();
-    let pool = ThreadPool::new(4);
+    let config = load_config().unwrap_or_else(|err| {
+        eprintln!("Could not load the configuration: {err}");
+        process::exit(1);
+    });
 
-    for stream in listener.incoming().take(2) {
-        let stream = stream.unwrap();
+    let sites = Observed::new(virtual_hosts(&config)).observer(AccessLog::stderr());
+    let policy = AcceptPolicy::new()
+        .max_connections(256)
+        .rate_limit(10, 20);
+    let server = Server::bind(config, sites)
+        .unwrap_or_else(|err| {
+            eprintln!("Could not start the server: {err}");
+            process::exit(1);
+        })
+        .accept_policy(policy);
 
-        pool.execute(|| {
-            handle_connection(stream);
-        });
-    }
+    server.serve_connections(2);
 
     println!("Shutting down.");
 }
 
-fn handle_connection(mut stream: TcpStream) {
-    let buf_reader = BufReader::new(&mut stream);
-    let request_line = buf_reader.lines().next().unwrap().unwrap();
-
-    let (status_line, filename) = match &request_line[..] {
-        "GET / HTTP/1.1" => ("HTTP/1.1 200 OK", "hello.html"),
-        "GET /sleep HTTP/1.1" => {
-            thread::sleep(Duration::from_secs(5));
-            ("HTTP/1.1 200 OK", "hello.html")
-        }
-        _ => ("HTTP/1.1 404 NOT FOUND", "404.html"),
+/// Serves the document root for every host but the virtual hosts of the
+/// configuration, which get their own roots.
+fn virtual_hosts(config: &ServerConfig) -> VirtualHosts {
+    let files = StaticFiles::serve(&config.document_root);
+    let slow_files = files.clone();
+    let slow_page = move |request: &Request, writer: &mut dyn Write| {
+        thread::sleep(Duration::from_secs(5));
+        let index = Request {
+            path: String::from("/"),
+            ..request.clone()
+        };
+        slow_files.handle(&index, writer)
     };
 
-    let contents = fs::read_to_string(filename).unwrap();
-    let lenght = contents.len();
+    let default = Router::new()
+        .route(Method::Get, "/sleep", slow_page)
+        .fallback(files);
+    let mut hosts = VirtualHosts::new().default_host(default);
+
+    for (name, root) in &config.virtual_hosts {
+        hosts = hosts.host(name, Router::new().fallback(StaticFiles::serve(root)));
+    }
+
+    hosts
+}
 
-    let response = format!(
-        "{status_line}\r\nContent-Lenght: {lenght}\r\n\r\n{contents}"
-    );
+/// Reads the file given with `--config path`, or uses the defaults.
+fn load_config() -> Result<ServerConfig, Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
 
-    stream.write_all(response.as_bytes()).unwrap();
-}
\ No newline at end of file
+    match (args.next().as_deref(), args.next(), args.next()) {
+        (None, ..) => {
+            let config = ServerConfig::default();
+            config.validate()?;
+            Ok(config)
+        }
+        (Some("--config"), Some(path), None) => Ok(ServerConfig::from_file(path)?),
+        _ => Err("usage: tcp_listener [--config path]".into()),
+    }
+}
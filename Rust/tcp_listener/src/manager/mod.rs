@@ -0,0 +1,206 @@
+//! A set of named thread pools that jobs are routed to by name.
+//!
+//! Keeping blocking work such as file IO on its own pool means a slow disk
+//! can never use up the threads that CPU-bound work runs on.
+
+use std::{collections::HashMap, fmt, sync::Arc, thread};
+
+use crate::{ExecuteError, Job, PoolCreationError, ThreadPool};
+
+/// Owns several named `ThreadPool`s and routes jobs to them by name.
+#[derive(Debug, Default)]
+pub struct PoolManager {
+    pools: HashMap<String, Arc<ThreadPool>>,
+}
+
+/// The error returned by `PoolManager::execute`.
+pub enum RouteError {
+    /// No pool has the given name. The job is handed back.
+    UnknownPool(String, Job),
+    /// The pool refused the job.
+    Execute(ExecuteError),
+}
+
+impl fmt::Debug for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::UnknownPool(name, _) => write!(f, "UnknownPool({name:?}, ..)"),
+            RouteError::Execute(err) => write!(f, "Execute({err:?})"),
+        }
+    }
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::UnknownPool(name, _) => write!(f, "there is no pool named {name:?}"),
+            RouteError::Execute(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RouteError::UnknownPool(..) => None,
+            RouteError::Execute(err) => Some(err),
+        }
+    }
+}
+
+impl From<ExecuteError> for RouteError {
+    fn from(err: ExecuteError) -> RouteError {
+        RouteError::Execute(err)
+    }
+}
+
+impl PoolManager {
+    /// Name of the preset pool for CPU-bound jobs.
+    pub const CPU: &'static str = "cpu";
+    /// Name of the preset pool for jobs that mostly wait on IO.
+    pub const IO: &'static str = "io";
+
+    /// How many IO threads to run per core. IO-bound jobs spend most of
+    /// their time blocked, so many more of them than cores can make
+    /// progress at once.
+    const IO_THREADS_PER_CORE: usize = 4;
+
+    /// Creates a manager without any pool.
+    pub fn new() -> PoolManager {
+        PoolManager {
+            pools: HashMap::new(),
+        }
+    }
+
+    /// Creates a manager with the two preset pools: `PoolManager::CPU`, with
+    /// one thread per core, and `PoolManager::IO`, with several threads per
+    /// core.
+    ///
+    /// # Errors
+    ///
+    /// The `with_presets` function will return an error if a worker thread
+    /// could not be spawned.
+    pub fn with_presets() -> Result<PoolManager, PoolCreationError> {
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+
+        PoolManager::with_preset_size(cores)
+    }
+
+    /// Creates a manager with the two preset pools sized as if there were
+    /// `cores` cores: `cores` threads for `PoolManager::CPU` and several
+    /// times as many for `PoolManager::IO`.
+    ///
+    /// # Errors
+    ///
+    /// The `with_preset_size` function will return an error if `cores` is
+    /// zero or if a worker thread could not be spawned.
+    pub fn with_preset_size(cores: usize) -> Result<PoolManager, PoolCreationError> {
+        let cpu = ThreadPool::builder()
+            .size(cores)
+            .thread_name(|id| format!("cpu-{id}"))
+            .build()?;
+        let io = ThreadPool::builder()
+            .size(cores * PoolManager::IO_THREADS_PER_CORE)
+            .thread_name(|id| format!("io-{id}"))
+            .build()?;
+
+        let mut manager = PoolManager::new();
+        manager.insert(PoolManager::CPU, cpu);
+        manager.insert(PoolManager::IO, io);
+
+        Ok(manager)
+    }
+
+    /// Adds a pool under `name`, returning the pool it replaces, if any.
+    pub fn insert(&mut self, name: impl Into<String>, pool: ThreadPool) -> Option<Arc<ThreadPool>> {
+        self.pools.insert(name.into(), Arc::new(pool))
+    }
+
+    /// Returns the pool named `name`.
+    ///
+    /// The pool is shared, so it can be handed to jobs that need to send
+    /// work to it themselves.
+    pub fn get(&self, name: &str) -> Option<Arc<ThreadPool>> {
+        self.pools.get(name).map(Arc::clone)
+    }
+
+    /// Returns the names of all pools, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.pools.keys().map(String::as_str)
+    }
+
+    /// Executes a function using the pool named `name`.
+    ///
+    /// # Errors
+    ///
+    /// The `execute` function will return `RouteError::UnknownPool` if no
+    /// pool has that name, and `RouteError::Execute` if the pool refused the
+    /// job.
+    pub fn execute<F>(&self, name: &str, f: F) -> Result<(), RouteError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match self.pools.get(name) {
+            Some(pool) => Ok(pool.execute(f)?),
+            None => Err(RouteError::UnknownPool(name.to_string(), Box::new(f))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn presets_create_cpu_and_io_pools() {
+        let manager = PoolManager::with_presets().unwrap();
+
+        let cpu = manager.get(PoolManager::CPU).unwrap();
+        let io = manager.get(PoolManager::IO).unwrap();
+        assert!(io.size() > cpu.size());
+
+        let mut names: Vec<&str> = manager.names().collect();
+        names.sort();
+        assert_eq!(vec!["cpu", "io"], names);
+    }
+
+    #[test]
+    fn jobs_run_on_the_pool_they_are_routed_to() {
+        let manager = PoolManager::with_presets().unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        for name in [PoolManager::CPU, PoolManager::IO] {
+            let tx = tx.clone();
+            manager
+                .execute(name, move || {
+                    let thread = thread::current().name().unwrap().to_string();
+                    tx.send(thread).unwrap();
+                })
+                .unwrap();
+        }
+
+        let mut threads: Vec<String> = rx.iter().take(2).collect();
+        threads.sort();
+        assert!(threads[0].starts_with("cpu-"));
+        assert!(threads[1].starts_with("io-"));
+    }
+
+    #[test]
+    fn unknown_pool_hands_job_back() {
+        let mut manager = PoolManager::new();
+        manager.insert("only", ThreadPool::new(1));
+        let (tx, rx) = mpsc::channel();
+
+        let job = match manager.execute("missing", move || tx.send(1).unwrap()) {
+            Err(RouteError::UnknownPool(name, job)) => {
+                assert_eq!("missing", name);
+                job
+            }
+            other => panic!("expected an unknown pool, got {other:?}"),
+        };
+
+        manager.get("only").unwrap().execute(job).unwrap();
+        assert_eq!(1, rx.recv().unwrap());
+    }
+}
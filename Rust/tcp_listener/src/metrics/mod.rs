@@ -0,0 +1,202 @@
+//! Counters kept by the ThreadPool and the snapshots built from them.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Condvar, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// A snapshot of the state of a `ThreadPool`, returned by
+/// `ThreadPool::metrics`.
+///
+/// Every counter is read separately, so while jobs are running the numbers
+/// may be off by the few jobs that moved between reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// Jobs sent to the pool that no worker has picked up yet.
+    pub queued: usize,
+    /// Jobs currently being executed.
+    pub running: usize,
+    /// Jobs that have finished without panicking.
+    pub completed: usize,
+    /// Jobs that have panicked.
+    pub panicked: usize,
+    /// Jobs that were discarded because their token was cancelled.
+    pub cancelled: usize,
+    /// The counters of each worker currently in the pool.
+    pub workers: Vec<WorkerMetrics>,
+}
+
+/// The counters of a single worker in a `PoolMetrics` snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerMetrics {
+    pub id: usize,
+    /// Whether the worker is executing a job right now.
+    pub busy: bool,
+    pub completed: usize,
+    pub panicked: usize,
+}
+
+/// Pool-wide counters, updated by the pool when sending jobs and by the
+/// workers when running them.
+#[derive(Default)]
+pub(crate) struct Counters {
+    queued: AtomicUsize,
+    running: AtomicUsize,
+    completed: AtomicUsize,
+    panicked: AtomicUsize,
+    dropped: AtomicUsize,
+    cancelled: AtomicUsize,
+    /// Jobs that are queued or running. `wait_idle` sleeps on the condvar
+    /// until it reaches zero, the mutex is only taken to sleep and wake.
+    pending: AtomicUsize,
+    idle_lock: Mutex<()>,
+    idle: Condvar,
+}
+
+impl Counters {
+    /// Must be called before a job is sent, so that a worker never takes a
+    /// job out of the queue before it was counted in.
+    pub(crate) fn job_queued(&self) {
+        self.jobs_queued(1);
+    }
+
+    /// Counts in a whole batch of jobs, see `job_queued`.
+    pub(crate) fn jobs_queued(&self, jobs: usize) {
+        self.pending.fetch_add(jobs, Ordering::SeqCst);
+        self.queued.fetch_add(jobs, Ordering::SeqCst);
+    }
+
+    /// Undoes `job_queued` for a job that could not be sent.
+    pub(crate) fn job_rejected(&self) {
+        self.jobs_rejected(1);
+    }
+
+    /// Undoes `jobs_queued` for the jobs of a batch that could not be sent.
+    pub(crate) fn jobs_rejected(&self, jobs: usize) {
+        self.queued.fetch_sub(jobs, Ordering::SeqCst);
+        self.jobs_done(jobs);
+    }
+
+    /// A queued job was discarded without being run.
+    pub(crate) fn job_dropped(&self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        self.dropped.fetch_add(1, Ordering::SeqCst);
+        self.job_done();
+    }
+
+    /// A queued job was discarded because its token was cancelled.
+    pub(crate) fn job_cancelled(&self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        self.cancelled.fetch_add(1, Ordering::SeqCst);
+        self.job_done();
+    }
+
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn job_started(&self, worker: &WorkerStats) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        self.running.fetch_add(1, Ordering::SeqCst);
+        worker.busy.store(true, Ordering::SeqCst);
+        *worker.busy_since.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub(crate) fn job_finished(&self, worker: &WorkerStats, panicked: bool) {
+        if panicked {
+            self.panicked.fetch_add(1, Ordering::SeqCst);
+            worker.panicked.fetch_add(1, Ordering::SeqCst);
+        } else {
+            self.completed.fetch_add(1, Ordering::SeqCst);
+            worker.completed.fetch_add(1, Ordering::SeqCst);
+        }
+        *worker.busy_since.lock().unwrap() = None;
+        worker.busy.store(false, Ordering::SeqCst);
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        self.job_done();
+    }
+
+    fn job_done(&self) {
+        self.jobs_done(1);
+    }
+
+    fn jobs_done(&self, jobs: usize) {
+        if jobs > 0 && self.pending.fetch_sub(jobs, Ordering::SeqCst) == jobs {
+            let _idle_lock = self.idle_lock.lock().unwrap();
+            self.idle.notify_all();
+        }
+    }
+
+    /// Blocks until no job is queued or running.
+    pub(crate) fn wait_idle(&self) {
+        let mut idle_lock = self.idle_lock.lock().unwrap();
+
+        while self.pending.load(Ordering::SeqCst) > 0 {
+            idle_lock = self.idle.wait(idle_lock).unwrap();
+        }
+    }
+
+    pub(crate) fn snapshot(&self, workers: Vec<WorkerMetrics>) -> PoolMetrics {
+        PoolMetrics {
+            queued: self.queued.load(Ordering::SeqCst),
+            running: self.running.load(Ordering::SeqCst),
+            completed: self.completed.load(Ordering::SeqCst),
+            panicked: self.panicked.load(Ordering::SeqCst),
+            cancelled: self.cancelled.load(Ordering::SeqCst),
+            workers,
+        }
+    }
+}
+
+/// Per-worker counters. They are kept by the `Worker`, not by its thread,
+/// so they survive a respawn.
+#[derive(Default)]
+pub(crate) struct WorkerStats {
+    busy: AtomicBool,
+    /// When the job the worker is running started.
+    busy_since: Mutex<Option<Instant>>,
+    completed: AtomicUsize,
+    panicked: AtomicUsize,
+}
+
+/// What a worker is doing, as shown by the `Debug` output of the pool.
+pub(crate) enum WorkerState {
+    Idle,
+    Busy {
+        /// How long the current job has been running.
+        elapsed: Duration,
+    },
+}
+
+impl fmt::Debug for WorkerState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorkerState::Idle => write!(f, "Idle"),
+            WorkerState::Busy { elapsed } => {
+                f.debug_struct("Busy").field("elapsed", elapsed).finish()
+            }
+        }
+    }
+}
+
+impl WorkerStats {
+    pub(crate) fn state(&self) -> WorkerState {
+        match *self.busy_since.lock().unwrap() {
+            Some(since) => WorkerState::Busy { elapsed: since.elapsed() },
+            None => WorkerState::Idle,
+        }
+    }
+
+    pub(crate) fn snapshot(&self, id: usize) -> WorkerMetrics {
+        WorkerMetrics {
+            id,
+            busy: self.busy.load(Ordering::SeqCst),
+            completed: self.completed.load(Ordering::SeqCst),
+            panicked: self.panicked.load(Ordering::SeqCst),
+        }
+    }
+}
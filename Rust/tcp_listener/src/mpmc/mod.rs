@@ -0,0 +1,226 @@
+//! A lock-free multi-producer multi-consumer queue.
+//!
+//! This is the bounded array queue described by Dmitry Vyukov. Every slot
+//! of a ring buffer carries a sequence number telling producers and
+//! consumers whose turn it is to use it:
+//!
+//! - A producer may write the slot at position `pos` when its sequence is
+//!   `pos`. After writing it sets the sequence to `pos + 1`.
+//! - A consumer may read the slot at position `pos` when its sequence is
+//!   `pos + 1`. After reading it sets the sequence to `pos + capacity`, which
+//!   is the position the next producer will write to it at.
+//!
+//! Producers and consumers claim positions with a compare-and-swap on the
+//! tail and head counters, so none of them ever holds a lock.
+
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct Slot<T> {
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A bounded lock-free queue. `push` hands the value back when it is full.
+pub(crate) struct ArrayQueue<T> {
+    buffer: Box<[Slot<T>]>,
+    mask: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// Safety: a value is only ever accessed by the single thread that won the
+// compare-and-swap for its position, and the sequence number of the slot
+// publishes it (Release) to the thread that reads it next (Acquire).
+unsafe impl<T: Send> Send for ArrayQueue<T> {}
+unsafe impl<T: Send> Sync for ArrayQueue<T> {}
+
+impl<T> ArrayQueue<T> {
+    /// Creates a queue holding up to `capacity` values, rounded up to the
+    /// next power of two.
+    pub(crate) fn new(capacity: usize) -> ArrayQueue<T> {
+        let capacity = capacity.max(1).next_power_of_two();
+
+        let buffer = (0..capacity)
+            .map(|i| Slot {
+                sequence: AtomicUsize::new(i),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+
+        ArrayQueue {
+            buffer,
+            mask: capacity - 1,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn push(&self, value: T) -> Result<(), T> {
+        let mut pos = self.tail.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.buffer[pos & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(pos) as isize;
+
+            if diff == 0 {
+                match self.tail.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // Safety: winning the compare-and-swap makes this
+                        // thread the only one writing the slot.
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.sequence.store(pos.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                // The slot still holds the value from the previous lap.
+                return Err(value);
+            } else {
+                pos = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(crate) fn pop(&self) -> Option<T> {
+        let mut pos = self.head.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.buffer[pos & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(pos.wrapping_add(1)) as isize;
+
+            if diff == 0 {
+                match self.head.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // Safety: the sequence says a producer finished
+                        // writing the slot, and winning the compare-and-swap
+                        // makes this thread the only one reading it.
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.sequence
+                            .store(pos.wrapping_add(self.mask + 1), Ordering::Release);
+                        return Some(value);
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                // Nothing was written to the slot yet: the queue is empty.
+                return None;
+            } else {
+                pos = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for ArrayQueue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn values_come_out_in_order() {
+        let queue = ArrayQueue::new(4);
+
+        for i in 0..4 {
+            queue.push(i).unwrap();
+        }
+        assert_eq!(Err(4), queue.push(4));
+
+        assert_eq!(vec![0, 1, 2, 3], (0..4).filter_map(|_| queue.pop()).collect::<Vec<_>>());
+        assert_eq!(None, queue.pop());
+    }
+
+    #[test]
+    fn slots_are_reused_after_wrapping_around() {
+        let queue = ArrayQueue::new(2);
+
+        for i in 0..10 {
+            queue.push(i).unwrap();
+            assert_eq!(Some(i), queue.pop());
+        }
+    }
+
+    #[test]
+    fn remaining_values_are_dropped_with_queue() {
+        let value = Arc::new(());
+        let queue = ArrayQueue::new(4);
+
+        queue.push(Arc::clone(&value)).unwrap();
+        queue.push(Arc::clone(&value)).unwrap();
+        drop(queue);
+
+        assert_eq!(1, Arc::strong_count(&value));
+    }
+
+    #[test]
+    fn many_producers_and_consumers_see_every_value_once() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+
+        let queue = Arc::new(ArrayQueue::new(64));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        let mut value = p * PER_PRODUCER + i;
+                        while let Err(v) = queue.push(value) {
+                            value = v;
+                            thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..PRODUCERS)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    let mut seen = Vec::new();
+                    while seen.len() < PER_PRODUCER {
+                        match queue.pop() {
+                            Some(value) => seen.push(value),
+                            None => thread::yield_now(),
+                        }
+                    }
+                    seen
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut all: Vec<usize> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+        all.sort();
+
+        assert_eq!((0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>(), all);
+    }
+}
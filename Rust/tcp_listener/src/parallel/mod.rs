@@ -0,0 +1,122 @@
+//! Data-parallel helpers built on top of `ThreadPool::scope`.
+
+use crate::ThreadPool;
+
+/// How many chunks every worker gets on average. More chunks than workers
+/// keep the pool busy when some chunks take longer than others.
+const CHUNKS_PER_WORKER: usize = 4;
+
+impl ThreadPool {
+    /// Applies `f` to every item of `iter` on the pool and returns the
+    /// results in the order of the input.
+    ///
+    /// The items are split into chunks, and each chunk runs as one job so
+    /// that small items don't pay for a job each.
+    ///
+    /// # Panics
+    ///
+    /// The `map` function will panic if `f` panics for any item.
+    pub fn map<I, F, T>(&self, iter: I, f: F) -> Vec<T>
+    where
+        I: IntoIterator,
+        I::Item: Send,
+        F: Fn(I::Item) -> T + Sync,
+        T: Send,
+    {
+        let chunks = self.chunk(iter);
+        let mut results: Vec<Vec<T>> = chunks.iter().map(|_| Vec::new()).collect();
+        let f = &f;
+
+        self.scope(|s| {
+            for (chunk, result) in chunks.into_iter().zip(results.iter_mut()) {
+                s.spawn(move || *result = chunk.into_iter().map(f).collect());
+            }
+        });
+
+        results.into_iter().flatten().collect()
+    }
+
+    /// Calls `f` on every item of `iter` on the pool, returning once all of
+    /// them were processed.
+    ///
+    /// # Panics
+    ///
+    /// The `for_each` function will panic if `f` panics for any item.
+    pub fn for_each<I, F>(&self, iter: I, f: F)
+    where
+        I: IntoIterator,
+        I::Item: Send,
+        F: Fn(I::Item) + Sync,
+    {
+        let chunks = self.chunk(iter);
+        let f = &f;
+
+        self.scope(|s| {
+            for chunk in chunks {
+                s.spawn(move || chunk.into_iter().for_each(f));
+            }
+        });
+    }
+
+    fn chunk<I: IntoIterator>(&self, iter: I) -> Vec<Vec<I::Item>> {
+        let mut items: Vec<I::Item> = iter.into_iter().collect();
+        let chunk_len = items.len().div_ceil(self.size() * CHUNKS_PER_WORKER).max(1);
+
+        let mut chunks = Vec::new();
+        while items.len() > chunk_len {
+            let rest = items.split_off(chunk_len);
+            chunks.push(items);
+            items = rest;
+        }
+        if !items.is_empty() {
+            chunks.push(items);
+        }
+
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ThreadPool;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn map_keeps_the_input_order() {
+        let pool = ThreadPool::new(3);
+
+        let squares = pool.map(0..100, |x| x * x);
+
+        assert_eq!((0..100).map(|x| x * x).collect::<Vec<_>>(), squares);
+    }
+
+    #[test]
+    fn map_borrows_from_the_caller() {
+        let pool = ThreadPool::new(2);
+        let words = vec![String::from("safe"), String::from("fast"), String::from("productive")];
+        let suffix = String::from("!");
+
+        let shouted = pool.map(&words, |word| format!("{word}{suffix}"));
+
+        assert_eq!(vec!["safe!", "fast!", "productive!"], shouted);
+    }
+
+    #[test]
+    fn map_of_empty_input_is_empty() {
+        let pool = ThreadPool::new(2);
+
+        assert!(pool.map(Vec::<i32>::new(), |x| x).is_empty());
+    }
+
+    #[test]
+    fn for_each_visits_every_item() {
+        let pool = ThreadPool::new(4);
+        let sum = AtomicUsize::new(0);
+
+        pool.for_each(1..=1000, |x| {
+            sum.fetch_add(x, Ordering::SeqCst);
+        });
+
+        assert_eq!(500_500, sum.load(Ordering::SeqCst));
+    }
+}
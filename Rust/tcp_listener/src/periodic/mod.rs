@@ -0,0 +1,244 @@
+//! Recurring jobs for the ThreadPool.
+//!
+//! All periodic jobs of a pool share a single timer thread. The timer never
+//! runs the jobs itself, it only sends a new copy of each job to the workers
+//! every time its interval elapses.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Message, QueuedJob, Shared};
+
+type PeriodicJob = Arc<dyn Fn() + Send + Sync + 'static>;
+
+/// A guard returned by `ThreadPool::execute_periodic`.
+///
+/// The job keeps being scheduled until this guard is dropped. Dropping it
+/// does not interrupt a run that was already sent to the workers.
+pub struct PeriodicHandle {
+    cancelled: Arc<AtomicBool>,
+    timer: Arc<TimerShared>,
+}
+
+impl PeriodicHandle {
+    /// Stops the recurrence. This is the same as dropping the guard.
+    pub fn cancel(self) {}
+}
+
+impl Drop for PeriodicHandle {
+    fn drop(&mut self) {
+        self.cancelled.store(true, AtomicOrdering::SeqCst);
+        self.timer.condvar.notify_one();
+    }
+}
+
+struct Entry {
+    next_run: Instant,
+    seq: u64,
+    interval: Duration,
+    job: PeriodicJob,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> Ordering {
+        (self.next_run, self.seq).cmp(&(other.next_run, other.seq))
+    }
+}
+
+struct TimerState {
+    entries: BinaryHeap<Reverse<Entry>>,
+    next_seq: u64,
+    shutdown: bool,
+}
+
+struct TimerShared {
+    state: Mutex<TimerState>,
+    condvar: Condvar,
+}
+
+/// The timer thread of a pool, created the first time a periodic job is
+/// scheduled.
+pub(crate) struct Timer {
+    shared: Arc<TimerShared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Timer {
+    pub(crate) fn new(pool: Arc<Shared>) -> Timer {
+        let shared = Arc::new(TimerShared {
+            state: Mutex::new(TimerState {
+                entries: BinaryHeap::new(),
+                next_seq: 0,
+                shutdown: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        let thread_shared = Arc::clone(&shared);
+        let thread = thread::Builder::new()
+            .name(String::from("pool-timer"))
+            .spawn(move || Timer::run(&thread_shared, &pool))
+            .unwrap_or_else(|err| panic!("Could not spawn timer thread: {err}"));
+
+        Timer {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Schedules `job` to be sent to the workers every `interval`, starting
+    /// one interval from now.
+    pub(crate) fn schedule(&self, interval: Duration, job: PeriodicJob) -> PeriodicHandle {
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let mut state = self.shared.state.lock().unwrap();
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.entries.push(Reverse(Entry {
+            next_run: Instant::now() + interval,
+            seq,
+            interval,
+            job,
+            cancelled: Arc::clone(&cancelled),
+        }));
+        drop(state);
+
+        self.shared.condvar.notify_one();
+
+        PeriodicHandle {
+            cancelled,
+            timer: Arc::clone(&self.shared),
+        }
+    }
+
+    fn run(shared: &TimerShared, pool: &Shared) {
+        let mut state = shared.state.lock().unwrap();
+
+        loop {
+            if state.shutdown {
+                break;
+            }
+
+            while let Some(Reverse(entry)) = state.entries.peek() {
+                if entry.cancelled.load(AtomicOrdering::SeqCst) {
+                    state.entries.pop();
+                } else {
+                    break;
+                }
+            }
+
+            let next_run = match state.entries.peek() {
+                Some(Reverse(entry)) => entry.next_run,
+                None => {
+                    state = shared.condvar.wait(state).unwrap();
+                    continue;
+                }
+            };
+
+            let now = Instant::now();
+            if next_run > now {
+                state = shared.condvar.wait_timeout(state, next_run - now).unwrap().0;
+                continue;
+            }
+
+            let Reverse(mut entry) = state.entries.pop().unwrap();
+            let job = Arc::clone(&entry.job);
+
+            // If the workers fell behind, skip the missed runs instead of
+            // sending all of them at once.
+            entry.next_run = (entry.next_run + entry.interval).max(now);
+            state.entries.push(Reverse(entry));
+
+            // Never hold the lock while sending, a bounded queue may block.
+            drop(state);
+            pool.counters.job_queued();
+            let message = Message::NewJob(Box::new(move || job()));
+            if pool.queue.push(QueuedJob(message)).is_err() {
+                pool.counters.job_rejected();
+                break;
+            }
+            state = shared.state.lock().unwrap();
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.condvar.notify_one();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ThreadPool;
+    use std::{
+        sync::{mpsc, Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn periodic_job_runs_repeatedly() {
+        let pool = ThreadPool::new(2);
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+
+        let _handle = pool.execute_periodic(Duration::from_millis(5), move || {
+            let _ = tx.lock().unwrap().send(());
+        });
+
+        for _ in 0..3 {
+            rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+    }
+
+    #[test]
+    fn dropping_handle_stops_recurrence() {
+        let pool = ThreadPool::new(1);
+        let runs = Arc::new(Mutex::new(0));
+        let runs_clone = Arc::clone(&runs);
+
+        let handle = pool.execute_periodic(Duration::from_millis(5), move || {
+            *runs_clone.lock().unwrap() += 1;
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        drop(handle);
+
+        // Let any run that was already queued finish.
+        thread::sleep(Duration::from_millis(20));
+        let after_drop = *runs.lock().unwrap();
+        assert!(after_drop > 0);
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(after_drop, *runs.lock().unwrap());
+    }
+}
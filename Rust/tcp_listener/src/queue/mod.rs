@@ -0,0 +1,198 @@
+//! The interface between a ThreadPool and the queue its jobs wait in.
+//!
+//! The pool only ever talks to its queue through the `JobQueue` trait, so
+//! the default work-stealing scheduler can be swapped for any other
+//! implementation with `ThreadPool::with_queue` or
+//! `ThreadPoolBuilder::queue`.
+
+use std::fmt;
+
+use crate::Message;
+
+/// A job waiting in a `JobQueue`.
+///
+/// It is opaque on purpose: besides the closures sent with `execute`, the
+/// pool also queues its own control messages, and a queue only has to store
+/// them and give them back.
+pub struct QueuedJob(pub(crate) Message);
+
+impl fmt::Debug for QueuedJob {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "QueuedJob(..)")
+    }
+}
+
+/// The error returned by `JobQueue::try_push`. The job is handed back in
+/// both cases.
+#[derive(Debug)]
+pub enum TryPushError {
+    /// The queue is bounded and full.
+    Full(QueuedJob),
+    /// The queue was closed.
+    Closed(QueuedJob),
+}
+
+/// A queue that the jobs of a `ThreadPool` wait in until a worker takes
+/// them.
+///
+/// Every worker thread calls `pop` in a loop, so it has to block while the
+/// queue is empty. Once `close` was called, `push` must refuse new jobs and
+/// `pop` must return `None` as soon as the queue is empty, which is how the
+/// workers know to stop.
+pub trait JobQueue: Send + Sync {
+    /// Adds a job to the queue, blocking while a bounded queue is full.
+    ///
+    /// # Errors
+    ///
+    /// The job is handed back if the queue was closed.
+    fn push(&self, job: QueuedJob) -> Result<(), QueuedJob>;
+
+    /// Takes the next job out of the queue, blocking until there is one.
+    /// Returns `None` once the queue is closed and empty.
+    fn pop(&self) -> Option<QueuedJob>;
+
+    /// Returns the number of jobs waiting in the queue.
+    fn len(&self) -> usize;
+
+    /// Closes the queue, waking up every worker blocked in `pop`.
+    fn close(&self);
+
+    /// Returns whether no job is waiting in the queue.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a job to the queue without blocking.
+    ///
+    /// The default implementation just calls `push`, which is right for
+    /// unbounded queues. Bounded queues should override it.
+    ///
+    /// # Errors
+    ///
+    /// The job is handed back if the queue is full or was closed.
+    fn try_push(&self, job: QueuedJob) -> Result<(), TryPushError> {
+        self.push(job).map_err(TryPushError::Closed)
+    }
+
+    /// Adds as many of `jobs`, in order, as fit without blocking, and hands
+    /// back the rest.
+    fn push_batch(&self, jobs: Vec<QueuedJob>) -> Vec<QueuedJob> {
+        let mut jobs = jobs.into_iter();
+
+        while let Some(job) = jobs.next() {
+            if let Err(TryPushError::Full(job) | TryPushError::Closed(job)) = self.try_push(job) {
+                return std::iter::once(job).chain(jobs).collect();
+            }
+        }
+
+        Vec::new()
+    }
+
+    /// Returns the bound of the queue, if it has one.
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Called on each worker thread when it starts, before its first `pop`.
+    fn register_worker(&self, _id: usize) {}
+
+    /// Called on a worker thread right before it retires because the pool
+    /// shrank. Jobs the queue keeps for that worker alone must be handed to
+    /// the others.
+    fn unregister_worker(&self, _id: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ThreadPool;
+    use std::sync::{mpsc, Condvar, Mutex};
+
+    /// A last in, first out queue, the simplest queue that behaves
+    /// differently from the default one.
+    #[derive(Default)]
+    struct LifoQueue {
+        state: Mutex<(Vec<QueuedJob>, bool)>,
+        available: Condvar,
+    }
+
+    impl JobQueue for LifoQueue {
+        fn push(&self, job: QueuedJob) -> Result<(), QueuedJob> {
+            let mut state = self.state.lock().unwrap();
+            if state.1 {
+                return Err(job);
+            }
+            state.0.push(job);
+            self.available.notify_one();
+            Ok(())
+        }
+
+        fn pop(&self) -> Option<QueuedJob> {
+            let mut state = self.state.lock().unwrap();
+            loop {
+                if let Some(job) = state.0.pop() {
+                    return Some(job);
+                }
+                if state.1 {
+                    return None;
+                }
+                state = self.available.wait(state).unwrap();
+            }
+        }
+
+        fn len(&self) -> usize {
+            self.state.lock().unwrap().0.len()
+        }
+
+        fn close(&self) {
+            self.state.lock().unwrap().1 = true;
+            self.available.notify_all();
+        }
+    }
+
+    #[test]
+    fn pool_takes_jobs_in_the_order_of_its_queue() {
+        let pool = ThreadPool::with_queue(1, LifoQueue::default());
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        })
+        .unwrap();
+        started_rx.recv().unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        for i in 0..3 {
+            let tx = tx.clone();
+            pool.execute(move || tx.send(i).unwrap()).unwrap();
+        }
+        drop(tx);
+
+        release_tx.send(()).unwrap();
+        assert_eq!(vec![2, 1, 0], rx.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn custom_queue_supports_batches_and_resizing() {
+        let mut pool = ThreadPool::with_queue(2, LifoQueue::default());
+        let (tx, rx) = mpsc::channel();
+
+        let jobs: Vec<crate::Job> = (0..4)
+            .map(|i| {
+                let tx = tx.clone();
+                Box::new(move || tx.send(i).unwrap()) as crate::Job
+            })
+            .collect();
+        assert_eq!(4, pool.execute_batch(jobs));
+
+        pool.resize(1);
+        assert_eq!(1, pool.size());
+
+        drop(tx);
+        let mut received: Vec<i32> = rx.iter().collect();
+        received.sort();
+        assert_eq!(vec![0, 1, 2, 3], received);
+    }
+}
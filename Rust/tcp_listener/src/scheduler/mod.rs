@@ -0,0 +1,504 @@
+//! Work-stealing job queues, the default `JobQueue` of the ThreadPool.
+//!
+//! Every worker owns a local deque. Jobs sent from outside of the pool go to
+//! a global injector queue, a lock-free ring buffer, while jobs sent from
+//! inside a job go to the local deque of the worker running it, so a job
+//! that fans out into many small
+//! jobs doesn't contend with the rest of the pool. A worker looks for work in
+//! its own deque first, then in the injector, and finally steals from the
+//! back of the deques of the other workers. Workers with nothing to do park
+//! their thread until a producer unparks them.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, RwLock,
+    },
+    thread,
+};
+
+use crate::mpmc::ArrayQueue;
+use crate::queue::{JobQueue, QueuedJob, TryPushError};
+use crate::Message;
+
+/// Number of messages the lock-free part of the injector can hold before
+/// producers spill over into its locked overflow queue.
+const INJECTOR_RING_CAPACITY: usize = 1024;
+
+thread_local! {
+    /// The scheduler and local deque of the worker running on this thread,
+    /// if any.
+    static CURRENT: RefCell<Option<(*const Scheduler, Arc<LocalQueue>)>> = const { RefCell::new(None) };
+}
+
+/// The order in which the workers of a pool take the jobs it was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedulingMode {
+    /// First in, first out: jobs run in the order they were sent, so no
+    /// job waits behind ones sent after it.
+    #[default]
+    Fifo,
+    /// Last in, first out: the most recently sent job runs first, while
+    /// the data it works on is most likely still in the cache. Thieves
+    /// still steal the oldest jobs, so nothing is left behind for long on
+    /// a busy pool.
+    Lifo,
+}
+
+/// The deque owned by one worker.
+struct LocalQueue {
+    id: usize,
+    mode: SchedulingMode,
+    jobs: Mutex<VecDeque<Message>>,
+}
+
+impl LocalQueue {
+    fn pop(&self) -> Option<Message> {
+        let mut jobs = self.jobs.lock().unwrap();
+
+        match self.mode {
+            SchedulingMode::Fifo => jobs.pop_front(),
+            SchedulingMode::Lifo => jobs.pop_back(),
+        }
+    }
+
+    /// Thieves take from the other end than the owner.
+    fn steal(&self) -> Option<Message> {
+        let mut jobs = self.jobs.lock().unwrap();
+
+        match self.mode {
+            SchedulingMode::Fifo => jobs.pop_back(),
+            SchedulingMode::Lifo => jobs.pop_front(),
+        }
+    }
+}
+
+/// The global queue for jobs sent from outside of the pool.
+///
+/// Messages normally go through the lock-free ring. Only when it is full do
+/// they go to the overflow queue, and they keep going there until it is
+/// drained so that messages mostly come out in the order they went in.
+///
+/// A ring can only be first in, first out, so in LIFO mode every message
+/// goes to the overflow queue, used as a stack.
+struct Injector {
+    mode: SchedulingMode,
+    ring: ArrayQueue<Message>,
+    overflow: Mutex<VecDeque<Message>>,
+    overflow_len: AtomicUsize,
+}
+
+impl Injector {
+    fn new(mode: SchedulingMode) -> Injector {
+        Injector {
+            mode,
+            ring: ArrayQueue::new(INJECTOR_RING_CAPACITY),
+            overflow: Mutex::new(VecDeque::new()),
+            overflow_len: AtomicUsize::new(0),
+        }
+    }
+
+    fn push(&self, message: Message) {
+        let ring_usable = self.mode == SchedulingMode::Fifo
+            && self.overflow_len.load(Ordering::SeqCst) == 0;
+
+        let message = if ring_usable {
+            match self.ring.push(message) {
+                Ok(()) => return,
+                Err(message) => message,
+            }
+        } else {
+            message
+        };
+
+        let mut overflow = self.overflow.lock().unwrap();
+        overflow.push_back(message);
+        self.overflow_len.store(overflow.len(), Ordering::SeqCst);
+    }
+
+    /// Pushes several messages in order, taking the overflow lock at most
+    /// once.
+    fn push_batch(&self, messages: Vec<Message>) {
+        let mut messages = messages.into_iter();
+        let mut spilled = None;
+
+        if self.mode == SchedulingMode::Fifo && self.overflow_len.load(Ordering::SeqCst) == 0 {
+            spilled = messages.by_ref().find_map(|message| self.ring.push(message).err());
+        }
+
+        let rest: Vec<Message> = spilled.into_iter().chain(messages).collect();
+        if rest.is_empty() {
+            return;
+        }
+
+        let mut overflow = self.overflow.lock().unwrap();
+        overflow.extend(rest);
+        self.overflow_len.store(overflow.len(), Ordering::SeqCst);
+    }
+
+    fn pop(&self) -> Option<Message> {
+        if let Some(message) = self.ring.pop() {
+            return Some(message);
+        }
+        if self.overflow_len.load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+
+        let mut overflow = self.overflow.lock().unwrap();
+        let message = match self.mode {
+            SchedulingMode::Fifo => overflow.pop_front(),
+            SchedulingMode::Lifo => overflow.pop_back(),
+        };
+        self.overflow_len.store(overflow.len(), Ordering::SeqCst);
+        message
+    }
+}
+
+/// The queues shared by a pool and its workers.
+///
+/// The counters are atomics so that sending and taking a job never touch a
+/// lock shared by the whole pool. The `sleepers` list is only locked by
+/// workers going to sleep and by whoever has to wake them up, and the `room`
+/// mutex only by producers waiting on a full bounded queue.
+pub(crate) struct Scheduler {
+    mode: SchedulingMode,
+    injector: Injector,
+    locals: RwLock<Vec<Arc<LocalQueue>>>,
+    capacity: Option<usize>,
+    /// Messages pushed (or about to be pushed) to any queue that no worker
+    /// has taken out yet.
+    queued: AtomicUsize,
+    /// Workers sleeping while waiting for a message.
+    idle: AtomicUsize,
+    /// Producers sleeping while waiting for room in a bounded queue.
+    blocked: AtomicUsize,
+    closed: AtomicBool,
+    sleepers: Mutex<Vec<thread::Thread>>,
+    room: Mutex<()>,
+    not_full: Condvar,
+}
+
+impl Scheduler {
+    /// Creates the scheduler. The queue is only bounded if a capacity is
+    /// given.
+    pub(crate) fn new(capacity: Option<usize>, mode: SchedulingMode) -> Scheduler {
+        Scheduler {
+            mode,
+            injector: Injector::new(mode),
+            locals: RwLock::new(Vec::new()),
+            capacity,
+            queued: AtomicUsize::new(0),
+            idle: AtomicUsize::new(0),
+            blocked: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            sleepers: Mutex::new(Vec::new()),
+            room: Mutex::new(()),
+            not_full: Condvar::new(),
+        }
+    }
+
+    /// Registers the calling thread as worker `id`, creating its local deque
+    /// unless a previous thread of the same worker already did.
+    fn register(&self, id: usize) {
+        let mut locals = self.locals.write().unwrap();
+
+        let local = match locals.iter().find(|local| local.id == id) {
+            Some(local) => Arc::clone(local),
+            None => {
+                let local = Arc::new(LocalQueue {
+                    id,
+                    mode: self.mode,
+                    jobs: Mutex::new(VecDeque::new()),
+                });
+                locals.push(Arc::clone(&local));
+                local
+            }
+        };
+        drop(locals);
+
+        CURRENT.with(|current| {
+            *current.borrow_mut() = Some((self as *const Scheduler, local));
+        });
+    }
+
+    /// Removes the deque of a retiring worker, handing any job left in it to
+    /// the injector so another worker runs it.
+    fn unregister(&self, id: usize) {
+        let mut locals = self.locals.write().unwrap();
+        let local = match locals.iter().position(|local| local.id == id) {
+            Some(index) => locals.remove(index),
+            None => return,
+        };
+        drop(locals);
+
+        let leftovers: Vec<Message> = local.jobs.lock().unwrap().drain(..).collect();
+        for message in leftovers {
+            self.injector.push(message);
+        }
+        self.wake_all();
+
+        CURRENT.with(|current| *current.borrow_mut() = None);
+    }
+
+    /// Returns the local deque of the calling thread if it is one of our
+    /// workers.
+    fn current_local(&self) -> Option<Arc<LocalQueue>> {
+        CURRENT.with(|current| match &*current.borrow() {
+            Some((scheduler, local)) if std::ptr::eq(*scheduler, self) => {
+                Some(Arc::clone(local))
+            }
+            _ => None,
+        })
+    }
+
+    fn has_room(&self, capacity: usize) -> bool {
+        self.queued.load(Ordering::SeqCst) < capacity + self.idle.load(Ordering::SeqCst)
+    }
+
+    /// Counts a message in, waiting for room first if the queue is bounded.
+    fn reserve(&self, local: bool, block: bool) -> bool {
+        let capacity = match self.capacity {
+            // Workers never wait for room in the queue: if every worker
+            // blocked on its own fan-out jobs, nobody would be left to make
+            // room.
+            Some(capacity) if !local => capacity,
+            _ => {
+                self.queued.fetch_add(1, Ordering::SeqCst);
+                return true;
+            }
+        };
+
+        loop {
+            let queued = self.queued.load(Ordering::SeqCst);
+
+            if queued < capacity + self.idle.load(Ordering::SeqCst) {
+                let reserved = self
+                    .queued
+                    .compare_exchange(queued, queued + 1, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok();
+                if reserved {
+                    return true;
+                }
+                continue;
+            }
+            if !block {
+                return false;
+            }
+
+            let room = self.room.lock().unwrap();
+            self.blocked.fetch_add(1, Ordering::SeqCst);
+            if !self.has_room(capacity) && !self.closed.load(Ordering::SeqCst) {
+                drop(self.not_full.wait(room).unwrap());
+            }
+            self.blocked.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Counts in as many as `wanted` messages as there is room for without
+    /// blocking, and returns how many that is.
+    fn reserve_many(&self, wanted: usize, local: bool) -> usize {
+        let capacity = match self.capacity {
+            Some(capacity) if !local => capacity,
+            _ => {
+                self.queued.fetch_add(wanted, Ordering::SeqCst);
+                return wanted;
+            }
+        };
+
+        loop {
+            let queued = self.queued.load(Ordering::SeqCst);
+            let room = (capacity + self.idle.load(Ordering::SeqCst)).saturating_sub(queued);
+            let reserved = wanted.min(room);
+
+            if reserved == 0 {
+                return 0;
+            }
+            if self
+                .queued
+                .compare_exchange(queued, queued + reserved, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                return reserved;
+            }
+        }
+    }
+
+    /// Pushes as many of `messages`, in order, as there is room for without
+    /// blocking, and hands back the rest.
+    fn push_batch(&self, mut messages: Vec<Message>) -> Vec<Message> {
+        if self.closed.load(Ordering::SeqCst) {
+            return messages;
+        }
+
+        let local = self.current_local();
+        let reserved = self.reserve_many(messages.len(), local.is_some());
+        let rest = messages.split_off(reserved);
+
+        match local {
+            Some(local) => local.jobs.lock().unwrap().extend(messages),
+            None => self.injector.push_batch(messages),
+        }
+
+        if reserved > 0 && self.idle.load(Ordering::SeqCst) > 0 {
+            let mut sleepers = self.sleepers.lock().unwrap();
+            for _ in 0..reserved {
+                match sleepers.pop() {
+                    Some(sleeper) => sleeper.unpark(),
+                    None => break,
+                }
+            }
+        }
+
+        rest
+    }
+
+    fn push(&self, message: Message, block: bool) -> Result<(), TryPushError> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(TryPushError::Closed(QueuedJob(message)));
+        }
+
+        let local = self.current_local();
+
+        if !self.reserve(local.is_some(), block) {
+            return Err(TryPushError::Full(QueuedJob(message)));
+        }
+
+        match local {
+            Some(local) => local.jobs.lock().unwrap().push_back(message),
+            None => self.injector.push(message),
+        }
+
+        if self.idle.load(Ordering::SeqCst) > 0 {
+            if let Some(sleeper) = self.sleepers.lock().unwrap().pop() {
+                sleeper.unpark();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn wake_all(&self) {
+        for sleeper in self.sleepers.lock().unwrap().drain(..) {
+            sleeper.unpark();
+        }
+    }
+
+    fn notify_room(&self) {
+        if self.blocked.load(Ordering::SeqCst) > 0 {
+            let _room = self.room.lock().unwrap();
+            self.not_full.notify_one();
+        }
+    }
+
+    /// Blocks until a message is available for the worker owning `local`.
+    /// Returns `None` once the scheduler is closed and all queues are empty.
+    fn next(&self, local: Option<&LocalQueue>) -> Option<Message> {
+        loop {
+            if let Some(message) = self.find(local) {
+                self.queued.fetch_sub(1, Ordering::SeqCst);
+                self.notify_room();
+                return Some(message);
+            }
+
+            if self.queued.load(Ordering::SeqCst) > 0 {
+                // A message was counted in but not pushed yet, or another
+                // worker is about to count one out. Try again shortly.
+                thread::yield_now();
+                continue;
+            }
+            if self.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            let me = thread::current();
+            self.sleepers.lock().unwrap().push(me.clone());
+            self.idle.fetch_add(1, Ordering::SeqCst);
+
+            // A sleeping worker makes room for a job to be handed to it.
+            self.notify_room();
+
+            // A producer that sends after we joined the sleepers sees us in
+            // `idle` and unparks someone, and an unpark that comes before the
+            // park makes it return right away, so no wakeup is lost. Spurious
+            // wakeups just go around the loop again.
+            if self.queued.load(Ordering::SeqCst) == 0 && !self.closed.load(Ordering::SeqCst) {
+                thread::park();
+            }
+
+            self.sleepers.lock().unwrap().retain(|sleeper| sleeper.id() != me.id());
+            self.idle.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    fn find(&self, local: Option<&LocalQueue>) -> Option<Message> {
+        if let Some(message) = local.and_then(LocalQueue::pop) {
+            return Some(message);
+        }
+        if let Some(message) = self.injector.pop() {
+            return Some(message);
+        }
+
+        let locals = self.locals.read().unwrap();
+        let start = local
+            .and_then(|local| locals.iter().position(|other| other.id == local.id))
+            .unwrap_or(0);
+
+        // Start stealing from the next worker so thieves spread out.
+        (1..locals.len())
+            .map(|offset| &locals[(start + offset) % locals.len()])
+            .find_map(|victim| victim.steal())
+    }
+}
+
+impl JobQueue for Scheduler {
+    fn push(&self, job: QueuedJob) -> Result<(), QueuedJob> {
+        Scheduler::push(self, job.0, true).map_err(|err| match err {
+            TryPushError::Full(job) | TryPushError::Closed(job) => job,
+        })
+    }
+
+    fn try_push(&self, job: QueuedJob) -> Result<(), TryPushError> {
+        Scheduler::push(self, job.0, false)
+    }
+
+    fn push_batch(&self, jobs: Vec<QueuedJob>) -> Vec<QueuedJob> {
+        let messages = jobs.into_iter().map(|job| job.0).collect();
+
+        Scheduler::push_batch(self, messages)
+            .into_iter()
+            .map(QueuedJob)
+            .collect()
+    }
+
+    fn pop(&self) -> Option<QueuedJob> {
+        let local = self.current_local();
+
+        self.next(local.as_deref()).map(QueuedJob)
+    }
+
+    fn len(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.wake_all();
+
+        let _room = self.room.lock().unwrap();
+        self.not_full.notify_all();
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn register_worker(&self, id: usize) {
+        self.register(id);
+    }
+
+    fn unregister_worker(&self, id: usize) {
+        self.unregister(id);
+    }
+}
@@ -0,0 +1,198 @@
+//! Scoped jobs that can borrow from the stack of the caller, like
+//! `std::thread::scope` but running on the workers of a ThreadPool.
+
+use std::{
+    any::Any,
+    marker::PhantomData,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+};
+
+use crate::{Job, ThreadPool};
+
+struct ScopeState {
+    pending: Mutex<usize>,
+    done: Condvar,
+    /// The payload of the first scoped job that panicked.
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl ScopeState {
+    fn job_done(&self) {
+        let mut pending = self.pending.lock().unwrap();
+
+        *pending -= 1;
+        if *pending == 0 {
+            self.done.notify_all();
+        }
+    }
+
+    fn wait(&self) {
+        let mut pending = self.pending.lock().unwrap();
+
+        while *pending > 0 {
+            pending = self.done.wait(pending).unwrap();
+        }
+    }
+}
+
+/// A scope to spawn jobs in, created by `ThreadPool::scope`.
+///
+/// The two lifetimes follow `std::thread::Scope`: `'scope` is the lifetime
+/// of the scope itself and `'env` the lifetime of anything borrowed by the
+/// jobs, which must outlive the scope.
+pub struct Scope<'scope, 'env: 'scope> {
+    pool: &'scope ThreadPool,
+    state: Arc<ScopeState>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Executes a function using the thread pool. Unlike
+    /// `ThreadPool::execute`, the function may borrow anything that lives
+    /// longer than the scope.
+    ///
+    /// # Panics
+    ///
+    /// The `spawn` function will panic if the job could not be handed to the
+    /// pool.
+    pub fn spawn<F>(&'scope self, f: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        *self.state.pending.lock().unwrap() += 1;
+
+        let state = Arc::clone(&self.state);
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+                state.panic.lock().unwrap().get_or_insert(payload);
+            }
+            state.job_done();
+        });
+
+        // Safety: `ThreadPool::scope` doesn't return before `pending` is back
+        // to zero, so the job runs to completion while everything it borrows
+        // for `'scope` is still alive.
+        let job: Job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+
+        if let Err(err) = self.pool.execute(job) {
+            // The job was handed back and dropped without running.
+            self.state.job_done();
+            panic!("Could not execute scoped job: {err}");
+        }
+    }
+}
+
+impl ThreadPool {
+    /// Creates a scope for spawning jobs that borrow non-`'static` data.
+    ///
+    /// Every job spawned in the scope has finished by the time this function
+    /// returns, even if `f` panics.
+    ///
+    /// Calling `scope` from inside a job of the same pool blocks that worker
+    /// while it waits, so the pool needs other free workers to make
+    /// progress.
+    ///
+    /// # Panics
+    ///
+    /// The `scope` function will panic if `f` panicked or if any of the
+    /// spawned jobs panicked, once all of them have finished.
+    pub fn scope<'env, F, T>(&self, f: F) -> T
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(ScopeState {
+                pending: Mutex::new(0),
+                done: Condvar::new(),
+                panic: Mutex::new(None),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        scope.state.wait();
+
+        match result {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(value) => {
+                if let Some(payload) = scope.state.panic.lock().unwrap().take() {
+                    panic::resume_unwind(payload);
+                }
+                value
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ThreadPool;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    #[test]
+    fn scoped_jobs_borrow_from_the_stack() {
+        let pool = ThreadPool::new(3);
+        let numbers = [1, 2, 3, 4, 5, 6];
+        let total = AtomicUsize::new(0);
+
+        pool.scope(|s| {
+            for chunk in numbers.chunks(2) {
+                let total = &total;
+                s.spawn(move || {
+                    total.fetch_add(chunk.iter().sum(), Ordering::SeqCst);
+                });
+            }
+        });
+
+        assert_eq!(21, total.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn scoped_jobs_can_mutate_borrowed_data() {
+        let pool = ThreadPool::new(2);
+        let mut left = vec![1, 2, 3];
+        let mut right = vec![4, 5, 6];
+
+        pool.scope(|s| {
+            s.spawn(|| left.iter_mut().for_each(|x| *x *= 10));
+            s.spawn(|| right.push(7));
+        });
+
+        assert_eq!(vec![10, 20, 30], left);
+        assert_eq!(vec![4, 5, 6, 7], right);
+    }
+
+    #[test]
+    fn scope_returns_the_value_of_its_closure() {
+        let pool = ThreadPool::new(1);
+        let log = Mutex::new(Vec::new());
+
+        let value = pool.scope(|s| {
+            s.spawn(|| log.lock().unwrap().push("spawned"));
+            42
+        });
+
+        assert_eq!(42, value);
+        assert_eq!(vec!["spawned"], *log.lock().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "scoped job failed")]
+    fn scope_propagates_panics_after_all_jobs_finish() {
+        let pool = ThreadPool::new(2);
+
+        pool.scope(|s| {
+            s.spawn(|| panic!("scoped job failed"));
+            s.spawn(|| {});
+        });
+    }
+}
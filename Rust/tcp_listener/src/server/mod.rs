@@ -0,0 +1,190 @@
+//! The web server: an accept loop feeding connections to the thread pools.
+//!
+//! Each connection is parsed on the CPU pool, then handed to the IO pool to
+//! be answered, since answering usually means waiting on the disk.
+
+use std::{
+    fmt, io,
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::Arc,
+};
+
+use crate::{
+    http::{AcceptPolicy, ConnectionGuard, Handler, Request, Response, Status},
+    PoolCreationError, PoolManager, ServerConfig, ThreadPool,
+};
+
+/// A listening socket, the pools that serve it and the handler that answers
+/// its requests.
+pub struct Server {
+    listener: TcpListener,
+    manager: PoolManager,
+    io: Arc<ThreadPool>,
+    sites: Arc<dyn Handler>,
+    policy: AcceptPolicy,
+    config: ServerConfig,
+}
+
+/// The error returned by `Server::bind`.
+#[derive(Debug)]
+pub enum ServerError {
+    /// The address of the configuration could not be listened on.
+    Bind(io::Error),
+    /// The pools could not be created.
+    Pools(PoolCreationError),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerError::Bind(err) => write!(f, "could not listen: {err}"),
+            ServerError::Pools(err) => write!(f, "could not create the pools: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ServerError::Bind(err) => Some(err),
+            ServerError::Pools(err) => Some(err),
+        }
+    }
+}
+
+impl Server {
+    /// Listens on the address of `config` and creates the pools, sized by
+    /// `config.pool_size`. Requests are answered by `sites`.
+    ///
+    /// Binding to port 0 picks a free port, which `local_addr` returns.
+    ///
+    /// # Errors
+    ///
+    /// The `bind` function will return an error if the address can't be
+    /// listened on or if a worker thread can't be spawned.
+    pub fn bind(config: ServerConfig, sites: impl Handler + 'static) -> Result<Server, ServerError> {
+        let listener = TcpListener::bind(config.bind).map_err(ServerError::Bind)?;
+        let manager = PoolManager::with_preset_size(config.pool_size).map_err(ServerError::Pools)?;
+        let io = manager.get(PoolManager::IO).unwrap();
+
+        Ok(Server {
+            listener,
+            manager,
+            io,
+            sites: Arc::new(sites),
+            policy: AcceptPolicy::new(),
+            config,
+        })
+    }
+
+    /// Sets the policy deciding which connections are taken on. By default,
+    /// all of them are.
+    pub fn accept_policy(mut self, policy: AcceptPolicy) -> Server {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the address the server listens on.
+    ///
+    /// # Errors
+    ///
+    /// The `local_addr` function will return an error if the operating
+    /// system can't tell.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves connections forever.
+    pub fn serve(&self) {
+        for stream in self.listener.incoming() {
+            self.accept(stream);
+        }
+    }
+
+    /// Serves the next `count` connections, then returns.
+    ///
+    /// Connections that are still being answered keep going on the pools,
+    /// which finish them when the server is dropped.
+    pub fn serve_connections(&self, count: usize) {
+        for stream in self.listener.incoming().take(count) {
+            self.accept(stream);
+        }
+    }
+
+    fn accept(&self, stream: io::Result<TcpStream>) {
+        let result = stream.and_then(|stream| {
+            stream.set_read_timeout(Some(self.config.read_timeout))?;
+            stream.set_write_timeout(Some(self.config.write_timeout))?;
+            Ok((stream.peer_addr()?, stream))
+        });
+        let (peer, mut stream) = match result {
+            Ok(connection) => connection,
+            Err(err) => {
+                eprintln!("Dropping connection: {err}");
+                return;
+            }
+        };
+
+        let guard = match self.policy.admit(peer.ip()) {
+            Ok(guard) => guard,
+            Err(rejection) => {
+                eprintln!("Rejecting connection: {rejection}");
+                let _ = rejection.response().write_to(&mut stream);
+                return;
+            }
+        };
+
+        let io = Arc::clone(&self.io);
+        let sites = Arc::clone(&self.sites);
+
+        let result = self.manager.execute(PoolManager::CPU, move || {
+            handle_connection(stream, guard, &io, sites);
+        });
+
+        if let Err(err) = result {
+            eprintln!("Dropping connection: {err}");
+        }
+    }
+}
+
+impl fmt::Debug for Server {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("listener", &self.listener)
+            .field("manager", &self.manager)
+            .field("policy", &self.policy)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    guard: ConnectionGuard,
+    io: &ThreadPool,
+    sites: Arc<dyn Handler>,
+) {
+    let request = match Request::from_stream(&stream) {
+        Ok(request) => request,
+        Err(err) => {
+            eprintln!("Bad request: {err}");
+            let _ = Response::new(Status::BadRequest).write_to(&mut stream);
+            return;
+        }
+    };
+
+    // Reading the file blocks on the disk, so it happens on the IO pool and
+    // this thread is free to handle the next request.
+    let result = io.execute(move || {
+        // The connection stays open until the response is sent.
+        let _guard = guard;
+
+        if let Err(err) = sites.handle(&request, &mut stream) {
+            eprintln!("Could not send {}: {err}", request.path);
+        }
+    });
+
+    if let Err(err) = result {
+        eprintln!("Dropping connection: {err}");
+    }
+}
@@ -0,0 +1,21 @@
+//! The report returned by `ThreadPool::shutdown`.
+
+/// What happened to the workers of a pool shut down with
+/// `ThreadPool::shutdown`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Ids of the workers that finished before the deadline.
+    pub finished: Vec<usize>,
+    /// Ids of the workers still running a job at the deadline. Their threads
+    /// are detached and keep running until the job returns.
+    pub abandoned: Vec<usize>,
+    /// Queued jobs that were discarded without being started.
+    pub dropped_jobs: usize,
+}
+
+impl ShutdownReport {
+    /// Returns true if every worker finished before the deadline.
+    pub fn is_clean(&self) -> bool {
+        self.abandoned.is_empty()
+    }
+}
@@ -0,0 +1,62 @@
+//! Helpers shared by the integration tests.
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::{self, JoinHandle},
+};
+
+use tcp_listener::{
+    http::{AcceptPolicy, Handler},
+    http_client::Client,
+    Server, ServerConfig,
+};
+
+/// A directory of files to serve, removed when dropped.
+pub struct Site(pub PathBuf);
+
+impl Site {
+    /// Creates an empty directory with a unique name.
+    pub fn new() -> Site {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let id = NEXT.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("tcp-listener-site-{}-{id}", process::id()));
+        fs::create_dir_all(&path).unwrap();
+        Site(path)
+    }
+
+    pub fn file(self, name: &str, contents: &str) -> Site {
+        fs::write(self.0.join(name), contents).unwrap();
+        self
+    }
+}
+
+impl Drop for Site {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Starts a server on a free port that answers `connections` connections
+/// with `sites`, and returns a client for it. Joining the handle waits for
+/// the server to finish them.
+pub fn start(
+    sites: impl Handler + 'static,
+    policy: AcceptPolicy,
+    connections: usize,
+) -> (Client, JoinHandle<()>) {
+    let config = ServerConfig {
+        bind: "127.0.0.1:0".parse().unwrap(),
+        pool_size: 2,
+        ..ServerConfig::default()
+    };
+    let server = Server::bind(config, sites).unwrap().accept_policy(policy);
+    let client = Client::new(server.local_addr().unwrap()).unwrap();
+
+    let handle = thread::spawn(move || server.serve_connections(connections));
+
+    (client, handle)
+}
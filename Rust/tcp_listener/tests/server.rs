@@ -0,0 +1,80 @@
+//! Runs the server on a free port and checks the responses it sends over
+//! real connections.
+
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+use tcp_listener::http::{
+    AcceptPolicy, Method, Request, Response, Router, Status, StaticFiles, VirtualHosts,
+};
+
+mod common;
+
+use common::Site;
+
+#[test]
+fn serves_static_files() {
+    let site = Site::new()
+        .file("index.html", "<h1>Home</h1>")
+        .file("style.css", "h1 {}");
+    let (client, server) = common::start(StaticFiles::serve(&site.0), AcceptPolicy::new(), 4);
+
+    let index = client.get("/").unwrap();
+    let style = client.get("/style.css").unwrap();
+    let missing = client.get("/missing.html").unwrap();
+    let outside = client.get("/../secret").unwrap();
+    server.join().unwrap();
+
+    assert_eq!(Status::Ok, index.status);
+    assert_eq!(b"<h1>Home</h1>", &index.body[..]);
+    assert_eq!(Some("text/css; charset=utf-8"), style.headers.get("Content-Type"));
+    assert_eq!(Status::NotFound, missing.status);
+    assert_eq!(Status::Forbidden, outside.status);
+}
+
+#[test]
+fn virtual_hosts_serve_their_own_sites() {
+    let main_site = Site::new().file("index.html", "main");
+    let blog = Site::new().file("index.html", "blog");
+    let hosts = VirtualHosts::new()
+        .host("blog.test", Router::new().fallback(StaticFiles::serve(&blog.0)))
+        .default_host(Router::new().fallback(StaticFiles::serve(&main_site.0)));
+    let (client, server) = common::start(hosts, AcceptPolicy::new(), 2);
+
+    let request = Request::new(Method::Get, "/").header("Host", "blog.test");
+    let from_blog = client.send(&request).unwrap();
+    let from_main = client.get("/").unwrap();
+    server.join().unwrap();
+
+    assert_eq!(b"blog", &from_blog.body[..]);
+    assert_eq!(b"main", &from_main.body[..]);
+}
+
+#[test]
+fn connections_over_the_rate_get_too_many_requests() {
+    let site = Site::new().file("index.html", "hi");
+    let policy = AcceptPolicy::new().rate_limit(1, 2);
+    let (client, server) = common::start(StaticFiles::serve(&site.0), policy, 3);
+
+    let statuses: Vec<Status> = (0..3).map(|_| client.get("/").unwrap().status).collect();
+    server.join().unwrap();
+
+    assert_eq!(vec![Status::Ok, Status::Ok, Status::TooManyRequests], statuses);
+}
+
+#[test]
+fn malformed_requests_get_bad_request() {
+    let site = Site::new();
+    let (client, server) = common::start(StaticFiles::serve(&site.0), AcceptPolicy::new(), 1);
+
+    let mut stream = TcpStream::connect(client.addr()).unwrap();
+    stream.write_all(b"GIBBERISH\r\n\r\n").unwrap();
+    let mut answer = Vec::new();
+    stream.read_to_end(&mut answer).unwrap();
+    server.join().unwrap();
+
+    let response = Response::parse(&mut &answer[..]).unwrap();
+    assert_eq!(Status::BadRequest, response.status);
+}